@@ -0,0 +1,179 @@
+use alloc::sync::Arc;
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+use embedded_hal_1::i2c as i2c_eh1;
+
+#[cfg(feature = "i2c")]
+use embedded_hal::blocking::i2c;
+
+/// Reference-counted bus manager, for sharing a bus without a `'static` manager.
+///
+/// The `ArcBusManager` keeps its [`BusManager`] in an [`Arc`], so proxies can own a reference to
+/// it instead of borrowing it.  Such owned proxies can be moved into tasks or threads without
+/// creating the manager with one of the `'static` macros, and the manager is dropped when the
+/// last proxy is gone.  Only `alloc` is needed, so together with a mutex like the
+/// [`AutoMutex`], this works on `no_std` targets which have an allocator:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # fn spawn(_: impl FnOnce() + Send + 'static) {}
+/// # fn _example(i2c: impl i2c::Write + Send + 'static) {
+/// let bus: shared_bus::ArcBusManager<shared_bus::AutoMutex<_>> =
+///     shared_bus::ArcBusManager::new(i2c);
+///
+/// let mut proxy = bus.acquire_i2c();
+/// spawn(move || {
+///     let _ = proxy.write(0x39, &[0xc0, 0xff, 0xee]);
+/// });
+/// # }
+/// ```
+///
+/// The `ArcBusManager` dereferences to the `BusManager`, so all its other methods can be used
+/// as well, e.g. for acquiring borrowed proxies.
+///
+/// [`BusManager`]: ./struct.BusManager.html
+/// [`Arc`]: https://doc.rust-lang.org/alloc/sync/struct.Arc.html
+/// [`AutoMutex`]: ./type.AutoMutex.html
+///
+/// This type is only available with the `alloc` feature.
+#[derive(Debug)]
+pub struct ArcBusManager<M> {
+    manager: Arc<crate::BusManager<M>>,
+}
+
+impl<M> Clone for ArcBusManager<M> {
+    fn clone(&self) -> Self {
+        ArcBusManager {
+            manager: self.manager.clone(),
+        }
+    }
+}
+
+impl<M: crate::BusMutex> ArcBusManager<M> {
+    /// Create a new reference-counted bus manager for a bus.
+    pub fn new(bus: M::Bus) -> Self
+    where
+        M: crate::CreateBusMutex,
+    {
+        Self::from_manager(crate::BusManager::new(bus))
+    }
+
+    /// Move an existing bus manager behind a reference count.
+    pub fn from_manager(manager: crate::BusManager<M>) -> Self {
+        ArcBusManager {
+            manager: Arc::new(manager),
+        }
+    }
+
+    /// Acquire an [`ArcI2cProxy`] for this bus.
+    ///
+    /// [`ArcI2cProxy`]: ./struct.ArcI2cProxy.html
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c(&self) -> ArcI2cProxy<M> {
+        ArcI2cProxy {
+            manager: self.manager.clone(),
+        }
+    }
+}
+
+impl<M> core::ops::Deref for ArcBusManager<M> {
+    type Target = crate::BusManager<M>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.manager
+    }
+}
+
+/// Proxy type for I2C bus sharing, owning a reference to its bus manager.
+///
+/// The `ArcI2cProxy` works just like the [`I2cProxy`] but holds a reference-counted handle to the
+/// bus manager instead of borrowing it, so it has no lifetime.  Each proxy keeps the manager
+/// (and with it, the bus) alive, even when the `ArcBusManager` it was acquired from is dropped.
+/// The bus is only dropped together with the last proxy.
+///
+/// An `ArcI2cProxy` is created by calling [`ArcBusManager::acquire_i2c()`][acquire_i2c].
+///
+/// [`I2cProxy`]: ./struct.I2cProxy.html
+/// [acquire_i2c]: ./struct.ArcBusManager.html#method.acquire_i2c
+///
+/// This type is only available with the `alloc` feature.
+#[cfg(feature = "i2c")]
+#[derive(Debug)]
+pub struct ArcI2cProxy<M> {
+    manager: Arc<crate::BusManager<M>>,
+}
+
+#[cfg(feature = "i2c")]
+impl<M> Clone for ArcI2cProxy<M> {
+    fn clone(&self) -> Self {
+        ArcI2cProxy {
+            manager: self.manager.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<M: crate::BusMutex> i2c::Write for ArcI2cProxy<M>
+where
+    M::Bus: i2c::Write,
+{
+    type Error = <M::Bus as i2c::Write>::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.manager.acquire_i2c().write(addr, buffer)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<M: crate::BusMutex> i2c::Read for ArcI2cProxy<M>
+where
+    M::Bus: i2c::Read,
+{
+    type Error = <M::Bus as i2c::Read>::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.manager.acquire_i2c().read(addr, buffer)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<M: crate::BusMutex> i2c::WriteRead for ArcI2cProxy<M>
+where
+    M::Bus: i2c::WriteRead,
+{
+    type Error = <M::Bus as i2c::WriteRead>::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.manager
+            .acquire_i2c()
+            .write_read(addr, buffer_in, buffer_out)
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<M: crate::BusMutex> i2c_eh1::ErrorType for ArcI2cProxy<M>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = <M::Bus as i2c_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<M: crate::BusMutex, A: i2c_eh1::AddressMode> i2c_eh1::I2c<A> for ArcI2cProxy<M>
+where
+    M::Bus: i2c_eh1::I2c<A>,
+{
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.manager.acquire_i2c().transaction(address, operations)
+    }
+}
@@ -0,0 +1,14 @@
+//! Async bus sharing, with the same names as in the [`blocking`] module.
+//!
+//! See the [`blocking`] module for how to use these to write drivers for both variants at once.
+//!
+//! This module is only available with the `async` feature.
+//!
+//! [`blocking`]: ../blocking/index.html
+pub use crate::AsyncBusManager as BusManager;
+#[cfg(feature = "i2c")]
+pub use crate::AsyncI2cProxy as I2cProxy;
+pub use crate::AsyncNullMutex as NullMutex;
+pub use crate::BusManagerAsyncSimple as BusManagerSimple;
+#[cfg(feature = "i2c")]
+pub use embedded_hal_async::i2c::I2c;
@@ -0,0 +1,48 @@
+//! Blocking bus sharing, with the same names as in the [`asynch`] module.
+//!
+//! Drivers written once for both blocking and async use (e.g. with [`maybe-async`]) need the
+//! same names for the types and traits in both variants.  This module and the [`asynch`] module
+//! provide them, so a driver only switches the module it imports from:
+//!
+//! ```
+//! # mod wrapper {
+//! #[cfg(not(feature = "async"))]
+//! use shared_bus::blocking as bus;
+//! #[cfg(feature = "async")]
+//! use shared_bus::asynch as bus;
+//!
+//! use bus::I2c;
+//!
+//! pub struct Sensor<I2C> {
+//!     i2c: I2C,
+//! }
+//!
+//! #[cfg_attr(not(feature = "async"), maybe_async::must_be_sync)]
+//! #[cfg_attr(feature = "async", maybe_async::must_be_async)]
+//! impl<I2C: I2c> Sensor<I2C> {
+//!     pub async fn temperature(&mut self) -> Result<u16, I2C::Error> {
+//!         let mut buf = [0u8; 2];
+//!         self.i2c.write_read(0x48, &[0x00], &mut buf).await?;
+//!         Ok(u16::from_be_bytes(buf))
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! Here, an `async` feature of the driver (which enables the `async` feature of `shared-bus`)
+//! selects the variant.  Application code uses `bus::BusManagerSimple::new()` and
+//! `acquire_i2c()` in either variant; only the calls into the driver get an `.await`.
+//!
+//! SPI devices are not part of this yet, as the blocking [`OwnedSpiDeviceProxy`] needs a
+//! `'static` bus manager while the async one does not.
+//!
+//! This module is only available with the `eh1` feature.
+//!
+//! [`asynch`]: ../asynch/index.html
+//! [`maybe-async`]: https://docs.rs/maybe-async
+//! [`OwnedSpiDeviceProxy`]: ../struct.OwnedSpiDeviceProxy.html
+#[cfg(feature = "i2c")]
+pub use crate::I2cProxy;
+pub use crate::{BusManager, BusManagerSimple, NullMutex};
+#[cfg(feature = "i2c")]
+pub use embedded_hal_1::i2c::I2c;
@@ -0,0 +1,184 @@
+use embedded_hal::blocking::i2c;
+use std::sync::mpsc;
+
+enum I2cOp {
+    Write(Vec<u8>),
+    Read(usize),
+    WriteRead(Vec<u8>, usize),
+}
+
+#[doc(hidden)]
+pub struct I2cRequest<E> {
+    addr: u8,
+    op: I2cOp,
+    reply: mpsc::Sender<Result<Vec<u8>, E>>,
+}
+
+/// Bus owner for sharing a bus by message passing instead of a mutex.
+///
+/// The `ChannelBus` owns the bus peripheral and performs all transfers on behalf of its proxies.
+/// Each proxy sends its requests over a channel to the owner and waits for the reply.  Run the
+/// owner in a dedicated thread using [`run()`][run], which returns once all proxies were dropped:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # fn _example<I2C>(i2c: I2C)
+/// # where
+/// #     I2C: i2c::Write + i2c::Read<Error = <I2C as i2c::Write>::Error>
+/// #         + i2c::WriteRead<Error = <I2C as i2c::Write>::Error> + Send + 'static,
+/// #     <I2C as i2c::Write>::Error: Send,
+/// # {
+/// let bus = shared_bus::ChannelBus::new(i2c);
+///
+/// let mut proxy1 = bus.acquire_i2c();
+/// let mut proxy2 = bus.acquire_i2c();
+///
+/// let owner = std::thread::spawn(move || bus.run());
+///
+/// std::thread::spawn(move || {
+///     let _ = proxy1.write(0x39, &[0xc0, 0xff, 0xee]);
+/// });
+/// let _ = proxy2.write(0x20, &[0x00]);
+/// # drop(proxy2);
+/// # owner.join().unwrap();
+/// # }
+/// ```
+///
+/// As no lock is ever held outside the owner thread, a proxy blocking or panicking in the middle of
+/// a driver call cannot block the bus for others.
+///
+/// [run]: #method.run
+///
+/// This type is only available with the `std` feature.
+pub struct ChannelBus<B, E> {
+    bus: B,
+    tx: mpsc::Sender<I2cRequest<E>>,
+    rx: mpsc::Receiver<I2cRequest<E>>,
+}
+
+impl<B, E> core::fmt::Debug for ChannelBus<B, E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ChannelBus").finish_non_exhaustive()
+    }
+}
+
+impl<B, E> ChannelBus<B, E>
+where
+    B: i2c::Write<Error = E> + i2c::Read<Error = E> + i2c::WriteRead<Error = E>,
+{
+    /// Take ownership of the bus.
+    pub fn new(bus: B) -> Self {
+        let (tx, rx) = mpsc::channel();
+        ChannelBus { bus, tx, rx }
+    }
+
+    /// Acquire a [`ChannelI2cProxy`] for this bus.
+    ///
+    /// [`ChannelI2cProxy`]: ./struct.ChannelI2cProxy.html
+    pub fn acquire_i2c(&self) -> ChannelI2cProxy<E> {
+        ChannelI2cProxy {
+            tx: self.tx.clone(),
+        }
+    }
+
+    /// Serve the requests of all proxies until the last one was dropped, then return the bus.
+    pub fn run(self) -> B {
+        let ChannelBus { mut bus, tx, rx } = self;
+        // Only the proxies may keep the channel open.
+        drop(tx);
+
+        for request in rx {
+            let result = match request.op {
+                I2cOp::Write(data) => bus.write(request.addr, &data).map(|_| Vec::new()),
+                I2cOp::Read(len) => {
+                    let mut buffer = vec![0u8; len];
+                    bus.read(request.addr, &mut buffer).map(|_| buffer)
+                }
+                I2cOp::WriteRead(data, len) => {
+                    let mut buffer = vec![0u8; len];
+                    bus.write_read(request.addr, &data, &mut buffer)
+                        .map(|_| buffer)
+                }
+            };
+            // The proxy might have gone away in the meantime.
+            let _ = request.reply.send(result);
+        }
+
+        bus
+    }
+}
+
+/// Proxy type for an I2C bus owned by a [`ChannelBus`].
+///
+/// Each transfer is sent to the owner of the bus, which performs it and sends back the result.
+/// The proxy is `Send` if the bus error type is, so it can be moved to other threads freely.
+///
+/// A `ChannelI2cProxy` is created by calling [`ChannelBus::acquire_i2c()`][acquire_i2c].
+///
+/// [`ChannelBus`]: ./struct.ChannelBus.html
+/// [acquire_i2c]: ./struct.ChannelBus.html#method.acquire_i2c
+///
+/// This type is only available with the `std` feature.
+pub struct ChannelI2cProxy<E> {
+    tx: mpsc::Sender<I2cRequest<E>>,
+}
+
+impl<E> core::fmt::Debug for ChannelI2cProxy<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ChannelI2cProxy").finish_non_exhaustive()
+    }
+}
+
+impl<E> Clone for ChannelI2cProxy<E> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<E> ChannelI2cProxy<E> {
+    fn request(&mut self, addr: u8, op: I2cOp) -> Result<Vec<u8>, E> {
+        // A fresh reply channel per request makes sure the proxy notices when the owner is gone.
+        let (reply, reply_rx) = mpsc::channel();
+        self.tx
+            .send(I2cRequest { addr, op, reply })
+            .expect("ChannelBus owner is gone");
+        reply_rx.recv().expect("ChannelBus owner is gone")
+    }
+}
+
+impl<E> i2c::Write for ChannelI2cProxy<E> {
+    type Error = E;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.request(addr, I2cOp::Write(buffer.to_vec()))
+            .map(|_| ())
+    }
+}
+
+impl<E> i2c::Read for ChannelI2cProxy<E> {
+    type Error = E;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let data = self.request(addr, I2cOp::Read(buffer.len()))?;
+        buffer.copy_from_slice(&data);
+        Ok(())
+    }
+}
+
+impl<E> i2c::WriteRead for ChannelI2cProxy<E> {
+    type Error = E;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let data = self.request(addr, I2cOp::WriteRead(buffer_in.to_vec(), buffer_out.len()))?;
+        buffer_out.copy_from_slice(&data);
+        Ok(())
+    }
+}
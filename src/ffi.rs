@@ -0,0 +1,176 @@
+//! C-compatible handles for sharing an I2C bus with foreign code.
+//!
+//! Rust code wraps a concrete I2C bus into a handle using [`i2c_handle_new()`].  The handle is an
+//! opaque pointer which can be passed to C code, which in turn uses the `extern "C"` functions of
+//! this module to access the bus:
+//!
+//! ```c
+//! typedef struct SharedBusI2c SharedBusI2c;
+//!
+//! SharedBusI2c *shared_bus_i2c_acquire(const SharedBusI2c *handle);
+//! void shared_bus_i2c_release(SharedBusI2c *handle);
+//! int shared_bus_i2c_write(SharedBusI2c *handle, uint8_t addr, const uint8_t *data, size_t len);
+//! int shared_bus_i2c_read(SharedBusI2c *handle, uint8_t addr, uint8_t *data, size_t len);
+//! ```
+//!
+//! Each handle acts like an [`I2cProxy`] of its own.  All handles share ownership of the bus
+//! manager, which is destroyed when the last handle was released.  Handles are `Send`, so they
+//! can be used from different threads.
+//!
+//! The I2C functions return [`SHARED_BUS_OK`] on success, [`SHARED_BUS_ERR_BUS`] if the bus
+//! reported an error, and [`SHARED_BUS_ERR_NULL`] if the handle or buffer was a null pointer.
+//!
+//! This module is only available with the `ffi` feature.
+//!
+//! [`i2c_handle_new()`]: ./fn.i2c_handle_new.html
+//! [`I2cProxy`]: ../struct.I2cProxy.html
+//! [`SHARED_BUS_OK`]: ./constant.SHARED_BUS_OK.html
+//! [`SHARED_BUS_ERR_BUS`]: ./constant.SHARED_BUS_ERR_BUS.html
+//! [`SHARED_BUS_ERR_NULL`]: ./constant.SHARED_BUS_ERR_NULL.html
+use embedded_hal::blocking::i2c;
+use std::os::raw::c_int;
+use std::sync::Arc;
+
+/// The operation was successful.
+pub const SHARED_BUS_OK: c_int = 0;
+/// The bus reported an error.
+pub const SHARED_BUS_ERR_BUS: c_int = -1;
+/// A null pointer was passed.
+pub const SHARED_BUS_ERR_NULL: c_int = -2;
+
+/// Object-safe subset of the I2C traits, used to erase the type of the bus.
+trait ErasedI2c: Send {
+    fn write(&mut self, addr: u8, data: &[u8]) -> Result<(), ()>;
+    fn read(&mut self, addr: u8, data: &mut [u8]) -> Result<(), ()>;
+}
+
+impl<B> ErasedI2c for B
+where
+    B: i2c::Write + i2c::Read + Send,
+{
+    fn write(&mut self, addr: u8, data: &[u8]) -> Result<(), ()> {
+        i2c::Write::write(self, addr, data).map_err(|_| ())
+    }
+
+    fn read(&mut self, addr: u8, data: &mut [u8]) -> Result<(), ()> {
+        i2c::Read::read(self, addr, data).map_err(|_| ())
+    }
+}
+
+struct ErasedBus(Box<dyn ErasedI2c>);
+
+impl i2c::Write for ErasedBus {
+    type Error = ();
+
+    fn write(&mut self, addr: u8, data: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(addr, data)
+    }
+}
+
+impl i2c::Read for ErasedBus {
+    type Error = ();
+
+    fn read(&mut self, addr: u8, data: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.read(addr, data)
+    }
+}
+
+/// Opaque handle to a shared I2C bus, passed to C code as a pointer.
+///
+/// See the [module documentation](./index.html) for details.
+pub struct SharedBusI2c {
+    manager: Arc<crate::BusManagerStd<ErasedBus>>,
+}
+
+impl core::fmt::Debug for SharedBusI2c {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SharedBusI2c").finish_non_exhaustive()
+    }
+}
+
+/// Create a bus manager for `bus` and return the first handle to it.
+///
+/// The handle must be released using [`shared_bus_i2c_release()`].
+///
+/// [`shared_bus_i2c_release()`]: ./fn.shared_bus_i2c_release.html
+pub fn i2c_handle_new<B>(bus: B) -> *mut SharedBusI2c
+where
+    B: i2c::Write + i2c::Read + Send + 'static,
+{
+    let manager = Arc::new(crate::BusManagerStd::new(ErasedBus(Box::new(bus))));
+    Box::into_raw(Box::new(SharedBusI2c { manager }))
+}
+
+/// Acquire another handle to the same bus as `handle`.
+///
+/// Returns a null pointer if `handle` is null.
+///
+/// # Safety
+/// `handle` must be null or a valid handle which was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn shared_bus_i2c_acquire(handle: *const SharedBusI2c) -> *mut SharedBusI2c {
+    match handle.as_ref() {
+        Some(handle) => Box::into_raw(Box::new(SharedBusI2c {
+            manager: handle.manager.clone(),
+        })),
+        None => core::ptr::null_mut(),
+    }
+}
+
+/// Release a handle.  The bus is dropped together with the last handle.
+///
+/// # Safety
+/// `handle` must be null or a valid handle which was not released yet.  It must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn shared_bus_i2c_release(handle: *mut SharedBusI2c) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Write `len` bytes from `data` to the device at `addr`.
+///
+/// # Safety
+/// `handle` must be null or a valid handle which was not released yet.  `data` must be null or
+/// point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn shared_bus_i2c_write(
+    handle: *mut SharedBusI2c,
+    addr: u8,
+    data: *const u8,
+    len: usize,
+) -> c_int {
+    let handle = match handle.as_ref() {
+        Some(handle) if !data.is_null() => handle,
+        _ => return SHARED_BUS_ERR_NULL,
+    };
+    let data = core::slice::from_raw_parts(data, len);
+    match i2c::Write::write(&mut handle.manager.acquire_i2c(), addr, data) {
+        Ok(()) => SHARED_BUS_OK,
+        Err(()) => SHARED_BUS_ERR_BUS,
+    }
+}
+
+/// Read `len` bytes from the device at `addr` into `data`.
+///
+/// # Safety
+/// `handle` must be null or a valid handle which was not released yet.  `data` must be null or
+/// point to `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn shared_bus_i2c_read(
+    handle: *mut SharedBusI2c,
+    addr: u8,
+    data: *mut u8,
+    len: usize,
+) -> c_int {
+    let handle = match handle.as_ref() {
+        Some(handle) if !data.is_null() => handle,
+        _ => return SHARED_BUS_ERR_NULL,
+    };
+    let data = core::slice::from_raw_parts_mut(data, len);
+    match i2c::Read::read(&mut handle.manager.acquire_i2c(), addr, data) {
+        Ok(()) => SHARED_BUS_OK,
+        Err(()) => SHARED_BUS_ERR_BUS,
+    }
+}
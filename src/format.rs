@@ -0,0 +1,200 @@
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+use embedded_hal_1::i2c as i2c_eh1;
+
+#[cfg(feature = "i2c")]
+use embedded_hal::blocking::i2c;
+#[cfg(feature = "spi")]
+use embedded_hal::blocking::spi;
+
+/// Bus wrapper which writes a textual record of all transfers into a [`core::fmt::Write`] sink.
+///
+/// The `FormatBus` wraps the bus peripheral _before_ it is handed to a bus manager, so the
+/// traffic of all proxies is recorded.  Each transfer is written as one line, for example:
+///
+/// ```text
+/// I2C 39 W: C0 FF EE
+/// I2C 39 R: 12 34
+/// SPI W: 01 02
+/// SPI T: AA BB -> 55 66
+/// ```
+///
+/// Failed transfers are marked with a trailing `(failed)`.  Errors of the sink itself are
+/// ignored.  No allocation is needed, so this can be used in `no_std` environments as well, e.g.
+/// with a fixed-size string buffer or a serial port as the sink:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # fn _example(i2c: impl i2c::Write) {
+/// let bus = shared_bus::BusManagerSimple::new(shared_bus::FormatBus::new(i2c, String::new()));
+///
+/// let mut proxy = bus.acquire_i2c();
+/// let _ = proxy.write(0x39, &[0xc0, 0xff, 0xee]);
+///
+/// bus.configure(|bus| println!("{}", bus.sink()));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FormatBus<B, W> {
+    bus: B,
+    sink: W,
+}
+
+impl<B, W: core::fmt::Write> FormatBus<B, W> {
+    /// Wrap `bus`, recording its transfers into `sink`.
+    pub fn new(bus: B, sink: W) -> Self {
+        FormatBus { bus, sink }
+    }
+
+    /// Access the sink.
+    pub fn sink(&self) -> &W {
+        &self.sink
+    }
+
+    /// Mutably access the sink, e.g. for clearing it.
+    pub fn sink_mut(&mut self) -> &mut W {
+        &mut self.sink
+    }
+
+    /// Destroy the wrapper, returning the bus and the sink.
+    pub fn into_inner(self) -> (B, W) {
+        (self.bus, self.sink)
+    }
+
+    #[cfg(any(feature = "i2c", feature = "spi"))]
+    fn record<T, E>(&mut self, res: &Result<T, E>, f: impl FnOnce(&mut W) -> core::fmt::Result) {
+        let _ = f(&mut self.sink);
+        let _ = match res {
+            Ok(_) => writeln!(self.sink),
+            Err(_) => writeln!(self.sink, " (failed)"),
+        };
+    }
+}
+
+#[cfg(any(feature = "i2c", feature = "spi"))]
+fn write_bytes<W: core::fmt::Write>(sink: &mut W, bytes: &[u8]) -> core::fmt::Result {
+    for (i, b) in bytes.iter().enumerate() {
+        if i == 0 {
+            write!(sink, "{:02X}", b)?;
+        } else {
+            write!(sink, " {:02X}", b)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "i2c")]
+fn write_i2c<W: core::fmt::Write>(
+    sink: &mut W,
+    addr: u8,
+    direction: &str,
+    bytes: &[u8],
+) -> core::fmt::Result {
+    write!(sink, "I2C {:02X} {}: ", addr, direction)?;
+    write_bytes(sink, bytes)
+}
+
+#[cfg(feature = "i2c")]
+impl<B: i2c::Write, W: core::fmt::Write> i2c::Write for FormatBus<B, W> {
+    type Error = B::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        let res = self.bus.write(addr, buffer);
+        self.record(&res, |sink| write_i2c(sink, addr, "W", buffer));
+        res
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<B: i2c::Read, W: core::fmt::Write> i2c::Read for FormatBus<B, W> {
+    type Error = B::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let res = self.bus.read(addr, buffer);
+        self.record(&res, |sink| write_i2c(sink, addr, "R", buffer));
+        res
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<B: i2c::WriteRead, W: core::fmt::Write> i2c::WriteRead for FormatBus<B, W> {
+    type Error = B::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let res = self.bus.write_read(addr, buffer_in, buffer_out);
+        self.record(&res, |sink| {
+            write_i2c(sink, addr, "W", buffer_in)?;
+            writeln!(sink)?;
+            write_i2c(sink, addr, "R", buffer_out)
+        });
+        res
+    }
+}
+
+#[cfg(feature = "spi")]
+impl<B: spi::Write<u8>, W: core::fmt::Write> spi::Write<u8> for FormatBus<B, W> {
+    type Error = B::Error;
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        let res = self.bus.write(words);
+        self.record(&res, |sink| {
+            write!(sink, "SPI W: ")?;
+            write_bytes(sink, words)
+        });
+        res
+    }
+}
+
+#[cfg(feature = "spi")]
+impl<B: spi::Transfer<u8>, W: core::fmt::Write> spi::Transfer<u8> for FormatBus<B, W> {
+    type Error = B::Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        // The outgoing data is overwritten by the transfer, so record it up front.
+        let _ = write!(self.sink, "SPI T: ");
+        let _ = write_bytes(&mut self.sink, words);
+        let _ = write!(self.sink, " -> ");
+
+        let res = self.bus.transfer(words);
+        let sink = &mut self.sink;
+        let _ = match &res {
+            Ok(read) => write_bytes(sink, read).and_then(|_| writeln!(sink)),
+            Err(_) => writeln!(sink, "(failed)"),
+        };
+        res
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<B: i2c_eh1::ErrorType, W> i2c_eh1::ErrorType for FormatBus<B, W> {
+    type Error = B::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<B: i2c_eh1::I2c, W: core::fmt::Write> i2c_eh1::I2c for FormatBus<B, W> {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let res = self.bus.transaction(address, operations);
+        self.record(&res, |sink| {
+            for (i, op) in operations.iter().enumerate() {
+                if i != 0 {
+                    writeln!(sink)?;
+                }
+                match op {
+                    i2c_eh1::Operation::Read(buf) => write_i2c(sink, address, "R", buf)?,
+                    i2c_eh1::Operation::Write(buf) => write_i2c(sink, address, "W", buf)?,
+                }
+            }
+            Ok(())
+        });
+        res
+    }
+}
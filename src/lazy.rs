@@ -0,0 +1,177 @@
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+use embedded_hal_1::i2c as i2c_eh1;
+#[cfg(all(feature = "spi", feature = "eh1"))]
+use embedded_hal_1::spi as spi_eh1;
+
+#[cfg(feature = "i2c")]
+use embedded_hal::blocking::i2c;
+#[cfg(feature = "spi")]
+use embedded_hal::blocking::spi;
+
+enum State<B, F> {
+    Uninit(F),
+    Initializing,
+    Init(B),
+}
+
+/// Bus wrapper which creates the bus on its first use.
+///
+/// The `LazyBus` holds a closure which constructs the bus peripheral.  The closure is only called
+/// when a proxy accesses the bus for the first time; the bus is then kept for all further
+/// accesses.  This is useful when the bus is expensive to initialize or to keep powered, and some
+/// boot paths never touch it.  Use it with any bus manager, e.g. via
+/// [`BusManager::new_lazy()`][new_lazy]:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # fn power_up_i2c() -> impl i2c::Write { struct I2c; impl i2c::Write for I2c {
+/// #     type Error = (); fn write(&mut self, _: u8, _: &[u8]) -> Result<(), ()> { Ok(()) } } I2c }
+/// let bus: shared_bus::BusManagerSimple<_> = shared_bus::BusManager::new_lazy(power_up_i2c);
+///
+/// let mut proxy = bus.acquire_i2c();
+/// // The bus is powered up here
+/// let _ = proxy.write(0x39, &[0xc0, 0xff, 0xee]);
+/// ```
+///
+/// No `once_cell` or similar is needed:  The initialization happens while the bus is locked, so
+/// the mutex of the bus manager already guarantees that it runs only once.  This works in
+/// `no_std` environments as well.
+///
+/// If the closure panics, all further accesses to the bus panic as well.
+///
+/// [new_lazy]: ./struct.BusManager.html#method.new_lazy
+pub struct LazyBus<B, F = fn() -> B> {
+    state: State<B, F>,
+}
+
+impl<B, F> core::fmt::Debug for LazyBus<B, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LazyBus")
+            .field("initialized", &self.is_initialized())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<B, F: FnOnce() -> B> LazyBus<B, F> {
+    /// Create a wrapper which calls `init` to create the bus on its first use.
+    pub fn new(init: F) -> Self {
+        LazyBus {
+            state: State::Uninit(init),
+        }
+    }
+
+    /// Access the bus, creating it first if needed.
+    pub fn force(&mut self) -> &mut B {
+        if let State::Uninit(_) = self.state {
+            if let State::Uninit(init) = core::mem::replace(&mut self.state, State::Initializing) {
+                self.state = State::Init(init());
+            }
+        }
+        match &mut self.state {
+            State::Init(bus) => bus,
+            _ => panic!("LazyBus initialization panicked"),
+        }
+    }
+}
+
+impl<B, F> LazyBus<B, F> {
+    /// Check whether the bus was created already.
+    pub fn is_initialized(&self) -> bool {
+        matches!(self.state, State::Init(_))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<B: i2c::Write, F: FnOnce() -> B> i2c::Write for LazyBus<B, F> {
+    type Error = B::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.force().write(addr, buffer)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<B: i2c::Read, F: FnOnce() -> B> i2c::Read for LazyBus<B, F> {
+    type Error = B::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.force().read(addr, buffer)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<B: i2c::WriteRead, F: FnOnce() -> B> i2c::WriteRead for LazyBus<B, F> {
+    type Error = B::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.force().write_read(addr, buffer_in, buffer_out)
+    }
+}
+
+#[cfg(feature = "spi")]
+impl<B: spi::Write<u8>, F: FnOnce() -> B> spi::Write<u8> for LazyBus<B, F> {
+    type Error = B::Error;
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.force().write(words)
+    }
+}
+
+#[cfg(feature = "spi")]
+impl<B: spi::Transfer<u8>, F: FnOnce() -> B> spi::Transfer<u8> for LazyBus<B, F> {
+    type Error = B::Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        self.force().transfer(words)
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<B: i2c_eh1::ErrorType, F> i2c_eh1::ErrorType for LazyBus<B, F> {
+    type Error = B::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<B: i2c_eh1::I2c, F: FnOnce() -> B> i2c_eh1::I2c for LazyBus<B, F> {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.force().transaction(address, operations)
+    }
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<B: spi_eh1::ErrorType, F> spi_eh1::ErrorType for LazyBus<B, F> {
+    type Error = B::Error;
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<B: spi_eh1::SpiBus, F: FnOnce() -> B> spi_eh1::SpiBus for LazyBus<B, F> {
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.force().read(words)
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.force().write(words)
+    }
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.force().transfer(read, write)
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.force().transfer_in_place(words)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.force().flush()
+    }
+}
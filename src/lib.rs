@@ -84,10 +84,20 @@
 //! | Mutex | Bus Manager | `'static` Bus Macro | Feature Name |
 //! | --- | --- | --- | --- |
 //! | `std::sync::Mutex` | [`BusManagerStd`] | [`new_std!()`] | `std` |
+//! | `shared_bus::FairStdMutex` (FIFO `std::sync::Mutex`) | [`BusManagerFairStd`] | [`new_fair_std!()`] | `std` |
+//! | `critical_section::Mutex` | [`BusManagerAuto`] | [`new_auto!()`] | `critical-section` |
 //! | `cortex_m::interrupt::Mutex` | [`BusManagerCortexM`] | [`new_cortexm!()`] | `cortex-m` |
 //! | `shared_bus::XtensaMutex` (`spin::Mutex` in critical section) | [`BusManagerXtensa`] | [`new_xtensa!()`] | `xtensa` |
 //! | None (Automatically Managed) | [`BusManagerAtomicCheck`] | [`new_atomic_check!()`] | `cortex-m` |
 //!
+//! On embedded targets, [`BusManagerAuto`] is the recommended choice:  It builds upon the
+//! [`critical-section`][critical-section] crate and thus works on any platform where the HAL/PAC
+//! registers a `critical-section` implementation, without selecting a platform-specific feature.
+//!
+//! If no implementation is registered, linking fails with undefined `_critical_section_1_0_*`
+//! symbols.  Enable the feature of your HAL which provides one, e.g. `critical-section-single-core`
+//! of `cortex-m`.
+//!
 //! # Supported buses and hardware blocks
 //! Currently, the following buses/blocks can be shared with _shared-bus_:
 //!
@@ -95,38 +105,114 @@
 //! | --- | --- | --- | --- |
 //! | I2C | [`I2cProxy`] | [`.acquire_i2c()`] | |
 //! | SPI | [`SpiProxy`] | [`.acquire_spi()`] | SPI can only be shared within a single task (See [`SpiProxy`] for details). |
+//! | SPI (with CS) | [`OwnedSpiDeviceProxy`] | [`.acquire_spi_device()`] | `embedded-hal` 1.0 `SpiDevice`, can be shared across tasks (requires the `eh1` feature). |
 //! | ADC | [`AdcProxy`] | [`.acquire_adc()`] | |
+//! | UART | [`SerialProxy`] | [`.acquire_serial()`] | `embedded-hal-nb` serial traits (requires the `serial` feature). |
+//!
+//! # Sharing between async tasks
+//! With the `async` feature, an [`AsyncBusManager`] is available which hands out proxies
+//! implementing the `embedded-hal-async` traits.  Tasks wait for the bus asynchronously and are
+//! served in the order they started waiting (see [`BusManagerAsync`]).
+//!
+//! [`AsyncBusManager`]: ./struct.AsyncBusManager.html
+//! [`BusManagerAsync`]: ./type.BusManagerAsync.html
+//!
+//! # Sharing without a `'static` manager
+//! With the `alloc` feature, an [`ArcBusManager`] is available which keeps the bus manager in an
+//! `Arc`.  Its proxies own a reference to the manager, so they can be moved into tasks without
+//! creating the manager using one of the macros.
+//!
+//! [`ArcBusManager`]: ./struct.ArcBusManager.html
 //!
+//! # `embedded-hal` versions
+//! The proxies always implement the traits of `embedded-hal` 0.2.  With the `eh1` feature, they
+//! additionally implement the traits of `embedded-hal` 1.0.  As those are distinct traits, enabling
+//! both versions never leads to conflicting implementations and a single proxy can be passed to
+//! drivers written against either version.  If a build fails with duplicate trait
+//! implementations, the dependency tree most likely contains two semver-incompatible versions of
+//! _shared-bus_ itself; `cargo tree -d` shows where they come from.
+//!
+//! # Disabling unused bus types
+//! The proxies for each bus type are guarded by the `i2c`, `spi`, and `adc` features, which are
+//! all enabled by default.  On constrained targets, the unused ones can be disabled to reduce
+//! compile times:
+//!
+//! ```toml
+//! [dependencies]
+//! shared-bus = { version = "0.3", default-features = false, features = ["i2c", "cortex-m"] }
+//! ```
 //!
 //! [`.acquire_i2c()`]: ./struct.BusManager.html#method.acquire_i2c
 //! [`.acquire_spi()`]: ./struct.BusManager.html#method.acquire_spi
+//! [`.acquire_spi_device()`]: ./struct.BusManager.html#method.acquire_spi_device
 //! [`.acquire_adc()`]: ./struct.BusManager.html#method.acquire_adc
+//! [`.acquire_serial()`]: ./struct.BusManager.html#method.acquire_serial
+//! [`BusManagerAuto`]: ./type.BusManagerAuto.html
 //! [`BusManagerCortexM`]: ./type.BusManagerCortexM.html
 //! [`BusManagerXtensa`]: ./type.BusManagerXtensa.html
 //! [`BusManagerAtomicCheck`]: ./type.BusManagerAtomicCheck.html
 //! [`BusManagerSimple`]: ./type.BusManagerSimple.html
 //! [`BusManagerStd`]: ./type.BusManagerStd.html
+//! [`BusManagerFairStd`]: ./type.BusManagerFairStd.html
 //! [`BusMutex`]: ./trait.BusMutex.html
 //! [`I2cProxy`]: ./struct.I2cProxy.html
 //! [`SpiProxy`]: ./struct.SpiProxy.html
+//! [`OwnedSpiDeviceProxy`]: ./struct.OwnedSpiDeviceProxy.html
 //! [`AdcProxy`]: ./struct.AdcProxy.html
+//! [`SerialProxy`]: ./struct.SerialProxy.html
+//! [`new_auto!()`]: ./macro.new_auto.html
 //! [`new_cortexm!()`]: ./macro.new_cortexm.html
 //! [`new_xtensa!()`]: ./macro.new_xtensa.html
 //! [`new_std!()`]: ./macro.new_std.html
+//! [`new_fair_std!()`]: ./macro.new_fair_std.html
 //! [`new_atomic_check!()`]: ./macro.new_atomic_check.html
 //! [blog-post]: https://blog.rahix.de/001-shared-bus
+//! [critical-section]: https://docs.rs/critical-section
 #![doc(html_root_url = "https://docs.rs/shared-bus")]
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
+// Without any bus, some of the internals are not used.
+#![cfg_attr(
+    not(any(feature = "i2c", feature = "spi", feature = "adc")),
+    allow(dead_code)
+)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+mod arc;
+#[cfg(feature = "async")]
+pub mod asynch;
+#[cfg(feature = "eh1")]
+pub mod blocking;
+#[cfg(all(feature = "std", feature = "i2c"))]
+mod channel;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod format;
+mod lazy;
 mod macros;
 mod manager;
+#[cfg(feature = "metrics")]
+mod metrics;
 mod mutex;
 mod proxies;
-
-#[doc(hidden)]
+#[cfg(feature = "i2c")]
+mod rate;
+#[cfg(all(feature = "std", feature = "i2c"))]
+mod record;
+#[cfg(feature = "device-driver")]
+mod registers;
+#[cfg(feature = "address-registry")]
+mod registry;
+#[cfg(feature = "serial")]
+mod serial;
+#[cfg(feature = "i2c")]
+mod smbus;
+mod tagged;
 #[cfg(feature = "std")]
-pub use once_cell;
+mod trace;
 
 #[doc(hidden)]
 #[cfg(feature = "cortex-m")]
@@ -136,19 +222,153 @@ pub use cortex_m;
 #[cfg(feature = "xtensa")]
 pub use xtensa_lx;
 
+#[doc(hidden)]
+#[cfg(feature = "critical-section")]
+pub use critical_section;
+
+#[cfg(feature = "alloc")]
+pub use arc::ArcBusManager;
+#[cfg(all(feature = "alloc", feature = "i2c"))]
+pub use arc::ArcI2cProxy;
+#[cfg(all(feature = "std", feature = "i2c"))]
+pub use channel::{ChannelBus, ChannelI2cProxy};
+pub use format::FormatBus;
+pub use lazy::LazyBus;
+pub use macros::BusManagerError;
+#[cfg(feature = "async")]
+pub use manager::AsyncBusManager;
+#[cfg(any(feature = "cortex-m", target_has_atomic = "ptr"))]
+pub use manager::BoundedBusManager;
 pub use manager::BusManager;
+pub use manager::BusTransaction;
+pub use manager::DualBusManager;
+#[cfg(feature = "i2c")]
+pub use manager::SegmentedBusManager;
+pub use manager::SingleKindBusManager;
+#[cfg(feature = "i2c")]
+pub use manager::SplitBusManager;
+#[cfg(feature = "i2c")]
+pub use manager::TeeBusManager;
+#[cfg(feature = "async")]
+pub use mutex::AsyncBusMutex;
+#[cfg(feature = "async")]
+pub use mutex::AsyncNullMutex;
+#[cfg(feature = "critical-section")]
+pub use mutex::AutoMutex;
 pub use mutex::BusMutex;
+pub use mutex::ClosureMutex;
 #[cfg(feature = "cortex-m")]
 pub use mutex::CortexMMutex;
+pub use mutex::CreateBusMutex;
+pub use mutex::Error;
+#[cfg(feature = "std")]
+pub use mutex::FairStdMutex;
+pub use mutex::GuardedBusMutex;
+pub use mutex::LockError;
 pub use mutex::NullMutex;
+#[cfg(all(feature = "rt", target_os = "linux"))]
+pub use mutex::PriorityCeilingMutex;
+pub use mutex::TryBusMutex;
+#[cfg(feature = "std")]
+pub use mutex::WatchdogMutex;
 #[cfg(feature = "xtensa")]
 pub use mutex::XtensaMutex;
+#[cfg(feature = "async")]
+pub use mutex::{AsyncAtomicCheckMutex, AsyncAtomicCheckMutexGuard};
+#[cfg(feature = "diagnostics")]
+pub use mutex::{DiagnosticMutex, HolderId};
+#[cfg(feature = "async")]
+pub use mutex::{FairAsyncMutex, FairAsyncMutexGuard};
+#[cfg(feature = "priority")]
+pub use mutex::{PriorityAsyncMutex, PriorityAsyncMutexGuard};
+pub use mutex::{QuietMutex, QuietPolicy};
+#[cfg(feature = "adc")]
 pub use proxies::AdcProxy;
+#[cfg(feature = "i2c")]
+pub use proxies::AddressedI2cProxy;
+#[cfg(all(feature = "adc", feature = "async"))]
+pub use proxies::AsyncAdcProxy;
+#[cfg(all(feature = "i2c", feature = "async"))]
+pub use proxies::AsyncI2cProxy;
+#[cfg(all(feature = "spi", feature = "async"))]
+pub use proxies::AsyncRetryingSpiDeviceProxy;
+#[cfg(all(feature = "spi", feature = "async"))]
+pub use proxies::AsyncSpiDeviceProxy;
+#[cfg(any(feature = "cortex-m", target_has_atomic = "ptr"))]
+pub use proxies::BoundedProxy;
+#[cfg(feature = "i2c")]
+pub use proxies::BufferedI2cProxy;
+pub use proxies::BusScope;
+#[cfg(feature = "i2c")]
+pub use proxies::ChunkedI2cProxy;
+#[cfg(all(feature = "spi", feature = "eh1"))]
+pub use proxies::CsPolarity;
+pub use proxies::DelayProxy;
+#[cfg(feature = "i2c")]
+pub use proxies::Direction;
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+pub use proxies::Eh0Compat;
+pub use proxies::ExclusiveBus;
+#[cfg(feature = "i2c")]
+pub use proxies::FailoverI2cProxy;
+#[cfg(feature = "i2c")]
 pub use proxies::I2cProxy;
+#[cfg(feature = "i2c")]
+pub use proxies::MappedI2cProxy;
+#[cfg(all(feature = "spi", feature = "eh1"))]
+pub use proxies::NoDelay;
+#[cfg(all(feature = "spi", feature = "eh1"))]
+pub use proxies::OwnedSpiDeviceProxy;
+#[cfg(feature = "i2c")]
+pub use proxies::RecoveringI2cProxy;
+#[cfg(all(feature = "spi", feature = "async"))]
+pub use proxies::RetryPolicy;
+#[cfg(feature = "i2c")]
+pub use proxies::RetryingI2cProxy;
+#[cfg(all(feature = "spi", feature = "eh1"))]
+pub use proxies::ScratchBus;
+#[cfg(feature = "i2c")]
+pub use proxies::SegmentI2cProxy;
+#[cfg(feature = "i2c")]
+pub use proxies::SpeedI2cProxy;
+#[cfg(all(feature = "spi", feature = "eh1"))]
+pub use proxies::SpiDeviceError;
+#[cfg(feature = "spi")]
 pub use proxies::SpiProxy;
+#[cfg(feature = "i2c")]
+pub use proxies::SplitI2cProxy;
+#[cfg(feature = "i2c")]
+pub use proxies::TeeI2cProxy;
+#[cfg(feature = "i2c")]
+pub use proxies::TracingI2cProxy;
+#[cfg(feature = "i2c")]
+pub use rate::Clock;
+#[cfg(feature = "rate-limit")]
+pub use rate::RateLimitedProxy;
+#[cfg(all(feature = "i2c", feature = "std"))]
+pub use rate::StdClock;
+#[cfg(feature = "i2c")]
+pub use rate::{TimedError, TimedI2cProxy};
+#[cfg(all(feature = "std", feature = "i2c"))]
+pub use record::{
+    ParseRecordingError, Record, RecordedError, RecordedOp, Recording, RecordingBus, ReplayBus,
+};
+#[cfg(all(feature = "device-driver", feature = "i2c"))]
+pub use registers::I2cRegisterInterface;
+#[cfg(all(feature = "device-driver", feature = "spi"))]
+pub use registers::SpiRegisterInterface;
+#[cfg(feature = "address-registry")]
+pub use registry::AddressSet;
+#[cfg(feature = "serial")]
+pub use serial::SerialProxy;
+#[cfg(feature = "i2c")]
+pub use smbus::{SmbusError, SmbusProxy};
+pub use tagged::Tagged;
+#[cfg(feature = "std")]
+pub use trace::{TraceEntry, TraceLog, TraceOp, TracingBus};
 
 #[cfg(feature = "cortex-m")]
-pub use mutex::AtomicCheckMutex;
+pub use mutex::{AtomicCheckMutex, DfuGuard};
 
 /// A bus manager for sharing within a single task/thread.
 ///
@@ -191,10 +411,181 @@ pub type BusManagerSimple<BUS> = BusManager<NullMutex<BUS>>;
 ///
 /// [new_std]: ./macro.new_std.html
 ///
+/// Alternatively, a manager with `'static` lifetime can be created by leaking a heap allocated
+/// one.  Unlike the macro, this works any number of times for the same bus type:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # fn _example<I: i2c::Write + Send + 'static>(i2c: I) {
+/// let bus: &'static _ = Box::leak(Box::new(shared_bus::BusManagerStd::new(i2c)));
+///
+/// let mut proxy = bus.acquire_i2c();
+/// std::thread::spawn(move || {
+///     let _ = proxy.write(0x39, &[0xc0, 0xff, 0xee]);
+/// });
+/// # }
+/// ```
+///
 /// This type is only available with the `std` feature.
 #[cfg(feature = "std")]
 pub type BusManagerStd<BUS> = BusManager<::std::sync::Mutex<BUS>>;
 
+/// A bus manager for sharing across threads which can detect bus users holding the bus for too long.
+///
+/// This manager works like the [`BusManagerStd`], but its [`WatchdogMutex`] records when the bus
+/// was locked.  A watchdog thread, started with [`spawn_watchdog()`][spawn_watchdog], calls a
+/// callback whenever the bus stays locked for longer than a given limit, e.g. because a driver
+/// hangs in the middle of a transfer:
+///
+/// ```
+/// # fn _example<I: Send + 'static>(i2c: I) {
+/// let bus: &'static _ = Box::leak(Box::new(shared_bus::BusManagerWatchdog::new(i2c)));
+///
+/// bus.spawn_watchdog(std::time::Duration::from_millis(100), |held| {
+///     eprintln!("Bus is locked for {:?} already!", held);
+/// });
+/// # }
+/// ```
+///
+/// The watchdog is purely advisory:  It cannot break the lock, the callback can only report the
+/// problem or e.g. reset the system.  The callback is called once per overlong lock.
+///
+/// [`BusManagerStd`]: ./type.BusManagerStd.html
+/// [`WatchdogMutex`]: ./struct.WatchdogMutex.html
+/// [spawn_watchdog]: ./struct.BusManager.html#method.spawn_watchdog
+///
+/// This type is only available with the `std` feature.
+#[cfg(feature = "std")]
+pub type BusManagerWatchdog<BUS> = BusManager<WatchdogMutex<BUS>>;
+
+/// A bus manager for sharing across threads which serves the threads in arrival order.
+///
+/// This manager works like the [`BusManagerStd`], but with a [`FairStdMutex`], threads waiting
+/// for the bus acquire it strictly first-come, first-served.  Use it when some threads access the
+/// bus much more often than others, and the rare users must not be starved.  The
+/// [`shared_bus::new_fair_std!()`][new_fair_std] macro creates such a manager with `'static`
+/// lifetime:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # struct SomeI2cBus;
+/// # impl i2c::Write for SomeI2cBus {
+/// #     type Error = ();
+/// #     fn write(&mut self, _: u8, _: &[u8]) -> Result<(), ()> { Ok(()) }
+/// # }
+/// # let i2c = SomeI2cBus;
+/// let bus: &'static _ = shared_bus::new_fair_std!(SomeI2cBus = i2c).unwrap();
+///
+/// let mut proxy = bus.acquire_i2c();
+/// # let t =
+/// std::thread::spawn(move || {
+///     let _ = proxy.write(0x39, &[0xc0, 0xff, 0xee]);
+/// });
+/// # t.join().unwrap();
+/// ```
+///
+/// [`BusManagerStd`]: ./type.BusManagerStd.html
+/// [`FairStdMutex`]: ./struct.FairStdMutex.html
+/// [new_fair_std]: ./macro.new_fair_std.html
+///
+/// This type is only available with the `std` feature.
+#[cfg(feature = "std")]
+pub type BusManagerFairStd<BUS> = BusManager<FairStdMutex<BUS>>;
+
+/// A bus manager for safely sharing between tasks on any platform with a `critical-section`
+/// implementation.
+///
+/// This manager works by entering a critical section for each bus transaction which prevents racy
+/// accesses from different tasks/execution contexts (e.g. interrupts).  The critical section
+/// implementation is provided by the HAL/PAC or runtime of your platform (see the
+/// [`critical-section`][critical-section] crate), so no platform-specific feature needs to be
+/// selected.  Usually, for sharing between tasks, a manager with `'static` lifetime is needed which
+/// can be created using the [`shared_bus::new_auto!()`][new_auto] macro.
+///
+/// [critical-section]: https://docs.rs/critical-section
+/// [new_auto]: ./macro.new_auto.html
+///
+/// This type is only available with the `critical-section` feature.
+#[cfg(feature = "critical-section")]
+pub type BusManagerAuto<BUS> = BusManager<AutoMutex<BUS>>;
+
+/// A bus manager for sharing between async tasks.
+///
+/// This manager uses a [`FairAsyncMutex`] so tasks waiting for the bus are served in the order
+/// they started waiting.  A task waiting for the bus yields to the executor instead of blocking.
+///
+/// [`FairAsyncMutex`]: ./struct.FairAsyncMutex.html
+///
+/// This type is only available with the `async` feature.
+#[cfg(feature = "async")]
+pub type BusManagerAsync<BUS> = AsyncBusManager<FairAsyncMutex<BUS>>;
+
+/// A bus manager for sharing between async drivers within a single task.
+///
+/// This manager uses an [`AsyncNullMutex`] which never waits for the bus, just like the
+/// [`BusManagerSimple`] does for blocking code:
+///
+/// ```
+/// # async fn _example<I: embedded_hal_async::i2c::I2c>(i2c: I) {
+/// # use embedded_hal_async::i2c::I2c;
+/// let bus = shared_bus::BusManagerAsyncSimple::new(i2c);
+///
+/// let mut proxy1 = bus.acquire_i2c();
+/// let mut proxy2 = bus.acquire_i2c();
+///
+/// let _ = proxy1.write(0x39, &[0xc0, 0xff, 0xee]).await;
+/// let _ = proxy2.write(0x48, &[0x00]).await;
+/// # }
+/// ```
+///
+/// [`AsyncNullMutex`]: ./struct.AsyncNullMutex.html
+/// [`BusManagerSimple`]: ./type.BusManagerSimple.html
+///
+/// This type is only available with the `async` feature.
+#[cfg(feature = "async")]
+pub type BusManagerAsyncSimple<BUS> = AsyncBusManager<AsyncNullMutex<BUS>>;
+
+/// A bus manager for sharing between async tasks which never access the bus at the same time.
+///
+/// This manager uses an [`AsyncAtomicCheckMutex`] which panics on overlapping bus access instead
+/// of waiting, just like the [`BusManagerAtomicCheck`] does for blocking code.
+///
+/// [`AsyncAtomicCheckMutex`]: ./struct.AsyncAtomicCheckMutex.html
+/// [`BusManagerAtomicCheck`]: ./type.BusManagerAtomicCheck.html
+///
+/// This type is only available with the `async` feature.
+#[cfg(feature = "async")]
+pub type BusManagerAsyncAtomicCheck<BUS> = AsyncBusManager<AsyncAtomicCheckMutex<BUS>>;
+
+/// A bus manager for sharing between async tasks with different priorities.
+///
+/// This manager uses a [`PriorityAsyncMutex`] so the waiting task with the highest priority gets
+/// the bus next.  Give each task a proxy with its priority using
+/// [`acquire_i2c_with_priority()`][acquire]:
+///
+/// ```
+/// # async fn _example<I: embedded_hal_async::i2c::I2c>(i2c: I) {
+/// # use embedded_hal_async::i2c::I2c;
+/// let bus = shared_bus::BusManagerAsyncPriority::new(i2c);
+///
+/// let mut sensor = bus.acquire_i2c_with_priority(10);
+/// let mut housekeeping = bus.acquire_i2c_with_priority(1);
+///
+/// // Under contention, `sensor` gets the bus before `housekeeping`
+/// # let _ = sensor.write(0x48, &[0x00]).await;
+/// # let _ = housekeeping.write(0x50, &[0x00]).await;
+/// # }
+/// ```
+///
+/// [`PriorityAsyncMutex`]: ./struct.PriorityAsyncMutex.html
+/// [acquire]: ./struct.AsyncBusManager.html#method.acquire_i2c_with_priority
+///
+/// This type is only available with the `priority` feature.
+#[cfg(feature = "priority")]
+pub type BusManagerAsyncPriority<BUS> = AsyncBusManager<PriorityAsyncMutex<BUS>>;
+
 /// A bus manager for safely sharing between tasks on Cortex-M.
 ///
 /// This manager works by turning off interrupts for each bus transaction which prevents racy
@@ -278,8 +669,59 @@ pub type BusManagerXtensa<BUS> = BusManager<XtensaMutex<BUS>>;
 /// }
 /// ```
 ///
+/// ## Usage without RTIC
+/// The manager can also be used on bare-metal, without a concurrency framework, as long as the
+/// application itself ensures bus accesses never overlap.  A common pattern is sharing the bus
+/// between the main loop and an interrupt handler where the main loop masks the interrupt while
+/// it is using the bus:
+///
+/// ```ignore
+/// use core::cell::RefCell;
+/// use cortex_m::interrupt::Mutex;
+///
+/// type Proxy = shared_bus::I2cProxy<'static, shared_bus::AtomicCheckMutex<I2c>>;
+///
+/// // Used for handing the proxy over to the interrupt handler
+/// static IRQ_SENSOR: Mutex<RefCell<Option<Sensor<Proxy>>>> = Mutex::new(RefCell::new(None));
+///
+/// #[entry]
+/// fn main() -> ! {
+///     let bus: &'static _ = shared_bus::new_atomic_check!(I2c = i2c).unwrap();
+///
+///     let mut display = Display::new(bus.acquire_i2c());
+///     let sensor = Sensor::new(bus.acquire_i2c());
+///     cortex_m::interrupt::free(|cs| IRQ_SENSOR.borrow(cs).replace(Some(sensor)));
+///     unsafe { NVIC::unmask(Interrupt::EXTI0) };
+///
+///     loop {
+///         // The interrupt must not access the bus while the main loop does
+///         NVIC::mask(Interrupt::EXTI0);
+///         display.update().unwrap();
+///         unsafe { NVIC::unmask(Interrupt::EXTI0) };
+///     }
+/// }
+///
+/// #[interrupt]
+/// fn EXTI0() {
+///     static mut SENSOR: Option<Sensor<Proxy>> = None;
+///
+///     let sensor = SENSOR.get_or_insert_with(|| {
+///         cortex_m::interrupt::free(|cs| IRQ_SENSOR.borrow(cs).take().unwrap())
+///     });
+///     sensor.sample().unwrap();
+/// }
+/// ```
+///
+/// If the main loop forgets to mask the interrupt, the overlapping access is detected and the
+/// manager panics.  Note that waiting for the bus to become available instead is not an option
+/// here:  The interrupt handler would wait for the main loop, which cannot continue before the
+/// handler returns.  If overlapping accesses are expected, use a manager that locks the bus, like
+/// [`BusManagerAuto`] or [`BusManagerCortexM`].
+///
 /// [new_atomic_check]: ./macro.new_atomic_check.html
 /// [shared-bus-rtic-example]: https://github.com/ryan-summers/shared-bus-example/blob/master/src/main.rs
+/// [`BusManagerAuto`]: ./type.BusManagerAuto.html
+/// [`BusManagerCortexM`]: ./type.BusManagerCortexM.html
 ///
 /// This type is only available with the `cortex-m` feature (but this may change in the future!).
 #[cfg(feature = "cortex-m")]
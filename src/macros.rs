@@ -1,3 +1,63 @@
+/// Error returned by the macros creating a bus manager with `'static` lifetime.
+///
+/// Each invocation of a macro like [`new_std!()`] owns a single `static` bus manager, so it can
+/// only create its manager once (e.g. calling a function containing the macro a second time
+/// fails).  The error hands the bus back, so it can be used otherwise:
+///
+/// ```
+/// # struct SomeI2cBus;
+/// fn create(i2c: SomeI2cBus) -> Result<
+///     &'static shared_bus::BusManagerStd<SomeI2cBus>,
+///     shared_bus::BusManagerError<SomeI2cBus>,
+/// > {
+///     shared_bus::new_std!(SomeI2cBus = i2c)
+/// }
+///
+/// let bus = create(SomeI2cBus).unwrap();
+/// if let Err(e) = create(SomeI2cBus) {
+///     let i2c: SomeI2cBus = e.into_bus();
+/// }
+/// ```
+///
+/// The error is `Debug` even when the bus is not, so `unwrap()` works for all bus types.
+///
+/// [`new_std!()`]: ./macro.new_std.html
+pub enum BusManagerError<B> {
+    /// The bus manager of this macro invocation was created before.
+    AlreadyInitialized(B),
+}
+
+impl<B> BusManagerError<B> {
+    /// Get the bus back.
+    pub fn into_bus(self) -> B {
+        match self {
+            BusManagerError::AlreadyInitialized(bus) => bus,
+        }
+    }
+}
+
+impl<B> core::fmt::Debug for BusManagerError<B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BusManagerError::AlreadyInitialized(_) => f.write_str("AlreadyInitialized(..)"),
+        }
+    }
+}
+
+impl<B> core::fmt::Display for BusManagerError<B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BusManagerError::AlreadyInitialized(_) => f.write_str(
+                "the bus manager of this macro invocation was already created, \
+                 each macro invocation can only create its manager once",
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<B> std::error::Error for BusManagerError<B> {}
+
 /// Macro for creating a `std`-based bus manager with `'static` lifetime.
 ///
 /// This macro is a convenience helper for creating a bus manager that lives for the `'static`
@@ -10,8 +70,13 @@
 /// let bus = shared_bus::new_std!(<Full Bus Type Signature> = <bus>).unwrap();
 /// ```
 ///
-/// The macro returns an Option which will be `Some(&'static bus_manager)` on the first run and
-/// `None` afterwards.  This is necessary to uphold safety around the inner `static` variable.
+/// The macro returns a `Result` which will be `Ok(&'static bus_manager)` on the first run and
+/// `Err(BusManagerError::AlreadyInitialized(bus))` afterwards.  This is necessary to uphold
+/// safety around the inner `static` variable.  The `<bus>` expression is evaluated exactly once
+/// per invocation, and the error hands the bus back, so it is not lost.  See
+/// [`BusManagerError`] for details.
+///
+/// [`BusManagerError`]: ./enum.BusManagerError.html
 ///
 /// # Example
 /// ```
@@ -44,14 +109,46 @@
 #[macro_export]
 macro_rules! new_std {
     ($bus_type:ty = $bus:expr) => {{
-        use $crate::once_cell::sync::OnceCell;
+        use ::std::sync::OnceLock;
+
+        static MANAGER: OnceLock<$crate::BusManagerStd<$bus_type>> = OnceLock::new();
+
+        let mut bus = Some($bus);
+        let m = MANAGER.get_or_init(|| $crate::BusManagerStd::new(bus.take().unwrap()));
+        match bus {
+            None => Ok(m),
+            Some(bus) => Err($crate::BusManagerError::AlreadyInitialized(bus)),
+        }
+    }};
+}
+
+/// Macro for creating a [`BusManagerFairStd`] with `'static` lifetime.
+///
+/// This works just like [`new_std!()`], but the manager serves threads waiting for the bus in
+/// arrival order.
+///
+/// [`BusManagerFairStd`]: ./type.BusManagerFairStd.html
+/// [`new_std!()`]: ./macro.new_std.html
+///
+/// This macro is only available with the `std` feature.
+///
+/// # Syntax
+/// ```ignore
+/// let bus = shared_bus::new_fair_std!(<Full Bus Type Signature> = <bus>).unwrap();
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! new_fair_std {
+    ($bus_type:ty = $bus:expr) => {{
+        use ::std::sync::OnceLock;
 
-        static MANAGER: OnceCell<$crate::BusManagerStd<$bus_type>> = OnceCell::new();
+        static MANAGER: OnceLock<$crate::BusManagerFairStd<$bus_type>> = OnceLock::new();
 
-        let m = $crate::BusManagerStd::new($bus);
-        match MANAGER.set(m) {
-            Ok(_) => MANAGER.get(),
-            Err(_) => None,
+        let mut bus = Some($bus);
+        let m = MANAGER.get_or_init(|| $crate::BusManagerFairStd::new(bus.take().unwrap()));
+        match bus {
+            None => Ok(m),
+            Some(bus) => Err($crate::BusManagerError::AlreadyInitialized(bus)),
         }
     }};
 }
@@ -68,8 +165,13 @@ macro_rules! new_std {
 /// let bus = shared_bus::new_cortexm!(<Full Bus Type Signature> = <bus>).unwrap();
 /// ```
 ///
-/// The macro returns an Option which will be `Some(&'static bus_manager)` on the first run and
-/// `None` afterwards.  This is necessary to uphold safety around the inner `static` variable.
+/// The macro returns a `Result` which will be `Ok(&'static bus_manager)` on the first run and
+/// `Err(BusManagerError::AlreadyInitialized(bus))` afterwards.  This is necessary to uphold
+/// safety around the inner `static` variable.  The `<bus>` expression is evaluated exactly once
+/// per invocation, and the error hands the bus back, so it is not lost.  See
+/// [`BusManagerError`] for details.
+///
+/// [`BusManagerError`]: ./enum.BusManagerError.html
 ///
 /// # Example
 /// ```no_run
@@ -124,12 +226,76 @@ macro_rules! new_std {
 #[macro_export]
 macro_rules! new_cortexm {
     ($bus_type:ty = $bus:expr) => {{
+        // Evaluate the bus expression exactly once, even when the singleton was already taken.
+        let mut bus = Some($bus);
         let m: Option<&'static mut _> = $crate::cortex_m::singleton!(
-            : $crate::BusManagerCortexM<$bus_type> =
-                $crate::BusManagerCortexM::new($bus)
+            : $crate::BusManagerCortexM<$bus_type> = $crate::BusManagerCortexM::new(bus.take().unwrap())
         );
 
-        m
+        match (m, bus) {
+            (Some(m), _) => Ok(m),
+            (None, bus) => Err($crate::BusManagerError::AlreadyInitialized(bus.unwrap())),
+        }
+    }};
+}
+
+/// Macro for creating a `critical-section` based bus manager with `'static` lifetime.
+///
+/// This macro is a convenience helper for creating a bus manager that lives for the `'static`
+/// lifetime an thus can be safely shared across tasks/execution contexts (like interrupts).
+///
+/// This macro is only available with the `critical-section` feature.
+///
+/// # Syntax
+/// ```ignore
+/// let bus = shared_bus::new_auto!(<Full Bus Type Signature> = <bus>).unwrap();
+/// ```
+///
+/// The macro returns a `Result` which will be `Ok(&'static bus_manager)` on the first run and
+/// `Err(BusManagerError::AlreadyInitialized(bus))` afterwards.  This is necessary to uphold
+/// safety around the inner `static` variable.  The `<bus>` expression is evaluated exactly once
+/// per invocation, and the error hands the bus back, so it is not lost.  See
+/// [`BusManagerError`] for details.
+///
+/// [`BusManagerError`]: ./enum.BusManagerError.html
+///
+/// # Example
+/// ```
+/// # use embedded_hal::blocking::i2c::Write;
+/// # struct SomeI2cBus;
+/// # impl Write for SomeI2cBus {
+/// #     type Error = ();
+/// #     fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+/// # }
+/// # let i2c = SomeI2cBus;
+/// // For example:
+/// // let i2c = I2c::i2c1(dp.I2C1, (scl, sda), 90.khz(), clocks, &mut rcc.apb1);
+///
+/// // The bus is a 'static reference -> it lives forever and references can be
+/// // shared with other tasks.
+/// let bus: &'static _ = shared_bus::new_auto!(SomeI2cBus = i2c).unwrap();
+///
+/// let mut proxy1 = bus.acquire_i2c();
+/// proxy1.write(0x39, &[0xaa]).unwrap();
+/// ```
+#[cfg(feature = "critical-section")]
+#[macro_export]
+macro_rules! new_auto {
+    ($bus_type:ty = $bus:expr) => {{
+        static mut MANAGER: Option<$crate::BusManagerAuto<$bus_type>> = None;
+
+        let bus = $bus;
+        $crate::critical_section::with(|_| {
+            // SAFETY: Accesses to MANAGER only happen inside this critical section and a
+            // reference is only handed out once.
+            let manager = unsafe { &mut *::core::ptr::addr_of_mut!(MANAGER) };
+            if manager.is_some() {
+                Err($crate::BusManagerError::AlreadyInitialized(bus))
+            } else {
+                let m: &'static mut _ = manager.insert($crate::BusManagerAuto::new(bus));
+                Ok(m)
+            }
+        })
     }};
 }
 
@@ -145,31 +311,64 @@ macro_rules! new_cortexm {
 /// let bus = shared_bus::new_xtensa!(<Full Bus Type Signature> = <bus>).unwrap();
 /// ```
 ///
-/// The macro returns an Option which will be `Some(&'static bus_manager)` on the first run and
-/// `None` afterwards.  This is necessary to uphold safety around the inner `static` variable.
+/// The macro returns a `Result` which will be `Ok(&'static bus_manager)` on the first run and
+/// `Err(BusManagerError::AlreadyInitialized(bus))` afterwards.  This is necessary to uphold
+/// safety around the inner `static` variable.  The `<bus>` expression is evaluated exactly once
+/// per invocation, and the error hands the bus back, so it is not lost.  See
+/// [`BusManagerError`] for details.
+///
+/// [`BusManagerError`]: ./enum.BusManagerError.html
 #[cfg(feature = "xtensa")]
 #[macro_export]
 macro_rules! new_xtensa {
     ($bus_type:ty = $bus:expr) => {{
+        // Evaluate the bus expression exactly once, even when the singleton was already taken.
+        let mut bus = Some($bus);
         let m: Option<&'static mut _> = $crate::xtensa_lx::singleton!(
-            : $crate::BusManagerXtensa<$bus_type> =
-                $crate::BusManagerXtensa::new($bus)
+            : $crate::BusManagerXtensa<$bus_type> = $crate::BusManagerXtensa::new(bus.take().unwrap())
         );
 
-        m
+        match (m, bus) {
+            (Some(m), _) => Ok(m),
+            (None, bus) => Err($crate::BusManagerError::AlreadyInitialized(bus.unwrap())),
+        }
     }};
 }
 
 /// Construct a statically allocated bus manager.
+///
+/// This macro is a convenience helper for creating a [`BusManagerAtomicCheck`] that lives for the
+/// `'static` lifetime and thus can be shared with RTIC tasks or interrupt handlers.
+///
+/// [`BusManagerAtomicCheck`]: ./type.BusManagerAtomicCheck.html
+///
+/// This macro is only available with the `cortex-m` feature.
+///
+/// # Syntax
+/// ```ignore
+/// let bus = shared_bus::new_atomic_check!(<Full Bus Type Signature> = <bus>).unwrap();
+/// ```
+///
+/// The macro returns a `Result` which will be `Ok(&'static bus_manager)` on the first run and
+/// `Err(BusManagerError::AlreadyInitialized(bus))` afterwards.  This is necessary to uphold
+/// safety around the inner `static` variable.  The `<bus>` expression is evaluated exactly once
+/// per invocation, and the error hands the bus back, so it is not lost.  See
+/// [`BusManagerError`] for details.
+///
+/// [`BusManagerError`]: ./enum.BusManagerError.html
 #[cfg(feature = "cortex-m")]
 #[macro_export]
 macro_rules! new_atomic_check {
     ($bus_type:ty = $bus:expr) => {{
+        // Evaluate the bus expression exactly once, even when the singleton was already taken.
+        let mut bus = Some($bus);
         let m: Option<&'static mut _> = $crate::cortex_m::singleton!(
-            : $crate::BusManagerAtomicCheck<$bus_type> =
-                $crate::BusManagerAtomicCheck::new($bus)
+            : $crate::BusManagerAtomicCheck<$bus_type> = $crate::BusManagerAtomicCheck::new(bus.take().unwrap())
         );
 
-        m
+        match (m, bus) {
+            (Some(m), _) => Ok(m),
+            (None, bus) => Err($crate::BusManagerError::AlreadyInitialized(bus.unwrap())),
+        }
     }};
 }
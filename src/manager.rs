@@ -1,3 +1,11 @@
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+use embedded_hal_1::i2c as i2c_eh1;
+
+#[cfg(feature = "adc")]
+use embedded_hal::adc;
+#[cfg(feature = "i2c")]
+use embedded_hal::blocking::i2c;
+
 /// "Manager" for a shared bus.
 ///
 /// The manager owns the original bus peripheral (wrapped inside a mutex) and hands out proxies
@@ -12,10 +20,14 @@
 /// | --- | --- | --- | --- |
 /// | [`BusManagerSimple`] | `shared_bus::NullMutex` | always available | For sharing within a single execution context. |
 /// | [`BusManagerStd`] | `std::sync::Mutex` | `std` | For platforms where `std` is available. |
+/// | [`BusManagerWatchdog`] | `shared_bus::WatchdogMutex` | `std` | Like `BusManagerStd`, but can detect a bus which stays locked for too long. |
 /// | [`BusManagerCortexM`] | `cortex_m::interrupt::Mutex` | `cortex-m` | For Cortex-M platforms; Uses a critcal section (i.e. turns off interrupts during bus transactions). |
+/// | [`BusManagerAuto`] | `critical_section::Mutex` | `critical-section` | For any platform with a `critical-section` implementation. |
 ///
 /// [`BusManagerSimple`]: ./type.BusManagerSimple.html
+/// [`BusManagerAuto`]: ./type.BusManagerAuto.html
 /// [`BusManagerStd`]: ./type.BusManagerStd.html
+/// [`BusManagerWatchdog`]: ./type.BusManagerWatchdog.html
 /// [`BusManagerCortexM`]: ./type.BusManagerCortexM.html
 ///
 /// # Constructing a `BusManager`
@@ -83,19 +95,149 @@
 ///    For other platforms, similar macros exist (e.g. [`new_cortexm!()`]).
 ///
 /// [`new_cortexm!()`]: ./macro.new_cortexm.html
+///
+/// # Using the manager directly
+/// The I2C and ADC traits are also implemented for `&BusManager`, locking the bus for each
+/// operation.  When there is only a single device for now, a reference to the manager can thus
+/// be passed to its driver directly, without acquiring a proxy:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # struct MyDevice<T>(T);
+/// # impl<T: i2c::Write> MyDevice<T> {
+/// #     pub fn new(t: T) -> Self { MyDevice(t) }
+/// # }
+/// # fn _example(i2c: impl i2c::Write) {
+/// let bus = shared_bus::BusManagerSimple::new(i2c);
+///
+/// let my_device = MyDevice::new(&bus);
+/// # }
+/// ```
+///
+/// SPI is excluded from this for the same reasons as for the [`SpiProxy`].
+///
+/// # Const construction
+/// A bus manager can be created in const context using [`from_mutex()`][from_mutex] and a mutex
+/// with a `const` constructor, like [`NullMutex::new()`], [`AtomicCheckMutex::new()`], or
+/// `std::sync::Mutex::new()`.  [`acquire_i2c()`][acquire_i2c] is a `const fn` as well, so a
+/// `static` table of devices can hold its proxies without any runtime initialization:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # pub struct SomeI2cBus;
+/// # impl i2c::Write for SomeI2cBus {
+/// #     type Error = ();
+/// #     fn write(&mut self, _: u8, _: &[u8]) -> Result<(), ()> { Ok(()) }
+/// # }
+/// use shared_bus::{BusManager, I2cProxy};
+/// use std::sync::Mutex;
+///
+/// struct Device {
+///     name: &'static str,
+///     address: u8,
+///     proxy: I2cProxy<'static, Mutex<SomeI2cBus>>,
+/// }
+///
+/// static BUS: BusManager<Mutex<SomeI2cBus>> = BusManager::from_mutex(Mutex::new(SomeI2cBus));
+///
+/// static DEVICES: [Device; 2] = [
+///     Device { name: "temperature", address: 0x48, proxy: BUS.acquire_i2c() },
+///     Device { name: "eeprom", address: 0x50, proxy: BUS.acquire_i2c() },
+/// ];
+///
+/// for device in DEVICES.iter() {
+///     // The I2C traits are implemented for `&I2cProxy` as well.
+///     i2c::Write::write(&mut &device.proxy, device.address, &[0x00]).unwrap();
+/// }
+/// ```
+///
+/// This needs a `const`-constructible bus, of course.  Because a `static` must be `Sync`, the
+/// [`NullMutex`] (which is based on a `RefCell`) can be created in const context but cannot be
+/// placed in a `static`.  All other `acquire_*()` methods do work at runtime (e.g. registering
+/// addresses or taking the bus) and are thus not `const`.
+///
+/// [`SpiProxy`]: ./struct.SpiProxy.html
+/// [from_mutex]: #method.from_mutex
+/// [acquire_i2c]: #method.acquire_i2c
+/// [`NullMutex::new()`]: ./struct.NullMutex.html#method.new
+/// [`NullMutex`]: ./struct.NullMutex.html
+/// [`AtomicCheckMutex::new()`]: ./struct.AtomicCheckMutex.html#method.new
 #[derive(Debug)]
 pub struct BusManager<M> {
     mutex: M,
+    #[cfg(feature = "address-registry")]
+    addresses: crate::registry::AddressRegistry,
+    #[cfg(feature = "metrics")]
+    metrics: crate::metrics::ByteCounters,
+    #[cfg(feature = "rate-limit")]
+    last_rate_limited: crate::rate::LastTransaction,
 }
 
 impl<M: crate::BusMutex> BusManager<M> {
     /// Create a new bus manager for a bus.
     ///
     /// See the documentation for `BusManager` for more details.
-    pub fn new(bus: M::Bus) -> Self {
+    pub fn new(bus: M::Bus) -> Self
+    where
+        M: crate::CreateBusMutex,
+    {
         let mutex = M::create(bus);
 
-        BusManager { mutex }
+        Self::from_mutex(mutex)
+    }
+
+    /// Create a new bus manager for the bus returned by `f`, if there is one.
+    ///
+    /// This is useful for peripherals which are obtained from a singleton that can only be taken
+    /// once:
+    ///
+    /// ```
+    /// # use embedded_hal::blocking::i2c;
+    /// # struct I2c;
+    /// # impl I2c { fn take() -> Option<I2c> { Some(I2c) } }
+    /// let bus: Option<shared_bus::BusManagerSimple<_>> = shared_bus::BusManager::try_new(I2c::take);
+    /// # assert!(bus.is_some());
+    /// ```
+    pub fn try_new(f: impl FnOnce() -> Option<M::Bus>) -> Option<Self>
+    where
+        M: crate::CreateBusMutex,
+    {
+        f().map(Self::new)
+    }
+
+    /// Create a new bus manager from an existing mutex which already contains the bus.
+    ///
+    /// This is needed for mutex types which can't be created from the bus alone, like the
+    /// [`ClosureMutex`].
+    ///
+    /// This is a `const fn`, see [const construction](#const-construction).
+    ///
+    /// [`ClosureMutex`]: ./struct.ClosureMutex.html
+    pub const fn from_mutex(mutex: M) -> Self {
+        BusManager {
+            mutex,
+            #[cfg(feature = "address-registry")]
+            addresses: crate::registry::AddressRegistry::new(),
+            #[cfg(feature = "metrics")]
+            metrics: crate::metrics::ByteCounters::new(),
+            #[cfg(feature = "rate-limit")]
+            last_rate_limited: crate::rate::LastTransaction::new(),
+        }
+    }
+}
+
+impl<M, B, F> BusManager<M>
+where
+    M: crate::CreateBusMutex<Bus = crate::LazyBus<B, F>>,
+    F: FnOnce() -> B,
+{
+    /// Create a new bus manager for a bus which is only created by `init` on its first use.
+    ///
+    /// See [`LazyBus`] for details.
+    ///
+    /// [`LazyBus`]: ./struct.LazyBus.html
+    pub fn new_lazy(init: F) -> Self {
+        Self::new(crate::LazyBus::new(init))
     }
 }
 
@@ -127,8 +269,508 @@ impl<M: crate::BusMutex> BusManager<M> {
     /// my_device.do_something_on_the_bus();
     /// # }
     /// ```
-    pub fn acquire_i2c<'a>(&'a self) -> crate::I2cProxy<'a, M> {
-        crate::I2cProxy { mutex: &self.mutex }
+    ///
+    /// This is a `const fn`, see [const construction](#const-construction).
+    #[cfg(feature = "i2c")]
+    pub const fn acquire_i2c<'a>(&'a self) -> crate::I2cProxy<'a, M> {
+        crate::I2cProxy {
+            mutex: &self.mutex,
+            #[cfg(feature = "metrics")]
+            metrics: &self.metrics,
+        }
+    }
+
+    /// Acquire an I2C proxy for this bus, converted into a bus type of a driver crate.
+    ///
+    /// Some driver crates define their own bus abstraction and an interface type implementing it,
+    /// which is created `From` an I2C peripheral.  This method acquires an [`I2cProxy`] and
+    /// converts it, so such drivers can sit on the shared bus without naming the proxy type:
+    ///
+    /// [`I2cProxy`]: ./struct.I2cProxy.html
+    ///
+    /// ```
+    /// use embedded_hal::blocking::i2c;
+    ///
+    /// // The bus abstraction of some driver crate
+    /// pub trait RegisterBus {
+    ///     type Error;
+    ///
+    ///     fn read_register(&mut self, reg: u8) -> Result<u8, Self::Error>;
+    /// }
+    ///
+    /// pub struct I2cInterface<I2C>(I2C);
+    ///
+    /// impl<I2C> From<I2C> for I2cInterface<I2C> {
+    ///     fn from(i2c: I2C) -> Self {
+    ///         I2cInterface(i2c)
+    ///     }
+    /// }
+    ///
+    /// impl<I2C: i2c::WriteRead> RegisterBus for I2cInterface<I2C> {
+    ///     type Error = I2C::Error;
+    ///
+    ///     fn read_register(&mut self, reg: u8) -> Result<u8, Self::Error> {
+    ///         let mut buf = [0u8];
+    ///         self.0.write_read(0x20, &[reg], &mut buf)?;
+    ///         Ok(buf[0])
+    ///     }
+    /// }
+    ///
+    /// # fn _example(i2c: impl i2c::WriteRead) {
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    ///
+    /// let mut interface: I2cInterface<_> = bus.acquire_i2c_into();
+    /// let _ = interface.read_register(0x0f);
+    /// # }
+    /// ```
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c_into<'a, B>(&'a self) -> B
+    where
+        B: From<crate::I2cProxy<'a, M>>,
+    {
+        B::from(self.acquire_i2c())
+    }
+
+    /// Acquire an I2C proxy for this bus, hiding its concrete type.
+    ///
+    /// This works just like [`acquire_i2c()`][acquire_i2c] but the proxy is only known as an
+    /// `embedded-hal` 1.0 `I2c` with the error type of the bus.  The mutex type thus doesn't leak
+    /// into the signatures of application code:
+    ///
+    /// ```
+    /// use embedded_hal_1::i2c::{ErrorType, I2c};
+    ///
+    /// fn make_sensor<I: I2c>(i2c: I) -> impl FnMut() -> Result<u16, I::Error> {
+    ///     let mut i2c = i2c;
+    ///     move || {
+    ///         let mut buf = [0u8; 2];
+    ///         i2c.write_read(0x48, &[0x00], &mut buf)?;
+    ///         Ok(u16::from_be_bytes(buf))
+    ///     }
+    /// }
+    ///
+    /// # fn _example<B: I2c>(i2c: B) {
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    ///
+    /// let proxy = bus.acquire_i2c_impl();
+    /// let mut read_temperature = make_sensor(proxy);
+    /// # let _: Result<u16, <B as ErrorType>::Error> = read_temperature();
+    /// # }
+    /// ```
+    ///
+    /// [acquire_i2c]: #method.acquire_i2c
+    ///
+    /// This method is only available with the `eh1` feature.
+    #[cfg(all(feature = "i2c", feature = "eh1"))]
+    pub fn acquire_i2c_impl(
+        &self,
+    ) -> impl i2c_eh1::I2c<Error = <M::Bus as i2c_eh1::ErrorType>::Error> + '_
+    where
+        M::Bus: i2c_eh1::I2c,
+    {
+        self.acquire_i2c()
+    }
+
+    /// Lend the bus to a closure as an `embedded-hal` 1.0 `I2c` trait object.
+    ///
+    /// This is meant for one-off accesses, e.g. initialization routines which touch several
+    /// devices once, without acquiring and storing a proxy.  The bus stays locked until the
+    /// closure returns:
+    ///
+    /// ```
+    /// # use embedded_hal_1::i2c::I2c;
+    /// # fn _example(i2c: impl I2c) {
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    ///
+    /// let res = bus.borrow_i2c(|i2c| {
+    ///     i2c.write(0x39, &[0x80, 0x03])?;
+    ///     i2c.write(0x48, &[0x01, 0x60])
+    /// });
+    /// # }
+    /// ```
+    ///
+    /// As the bus is locked, other proxies of this manager must not be used inside the closure.
+    ///
+    /// This method is only available with the `eh1` feature.
+    #[cfg(all(feature = "i2c", feature = "eh1"))]
+    pub fn borrow_i2c<R>(
+        &self,
+        f: impl FnOnce(&mut dyn i2c_eh1::I2c<Error = <M::Bus as i2c_eh1::ErrorType>::Error>) -> R,
+    ) -> R
+    where
+        M::Bus: i2c_eh1::I2c,
+    {
+        self.mutex.lock(|bus| f(bus))
+    }
+
+    /// Acquire an [`AddressedI2cProxy`] for the device at address `ADDR`.
+    ///
+    /// [`AddressedI2cProxy`]: ./struct.AddressedI2cProxy.html
+    ///
+    /// With the `address-registry` feature, the manager remembers the addresses of all
+    /// `AddressedI2cProxy`s it handed out and logs a warning when a second proxy is acquired for
+    /// the same address.  This usually means two drivers were configured for the same device.
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c_at<'a, const ADDR: u8>(&'a self) -> crate::AddressedI2cProxy<'a, ADDR, M> {
+        #[cfg(feature = "address-registry")]
+        self.addresses.register(ADDR);
+
+        crate::AddressedI2cProxy { mutex: &self.mutex }
+    }
+
+    /// Check that all devices registered with [`acquire_i2c_at()`][acquire_i2c_at] respond.
+    ///
+    /// Each registered address is pinged with an empty write.  Returns the set of addresses
+    /// which did not acknowledge, for failing early during bring-up when the wiring is wrong:
+    ///
+    /// ```
+    /// # use embedded_hal::blocking::i2c;
+    /// # fn _example(i2c: impl i2c::Write) {
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    /// let sensor = bus.acquire_i2c_at::<0x48>();
+    /// let eeprom = bus.acquire_i2c_at::<0x50>();
+    ///
+    /// if let Err(missing) = bus.validate_devices() {
+    ///     for addr in missing.iter() {
+    ///         println!("No device at 0x{:02x}", addr);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// The bus is locked separately for each ping, so other proxies are not starved.  Note that
+    /// with `embedded-hal` 0.2, a missing acknowledge cannot be told apart from other bus errors,
+    /// so any error counts as a missing device.
+    ///
+    /// [acquire_i2c_at]: #method.acquire_i2c_at
+    ///
+    /// This method is only available with the `address-registry` feature.
+    #[cfg(feature = "address-registry")]
+    pub fn validate_devices(&self) -> Result<(), crate::AddressSet>
+    where
+        M::Bus: i2c::Write,
+    {
+        let mut missing = crate::AddressSet::default();
+        for addr in self.addresses.acquired().iter() {
+            if self
+                .mutex
+                .lock(|bus| i2c::Write::write(bus, addr, &[]))
+                .is_err()
+            {
+                missing.insert(addr);
+            }
+        }
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
+        }
+    }
+
+    /// Acquire a [`TracingI2cProxy`] for this bus.
+    ///
+    /// [`TracingI2cProxy`]: ./struct.TracingI2cProxy.html
+    ///
+    /// The proxy works like a normal [`I2cProxy`] but additionally calls `callback` with the
+    /// address, direction, and data of each successful transfer.  This is useful for debugging
+    /// the communication of a driver without modifying it:
+    ///
+    /// [`I2cProxy`]: ./struct.I2cProxy.html
+    ///
+    /// ```
+    /// # use embedded_hal::blocking::i2c;
+    /// # fn _example(i2c: impl i2c::Write) {
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    ///
+    /// let proxy = bus.acquire_i2c_traced(|addr, direction, bytes: &[u8]| {
+    ///     println!("{:02x} {:?}: {:02x?}", addr, direction, bytes);
+    /// });
+    /// # }
+    /// ```
+    ///
+    /// For buses with 10-bit addresses, use [`acquire_i2c_traced_ten_bit()`] instead.
+    ///
+    /// [`acquire_i2c_traced_ten_bit()`]: #method.acquire_i2c_traced_ten_bit
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c_traced<'a, F>(&'a self, callback: F) -> crate::TracingI2cProxy<'a, M, F>
+    where
+        F: FnMut(u8, crate::Direction, &[u8]),
+    {
+        crate::TracingI2cProxy {
+            mutex: &self.mutex,
+            callback,
+        }
+    }
+
+    /// Acquire a [`TracingI2cProxy`] for a bus with 10-bit addresses.
+    ///
+    /// [`TracingI2cProxy`]: ./struct.TracingI2cProxy.html
+    ///
+    /// This works like [`acquire_i2c_traced()`] but `callback` gets the 10-bit address of each
+    /// transfer.  The proxy implements the `embedded-hal` 1.0 `I2c<TenBitAddress>` trait.
+    ///
+    /// [`acquire_i2c_traced()`]: #method.acquire_i2c_traced
+    ///
+    /// This method is only available with the `eh1` feature.
+    #[cfg(all(feature = "i2c", feature = "eh1"))]
+    pub fn acquire_i2c_traced_ten_bit<'a, F>(
+        &'a self,
+        callback: F,
+    ) -> crate::TracingI2cProxy<'a, M, F>
+    where
+        F: FnMut(u16, crate::Direction, &[u8]),
+    {
+        crate::TracingI2cProxy {
+            mutex: &self.mutex,
+            callback,
+        }
+    }
+
+    /// Acquire a [`RetryingI2cProxy`] for this bus.
+    ///
+    /// [`RetryingI2cProxy`]: ./struct.RetryingI2cProxy.html
+    ///
+    /// The proxy works like a normal [`I2cProxy`] but retries an operation up to `retries` times
+    /// when it fails with an error for which `is_retryable` returns `true`.  As `embedded-hal`
+    /// errors are opaque, the predicate needs to be supplied by the user.  For example, to retry
+    /// interrupted system calls of a Linux `I2cdev`:
+    ///
+    /// [`I2cProxy`]: ./struct.I2cProxy.html
+    ///
+    /// ```ignore
+    /// let i2c = linux_embedded_hal::I2cdev::new("/dev/i2c-1").unwrap();
+    /// let bus: &'static _ = shared_bus::new_std!(I2cdev = i2c).unwrap();
+    ///
+    /// let proxy = bus.acquire_i2c_retrying(3, |e: &LinuxI2CError| {
+    ///     matches!(e, LinuxI2CError::Nix(nix::Error::EINTR))
+    /// });
+    /// ```
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c_retrying<'a, F>(
+        &'a self,
+        retries: usize,
+        is_retryable: F,
+    ) -> crate::RetryingI2cProxy<'a, M, F> {
+        crate::RetryingI2cProxy {
+            mutex: &self.mutex,
+            retries,
+            is_retryable,
+        }
+    }
+
+    /// Acquire a [`SpeedI2cProxy`] for a device which needs the bus clocked at `hz`.
+    ///
+    /// [`SpeedI2cProxy`]: ./struct.SpeedI2cProxy.html
+    ///
+    /// Before each transaction, `set_speed` is called with the bus and `hz`, while holding the
+    /// bus lock.  This way, a slow device can share the bus with fast ones:
+    ///
+    /// ```
+    /// # struct I2c;
+    /// # impl I2c { fn set_clock_speed(&mut self, _hz: u32) {} }
+    /// # let i2c = I2c;
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    ///
+    /// let eeprom = bus.acquire_i2c_at_speed(100_000, I2c::set_clock_speed);
+    /// let imu = bus.acquire_i2c_at_speed(400_000, I2c::set_clock_speed);
+    /// ```
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c_at_speed<'a, F>(
+        &'a self,
+        hz: u32,
+        set_speed: F,
+    ) -> crate::SpeedI2cProxy<'a, M, F>
+    where
+        F: FnMut(&mut M::Bus, u32),
+    {
+        crate::SpeedI2cProxy {
+            mutex: &self.mutex,
+            hz,
+            restore: None,
+            set_speed,
+        }
+    }
+
+    /// Acquire a [`RecoveringI2cProxy`] for this bus.
+    ///
+    /// [`RecoveringI2cProxy`]: ./struct.RecoveringI2cProxy.html
+    ///
+    /// When an operation fails, `recover` is called with the bus and the operation is retried
+    /// once, all while holding the bus lock.  For example, to free a bus where a device holds SDA
+    /// low by clocking SCL manually:
+    ///
+    /// ```ignore
+    /// let proxy = bus.acquire_i2c_recovering(move |_i2c| {
+    ///     for _ in 0..9 {
+    ///         scl.set_low().ok();
+    ///         delay.delay_us(5);
+    ///         scl.set_high().ok();
+    ///         delay.delay_us(5);
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// Depending on the HAL, the SCL pin needs to be switched between its I2C function and GPIO
+    /// mode for this, which can be done inside the routine as well.
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c_recovering<'a, F>(
+        &'a self,
+        recover: F,
+    ) -> crate::RecoveringI2cProxy<'a, M, F>
+    where
+        F: FnMut(&mut M::Bus),
+    {
+        crate::RecoveringI2cProxy {
+            mutex: &self.mutex,
+            recover,
+        }
+    }
+
+    /// Acquire a [`MappedI2cProxy`] for this bus.
+    ///
+    /// [`MappedI2cProxy`]: ./struct.MappedI2cProxy.html
+    ///
+    /// The proxy works like a normal [`I2cProxy`] but converts all bus errors using `map`.  This
+    /// allows handing drivers a bus with a unified error type:
+    ///
+    /// [`I2cProxy`]: ./struct.I2cProxy.html
+    ///
+    /// ```
+    /// # use embedded_hal::blocking::i2c;
+    /// #[derive(Debug)]
+    /// enum AppError {
+    ///     Bus,
+    ///     Sensor,
+    /// }
+    ///
+    /// # fn _example(i2c: impl i2c::Write) {
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    ///
+    /// let mut proxy = bus.acquire_i2c_map_err(|_| AppError::Bus);
+    /// let res: Result<(), AppError> = i2c::Write::write(&mut proxy, 0x39, &[0xc0, 0xff, 0xee]);
+    /// # }
+    /// ```
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c_map_err<'a, F>(&'a self, map: F) -> crate::MappedI2cProxy<'a, M, F> {
+        crate::MappedI2cProxy {
+            mutex: &self.mutex,
+            map,
+        }
+    }
+
+    /// Acquire an [`SmbusProxy`] for SMBus devices on this bus.
+    ///
+    /// [`SmbusProxy`]: ./struct.SmbusProxy.html
+    ///
+    /// With `pec`, all commands of the proxy use packet error checking.
+    #[cfg(feature = "i2c")]
+    pub fn acquire_smbus<'a>(&'a self, pec: bool) -> crate::SmbusProxy<'a, M> {
+        crate::SmbusProxy {
+            mutex: &self.mutex,
+            pec,
+        }
+    }
+
+    /// Acquire a [`RateLimitedProxy`] for this bus.
+    ///
+    /// [`RateLimitedProxy`]: ./struct.RateLimitedProxy.html
+    ///
+    /// Each transaction of the proxy starts at least `min_interval` after the previous
+    /// transaction of any rate-limited proxy of this bus manager.  `clock` is used for measuring
+    /// the time and for waiting.
+    ///
+    /// This method is only available with the `rate-limit` feature.
+    #[cfg(feature = "rate-limit")]
+    pub fn acquire_i2c_rate_limited<'a, C>(
+        &'a self,
+        min_interval: core::time::Duration,
+        clock: C,
+    ) -> crate::RateLimitedProxy<'a, M, C>
+    where
+        C: crate::Clock,
+    {
+        crate::RateLimitedProxy {
+            mutex: &self.mutex,
+            last: &self.last_rate_limited,
+            min_interval: crate::rate::interval_us(min_interval),
+            clock,
+        }
+    }
+
+    /// Acquire a [`TimedI2cProxy`] for this bus.
+    ///
+    /// [`TimedI2cProxy`]: ./struct.TimedI2cProxy.html
+    ///
+    /// When a transaction of the proxy takes longer than `timeout`, measured with `clock`, `abort`
+    /// is called with the bus (e.g. for resetting the peripheral) and the transaction fails with
+    /// a timeout error.  The timeout is only checked between transactions, a HAL call which
+    /// hangs is not interrupted.
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c_timed<'a, C, F>(
+        &'a self,
+        clock: C,
+        timeout: core::time::Duration,
+        abort: F,
+    ) -> crate::TimedI2cProxy<'a, M, C, F>
+    where
+        C: crate::Clock,
+        F: FnMut(&mut M::Bus),
+    {
+        crate::TimedI2cProxy {
+            mutex: &self.mutex,
+            clock,
+            timeout: crate::rate::interval_us(timeout),
+            abort,
+        }
+    }
+
+    /// Acquire a [`ChunkedI2cProxy`] which splits transfers longer than `max_len` bytes.
+    ///
+    /// [`ChunkedI2cProxy`]: ./struct.ChunkedI2cProxy.html
+    ///
+    /// `next_address` adjusts the device address and the write part of a `write_read()` for
+    /// each chunk after the first one, see [`ChunkedI2cProxy`] for details.
+    ///
+    /// # Panics
+    /// Panics if `max_len` is zero.
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c_chunked<'a, F>(
+        &'a self,
+        max_len: usize,
+        next_address: F,
+    ) -> crate::ChunkedI2cProxy<'a, M, F>
+    where
+        F: FnMut(usize, &mut u8, &mut [u8]),
+    {
+        assert!(max_len > 0, "chunks must be at least one byte long");
+        crate::ChunkedI2cProxy {
+            mutex: &self.mutex,
+            max_len,
+            next_address,
+        }
+    }
+
+    /// Acquire a [`BufferedI2cProxy`] which coalesces consecutive writes to register `reg`.
+    ///
+    /// [`BufferedI2cProxy`]: ./struct.BufferedI2cProxy.html
+    ///
+    /// The buffer holds `N` bytes, including the register byte, so `N` must be at least 2.
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c_buffered<'a, const N: usize>(
+        &'a self,
+        reg: u8,
+    ) -> crate::BufferedI2cProxy<'a, M, N> {
+        assert!(
+            N >= 2,
+            "buffer must hold the register and at least one data byte"
+        );
+        crate::BufferedI2cProxy {
+            mutex: &self.mutex,
+            reg,
+            addr: 0,
+            len: 0,
+            buf: [0; N],
+        }
     }
 
     /// Acquire an [`AdcProxy`] for this hardware block.
@@ -151,54 +793,1585 @@ impl<M: crate::BusMutex> BusManager<M> {
     /// proxy2.read(ch1).unwrap();
     ///
     /// ```
-
+    #[cfg(feature = "adc")]
     pub fn acquire_adc<'a>(&'a self) -> crate::AdcProxy<'a, M> {
         crate::AdcProxy { mutex: &self.mutex }
     }
-}
 
-impl<T> BusManager<crate::NullMutex<T>> {
-    /// Acquire an [`SpiProxy`] for this bus.
+    /// Acquire a [`SerialProxy`] for this UART.
     ///
-    /// **Note**: SPI Proxies can only be created from [`BusManagerSimple`] (= bus managers using
-    /// the [`NullMutex`]).  See [`SpiProxy`] for more details why.
+    /// [`SerialProxy`]: ./struct.SerialProxy.html
     ///
-    /// [`BusManagerSimple`]: ./type.BusManagerSimple.html
-    /// [`NullMutex`]: ./struct.NullMutex.html
-    /// [`SpiProxy`]: ./struct.SpiProxy.html
+    /// This method is only available with the `serial` feature.
+    #[cfg(feature = "serial")]
+    pub fn acquire_serial<'a>(&'a self) -> crate::SerialProxy<'a, M> {
+        crate::SerialProxy { mutex: &self.mutex }
+    }
+
+    /// Acquire a [`DelayProxy`] for this timer.
     ///
-    /// The returned proxy object can then be used for accessing the bus by e.g. a driver:
+    /// [`DelayProxy`]: ./struct.DelayProxy.html
+    pub fn acquire_delay<'a>(&'a self) -> crate::DelayProxy<'a, M> {
+        crate::DelayProxy { mutex: &self.mutex }
+    }
+
+    /// Acquire an [`I2cProxy`] for this bus together with a [`DelayProxy`] from the timer
+    /// manager `delay`.
+    ///
+    /// This is a shorthand for initializing drivers which need both a bus and a delay:
     ///
     /// ```
-    /// # use embedded_hal::blocking::spi;
-    /// # use embedded_hal::digital::v2;
-    /// # use embedded_hal::blocking::spi::Write as _;
-    /// # struct MyDevice<T>(T);
-    /// # impl<T: spi::Write<u8>> MyDevice<T> {
-    /// #     pub fn new(t: T) -> Self { MyDevice(t) }
-    /// #     pub fn do_something_on_the_bus(&mut self) {
-    /// #         self.0.write(&[0x00]);
-    /// #     }
-    /// # }
-    /// #
-    /// # fn _example(mut cs1: impl v2::OutputPin, spi: impl spi::Write<u8>) {
-    /// let bus = shared_bus::BusManagerSimple::new(spi);
+    /// # use embedded_hal::blocking::delay::DelayMs;
+    /// # use embedded_hal::blocking::i2c;
+    /// struct Sensor<I2C, D> {
+    ///     i2c: I2C,
+    ///     delay: D,
+    /// }
     ///
-    /// let mut proxy1 = bus.acquire_spi();
-    /// let mut my_device = MyDevice::new(bus.acquire_spi());
+    /// impl<I2C: i2c::Write, D: DelayMs<u8>> Sensor<I2C, D> {
+    ///     fn new(i2c: I2C, delay: D) -> Self {
+    ///         Sensor { i2c, delay }
+    ///     }
     ///
-    /// // Chip-select needs to be managed manually
-    /// cs1.set_high();
-    /// proxy1.write(&[0xc0, 0xff, 0xee]);
-    /// cs1.set_low();
+    ///     fn reset(&mut self) -> Result<(), I2C::Error> {
+    ///         self.i2c.write(0x40, &[0xfe])?;
+    ///         // Wait for the sensor to come back up
+    ///         self.delay.delay_ms(15);
+    ///         Ok(())
+    ///     }
+    /// }
     ///
-    /// my_device.do_something_on_the_bus();
+    /// # fn _example(i2c: impl i2c::Write, timer: impl DelayMs<u8>) {
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    /// let timer = shared_bus::BusManagerSimple::new(timer);
+    ///
+    /// let (i2c, delay) = bus.acquire_i2c_with_delay(&timer);
+    /// let mut sensor = Sensor::new(i2c, delay);
+    /// let _ = sensor.reset();
     /// # }
     /// ```
-    pub fn acquire_spi<'a>(&'a self) -> crate::SpiProxy<'a, crate::NullMutex<T>> {
-        crate::SpiProxy {
-            mutex: &self.mutex,
-            _u: core::marker::PhantomData,
-        }
+    ///
+    /// [`I2cProxy`]: ./struct.I2cProxy.html
+    /// [`DelayProxy`]: ./struct.DelayProxy.html
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c_with_delay<'a, 'd, D: crate::BusMutex>(
+        &'a self,
+        delay: &'d BusManager<D>,
+    ) -> (crate::I2cProxy<'a, M>, crate::DelayProxy<'d, D>) {
+        (self.acquire_i2c(), delay.acquire_delay())
+    }
+
+    /// Acquire an [`OwnedSpiDeviceProxy`] for a device on this bus.
+    ///
+    /// [`OwnedSpiDeviceProxy`]: ./struct.OwnedSpiDeviceProxy.html
+    ///
+    /// The proxy takes ownership of the device's chip-select pin and manages it inside the bus
+    /// lock.  Thus, unlike the [`SpiProxy`], it can be used with any mutex type and be moved into
+    /// other tasks/threads:
+    ///
+    /// [`SpiProxy`]: ./struct.SpiProxy.html
+    ///
+    /// ```
+    /// # use embedded_hal_1::spi::{self, SpiBus, SpiDevice};
+    /// # use embedded_hal_1::digital::OutputPin;
+    /// # struct SomeSpiBus;
+    /// # impl spi::ErrorType for SomeSpiBus { type Error = core::convert::Infallible; }
+    /// # impl SpiBus for SomeSpiBus {
+    /// #     fn read(&mut self, _: &mut [u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn write(&mut self, _: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn transfer(&mut self, _: &mut [u8], _: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn transfer_in_place(&mut self, _: &mut [u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn flush(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    /// # }
+    /// # fn _example(spi: SomeSpiBus, cs1: impl OutputPin + Send + 'static, cs2: impl OutputPin) {
+    /// let bus: &'static _ = shared_bus::new_std!(SomeSpiBus = spi).unwrap();
+    ///
+    /// let mut device1 = bus.acquire_spi_device(cs1);
+    /// let mut device2 = bus.acquire_spi_device(cs2);
+    ///
+    /// std::thread::spawn(move || {
+    ///     device1.write(&[0xc0, 0xff, 0xee]).unwrap();
+    /// });
+    ///
+    /// device2.write(&[0xde, 0xad]).unwrap();
+    /// # }
+    /// ```
+    ///
+    /// The proxy has no delay source, so transactions containing `Operation::DelayNs` fail.  Use
+    /// [`acquire_spi_device_with_delay()`][with_delay] for devices which need delays.
+    ///
+    /// [with_delay]: #method.acquire_spi_device_with_delay
+    ///
+    /// This method is only available with the `eh1` feature.
+    #[cfg(all(feature = "spi", feature = "eh1"))]
+    pub fn acquire_spi_device<CS>(&'static self, cs: CS) -> crate::OwnedSpiDeviceProxy<M, CS> {
+        crate::OwnedSpiDeviceProxy {
+            mutex: &self.mutex,
+            cs,
+            polarity: crate::CsPolarity::ActiveLow,
+            delay: None,
+            #[cfg(feature = "metrics")]
+            metrics: &self.metrics,
+        }
+    }
+
+    /// Acquire an [`OwnedSpiDeviceProxy`] with a delay source for `Operation::DelayNs`.
+    ///
+    /// [`OwnedSpiDeviceProxy`]: ./struct.OwnedSpiDeviceProxy.html
+    ///
+    /// Some devices need a pause between two operations while chip-select stays asserted.  The
+    /// `delay` is used for all `Operation::DelayNs` in transactions of this proxy:
+    ///
+    /// ```
+    /// # use embedded_hal_1::spi::{self, SpiBus, SpiDevice, Operation};
+    /// # use embedded_hal_1::digital::OutputPin;
+    /// # use embedded_hal_1::delay::DelayNs;
+    /// # struct SomeSpiBus;
+    /// # impl spi::ErrorType for SomeSpiBus { type Error = core::convert::Infallible; }
+    /// # impl SpiBus for SomeSpiBus {
+    /// #     fn read(&mut self, _: &mut [u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn write(&mut self, _: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn transfer(&mut self, _: &mut [u8], _: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn transfer_in_place(&mut self, _: &mut [u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn flush(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    /// # }
+    /// # fn _example(spi: SomeSpiBus, cs: impl OutputPin, delay: impl DelayNs) {
+    /// let bus: &'static _ = shared_bus::new_std!(SomeSpiBus = spi).unwrap();
+    /// let mut device = bus.acquire_spi_device_with_delay(cs, delay);
+    ///
+    /// let mut data = [0u8; 2];
+    /// device.transaction(&mut [
+    ///     Operation::Write(&[0x9f]),
+    ///     Operation::DelayNs(10_000),
+    ///     Operation::Read(&mut data),
+    /// ]).unwrap();
+    /// # }
+    /// ```
+    ///
+    /// This method is only available with the `eh1` feature.
+    #[cfg(all(feature = "spi", feature = "eh1"))]
+    pub fn acquire_spi_device_with_delay<CS, D>(
+        &'static self,
+        cs: CS,
+        delay: D,
+    ) -> crate::OwnedSpiDeviceProxy<M, CS, D> {
+        crate::OwnedSpiDeviceProxy {
+            mutex: &self.mutex,
+            cs,
+            polarity: crate::CsPolarity::ActiveLow,
+            delay: Some(delay),
+            #[cfg(feature = "metrics")]
+            metrics: &self.metrics,
+        }
+    }
+
+    /// Create a new bus manager for an SPI bus with a scratch buffer of `N` bytes.
+    ///
+    /// The bus is wrapped in a [`ScratchBus`] which stores the buffer next to the bus, inside
+    /// the mutex.  Devices acquired using [`acquire_spi_device()`][acquire_spi_device] can borrow
+    /// the buffer while they hold the bus, instead of each driver placing its own transfer
+    /// buffers on the stack.  As only one device can hold the bus at a time, a single buffer is
+    /// enough for all of them:
+    ///
+    /// ```
+    /// # use embedded_hal_1::spi::{self, SpiBus};
+    /// # use embedded_hal_1::digital::OutputPin;
+    /// # struct SomeSpiBus;
+    /// # impl spi::ErrorType for SomeSpiBus { type Error = core::convert::Infallible; }
+    /// # impl SpiBus for SomeSpiBus {
+    /// #     fn read(&mut self, _: &mut [u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn write(&mut self, _: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn transfer(&mut self, _: &mut [u8], _: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn transfer_in_place(&mut self, _: &mut [u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn flush(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    /// # }
+    /// # fn _example(spi: SomeSpiBus, cs: impl OutputPin) {
+    /// let bus: shared_bus::BusManagerStd<_> = shared_bus::BusManager::with_scratch::<_, 256>(spi);
+    /// let bus: &'static _ = Box::leak(Box::new(bus));
+    ///
+    /// let mut device = bus.acquire_spi_device(cs);
+    /// let res = device.with_scratch(|bus, scratch| {
+    ///     scratch[0] = 0x0b;
+    ///     bus.transfer_in_place(&mut scratch[..200])
+    /// });
+    /// # let _ = res;
+    /// # }
+    /// ```
+    ///
+    /// [`ScratchBus`]: ./struct.ScratchBus.html
+    /// [acquire_spi_device]: #method.acquire_spi_device
+    ///
+    /// This method is only available with the `eh1` feature.
+    #[cfg(all(feature = "spi", feature = "eh1"))]
+    pub fn with_scratch<B, const N: usize>(bus: B) -> Self
+    where
+        M: crate::CreateBusMutex<Bus = crate::ScratchBus<B, N>>,
+    {
+        Self::new(crate::ScratchBus::new(bus))
+    }
+
+    /// Run a [`BusTransaction`] while holding the bus lock.
+    ///
+    /// [`BusTransaction`]: ./trait.BusTransaction.html
+    ///
+    /// The whole transaction is executed atomically, no other proxy can access the bus while it
+    /// runs.  This is useful for encapsulating multi-step sequences which must not be interrupted:
+    ///
+    /// ```
+    /// use embedded_hal::blocking::i2c;
+    ///
+    /// /// Unlock a device by writing a magic sequence, then configure it.
+    /// struct Unlock {
+    ///     addr: u8,
+    ///     config: u8,
+    /// }
+    ///
+    /// impl<B: i2c::Write> shared_bus::BusTransaction<B> for Unlock {
+    ///     type Output = Result<(), B::Error>;
+    ///
+    ///     fn run(self, bus: &mut B) -> Self::Output {
+    ///         bus.write(self.addr, &[0x55])?;
+    ///         bus.write(self.addr, &[0xaa])?;
+    ///         bus.write(self.addr, &[0x01, self.config])
+    ///     }
+    /// }
+    ///
+    /// # fn _example(i2c: impl i2c::Write) {
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    ///
+    /// let _ = bus.execute(Unlock { addr: 0x39, config: 0x80 });
+    /// # }
+    /// ```
+    pub fn execute<T: BusTransaction<M::Bus>>(&self, transaction: T) -> T::Output {
+        self.mutex.lock(|bus| transaction.run(bus))
+    }
+
+    /// Lock the bus and give a closure direct access to it, e.g. for changing its configuration.
+    ///
+    /// Bus peripherals often have HAL-specific settings which are not covered by `embedded-hal`,
+    /// like the clock speed.  This method allows changing them between transactions of the
+    /// proxies.  The closure runs while holding the bus lock, so no proxy can access the bus at
+    /// the same time:
+    ///
+    /// ```
+    /// # struct I2c { speed: u32 }
+    /// # impl I2c { fn set_clock_speed(&mut self, speed: u32) { self.speed = speed; } }
+    /// # let i2c = I2c { speed: 100_000 };
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    ///
+    /// bus.configure(|i2c| i2c.set_clock_speed(400_000));
+    /// ```
+    pub fn configure<R, F: FnOnce(&mut M::Bus) -> R>(&self, f: F) -> R {
+        self.mutex.lock(f)
+    }
+
+    /// Replace the bus, returning the old one.
+    ///
+    /// The swap happens while holding the bus lock.  All proxies acquired from this manager stay
+    /// valid and use the new bus from then on.  This allows e.g. switching to a bit-banged
+    /// fallback when the bus peripheral stopped working:
+    ///
+    /// ```
+    /// # enum Bus { Hardware, BitBang }
+    /// let bus = shared_bus::BusManagerSimple::new(Bus::Hardware);
+    ///
+    /// let old = bus.replace_bus(Bus::BitBang);
+    /// # assert!(matches!(old, Bus::Hardware));
+    /// ```
+    pub fn replace_bus(&self, new: M::Bus) -> M::Bus {
+        self.mutex.lock(|bus| core::mem::replace(bus, new))
+    }
+
+    /// Name of the mutex type used by this manager, for diagnostics.
+    ///
+    /// This returns [`BusMutex::KIND`], e.g. for a startup log:
+    ///
+    /// ```
+    /// # struct SomeBus;
+    /// let bus = shared_bus::BusManagerSimple::new(SomeBus);
+    ///
+    /// println!("bus uses a {}", bus.mutex_kind());
+    /// # assert_eq!(bus.mutex_kind(), "NullMutex");
+    /// ```
+    ///
+    /// [`BusMutex::KIND`]: ./trait.BusMutex.html#associatedconstant.KIND
+    pub fn mutex_kind(&self) -> &'static str {
+        M::KIND
+    }
+
+    /// Total number of bytes written to the bus.
+    ///
+    /// All successful transfers of the [`I2cProxy`], [`SpiProxy`], and [`OwnedSpiDeviceProxy`]
+    /// proxies of this manager are counted.  For SPI transfers, the bytes sent and received are
+    /// counted separately.  The counter wraps around on overflow.
+    ///
+    /// ```
+    /// # use embedded_hal::blocking::i2c;
+    /// # use embedded_hal::prelude::*;
+    /// # fn _example(i2c: impl i2c::Write) {
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    /// let mut proxy = bus.acquire_i2c();
+    ///
+    /// if proxy.write(0x39, &[0xc0, 0xff, 0xee]).is_ok() {
+    ///     assert_eq!(bus.bytes_written(), 3);
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// [`I2cProxy`]: ./struct.I2cProxy.html
+    /// [`SpiProxy`]: ./struct.SpiProxy.html
+    /// [`OwnedSpiDeviceProxy`]: ./struct.OwnedSpiDeviceProxy.html
+    ///
+    /// This method is only available with the `metrics` feature.
+    #[cfg(feature = "metrics")]
+    pub fn bytes_written(&self) -> usize {
+        self.metrics.written()
+    }
+
+    /// Total number of bytes read from the bus.
+    ///
+    /// See [`bytes_written()`](#method.bytes_written) for details.
+    ///
+    /// This method is only available with the `metrics` feature.
+    #[cfg(feature = "metrics")]
+    pub fn bytes_read(&self) -> usize {
+        self.metrics.read()
+    }
+
+    /// Lock the bus for the entire duration of a closure.
+    ///
+    /// The closure receives an [`ExclusiveBus`] which implements the same bus traits as the bus
+    /// itself, so driver code can use it like a proxy.  Other proxies are locked out until the
+    /// closure returns, even between multiple calls of the driver.  This is useful for longer
+    /// sequences which must not be interrupted, like a firmware update of a device:
+    ///
+    /// [`ExclusiveBus`]: ./struct.ExclusiveBus.html
+    ///
+    /// ```
+    /// # use embedded_hal::blocking::i2c;
+    /// # struct Device<I2C>(I2C);
+    /// # impl<I2C: i2c::Write> Device<I2C> {
+    /// #     fn new(i2c: I2C) -> Self { Device(i2c) }
+    /// #     fn unlock_flash(&mut self) -> Result<(), I2C::Error> { self.0.write(0x50, &[0xa5]) }
+    /// #     fn flash_page(&mut self, page: &[u8]) -> Result<(), I2C::Error> {
+    /// #         self.0.write(0x50, page)
+    /// #     }
+    /// #     fn lock_flash(&mut self) -> Result<(), I2C::Error> { self.0.write(0x50, &[0x5a]) }
+    /// # }
+    /// # fn _example(i2c: impl i2c::Write, firmware: &[u8]) {
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    ///
+    /// let result = bus.lock_exclusive(|bus| {
+    ///     let mut device = Device::new(bus);
+    ///     device.unlock_flash()?;
+    ///     for page in firmware.chunks(16) {
+    ///         device.flash_page(page)?;
+    ///     }
+    ///     device.lock_flash()
+    /// });
+    /// # let _ = result;
+    /// # }
+    /// ```
+    pub fn lock_exclusive<R, F: FnOnce(crate::ExclusiveBus<'_, M::Bus>) -> R>(&self, f: F) -> R {
+        self.mutex.lock(|bus| f(crate::ExclusiveBus { bus }))
+    }
+
+    /// Create a [`SegmentedBusManager`] for one segment of this bus.
+    ///
+    /// [`SegmentedBusManager`]: ./struct.SegmentedBusManager.html
+    ///
+    /// The `select` closure is called with the bus, inside the lock, before each transaction of
+    /// a proxy acquired from the segment.  This is useful for I2C multiplexers where the right
+    /// channel needs to be selected before talking to a device behind it:
+    ///
+    /// ```
+    /// # use embedded_hal::blocking::i2c;
+    /// # fn _example<I: i2c::Write + i2c::WriteRead<Error = <I as i2c::Write>::Error>>(i2c: I) {
+    /// const MUX_ADDR: u8 = 0x70;
+    ///
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    ///
+    /// let channel0 = bus.segment(|bus: &mut I| bus.write(MUX_ADDR, &[1 << 0]));
+    /// let channel1 = bus.segment(|bus: &mut I| bus.write(MUX_ADDR, &[1 << 1]));
+    ///
+    /// // Both sensors have the same address but live on different channels of the mux
+    /// let mut sensor0 = channel0.acquire_i2c();
+    /// let mut sensor1 = channel1.acquire_i2c();
+    /// # let _ = i2c::WriteRead::write_read(&mut sensor0, 0x48, &[0x00], &mut [0; 2]);
+    /// # let _ = i2c::WriteRead::write_read(&mut sensor1, 0x48, &[0x00], &mut [0; 2]);
+    /// # }
+    /// ```
+    #[cfg(feature = "i2c")]
+    pub fn segment<F, E>(&self, select: F) -> SegmentedBusManager<'_, M, F>
+    where
+        F: Fn(&mut M::Bus) -> Result<(), E>,
+    {
+        SegmentedBusManager {
+            mutex: &self.mutex,
+            select,
+        }
+    }
+}
+
+impl<M: crate::GuardedBusMutex> BusManager<M> {
+    /// Lock the bus and return a guard giving access to it.
+    ///
+    /// The bus stays locked until the guard is dropped.  No proxy can access the bus in the
+    /// meantime.  This is only available for mutex types implementing [`GuardedBusMutex`]:
+    ///
+    /// [`GuardedBusMutex`]: ./trait.GuardedBusMutex.html
+    ///
+    /// ```
+    /// # use embedded_hal::blocking::i2c;
+    /// # fn _example(i2c: impl i2c::Write) {
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    ///
+    /// let mut guard = bus.lock_guard();
+    /// let _ = guard.write(0x39, &[0x55]);
+    /// let _ = guard.write(0x39, &[0xaa]);
+    /// drop(guard);
+    /// # }
+    /// ```
+    pub fn lock_guard(&self) -> M::Guard<'_> {
+        self.mutex.lock_guard()
+    }
+
+    /// Begin an exclusive scope on the bus, returning a [`BusScope`] token.
+    ///
+    /// [`BusScope`]: ./struct.BusScope.html
+    ///
+    /// The bus stays locked until the token is dropped.  Unlike the guard returned by
+    /// [`lock_guard()`][lock_guard], the token implements the bus traits itself, so it can be
+    /// passed to drivers.  Compared to [`lock_exclusive()`][lock_exclusive], the token is a value
+    /// which can be stored and dropped wherever the driver's control flow needs it:
+    ///
+    /// [lock_guard]: #method.lock_guard
+    /// [lock_exclusive]: #method.lock_exclusive
+    ///
+    /// ```
+    /// # use embedded_hal::blocking::i2c;
+    /// # struct Device<I2C>(I2C);
+    /// # impl<I2C: i2c::Write> Device<I2C> {
+    /// #     fn new(i2c: I2C) -> Self { Device(i2c) }
+    /// #     fn unlock_flash(&mut self) -> Result<(), I2C::Error> { self.0.write(0x50, &[0xa5]) }
+    /// #     fn flash_page(&mut self, page: &[u8]) -> Result<(), I2C::Error> {
+    /// #         self.0.write(0x50, page)
+    /// #     }
+    /// #     fn release(self) -> I2C { self.0 }
+    /// # }
+    /// # fn _example(i2c: impl i2c::Write, firmware: &[u8]) {
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    ///
+    /// let mut device = Device::new(bus.begin_scope());
+    /// if device.unlock_flash().is_ok() {
+    ///     for page in firmware.chunks(16) {
+    ///         let _ = device.flash_page(page);
+    ///     }
+    /// }
+    /// // Dropping the scope releases the bus again
+    /// drop(device.release());
+    ///
+    /// let proxy = bus.acquire_i2c();
+    /// # }
+    /// ```
+    ///
+    /// **Note**: Acquiring the bus through another proxy while the token is alive deadlocks or
+    /// panics, depending on the mutex type.
+    pub fn begin_scope(&self) -> crate::BusScope<'_, M> {
+        crate::BusScope {
+            guard: self.mutex.lock_guard(),
+        }
+    }
+}
+
+/// Guard returned by [`BusManager::downcast_bus_ref()`].
+struct DowncastGuard<G, B> {
+    guard: G,
+    _bus: core::marker::PhantomData<fn() -> B>,
+}
+
+impl<G, B> core::ops::Deref for DowncastGuard<G, B>
+where
+    G: core::ops::Deref,
+    G::Target: core::any::Any + Sized,
+    B: 'static,
+{
+    type Target = B;
+
+    fn deref(&self) -> &B {
+        // The type was checked when creating the guard.
+        (&*self.guard as &dyn core::any::Any)
+            .downcast_ref()
+            .unwrap()
+    }
+}
+
+impl<G, B> core::ops::DerefMut for DowncastGuard<G, B>
+where
+    G: core::ops::DerefMut,
+    G::Target: core::any::Any + Sized,
+    B: 'static,
+{
+    fn deref_mut(&mut self) -> &mut B {
+        (&mut *self.guard as &mut dyn core::any::Any)
+            .downcast_mut()
+            .unwrap()
+    }
+}
+
+impl<M: crate::GuardedBusMutex> BusManager<M>
+where
+    M::Bus: 'static,
+{
+    /// Lock the bus and give access to it as the concrete type `B`, if the bus is a `B`.
+    ///
+    /// This is meant for code which only knows the bus type at runtime, e.g. a plugin framework
+    /// which stores its bus managers type-erased, and needs to call a HAL-specific method of the
+    /// bus.  Returns `None` without locking the bus if it is not a `B`.  Otherwise, the bus stays
+    /// locked until the returned guard is dropped, just like with [`lock_guard()`][lock_guard]:
+    ///
+    /// [lock_guard]: #method.lock_guard
+    ///
+    /// ```
+    /// # struct Twim;
+    /// # impl Twim { fn set_frequency(&mut self, _hz: u32) {} }
+    /// # fn _example<I: 'static>(bus: &shared_bus::BusManagerSimple<I>) {
+    /// if let Some(mut twim) = bus.downcast_bus_ref::<Twim>() {
+    ///     twim.set_frequency(400_000);
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// The check is based on [`core::any::Any`], so both the bus and `B` must be `'static` types
+    /// (i.e. not contain any non-`'static` references).  `B` has to be the exact type of the bus:
+    /// Downcasting to a trait object or to the bus inside a wrapper (like a [`FormatBus`]) is not
+    /// possible.
+    ///
+    /// [`FormatBus`]: ./struct.FormatBus.html
+    pub fn downcast_bus_ref<B: 'static>(
+        &self,
+    ) -> Option<impl core::ops::DerefMut<Target = B> + '_> {
+        if core::any::TypeId::of::<M::Bus>() != core::any::TypeId::of::<B>() {
+            return None;
+        }
+        Some(DowncastGuard {
+            guard: self.mutex.lock_guard(),
+            _bus: core::marker::PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "cortex-m")]
+impl<T> BusManager<crate::AtomicCheckMutex<T>> {
+    /// Run `f` with the bus conflict check disabled.
+    ///
+    /// See [`AtomicCheckMutex::with_checks_disabled()`][disabled] for details.
+    ///
+    /// # Safety
+    /// See [`AtomicCheckMutex::with_checks_disabled()`][disabled].
+    ///
+    /// [disabled]: ./struct.AtomicCheckMutex.html#method.with_checks_disabled
+    pub unsafe fn with_checks_disabled<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.mutex.with_checks_disabled(f)
+    }
+
+    /// Take the bus for a firmware update (DFU) or similar bootloader routine.
+    ///
+    /// This succeeds only when no proxy is in the middle of a transaction.  The returned
+    /// [`DfuGuard`] gives exclusive access to the bus until it is dropped; all proxies fail with
+    /// a bus conflict in the meantime.  Returns `None` if the bus is in use:
+    ///
+    /// [`DfuGuard`]: ./struct.DfuGuard.html
+    ///
+    /// ```ignore
+    /// let bus: &'static _ = shared_bus::new_atomic_check!(I2c<I2C1> = i2c).unwrap();
+    /// // ... create the drivers, run the application, tear the drivers down ...
+    ///
+    /// let mut guard = bus.take_for_dfu().expect("bus still in use");
+    /// run_dfu(&mut *guard);
+    /// ```
+    ///
+    /// The check only covers the moment the bus is taken.  The application must make sure that
+    /// no driver tries to use the bus while the guard is alive, as such accesses panic.
+    pub fn take_for_dfu(&self) -> Option<crate::DfuGuard<'_, T>> {
+        self.mutex.try_take()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> BusManager<crate::WatchdogMutex<T>> {
+    /// Return for how long the bus has been locked, if it is locked right now.
+    pub fn held_for(&self) -> Option<std::time::Duration> {
+        self.mutex.held_since().map(|(since, _)| since.elapsed())
+    }
+
+    /// Spawn a watchdog thread which calls `callback` when the bus is locked for longer than
+    /// `limit`.
+    ///
+    /// The callback receives the time the bus has been locked so far.  It is called at most once
+    /// per lock.  See [`BusManagerWatchdog`] for details.
+    ///
+    /// [`BusManagerWatchdog`]: ./type.BusManagerWatchdog.html
+    ///
+    /// This method is only available with the `std` feature.
+    pub fn spawn_watchdog<F>(&'static self, limit: std::time::Duration, mut callback: F)
+    where
+        T: Send,
+        F: FnMut(std::time::Duration) + Send + 'static,
+    {
+        let period = (limit / 4).max(std::time::Duration::from_millis(1));
+
+        std::thread::spawn(move || {
+            let mut reported = None;
+            loop {
+                std::thread::sleep(period);
+
+                if let Some((since, count)) = self.mutex.held_since() {
+                    let held = since.elapsed();
+                    if held > limit && reported != Some(count) {
+                        reported = Some(count);
+                        callback(held);
+                    }
+                }
+            }
+        });
+    }
+}
+
+impl<M: crate::BusMutex> BusManager<crate::QuietMutex<M>> {
+    /// Start or end a quiet window, in which no proxy can access the bus.
+    ///
+    /// See [`QuietMutex`] for details.
+    ///
+    /// [`QuietMutex`]: ./struct.QuietMutex.html
+    pub fn set_quiet(&self, quiet: bool) {
+        self.mutex.set_quiet(quiet)
+    }
+
+    /// Check whether the bus is quiet right now.
+    pub fn is_quiet(&self) -> bool {
+        self.mutex.is_quiet()
+    }
+}
+
+#[cfg(feature = "diagnostics")]
+impl<M: crate::BusMutex> BusManager<crate::DiagnosticMutex<M>> {
+    /// Return the current holder of the bus, if it is locked right now.
+    ///
+    /// See [`DiagnosticMutex`] for details.
+    ///
+    /// [`DiagnosticMutex`]: ./struct.DiagnosticMutex.html
+    ///
+    /// This method is only available with the `diagnostics` feature.
+    pub fn current_holder(&self) -> Option<crate::HolderId> {
+        self.mutex.current_holder()
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<M: crate::BusMutex> i2c::Write for &BusManager<M>
+where
+    M::Bus: i2c::Write,
+{
+    type Error = <M::Bus as i2c::Write>::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.acquire_i2c().write(addr, buffer)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<M: crate::BusMutex> i2c::Read for &BusManager<M>
+where
+    M::Bus: i2c::Read,
+{
+    type Error = <M::Bus as i2c::Read>::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.acquire_i2c().read(addr, buffer)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<M: crate::BusMutex> i2c::WriteRead for &BusManager<M>
+where
+    M::Bus: i2c::WriteRead,
+{
+    type Error = <M::Bus as i2c::WriteRead>::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.acquire_i2c().write_read(addr, buffer_in, buffer_out)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<M: crate::BusMutex> i2c::WriteIterRead for &BusManager<M>
+where
+    M::Bus: i2c::WriteIterRead,
+{
+    type Error = <M::Bus as i2c::WriteIterRead>::Error;
+
+    fn write_iter_read<B>(
+        &mut self,
+        address: u8,
+        bytes: B,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        self.acquire_i2c().write_iter_read(address, bytes, buffer)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<M: crate::BusMutex> i2c::WriteIter for &BusManager<M>
+where
+    M::Bus: i2c::WriteIter,
+{
+    type Error = <M::Bus as i2c::WriteIter>::Error;
+
+    fn write<B>(&mut self, address: u8, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        i2c::WriteIter::write(&mut self.acquire_i2c(), address, bytes)
+    }
+}
+
+#[cfg(feature = "adc")]
+impl<M: crate::BusMutex, ADC, Word, Pin> adc::OneShot<ADC, Word, Pin> for &BusManager<M>
+where
+    Pin: adc::Channel<ADC>,
+    M::Bus: adc::OneShot<ADC, Word, Pin>,
+{
+    type Error = <M::Bus as adc::OneShot<ADC, Word, Pin>>::Error;
+
+    fn read(&mut self, pin: &mut Pin) -> nb::Result<Word, Self::Error> {
+        self.acquire_adc().read(pin)
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<M: crate::BusMutex> i2c_eh1::ErrorType for &BusManager<M>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = <M::Bus as i2c_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<M: crate::BusMutex, A: i2c_eh1::AddressMode> i2c_eh1::I2c<A> for &BusManager<M>
+where
+    M::Bus: i2c_eh1::I2c<A>,
+{
+    fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.acquire_i2c().read(address, read)
+    }
+
+    fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        self.acquire_i2c().write(address, write)
+    }
+
+    fn write_read(&mut self, address: A, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        self.acquire_i2c().write_read(address, write, read)
+    }
+
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.acquire_i2c().transaction(address, operations)
+    }
+}
+
+/// "Manager" for a shared bus used from async code.
+///
+/// This is the `async` counterpart to the [`BusManager`].  It wraps the bus in an
+/// [`AsyncBusMutex`] and hands out proxies implementing the `embedded-hal-async` traits.  While
+/// one task is using the bus, others wait for it asynchronously instead of blocking.
+///
+/// [`BusManager`]: ./struct.BusManager.html
+/// [`AsyncBusMutex`]: ./trait.AsyncBusMutex.html
+///
+/// Usually the [`BusManagerAsync`] type alias should be used:
+///
+/// [`BusManagerAsync`]: ./type.BusManagerAsync.html
+///
+/// ```
+/// # use embedded_hal_async::i2c::I2c;
+/// # async fn _example(i2c: impl I2c) {
+/// let bus = shared_bus::BusManagerAsync::new(i2c);
+///
+/// let mut proxy1 = bus.acquire_i2c();
+/// let mut proxy2 = bus.acquire_i2c();
+///
+/// let _ = proxy1.write(0x39, &[0xc0, 0xff, 0xee]).await;
+/// let _ = proxy2.write(0x39, &[0xde, 0xad]).await;
+/// # }
+/// ```
+///
+/// This type is only available with the `async` feature.
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub struct AsyncBusManager<M> {
+    mutex: M,
+}
+
+#[cfg(feature = "async")]
+impl<M: crate::AsyncBusMutex> AsyncBusManager<M> {
+    /// Create a new async bus manager for a bus.
+    pub fn new(bus: M::Bus) -> Self {
+        let mutex = M::create(bus);
+
+        AsyncBusManager { mutex }
+    }
+
+    /// Acquire an [`AsyncI2cProxy`] for this bus.
+    ///
+    /// [`AsyncI2cProxy`]: ./struct.AsyncI2cProxy.html
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c<'a>(&'a self) -> crate::AsyncI2cProxy<'a, M> {
+        self.acquire_i2c_with_priority(0)
+    }
+
+    /// Acquire an [`AsyncI2cProxy`] which locks the bus with the given priority.
+    ///
+    /// [`AsyncI2cProxy`]: ./struct.AsyncI2cProxy.html
+    ///
+    /// The priority only has an effect with a mutex which serves waiting tasks by priority, like
+    /// the [`PriorityAsyncMutex`].  Higher values mean higher priority.
+    ///
+    /// [`PriorityAsyncMutex`]: ./struct.PriorityAsyncMutex.html
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c_with_priority<'a>(&'a self, priority: u8) -> crate::AsyncI2cProxy<'a, M> {
+        crate::AsyncI2cProxy {
+            mutex: &self.mutex,
+            priority,
+        }
+    }
+
+    /// Acquire an [`AsyncAdcProxy`] for one channel of this ADC.
+    ///
+    /// [`AsyncAdcProxy`]: ./struct.AsyncAdcProxy.html
+    ///
+    /// The proxy takes ownership of the channel's pin.
+    #[cfg(feature = "adc")]
+    pub fn acquire_adc<'a, Pin>(&'a self, pin: Pin) -> crate::AsyncAdcProxy<'a, M, Pin> {
+        crate::AsyncAdcProxy {
+            mutex: &self.mutex,
+            pin,
+        }
+    }
+
+    /// Acquire an [`AsyncSpiDeviceProxy`] for a device on this bus.
+    ///
+    /// [`AsyncSpiDeviceProxy`]: ./struct.AsyncSpiDeviceProxy.html
+    ///
+    /// The proxy takes ownership of the device's chip-select pin and manages it while holding the
+    /// bus lock.
+    ///
+    /// The proxy has no delay source, so transactions containing `Operation::DelayNs` fail.  Use
+    /// [`acquire_spi_device_with_delay()`][with_delay] for devices which need delays.
+    ///
+    /// [with_delay]: #method.acquire_spi_device_with_delay
+    #[cfg(feature = "spi")]
+    pub fn acquire_spi_device<'a, CS>(&'a self, cs: CS) -> crate::AsyncSpiDeviceProxy<'a, M, CS> {
+        crate::AsyncSpiDeviceProxy {
+            mutex: &self.mutex,
+            cs,
+            delay: None,
+        }
+    }
+
+    /// Acquire an [`AsyncSpiDeviceProxy`] with a delay source for `Operation::DelayNs`.
+    ///
+    /// [`AsyncSpiDeviceProxy`]: ./struct.AsyncSpiDeviceProxy.html
+    ///
+    /// The `delay` is awaited for all `Operation::DelayNs` in transactions of this proxy, while
+    /// chip-select stays asserted and the bus remains locked:
+    ///
+    /// ```
+    /// # use embedded_hal_async::spi::{Operation, SpiBus, SpiDevice};
+    /// # use embedded_hal_async::delay::DelayNs;
+    /// # use embedded_hal_1::digital::OutputPin;
+    /// # async fn _example(spi: impl SpiBus, cs: impl OutputPin, delay: impl DelayNs) {
+    /// let bus = shared_bus::BusManagerAsync::new(spi);
+    /// let mut device = bus.acquire_spi_device_with_delay(cs, delay);
+    ///
+    /// let mut data = [0u8; 2];
+    /// let _ = device.transaction(&mut [
+    ///     Operation::Write(&[0x9f]),
+    ///     Operation::DelayNs(10_000),
+    ///     Operation::Read(&mut data),
+    /// ]).await;
+    /// # }
+    /// ```
+    #[cfg(feature = "spi")]
+    pub fn acquire_spi_device_with_delay<'a, CS, D>(
+        &'a self,
+        cs: CS,
+        delay: D,
+    ) -> crate::AsyncSpiDeviceProxy<'a, M, CS, D> {
+        crate::AsyncSpiDeviceProxy {
+            mutex: &self.mutex,
+            cs,
+            delay: Some(delay),
+        }
+    }
+
+    /// Acquire an [`AsyncRetryingSpiDeviceProxy`] for a device on this bus.
+    ///
+    /// [`AsyncRetryingSpiDeviceProxy`]: ./struct.AsyncRetryingSpiDeviceProxy.html
+    ///
+    /// Failed transactions are retried according to the `policy`, waiting for the backoff delay
+    /// without holding the bus:
+    ///
+    /// ```
+    /// # use embedded_hal_async::spi::{SpiBus, SpiDevice};
+    /// # use embedded_hal_async::delay::DelayNs;
+    /// # use embedded_hal_1::digital::OutputPin;
+    /// # async fn _example(spi: impl SpiBus, cs: impl OutputPin, delay: impl DelayNs) {
+    /// let bus = shared_bus::BusManagerAsync::new(spi);
+    ///
+    /// // Up to 3 attempts, with 500us between them
+    /// let policy = shared_bus::RetryPolicy::new(3, 500_000, delay);
+    /// let mut radio = bus.acquire_spi_device_retrying(cs, policy);
+    ///
+    /// let _ = radio.write(&[0x01, 0x02]).await;
+    /// # }
+    /// ```
+    #[cfg(feature = "spi")]
+    pub fn acquire_spi_device_retrying<'a, CS, D>(
+        &'a self,
+        cs: CS,
+        policy: crate::RetryPolicy<D>,
+    ) -> crate::AsyncRetryingSpiDeviceProxy<'a, M, CS, D> {
+        crate::AsyncRetryingSpiDeviceProxy {
+            device: self.acquire_spi_device(cs),
+            policy,
+        }
+    }
+}
+
+/// A complete bus exchange which is executed atomically.
+///
+/// Implement this trait for types describing a sequence of bus operations that must not be
+/// interrupted by other bus users, then run them using [`BusManager::execute()`][execute].
+///
+/// [execute]: ./struct.BusManager.html#method.execute
+pub trait BusTransaction<B> {
+    /// The result of running this transaction.
+    type Output;
+
+    /// Run the transaction on the bus.
+    fn run(self, bus: &mut B) -> Self::Output;
+}
+
+/// "Manager" for one segment of a shared bus.
+///
+/// A segmented bus manager is created by calling [`BusManager::segment()`][segment].  It hands
+/// out proxies which behave just like the proxies of the parent manager, but additionally call a
+/// `select` closure, while holding the bus lock, before each transaction.  This is meant for
+/// buses behind a multiplexer: drivers receive a normal proxy and do not need to know about the
+/// mux at all.
+///
+/// [segment]: ./struct.BusManager.html#method.segment
+#[cfg(feature = "i2c")]
+#[derive(Debug)]
+pub struct SegmentedBusManager<'a, M, F> {
+    mutex: &'a M,
+    select: F,
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> SegmentedBusManager<'a, M, F> {
+    /// Acquire a [`SegmentI2cProxy`] for this bus segment.
+    ///
+    /// [`SegmentI2cProxy`]: ./struct.SegmentI2cProxy.html
+    pub fn acquire_i2c<'b>(&'b self) -> crate::SegmentI2cProxy<'b, M, F> {
+        crate::SegmentI2cProxy {
+            mutex: self.mutex,
+            select: &self.select,
+        }
+    }
+}
+
+/// Counter of the proxies acquired from a [`BoundedBusManager`].
+///
+/// Targets without native atomic read-modify-write instructions need `portable-atomic`, which is
+/// only available with the `cortex-m` feature.
+#[cfg(feature = "cortex-m")]
+pub(crate) type SlotCounter = portable_atomic::AtomicUsize;
+#[cfg(all(not(feature = "cortex-m"), target_has_atomic = "ptr"))]
+pub(crate) type SlotCounter = core::sync::atomic::AtomicUsize;
+
+/// "Manager" for a shared bus with a bounded number of users.
+///
+/// A `BoundedBusManager` works like a [`BusManager`] but hands out at most `MAX` proxies at the
+/// same time.  Further attempts to acquire a proxy return `None` until one of the proxies is
+/// dropped.  This allows statically bounding the number of devices sharing a bus, e.g. to reason
+/// about the worst-case latency of a bus access:
+///
+/// [`BusManager`]: ./struct.BusManager.html
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # fn _example(i2c: impl i2c::Write) {
+/// let bus: shared_bus::BoundedBusManager<shared_bus::NullMutex<_>, 2> =
+///     shared_bus::BoundedBusManager::new(i2c);
+///
+/// let proxy1 = bus.acquire_i2c_checked().unwrap();
+/// let proxy2 = bus.acquire_i2c_checked().unwrap();
+/// assert!(bus.acquire_i2c_checked().is_none());
+///
+/// // Dropping a proxy gives its slot back
+/// drop(proxy1);
+/// let proxy3 = bus.acquire_i2c_checked().unwrap();
+/// # }
+/// ```
+///
+/// The proxies are wrapped in a [`BoundedProxy`], which is not `Clone`, so the bound cannot be
+/// circumvented by cloning a proxy.
+///
+/// The number of acquired proxies is tracked atomically, so a `BoundedBusManager` with a `Sync`
+/// mutex is `Sync` as well.  Using [`from_mutex()`][from_mutex], it can be placed in a `static`:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # pub struct SomeI2cBus;
+/// # impl i2c::Write for SomeI2cBus {
+/// #     type Error = ();
+/// #     fn write(&mut self, _: u8, _: &[u8]) -> Result<(), ()> { Ok(()) }
+/// # }
+/// use shared_bus::BoundedBusManager;
+/// use std::sync::Mutex;
+///
+/// static BUS: BoundedBusManager<Mutex<SomeI2cBus>, 2> =
+///     BoundedBusManager::from_mutex(Mutex::new(SomeI2cBus));
+///
+/// let mut proxy = BUS.acquire_i2c_checked().unwrap();
+/// std::thread::spawn(move || {
+///     i2c::Write::write(&mut proxy, 0x39, &[0x00]).unwrap();
+/// });
+/// ```
+///
+/// [`BoundedProxy`]: ./struct.BoundedProxy.html
+/// [from_mutex]: #method.from_mutex
+///
+/// This type is only available on targets with atomic compare-and-swap, or with the `cortex-m`
+/// feature.
+#[cfg(any(feature = "cortex-m", target_has_atomic = "ptr"))]
+#[derive(Debug)]
+pub struct BoundedBusManager<M, const MAX: usize> {
+    manager: BusManager<M>,
+    acquired: SlotCounter,
+}
+
+#[cfg(any(feature = "cortex-m", target_has_atomic = "ptr"))]
+impl<M: crate::BusMutex, const MAX: usize> BoundedBusManager<M, MAX> {
+    /// Create a new bounded bus manager for a bus.
+    pub fn new(bus: M::Bus) -> Self
+    where
+        M: crate::CreateBusMutex,
+    {
+        Self::from_mutex(M::create(bus))
+    }
+
+    /// Create a new bounded bus manager from an existing mutex which already contains the bus.
+    ///
+    /// This is a `const fn`, so the manager can be placed in a `static`.  See
+    /// [`BusManager::from_mutex()`][from_mutex] for details.
+    ///
+    /// [from_mutex]: ./struct.BusManager.html#method.from_mutex
+    pub const fn from_mutex(mutex: M) -> Self {
+        BoundedBusManager {
+            manager: BusManager::from_mutex(mutex),
+            acquired: SlotCounter::new(0),
+        }
+    }
+
+    /// Number of proxies which can still be acquired.
+    pub fn remaining(&self) -> usize {
+        MAX - self.acquired.load(core::sync::atomic::Ordering::Acquire)
+    }
+
+    fn bounded<'a, P>(&'a self, acquire: impl FnOnce() -> P) -> Option<crate::BoundedProxy<'a, P>> {
+        use core::sync::atomic::Ordering;
+
+        self.acquired
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |acquired| {
+                if acquired < MAX {
+                    Some(acquired + 1)
+                } else {
+                    None
+                }
+            })
+            .ok()?;
+        Some(crate::BoundedProxy {
+            proxy: acquire(),
+            acquired: &self.acquired,
+        })
+    }
+
+    /// Acquire an [`I2cProxy`] for this bus, unless `MAX` proxies are acquired already.
+    ///
+    /// [`I2cProxy`]: ./struct.I2cProxy.html
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c_checked<'a>(
+        &'a self,
+    ) -> Option<crate::BoundedProxy<'a, crate::I2cProxy<'a, M>>> {
+        self.bounded(|| self.manager.acquire_i2c())
+    }
+
+    /// Acquire an [`AdcProxy`] for this hardware block, unless `MAX` proxies are acquired
+    /// already.
+    ///
+    /// [`AdcProxy`]: ./struct.AdcProxy.html
+    #[cfg(feature = "adc")]
+    pub fn acquire_adc_checked<'a>(
+        &'a self,
+    ) -> Option<crate::BoundedProxy<'a, crate::AdcProxy<'a, M>>> {
+        self.bounded(|| self.manager.acquire_adc())
+    }
+}
+
+#[cfg(all(feature = "spi", any(feature = "cortex-m", target_has_atomic = "ptr")))]
+impl<T, const MAX: usize> BoundedBusManager<crate::NullMutex<T>, MAX> {
+    /// Acquire an [`SpiProxy`] for this bus, unless `MAX` proxies are acquired already.
+    ///
+    /// See [`BusManager::acquire_spi()`][acquire_spi] for details.
+    ///
+    /// [`SpiProxy`]: ./struct.SpiProxy.html
+    /// [acquire_spi]: ./struct.BusManager.html#method.acquire_spi
+    pub fn acquire_spi_checked<'a>(
+        &'a self,
+    ) -> Option<crate::BoundedProxy<'a, crate::SpiProxy<'a, crate::NullMutex<T>>>> {
+        self.bounded(|| self.manager.acquire_spi())
+    }
+}
+
+/// Kind of proxies a [`SingleKindBusManager`] was locked in to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BusKind {
+    #[cfg(feature = "i2c")]
+    I2c,
+    #[cfg(feature = "spi")]
+    Spi,
+    #[cfg(feature = "adc")]
+    Adc,
+}
+
+/// "Manager" which only hands out proxies of a single kind.
+///
+/// A single bus peripheral is usually either an I2C or an SPI bus.  Acquiring both an I2C and an
+/// SPI proxy from the same manager is thus almost always a bug, which otherwise only shows up when
+/// the bus type happens to implement both traits.  The `SingleKindBusManager` remembers the kind
+/// of the first proxy which was acquired and panics when a proxy of a different kind is acquired
+/// later:
+///
+/// ```should_panic
+/// # use embedded_hal::blocking::i2c;
+/// # fn _example(i2c: impl i2c::Write) {
+/// let bus: shared_bus::SingleKindBusManager<shared_bus::NullMutex<_>> =
+///     shared_bus::SingleKindBusManager::new(i2c);
+///
+/// let proxy1 = bus.acquire_i2c();
+/// let proxy2 = bus.acquire_i2c();
+///
+/// // Panics, the bus was already used for I2C
+/// let proxy3 = bus.acquire_spi();
+/// # }
+/// # _example(embedded_hal_mock::eh0::i2c::Mock::new(&[]));
+/// ```
+///
+/// This check is opt-in as some peripherals can legitimately be used in different modes, which
+/// works fine with a plain [`BusManager`].
+///
+/// The kind is tracked in a `Cell`, so proxies must be acquired from a single execution context.
+///
+/// [`BusManager`]: ./struct.BusManager.html
+#[derive(Debug)]
+pub struct SingleKindBusManager<M> {
+    manager: BusManager<M>,
+    kind: core::cell::Cell<Option<BusKind>>,
+}
+
+impl<M: crate::BusMutex> SingleKindBusManager<M> {
+    /// Create a new single-kind bus manager for a bus.
+    pub fn new(bus: M::Bus) -> Self
+    where
+        M: crate::CreateBusMutex,
+    {
+        SingleKindBusManager {
+            manager: BusManager::new(bus),
+            kind: core::cell::Cell::new(None),
+        }
+    }
+
+    #[cfg_attr(
+        not(any(feature = "i2c", feature = "spi", feature = "adc")),
+        allow(dead_code)
+    )]
+    fn lock_in(&self, kind: BusKind) {
+        match self.kind.get() {
+            None => self.kind.set(Some(kind)),
+            Some(k) if k == kind => (),
+            Some(k) => panic!(
+                "bus was already acquired as {:?}, cannot acquire it as {:?}",
+                k, kind
+            ),
+        }
+    }
+
+    /// Acquire an [`I2cProxy`] for this bus.
+    ///
+    /// [`I2cProxy`]: ./struct.I2cProxy.html
+    ///
+    /// # Panics
+    /// Panics if a proxy of another kind was acquired before.
+    #[cfg(feature = "i2c")]
+    pub fn acquire_i2c<'a>(&'a self) -> crate::I2cProxy<'a, M> {
+        self.lock_in(BusKind::I2c);
+        self.manager.acquire_i2c()
+    }
+
+    /// Acquire an [`AdcProxy`] for this hardware block.
+    ///
+    /// [`AdcProxy`]: ./struct.AdcProxy.html
+    ///
+    /// # Panics
+    /// Panics if a proxy of another kind was acquired before.
+    #[cfg(feature = "adc")]
+    pub fn acquire_adc<'a>(&'a self) -> crate::AdcProxy<'a, M> {
+        self.lock_in(BusKind::Adc);
+        self.manager.acquire_adc()
+    }
+
+    /// Acquire an [`OwnedSpiDeviceProxy`] for a device with chip-select pin `cs`.
+    ///
+    /// See [`BusManager::acquire_spi_device()`][acquire_spi_device] for details.
+    ///
+    /// [`OwnedSpiDeviceProxy`]: ./struct.OwnedSpiDeviceProxy.html
+    /// [acquire_spi_device]: ./struct.BusManager.html#method.acquire_spi_device
+    ///
+    /// # Panics
+    /// Panics if a proxy of another kind was acquired before.
+    ///
+    /// This method is only available with the `eh1` feature.
+    #[cfg(all(feature = "spi", feature = "eh1"))]
+    pub fn acquire_spi_device<CS>(&'static self, cs: CS) -> crate::OwnedSpiDeviceProxy<M, CS> {
+        self.lock_in(BusKind::Spi);
+        self.manager.acquire_spi_device(cs)
+    }
+}
+
+#[cfg(feature = "spi")]
+impl<T> SingleKindBusManager<crate::NullMutex<T>> {
+    /// Acquire an [`SpiProxy`] for this bus.
+    ///
+    /// See [`BusManager::acquire_spi()`][acquire_spi] for details.
+    ///
+    /// [`SpiProxy`]: ./struct.SpiProxy.html
+    /// [acquire_spi]: ./struct.BusManager.html#method.acquire_spi
+    ///
+    /// # Panics
+    /// Panics if a proxy of another kind was acquired before.
+    pub fn acquire_spi<'a>(&'a self) -> crate::SpiProxy<'a, crate::NullMutex<T>> {
+        self.lock_in(BusKind::Spi);
+        self.manager.acquire_spi()
+    }
+}
+
+/// "Manager" for two interchangeable buses, e.g. for failover.
+///
+/// A `DualBusManager` manages a primary and a secondary bus, each behind its own mutex.  Devices
+/// which are reachable on both buses can use a [`FailoverI2cProxy`] which falls back to the
+/// secondary bus when an operation on the primary bus fails:
+///
+/// [`FailoverI2cProxy`]: ./struct.FailoverI2cProxy.html
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # fn _example<I: i2c::Write>(i2c1: I, i2c2: I) {
+/// let bus: shared_bus::DualBusManager<shared_bus::NullMutex<_>> =
+///     shared_bus::DualBusManager::new(i2c1, i2c2);
+///
+/// let mut proxy = bus.acquire_failover_i2c();
+/// let _ = proxy.write(0x39, &[0xc0, 0xff, 0xee]);
+///
+/// // Devices only connected to one of the buses use a regular proxy
+/// let proxy2 = bus.primary().acquire_i2c();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DualBusManager<M> {
+    primary: BusManager<M>,
+    secondary: BusManager<M>,
+}
+
+impl<M: crate::BusMutex> DualBusManager<M> {
+    /// Create a new manager for a primary and a secondary bus.
+    pub fn new(primary: M::Bus, secondary: M::Bus) -> Self
+    where
+        M: crate::CreateBusMutex,
+    {
+        DualBusManager {
+            primary: BusManager::new(primary),
+            secondary: BusManager::new(secondary),
+        }
+    }
+
+    /// Access the manager of the primary bus.
+    pub fn primary(&self) -> &BusManager<M> {
+        &self.primary
+    }
+
+    /// Access the manager of the secondary bus.
+    pub fn secondary(&self) -> &BusManager<M> {
+        &self.secondary
+    }
+
+    /// Acquire a [`FailoverI2cProxy`] which uses both buses.
+    ///
+    /// [`FailoverI2cProxy`]: ./struct.FailoverI2cProxy.html
+    #[cfg(feature = "i2c")]
+    pub fn acquire_failover_i2c<'a>(&'a self) -> crate::FailoverI2cProxy<'a, M> {
+        crate::FailoverI2cProxy {
+            primary: &self.primary.mutex,
+            secondary: &self.secondary.mutex,
+        }
+    }
+}
+
+/// "Manager" for an I2C bus which is split into separate read and write peripherals.
+///
+/// Some platforms use one peripheral for writing to and another one for reading from the same
+/// I2C bus.  The `SplitBusManager` owns both halves, each behind its own mutex, and hands out
+/// [`SplitI2cProxy`] proxies which lock only the half they need:
+///
+/// [`SplitI2cProxy`]: ./struct.SplitI2cProxy.html
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # fn _example<R, W>(i2c_read: R, i2c_write: W)
+/// # where
+/// #     R: i2c::Read + Send + 'static,
+/// #     W: i2c::Write<Error = R::Error> + Send + 'static,
+/// # {
+/// let bus: shared_bus::SplitBusManager<std::sync::Mutex<_>, std::sync::Mutex<_>> =
+///     shared_bus::SplitBusManager::new(i2c_read, i2c_write);
+///
+/// let mut proxy = bus.acquire_i2c();
+/// let mut buf = [0u8; 2];
+/// let _ = proxy.write_read(0x39, &[0x0f], &mut buf);
+/// # }
+/// ```
+#[cfg(feature = "i2c")]
+#[derive(Debug)]
+pub struct SplitBusManager<R, W> {
+    read: R,
+    write: W,
+}
+
+#[cfg(feature = "i2c")]
+impl<R: crate::BusMutex, W: crate::BusMutex> SplitBusManager<R, W> {
+    /// Create a new manager for the read and the write half of a bus.
+    pub fn new(read: R::Bus, write: W::Bus) -> Self
+    where
+        R: crate::CreateBusMutex,
+        W: crate::CreateBusMutex,
+    {
+        SplitBusManager {
+            read: R::create(read),
+            write: W::create(write),
+        }
+    }
+
+    /// Acquire a [`SplitI2cProxy`] for this bus.
+    ///
+    /// [`SplitI2cProxy`]: ./struct.SplitI2cProxy.html
+    pub fn acquire_i2c<'a>(&'a self) -> crate::SplitI2cProxy<'a, R, W> {
+        crate::SplitI2cProxy {
+            read: &self.read,
+            write: &self.write,
+        }
+    }
+}
+
+/// "Manager" for an I2C bus whose writes are mirrored to a second, "shadow" bus.
+///
+/// This is a debugging aid:  The `TeeBusManager` owns the real bus and a shadow bus (e.g. a dummy
+/// device watched by a logic analyzer, or a [`FormatBus`] writing into a log) behind a single
+/// mutex.  Its [`TeeI2cProxy`] proxies perform each operation on the real bus and then replay the
+/// writes on the shadow bus, while still holding the lock:
+///
+/// [`FormatBus`]: ./struct.FormatBus.html
+/// [`TeeI2cProxy`]: ./struct.TeeI2cProxy.html
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # fn _example(i2c: impl i2c::Write, shadow: impl i2c::Write) {
+/// let bus: shared_bus::TeeBusManager<shared_bus::NullMutex<_>> =
+///     shared_bus::TeeBusManager::new(i2c, shadow);
+///
+/// let mut proxy = bus.acquire_i2c();
+/// // Written to both buses
+/// let _ = proxy.write(0x39, &[0xc0, 0xff, 0xee]);
+/// # }
+/// ```
+#[cfg(feature = "i2c")]
+#[derive(Debug)]
+pub struct TeeBusManager<M> {
+    mutex: M,
+}
+
+#[cfg(feature = "i2c")]
+impl<M: crate::BusMutex<Bus = (P, S)>, P, S> TeeBusManager<M> {
+    /// Create a new manager for the real bus `primary` and the `shadow` bus.
+    pub fn new(primary: P, shadow: S) -> Self
+    where
+        M: crate::CreateBusMutex,
+    {
+        TeeBusManager {
+            mutex: M::create((primary, shadow)),
+        }
+    }
+
+    /// Acquire a [`TeeI2cProxy`] for this bus.
+    ///
+    /// [`TeeI2cProxy`]: ./struct.TeeI2cProxy.html
+    pub fn acquire_i2c<'a>(&'a self) -> crate::TeeI2cProxy<'a, M> {
+        crate::TeeI2cProxy { mutex: &self.mutex }
+    }
+}
+
+impl<T> BusManager<crate::NullMutex<T>> {
+    /// Acquire an [`SpiProxy`] for this bus.
+    ///
+    /// **Note**: SPI Proxies can only be created from [`BusManagerSimple`] (= bus managers using
+    /// the [`NullMutex`]).  See [`SpiProxy`] for more details why.
+    ///
+    /// [`BusManagerSimple`]: ./type.BusManagerSimple.html
+    /// [`NullMutex`]: ./struct.NullMutex.html
+    /// [`SpiProxy`]: ./struct.SpiProxy.html
+    ///
+    /// For peripherals which can act as both SPI and I2C, `acquire_spi()` and [`acquire_i2c()`]
+    /// can be used on the same manager.  Both proxies forward to the respective trait
+    /// implementations of the same underlying bus object.
+    ///
+    /// [`acquire_i2c()`]: #method.acquire_i2c
+    ///
+    /// The returned proxy object can then be used for accessing the bus by e.g. a driver:
+    ///
+    /// ```
+    /// # use embedded_hal::blocking::spi;
+    /// # use embedded_hal::digital::v2;
+    /// # use embedded_hal::blocking::spi::Write as _;
+    /// # struct MyDevice<T>(T);
+    /// # impl<T: spi::Write<u8>> MyDevice<T> {
+    /// #     pub fn new(t: T) -> Self { MyDevice(t) }
+    /// #     pub fn do_something_on_the_bus(&mut self) {
+    /// #         self.0.write(&[0x00]);
+    /// #     }
+    /// # }
+    /// #
+    /// # fn _example(mut cs1: impl v2::OutputPin, spi: impl spi::Write<u8>) {
+    /// let bus = shared_bus::BusManagerSimple::new(spi);
+    ///
+    /// let mut proxy1 = bus.acquire_spi();
+    /// let mut my_device = MyDevice::new(bus.acquire_spi());
+    ///
+    /// // Chip-select needs to be managed manually
+    /// cs1.set_high();
+    /// proxy1.write(&[0xc0, 0xff, 0xee]);
+    /// cs1.set_low();
+    ///
+    /// my_device.do_something_on_the_bus();
+    /// # }
+    /// ```
+    #[cfg(feature = "spi")]
+    pub fn acquire_spi<'a>(&'a self) -> crate::SpiProxy<'a, crate::NullMutex<T>> {
+        crate::SpiProxy {
+            mutex: &self.mutex,
+            _u: core::marker::PhantomData,
+            #[cfg(feature = "log")]
+            check_cs: true,
+            #[cfg(feature = "metrics")]
+            metrics: &self.metrics,
+        }
+    }
+
+    /// Convert this manager into one using a different mutex type.
+    ///
+    /// The bus is taken out of the [`NullMutex`] and wrapped in the new mutex.  This allows
+    /// prototyping with a [`BusManagerSimple`] and moving to multiple tasks/threads later by
+    /// changing a single line at initialization:
+    ///
+    /// [`BusManagerSimple`]: ./type.BusManagerSimple.html
+    /// [`NullMutex`]: ./struct.NullMutex.html
+    ///
+    /// ```
+    /// # use embedded_hal::blocking::i2c;
+    /// # fn _example<I: i2c::Write + Send>(i2c: I) {
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    /// let bus: shared_bus::BusManagerStd<_> = bus.upgrade();
+    /// # }
+    /// ```
+    ///
+    /// As the manager is consumed, all proxies acquired from it must have been dropped before.
+    /// For the common mutex types, there are shorthands like [`upgrade_std()`].
+    ///
+    /// [`upgrade_std()`]: #method.upgrade_std
+    pub fn upgrade<M: crate::CreateBusMutex<Bus = T>>(self) -> BusManager<M> {
+        BusManager::new(self.mutex.into_inner())
+    }
+
+    /// Convert this manager into a [`BusManagerStd`].
+    ///
+    /// See [`upgrade()`] for details.
+    ///
+    /// [`BusManagerStd`]: ./type.BusManagerStd.html
+    /// [`upgrade()`]: #method.upgrade
+    ///
+    /// This method is only available with the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn upgrade_std(self) -> crate::BusManagerStd<T> {
+        self.upgrade()
+    }
+
+    /// Convert this manager into a [`BusManagerAuto`].
+    ///
+    /// See [`upgrade()`] for details.
+    ///
+    /// [`BusManagerAuto`]: ./type.BusManagerAuto.html
+    /// [`upgrade()`]: #method.upgrade
+    ///
+    /// This method is only available with the `critical-section` feature.
+    #[cfg(feature = "critical-section")]
+    pub fn upgrade_auto(self) -> crate::BusManagerAuto<T> {
+        self.upgrade()
+    }
+
+    /// Convert this manager into a [`BusManagerCortexM`].
+    ///
+    /// See [`upgrade()`] for details.
+    ///
+    /// [`BusManagerCortexM`]: ./type.BusManagerCortexM.html
+    /// [`upgrade()`]: #method.upgrade
+    ///
+    /// This method is only available with the `cortex-m` feature.
+    #[cfg(feature = "cortex-m")]
+    pub fn upgrade_cortexm(self) -> crate::BusManagerCortexM<T> {
+        self.upgrade()
     }
 }
@@ -0,0 +1,33 @@
+use portable_atomic::{AtomicUsize, Ordering};
+
+/// Number of bytes transferred over a bus, in both directions.
+#[derive(Debug, Default)]
+pub(crate) struct ByteCounters {
+    written: AtomicUsize,
+    read: AtomicUsize,
+}
+
+impl ByteCounters {
+    pub(crate) const fn new() -> Self {
+        ByteCounters {
+            written: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+        }
+    }
+
+    /// Account for a transfer of `written` bytes to and `read` bytes from the bus.
+    pub(crate) fn add(&self, written: usize, read: usize) {
+        // The counters are independent of each other and of the bus accesses, so no ordering is
+        // needed.  On overflow, they wrap around.
+        self.written.fetch_add(written, Ordering::Relaxed);
+        self.read.fetch_add(read, Ordering::Relaxed);
+    }
+
+    pub(crate) fn written(&self) -> usize {
+        self.written.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn read(&self) -> usize {
+        self.read.load(Ordering::Relaxed)
+    }
+}
@@ -12,8 +12,10 @@ use core::cell;
 /// | [`shared_bus::NullMutex`][null-mutex] | always available | For sharing within a single execution context. |
 /// | [`std::sync::Mutex`][std-mutex] | `std` | For platforms where `std` is available. |
 /// | [`cortex_m::interrupt::Mutex`][cortexm-mutex] | `cortex-m` | For Cortex-M platforms; Uses a critcal section (i.e. turns off interrupts during bus transactions). |
+/// | [`critical_section::Mutex`][auto-mutex] | `critical-section` | For any platform with a `critical-section` implementation. |
 ///
 /// [null-mutex]: ./struct.NullMutex.html
+/// [auto-mutex]: ./type.AutoMutex.html
 /// [std-mutex]: https://doc.rust-lang.org/std/sync/struct.Mutex.html
 /// [cortexm-mutex]: https://docs.rs/cortex-m/0.6.3/cortex_m/interrupt/struct.Mutex.html
 ///
@@ -27,123 +29,2092 @@ use core::cell;
 /// impl<T> shared_bus::BusMutex for MyMutex<T> {
 ///     type Bus = T;
 ///
-///     fn create(v: T) -> Self {
-///         Self(std::sync::Mutex::new(v))
-///     }
-///
 ///     fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
 ///         let mut v = self.0.lock().unwrap();
 ///         f(&mut v)
 ///     }
 /// }
 ///
+/// impl<T> shared_bus::CreateBusMutex for MyMutex<T> {
+///     fn create(v: T) -> Self {
+///         Self(std::sync::Mutex::new(v))
+///     }
+/// }
+///
 /// // It is also beneficial to define a type alias for the BusManager
 /// type BusManagerCustom<BUS> = shared_bus::BusManager<MyMutex<BUS>>;
 /// ```
+///
+/// When a platform only offers a way to enter a critical section, the [`ClosureMutex`] can be
+/// used instead of writing a custom implementation.
+///
+/// [`ClosureMutex`]: ./struct.ClosureMutex.html
 pub trait BusMutex {
     /// The actual bus that is wrapped inside this mutex.
     type Bus;
 
-    /// Create a new mutex of this type.
-    fn create(v: Self::Bus) -> Self;
+    /// Name of the mutex type, for diagnostics.
+    ///
+    /// The mutexes of this crate report their type name, e.g. `"CortexMMutex"`.  Custom
+    /// implementations default to `"custom"`.
+    const KIND: &'static str = "custom";
+
+    /// Lock the mutex and give a closure access to the bus inside.
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R;
+}
+
+/// Mutex implementations which can be created from the bus alone.
+///
+/// This is needed by [`BusManager::new()`] and the other constructors which take the bus.  All
+/// mutex types of this crate implement it, except for the [`ClosureMutex`]:  It needs a function
+/// besides the bus, so it is created by the user and passed to [`BusManager::from_mutex()`].
+///
+/// [`BusManager::new()`]: ./struct.BusManager.html#method.new
+/// [`ClosureMutex`]: ./struct.ClosureMutex.html
+/// [`BusManager::from_mutex()`]: ./struct.BusManager.html#method.from_mutex
+pub trait CreateBusMutex: BusMutex {
+    /// Create a new mutex of this type.
+    fn create(v: Self::Bus) -> Self;
+}
+
+/// Mutex implementations which can hand out an RAII guard for the bus.
+///
+/// The closure-based [`BusMutex::lock()`] is supported by all mutex types.  Some of them can also
+/// return a guard which keeps the mutex locked until it is dropped.  This allows holding the lock
+/// across several statements without nesting everything in a closure.
+///
+/// Mutexes which lock by entering a critical section (e.g. [`CortexMMutex`]) cannot support this
+/// safely, because the guard could outlive the critical section.  Here is an overview of the
+/// mutex types implementing this trait:
+///
+/// | Mutex | Feature Name |
+/// | --- | --- |
+/// | [`shared_bus::NullMutex`][null-mutex] | always available |
+/// | [`std::sync::Mutex`][std-mutex] | `std` |
+///
+/// [`BusMutex::lock()`]: ./trait.BusMutex.html#tymethod.lock
+/// [`CortexMMutex`]: ./type.CortexMMutex.html
+/// [null-mutex]: ./struct.NullMutex.html
+/// [std-mutex]: https://doc.rust-lang.org/std/sync/struct.Mutex.html
+pub trait GuardedBusMutex: BusMutex {
+    /// The guard type which keeps the mutex locked while it is alive.
+    type Guard<'a>: core::ops::DerefMut<Target = Self::Bus>
+    where
+        Self: 'a;
+
+    /// Lock the mutex and return a guard giving access to the bus inside.
+    fn lock_guard(&self) -> Self::Guard<'_>;
+}
+
+/// Reason why a [`TryBusMutex`] could not be locked.
+///
+/// [`TryBusMutex`]: ./trait.TryBusMutex.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockError {
+    /// The bus is already in use, e.g. a bus conflict was detected by the
+    /// [`AtomicCheckMutex`][atomic-check] or the bus was locked re-entrantly.
+    ///
+    /// [atomic-check]: ./struct.AtomicCheckMutex.html
+    Busy,
+    /// A thread panicked while holding the lock (`std::sync::Mutex` poisoning).
+    Poisoned,
+    /// The bus is quiet and the [`QuietMutex`][quiet] is configured to reject accesses.
+    ///
+    /// [quiet]: ./struct.QuietMutex.html
+    Quiet,
+}
+
+/// Error returned by the `try_*` methods of the proxies.
+///
+/// See [`TryBusMutex`] for details.
+///
+/// [`TryBusMutex`]: ./trait.TryBusMutex.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<E> {
+    /// The bus could not be locked.
+    Lock(LockError),
+    /// An error occurred on the bus.
+    Bus(E),
+}
+
+impl<E> From<LockError> for Error<E> {
+    fn from(e: LockError) -> Self {
+        Error::Lock(e)
+    }
+}
+
+/// Mutex implementations which can report locking failures instead of panicking.
+///
+/// [`BusMutex::lock()`] panics when the mutex cannot be locked.  Mutexes implementing this trait
+/// can instead report the failure as a [`LockError`].  The proxies then offer `try_*` variants of
+/// their methods which return an [`Error`], so the failure policy is chosen at the call site: The
+/// `embedded-hal` trait methods panic, the `try_*` methods return an error.
+///
+/// | Mutex | Failure | `lock()` / trait methods | `try_lock()` / `try_*` methods |
+/// | --- | --- | --- | --- |
+/// | [`NullMutex`] | re-entrant locking | panic | `LockError::Busy` |
+/// | [`std::sync::Mutex`][std-mutex] | poisoned | panic | `LockError::Poisoned` |
+/// | [`CortexMMutex`] | re-entrant locking | panic | `LockError::Busy` |
+/// | [`AutoMutex`] | re-entrant locking | panic | `LockError::Busy` |
+/// | [`AtomicCheckMutex`] | bus conflict | panic | `LockError::Busy` |
+/// | [`XtensaMutex`] | locked by preempted code | spins forever | `LockError::Busy` |
+/// | [`QuietMutex`] | bus is quiet (with `QuietPolicy::Error`) | panic | `LockError::Quiet` |
+///
+/// Contention on a `std::sync::Mutex` is not a failure, the `try_*` methods still wait for the
+/// bus to become available.
+///
+/// [`BusMutex::lock()`]: ./trait.BusMutex.html#tymethod.lock
+/// [`LockError`]: ./enum.LockError.html
+/// [`Error`]: ./enum.Error.html
+/// [`NullMutex`]: ./struct.NullMutex.html
+/// [std-mutex]: https://doc.rust-lang.org/std/sync/struct.Mutex.html
+/// [`CortexMMutex`]: ./type.CortexMMutex.html
+/// [`AutoMutex`]: ./type.AutoMutex.html
+/// [`AtomicCheckMutex`]: ./struct.AtomicCheckMutex.html
+/// [`XtensaMutex`]: ./struct.XtensaMutex.html
+/// [`QuietMutex`]: ./struct.QuietMutex.html
+pub trait TryBusMutex: BusMutex {
+    /// Try to lock the mutex and give a closure access to the bus inside.
+    fn try_lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> Result<R, LockError>;
+}
+
+/// "Dummy" mutex for sharing in a single task/thread.
+///
+/// This mutex type can be used when all bus users are contained in a single execution context.  In
+/// such a situation, no actual mutex is needed, because a RefCell alone is sufficient to ensuring
+/// only a single peripheral can access the bus at the same time.
+///
+/// This mutex type is used with the [`BusManagerSimple`] type.
+///
+/// To uphold safety, this type is `!Send` and `!Sync`.
+///
+/// Locking the mutex while it is already locked, e.g. from a cloned proxy while a
+/// [`BusScope`] is held, panics.  In debug builds, the panic message explains the overlapping
+/// bus access.
+///
+/// [`BusScope`]: ./struct.BusScope.html
+/// [`BusManagerSimple`]: ./type.BusManagerSimple.html
+#[derive(Debug)]
+pub struct NullMutex<T> {
+    bus: cell::RefCell<T>,
+}
+
+impl<T> BusMutex for NullMutex<T> {
+    type Bus = T;
+    const KIND: &'static str = "NullMutex";
+
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
+        let mut v = self.borrow_bus();
+        f(&mut v)
+    }
+}
+
+impl<T> CreateBusMutex for NullMutex<T> {
+    fn create(v: Self::Bus) -> Self {
+        NullMutex::new(v)
+    }
+}
+
+impl<T> TryBusMutex for NullMutex<T> {
+    fn try_lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> Result<R, LockError> {
+        let mut v = self.bus.try_borrow_mut().map_err(|_| LockError::Busy)?;
+        Ok(f(&mut v))
+    }
+}
+
+impl<T> NullMutex<T> {
+    /// Create a new mutex for `bus`.
+    ///
+    /// Unlike [`CreateBusMutex::create()`], this can be used in const context.
+    ///
+    /// [`CreateBusMutex::create()`]: ./trait.CreateBusMutex.html#tymethod.create
+    pub const fn new(bus: T) -> Self {
+        NullMutex {
+            bus: cell::RefCell::new(bus),
+        }
+    }
+
+    /// Consume the mutex, returning the bus inside.
+    pub fn into_inner(self) -> T {
+        self.bus.into_inner()
+    }
+
+    /// Borrow the bus, explaining overlapping bus accesses in debug builds.
+    fn borrow_bus(&self) -> cell::RefMut<'_, T> {
+        #[cfg(debug_assertions)]
+        match self.bus.try_borrow_mut() {
+            Ok(v) => v,
+            Err(_) => panic!(
+                "overlapping bus access: the bus was locked while it was already in use.  \
+                 For SPI, this means a transfer was started while another transaction on the \
+                 same bus was still ongoing, e.g. through a cloned SpiProxy or while a BusScope \
+                 was held.  Finish the ongoing transaction before starting the next one."
+            ),
+        }
+        #[cfg(not(debug_assertions))]
+        self.bus.borrow_mut()
+    }
+}
+
+impl<T> GuardedBusMutex for NullMutex<T> {
+    type Guard<'a>
+        = cell::RefMut<'a, T>
+    where
+        T: 'a;
+
+    fn lock_guard(&self) -> Self::Guard<'_> {
+        self.borrow_bus()
+    }
+}
+
+#[cfg(all(feature = "std", debug_assertions))]
+std::thread_local! {
+    /// The mutex whose lock is held while a user callback runs on this thread, if any.
+    static CALLBACK_MUTEX: cell::Cell<*const ()> = const { cell::Cell::new(core::ptr::null()) };
+}
+
+/// Call a user callback (e.g. a retry predicate) while the lock of `mutex` is held.
+///
+/// In debug builds with `std`, the mutex is remembered so the blocking mutex implementations
+/// can detect a callback which tries to lock it again, instead of deadlocking silently.  The
+/// non-blocking mutexes detect this on their own by panicking.
+#[cfg_attr(not(feature = "i2c"), allow(dead_code))]
+pub(crate) fn in_callback<M, R>(mutex: &M, f: impl FnOnce() -> R) -> R {
+    #[cfg(all(feature = "std", debug_assertions))]
+    {
+        struct Restore(*const ());
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                CALLBACK_MUTEX.with(|c| c.set(self.0));
+            }
+        }
+
+        let _restore = Restore(CALLBACK_MUTEX.with(|c| c.replace(mutex as *const M as *const ())));
+        f()
+    }
+    #[cfg(not(all(feature = "std", debug_assertions)))]
+    {
+        let _ = mutex;
+        f()
+    }
+}
+
+/// Panic (in debug builds) if a callback tries to lock the mutex it was called from.
+#[cfg(feature = "std")]
+fn check_reentrancy<M>(mutex: &M) {
+    #[cfg(debug_assertions)]
+    CALLBACK_MUTEX.with(|c| {
+        assert!(
+            c.get() != mutex as *const M as *const (),
+            "callback re-entered the bus it was called from, this would deadlock"
+        )
+    });
+    #[cfg(not(debug_assertions))]
+    let _ = mutex;
+}
+
+#[cfg(feature = "std")]
+impl<T> BusMutex for ::std::sync::Mutex<T> {
+    type Bus = T;
+    const KIND: &'static str = "std::sync::Mutex";
+
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
+        check_reentrancy(self);
+        let mut v = self.lock().unwrap();
+        f(&mut v)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> CreateBusMutex for ::std::sync::Mutex<T> {
+    fn create(v: Self::Bus) -> Self {
+        ::std::sync::Mutex::new(v)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> TryBusMutex for ::std::sync::Mutex<T> {
+    fn try_lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> Result<R, LockError> {
+        check_reentrancy(self);
+        let mut v = self.lock().map_err(|_| LockError::Poisoned)?;
+        Ok(f(&mut v))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> GuardedBusMutex for ::std::sync::Mutex<T> {
+    type Guard<'a>
+        = ::std::sync::MutexGuard<'a, T>
+    where
+        T: 'a;
+
+    fn lock_guard(&self) -> Self::Guard<'_> {
+        check_reentrancy(self);
+        self.lock().unwrap()
+    }
+}
+
+/// Mutex which records how long it is held, for use with a watchdog.
+///
+/// The `WatchdogMutex` works just like a [`std::sync::Mutex`][std-mutex] but additionally
+/// remembers when it was locked.  This allows a watchdog thread to detect bus users which hold
+/// the bus for too long.  See [`BusManagerWatchdog`] for details.
+///
+/// [std-mutex]: https://doc.rust-lang.org/std/sync/struct.Mutex.html
+/// [`BusManagerWatchdog`]: ./type.BusManagerWatchdog.html
+///
+/// This type is only available with the `std` feature.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct WatchdogMutex<T> {
+    bus: ::std::sync::Mutex<T>,
+    held: ::std::sync::Mutex<HoldState>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+struct HoldState {
+    since: Option<::std::time::Instant>,
+    /// Number of times the mutex was locked, to tell different lock holders apart.
+    count: u64,
+}
+
+#[cfg(feature = "std")]
+impl<T> WatchdogMutex<T> {
+    /// Return when the mutex was locked and a number identifying this particular lock, if it is
+    /// locked right now.
+    pub(crate) fn held_since(&self) -> Option<(::std::time::Instant, u64)> {
+        let held = self.held.lock().unwrap();
+        held.since.map(|since| (since, held.count))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> BusMutex for WatchdogMutex<T> {
+    type Bus = T;
+    const KIND: &'static str = "WatchdogMutex";
+
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
+        check_reentrancy(self);
+        let mut v = self.bus.lock().unwrap();
+
+        /// Marks the mutex as released again, even if `f` panics.
+        struct Release<'a>(&'a ::std::sync::Mutex<HoldState>);
+
+        impl Drop for Release<'_> {
+            fn drop(&mut self) {
+                self.0.lock().unwrap_or_else(|e| e.into_inner()).since = None;
+            }
+        }
+
+        {
+            let mut held = self.held.lock().unwrap();
+            held.since = Some(::std::time::Instant::now());
+            held.count += 1;
+        }
+        let _release = Release(&self.held);
+
+        f(&mut v)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> CreateBusMutex for WatchdogMutex<T> {
+    fn create(v: T) -> Self {
+        WatchdogMutex {
+            bus: ::std::sync::Mutex::new(v),
+            held: Default::default(),
+        }
+    }
+}
+
+/// Identifies the holder of a [`DiagnosticMutex`].
+///
+/// [`DiagnosticMutex`]: ./struct.DiagnosticMutex.html
+///
+/// This type is only available with the `diagnostics` feature.
+#[cfg(feature = "diagnostics")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HolderId {
+    /// The thread holding the lock.
+    ///
+    /// This variant is only available with the `std` feature.
+    #[cfg(feature = "std")]
+    Thread(::std::thread::ThreadId),
+    /// The task holding the lock, as reported by the function passed to
+    /// [`DiagnosticMutex::with_task_id()`][with_task_id].
+    ///
+    /// [with_task_id]: ./struct.DiagnosticMutex.html#method.with_task_id
+    Task(usize),
+}
+
+/// Storage for the current holder, readable while the mutex is locked by someone else.
+#[cfg(all(feature = "diagnostics", feature = "std"))]
+#[derive(Debug, Default)]
+struct HolderCell(::std::sync::Mutex<Option<HolderId>>);
+
+#[cfg(all(feature = "diagnostics", feature = "std"))]
+impl HolderCell {
+    fn set(&self, holder: Option<HolderId>) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = holder;
+    }
+
+    fn get(&self) -> Option<HolderId> {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Storage for the current holder, readable while the mutex is locked by someone else.
+///
+/// Without `std`, only task ids can be recorded.  Plain loads and stores are enough for a
+/// diagnostic value; in the worst case, a reader sees the id of a holder which just released the
+/// lock.
+#[cfg(all(feature = "diagnostics", not(feature = "std")))]
+#[derive(Debug, Default)]
+struct HolderCell {
+    held: core::sync::atomic::AtomicBool,
+    task: core::sync::atomic::AtomicUsize,
+}
+
+#[cfg(all(feature = "diagnostics", not(feature = "std")))]
+impl HolderCell {
+    fn set(&self, holder: Option<HolderId>) {
+        use core::sync::atomic::Ordering;
+        match holder {
+            Some(HolderId::Task(task)) => {
+                self.task.store(task, Ordering::Relaxed);
+                self.held.store(true, Ordering::Release);
+            }
+            None => self.held.store(false, Ordering::Release),
+        }
+    }
+
+    fn get(&self) -> Option<HolderId> {
+        use core::sync::atomic::Ordering;
+        if self.held.load(Ordering::Acquire) {
+            Some(HolderId::Task(self.task.load(Ordering::Relaxed)))
+        } else {
+            None
+        }
+    }
+}
+
+/// Mutex wrapper which records who is holding the lock, for diagnosing deadlocks.
+///
+/// The `DiagnosticMutex` wraps any other [`BusMutex`] and remembers the current holder while the
+/// bus is locked.  [`BusManager::current_holder()`][current_holder] reports it, e.g. to find out
+/// which driver is hogging the bus:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # fn _example(i2c: impl i2c::Write + Send) {
+/// let bus: shared_bus::BusManager<shared_bus::DiagnosticMutex<std::sync::Mutex<_>>> =
+///     shared_bus::BusManager::new(i2c);
+///
+/// if let Some(holder) = bus.current_holder() {
+///     eprintln!("The bus is held by {:?}", holder);
+/// }
+/// # }
+/// ```
+///
+/// With the `std` feature, the [`ThreadId`] of the holder is recorded.  Without it (or on top of
+/// it), a function returning an id of the current task can be passed to
+/// [`with_task_id()`][with_task_id].  Without `std` and without such a function, nothing is
+/// recorded.
+///
+/// Recording the holder adds some overhead to each lock, so this type is only available with
+/// the `diagnostics` feature.
+///
+/// [`BusMutex`]: ./trait.BusMutex.html
+/// [current_holder]: ./struct.BusManager.html#method.current_holder
+/// [`ThreadId`]: https://doc.rust-lang.org/std/thread/struct.ThreadId.html
+/// [with_task_id]: #method.with_task_id
+#[cfg(feature = "diagnostics")]
+#[derive(Debug)]
+pub struct DiagnosticMutex<M> {
+    inner: M,
+    holder: HolderCell,
+    task_id: Option<fn() -> usize>,
+}
+
+#[cfg(feature = "diagnostics")]
+impl<M: BusMutex> DiagnosticMutex<M> {
+    /// Create a mutex which records the holder as reported by `task_id`.
+    ///
+    /// Use it with [`BusManager::from_mutex()`][from_mutex].
+    ///
+    /// [from_mutex]: ./struct.BusManager.html#method.from_mutex
+    pub fn with_task_id(bus: M::Bus, task_id: fn() -> usize) -> Self
+    where
+        M: CreateBusMutex,
+    {
+        DiagnosticMutex {
+            task_id: Some(task_id),
+            ..Self::create(bus)
+        }
+    }
+
+    /// Return the current holder of the lock, if it is locked right now.
+    pub fn current_holder(&self) -> Option<HolderId> {
+        self.holder.get()
+    }
+
+    fn holder_id(&self) -> Option<HolderId> {
+        match self.task_id {
+            Some(task_id) => Some(HolderId::Task(task_id())),
+            #[cfg(feature = "std")]
+            None => Some(HolderId::Thread(::std::thread::current().id())),
+            #[cfg(not(feature = "std"))]
+            None => None,
+        }
+    }
+
+    /// Run `f` while recording the current holder, forgetting it again afterwards (even if `f`
+    /// panics).
+    fn held<R>(&self, f: impl FnOnce() -> R) -> R {
+        struct Release<'a>(&'a HolderCell);
+
+        impl Drop for Release<'_> {
+            fn drop(&mut self) {
+                self.0.set(None);
+            }
+        }
+
+        self.holder.set(self.holder_id());
+        let _release = Release(&self.holder);
+        f()
+    }
+}
+
+#[cfg(feature = "diagnostics")]
+impl<M: BusMutex> BusMutex for DiagnosticMutex<M> {
+    type Bus = M::Bus;
+    const KIND: &'static str = M::KIND;
+
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
+        self.inner.lock(|bus| self.held(|| f(bus)))
+    }
+}
+
+#[cfg(feature = "diagnostics")]
+impl<M: CreateBusMutex> CreateBusMutex for DiagnosticMutex<M> {
+    fn create(v: Self::Bus) -> Self {
+        DiagnosticMutex {
+            inner: M::create(v),
+            holder: HolderCell::default(),
+            task_id: None,
+        }
+    }
+}
+
+#[cfg(feature = "diagnostics")]
+impl<M: TryBusMutex> TryBusMutex for DiagnosticMutex<M> {
+    fn try_lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> Result<R, LockError> {
+        self.inner.try_lock(|bus| self.held(|| f(bus)))
+    }
+}
+
+/// What a [`QuietMutex`] does with accesses while the bus is quiet.
+///
+/// [`QuietMutex`]: ./struct.QuietMutex.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuietPolicy {
+    /// Wait until the quiet window ends.
+    #[default]
+    Block,
+    /// Fail with [`LockError::Quiet`] (or panic in the `embedded-hal` trait methods).
+    ///
+    /// [`LockError::Quiet`]: ./enum.LockError.html#variant.Quiet
+    Error,
+}
+
+/// Mutex wrapper which can keep the bus quiet, e.g. during sensitive measurements.
+///
+/// The `QuietMutex` wraps any other [`BusMutex`].  While the bus is quiet (see
+/// [`BusManager::set_quiet()`][set_quiet]), no proxy can access it.  Depending on the
+/// [`QuietPolicy`], accesses either wait until the bus is no longer quiet, or fail:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # fn measure_rf() {}
+/// # fn _example(i2c: impl i2c::Write + Send) {
+/// let bus: shared_bus::BusManager<shared_bus::QuietMutex<std::sync::Mutex<_>>> =
+///     shared_bus::BusManager::new(i2c);
+///
+/// // Low-EMI window, all other threads accessing the bus wait until it ends
+/// bus.set_quiet(true);
+/// measure_rf();
+/// bus.set_quiet(false);
+/// # }
+/// ```
+///
+/// Setting the bus quiet does not wait for an access which is already in progress to finish.
+/// Lock the bus (e.g. with [`BusManager::lock_exclusive()`][lock_exclusive]) before setting it
+/// quiet if that is needed.
+///
+/// With the `Block` policy, the waiting is done by spinning (or yielding the thread with the
+/// `std` feature).  Bus users which preempt the code that sets the bus quiet (e.g. interrupt
+/// handlers) must not wait, as the quiet window would never end.  Use the `Error` policy for
+/// them.
+///
+/// [`BusMutex`]: ./trait.BusMutex.html
+/// [set_quiet]: ./struct.BusManager.html#method.set_quiet
+/// [`QuietPolicy`]: ./enum.QuietPolicy.html
+/// [lock_exclusive]: ./struct.BusManager.html#method.lock_exclusive
+#[derive(Debug)]
+pub struct QuietMutex<M> {
+    inner: M,
+    quiet: core::sync::atomic::AtomicBool,
+    policy: QuietPolicy,
+}
+
+impl<M: BusMutex> QuietMutex<M> {
+    /// Create a mutex which handles accesses during a quiet window according to `policy`.
+    ///
+    /// Use it with [`BusManager::from_mutex()`][from_mutex].
+    ///
+    /// [from_mutex]: ./struct.BusManager.html#method.from_mutex
+    pub fn with_policy(bus: M::Bus, policy: QuietPolicy) -> Self
+    where
+        M: CreateBusMutex,
+    {
+        QuietMutex {
+            policy,
+            ..Self::create(bus)
+        }
+    }
+
+    /// Start or end a quiet window.
+    pub fn set_quiet(&self, quiet: bool) {
+        self.quiet
+            .store(quiet, core::sync::atomic::Ordering::Release);
+    }
+
+    /// Check whether the bus is quiet right now.
+    pub fn is_quiet(&self) -> bool {
+        self.quiet.load(core::sync::atomic::Ordering::Acquire)
+    }
+
+    /// Run `f` on the bus unless it is quiet, waiting for the quiet window to end with the
+    /// `Block` policy.
+    fn access<R, F: FnOnce(&mut M::Bus) -> R>(
+        &self,
+        f: F,
+        mut lock: impl FnMut(&mut dyn FnMut(&mut M::Bus) -> Option<R>) -> Result<Option<R>, LockError>,
+    ) -> Result<R, LockError> {
+        let mut f = Some(f);
+        loop {
+            while self.is_quiet() {
+                if self.policy == QuietPolicy::Error {
+                    return Err(LockError::Quiet);
+                }
+                #[cfg(feature = "std")]
+                ::std::thread::yield_now();
+                #[cfg(not(feature = "std"))]
+                core::hint::spin_loop();
+            }
+            // Check again while holding the lock, the bus may have become quiet in between.
+            let res = lock(&mut |bus| match self.is_quiet() {
+                false => f.take().map(|f| f(bus)),
+                true => None,
+            })?;
+            if let Some(res) = res {
+                return Ok(res);
+            }
+        }
+    }
+}
+
+impl<M: BusMutex> BusMutex for QuietMutex<M> {
+    type Bus = M::Bus;
+    const KIND: &'static str = M::KIND;
+
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
+        self.access(f, |g| Ok(self.inner.lock(g)))
+            .expect("Bus accessed while quiet")
+    }
+}
+
+impl<M: CreateBusMutex> CreateBusMutex for QuietMutex<M> {
+    fn create(v: Self::Bus) -> Self {
+        QuietMutex {
+            inner: M::create(v),
+            quiet: core::sync::atomic::AtomicBool::new(false),
+            policy: QuietPolicy::Block,
+        }
+    }
+}
+
+impl<M: TryBusMutex> TryBusMutex for QuietMutex<M> {
+    fn try_lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> Result<R, LockError> {
+        self.access(f, |g| self.inner.try_lock(g))
+    }
+}
+
+/// Mutex which serves waiting threads in the order they arrived.
+///
+/// A [`std::sync::Mutex`][std-mutex] makes no promise about which of the waiting threads gets the
+/// lock next, so under contention, one thread can be starved by others which lock the bus in a
+/// tight loop.  The `FairStdMutex` hands out tickets to the threads and wakes them (using a
+/// `Condvar`) strictly in ticket order, so the bus is acquired first-come, first-served.  This
+/// costs a few extra wakeups compared to the plain `std::sync::Mutex`.
+///
+/// This mutex type is used with the [`BusManagerFairStd`] type.
+///
+/// [std-mutex]: https://doc.rust-lang.org/std/sync/struct.Mutex.html
+/// [`BusManagerFairStd`]: ./type.BusManagerFairStd.html
+///
+/// This type is only available with the `std` feature.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct FairStdMutex<T> {
+    bus: ::std::sync::Mutex<T>,
+    queue: ::std::sync::Mutex<TicketQueue>,
+    turn: ::std::sync::Condvar,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+struct TicketQueue {
+    /// Ticket for the next thread to arrive.
+    next: u64,
+    /// Ticket of the thread which may hold the bus.
+    serving: u64,
+}
+
+#[cfg(feature = "std")]
+impl<T> FairStdMutex<T> {
+    /// Get the number of threads holding or waiting for the bus.
+    pub fn queue_len(&self) -> usize {
+        let queue = self.queue.lock().unwrap();
+        (queue.next - queue.serving) as usize
+    }
+
+    /// Wait until it is the turn of this thread, and return a token which passes the turn on to
+    /// the next thread when dropped.
+    fn wait_turn(&self) -> NextTurn<'_, T> {
+        let mut queue = self.queue.lock().unwrap();
+        let ticket = queue.next;
+        queue.next += 1;
+        while queue.serving != ticket {
+            queue = self.turn.wait(queue).unwrap();
+        }
+        NextTurn(self)
+    }
+}
+
+/// Passes the turn on to the next thread, even if the current one panics.
+#[cfg(feature = "std")]
+struct NextTurn<'a, T>(&'a FairStdMutex<T>);
+
+#[cfg(feature = "std")]
+impl<T> Drop for NextTurn<'_, T> {
+    fn drop(&mut self) {
+        // Don't panic while the thread might be unwinding already.
+        if let Ok(mut queue) = self.0.queue.lock() {
+            queue.serving += 1;
+        }
+        self.0.turn.notify_all();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> BusMutex for FairStdMutex<T> {
+    type Bus = T;
+    const KIND: &'static str = "FairStdMutex";
+
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
+        check_reentrancy(self);
+        let _turn = self.wait_turn();
+        // Only the thread whose turn it is locks the bus, so this never blocks.
+        let mut v = self.bus.lock().unwrap();
+        f(&mut v)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> CreateBusMutex for FairStdMutex<T> {
+    fn create(v: T) -> Self {
+        FairStdMutex {
+            bus: ::std::sync::Mutex::new(v),
+            queue: Default::default(),
+            turn: ::std::sync::Condvar::new(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> TryBusMutex for FairStdMutex<T> {
+    fn try_lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> Result<R, LockError> {
+        check_reentrancy(self);
+        let _turn = self.wait_turn();
+        let mut v = self.bus.lock().map_err(|_| LockError::Poisoned)?;
+        Ok(f(&mut v))
+    }
+}
+
+#[cfg(all(feature = "rt", target_os = "linux"))]
+extern "C" {
+    // Not exposed by the `libc` crate for all Linux targets.
+    fn pthread_mutexattr_setprioceiling(
+        attr: *mut libc::pthread_mutexattr_t,
+        prioceiling: libc::c_int,
+    ) -> libc::c_int;
+    fn pthread_mutex_getprioceiling(
+        mutex: *const libc::pthread_mutex_t,
+        prioceiling: *mut libc::c_int,
+    ) -> libc::c_int;
+}
+
+/// Mutex implementing the priority ceiling protocol, for sharing a bus with real-time threads.
+///
+/// The `PriorityCeilingMutex` is a pthread mutex configured with `PTHREAD_PRIO_PROTECT`.  While a
+/// thread holds the lock, its priority is raised to the ceiling of the mutex.  A low-priority
+/// thread holding the bus can therefore not be preempted by medium-priority threads, which would
+/// otherwise delay a high-priority thread waiting for the bus (priority inversion).
+///
+/// The ceiling must be at least the highest `SCHED_FIFO`/`SCHED_RR` priority of all threads
+/// using the bus, otherwise locking fails and panics.  [`CreateBusMutex::create()`] uses the highest
+/// `SCHED_FIFO` priority as the ceiling; use [`new()`][new] and
+/// [`BusManager::from_mutex()`][from_mutex] to choose a lower one:
+///
+/// ```no_run
+/// # struct SomeI2cBus;
+/// # let i2c = SomeI2cBus;
+/// let mutex = shared_bus::PriorityCeilingMutex::new(i2c, 80).unwrap();
+/// let bus: &'static _ = Box::leak(Box::new(shared_bus::BusManager::from_mutex(mutex)));
+///
+/// let proxy = bus.acquire_i2c();
+/// ```
+///
+/// The mutex is of the error-checking type, so a thread locking it again while already holding it
+/// panics instead of deadlocking.
+///
+/// # Platform requirements
+/// - Linux, ideally with the `PREEMPT_RT` patches for bounded latencies.
+/// - The C library must support `PTHREAD_PRIO_PROTECT` (glibc and musl do).
+/// - Raising the priority of a thread needs the `CAP_SYS_NICE` capability or a suitable
+///   `RLIMIT_RTPRIO` limit.  Without it, locking the mutex fails and panics.  Containers often
+///   do not permit real-time scheduling at all.
+///
+/// [new]: #method.new
+/// [from_mutex]: ./struct.BusManager.html#method.from_mutex
+///
+/// This type is only available with the `rt` feature on Linux.
+#[cfg(all(feature = "rt", target_os = "linux"))]
+pub struct PriorityCeilingMutex<T> {
+    // Boxed, as a pthread mutex must not be moved after its initialization.
+    mutex: Box<cell::UnsafeCell<libc::pthread_mutex_t>>,
+    bus: cell::UnsafeCell<T>,
+}
+
+// SAFETY: The bus is only accessed while the pthread mutex is locked.
+#[cfg(all(feature = "rt", target_os = "linux"))]
+unsafe impl<T: Send> Send for PriorityCeilingMutex<T> {}
+#[cfg(all(feature = "rt", target_os = "linux"))]
+unsafe impl<T: Send> Sync for PriorityCeilingMutex<T> {}
+
+#[cfg(all(feature = "rt", target_os = "linux"))]
+impl<T> core::fmt::Debug for PriorityCeilingMutex<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PriorityCeilingMutex")
+            .field("ceiling", &self.ceiling())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(all(feature = "rt", target_os = "linux"))]
+impl<T> PriorityCeilingMutex<T> {
+    /// Create a new mutex for `bus` with the given priority `ceiling`.
+    pub fn new(bus: T, ceiling: i32) -> std::io::Result<Self> {
+        fn check(ret: libc::c_int) -> std::io::Result<()> {
+            match ret {
+                0 => Ok(()),
+                e => Err(std::io::Error::from_raw_os_error(e)),
+            }
+        }
+
+        let mutex = Box::new(cell::UnsafeCell::new(libc::PTHREAD_MUTEX_INITIALIZER));
+        // SAFETY: The attributes are initialized before use and destroyed afterwards.  The mutex
+        // is at its final address.
+        unsafe {
+            let mut attr = core::mem::MaybeUninit::<libc::pthread_mutexattr_t>::uninit();
+            check(libc::pthread_mutexattr_init(attr.as_mut_ptr()))?;
+            let res = check(libc::pthread_mutexattr_setprotocol(
+                attr.as_mut_ptr(),
+                libc::PTHREAD_PRIO_PROTECT,
+            ))
+            .and_then(|_| check(pthread_mutexattr_setprioceiling(attr.as_mut_ptr(), ceiling)))
+            .and_then(|_| {
+                check(libc::pthread_mutexattr_settype(
+                    attr.as_mut_ptr(),
+                    libc::PTHREAD_MUTEX_ERRORCHECK,
+                ))
+            })
+            .and_then(|_| check(libc::pthread_mutex_init(mutex.get(), attr.as_ptr())));
+            libc::pthread_mutexattr_destroy(attr.as_mut_ptr());
+            res?;
+        }
+
+        Ok(PriorityCeilingMutex {
+            mutex,
+            bus: cell::UnsafeCell::new(bus),
+        })
+    }
+
+    /// Return the priority ceiling of this mutex.
+    pub fn ceiling(&self) -> i32 {
+        let mut ceiling = 0;
+        // SAFETY: The mutex was initialized in `new()`.
+        let ret = unsafe { pthread_mutex_getprioceiling(self.mutex.get(), &mut ceiling) };
+        assert_eq!(ret, 0, "pthread_mutex_getprioceiling() failed");
+        ceiling
+    }
+}
+
+#[cfg(all(feature = "rt", target_os = "linux"))]
+impl<T> Drop for PriorityCeilingMutex<T> {
+    fn drop(&mut self) {
+        // SAFETY: The mutex cannot be locked anymore, as it is exclusively borrowed.
+        unsafe {
+            libc::pthread_mutex_destroy(self.mutex.get());
+        }
+    }
+}
+
+#[cfg(all(feature = "rt", target_os = "linux"))]
+impl<T> BusMutex for PriorityCeilingMutex<T> {
+    type Bus = T;
+    const KIND: &'static str = "PriorityCeilingMutex";
+
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
+        check_reentrancy(self);
+
+        /// Unlocks the mutex again, even if `f` panics.
+        struct Unlock<'a>(&'a cell::UnsafeCell<libc::pthread_mutex_t>);
+
+        impl Drop for Unlock<'_> {
+            fn drop(&mut self) {
+                // SAFETY: The mutex is locked by this thread.
+                unsafe {
+                    libc::pthread_mutex_unlock(self.0.get());
+                }
+            }
+        }
+
+        // SAFETY: The mutex was initialized in `new()`.
+        let ret = unsafe { libc::pthread_mutex_lock(self.mutex.get()) };
+        if ret != 0 {
+            panic!(
+                "failed to lock PriorityCeilingMutex: {}",
+                std::io::Error::from_raw_os_error(ret)
+            );
+        }
+        let _unlock = Unlock(&self.mutex);
+
+        // SAFETY: The mutex is locked, so this is the only reference to the bus.
+        f(unsafe { &mut *self.bus.get() })
+    }
+}
+
+#[cfg(all(feature = "rt", target_os = "linux"))]
+impl<T> CreateBusMutex for PriorityCeilingMutex<T> {
+    fn create(v: T) -> Self {
+        // SAFETY: Only queries a constant of the scheduler.
+        let ceiling = unsafe { libc::sched_get_priority_max(libc::SCHED_FIFO) };
+        PriorityCeilingMutex::new(v, ceiling).expect("failed to create PriorityCeilingMutex")
+    }
+}
+
+/// Alias for a Cortex-M mutex.
+///
+/// Based on [`cortex_m::interrupt::Mutex`][cortexm-mutex].  This mutex works by disabling
+/// interrupts while the mutex is locked.
+///
+/// The mutex is `Sync` as long as the bus is `Send`, so proxies using it are `Send + Sync`.  A
+/// single proxy can thus even be shared by reference between multiple interrupt handlers.
+///
+/// [cortexm-mutex]: https://docs.rs/cortex-m/0.6.3/cortex_m/interrupt/struct.Mutex.html
+///
+/// This type is only available with the `cortex-m` feature.
+#[cfg(feature = "cortex-m")]
+pub type CortexMMutex<T> = cortex_m::interrupt::Mutex<cell::RefCell<T>>;
+
+#[cfg(feature = "cortex-m")]
+impl<T> BusMutex for CortexMMutex<T> {
+    type Bus = T;
+    const KIND: &'static str = "CortexMMutex";
+
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
+        cortex_m::interrupt::free(|cs| {
+            let c = self.borrow(cs);
+            f(&mut c.borrow_mut())
+        })
+    }
+}
+
+#[cfg(feature = "cortex-m")]
+impl<T> CreateBusMutex for CortexMMutex<T> {
+    fn create(v: T) -> Self {
+        cortex_m::interrupt::Mutex::new(cell::RefCell::new(v))
+    }
+}
+
+#[cfg(feature = "cortex-m")]
+impl<T> TryBusMutex for CortexMMutex<T> {
+    fn try_lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> Result<R, LockError> {
+        cortex_m::interrupt::free(|cs| {
+            let mut v = self
+                .borrow(cs)
+                .try_borrow_mut()
+                .map_err(|_| LockError::Busy)?;
+            Ok(f(&mut v))
+        })
+    }
+}
+
+/// Alias for a mutex based on the [`critical-section`][critical-section] crate.
+///
+/// Based on [`critical_section::Mutex`][cs-mutex].  This mutex works by entering a critical
+/// section while the mutex is locked.  What that means is defined by the `critical-section`
+/// implementation registered by your HAL/PAC (or runtime), so this mutex works on any platform
+/// which provides one, without needing a platform-specific feature of `shared-bus`.
+///
+/// # Missing `critical-section` implementation
+/// If no implementation is registered, the program does not link.  The linker reports undefined
+/// symbols named `_critical_section_1_0_acquire` and `_critical_section_1_0_release`, which is not
+/// very telling.  To fix this, enable the feature of your HAL/PAC (or runtime) which provides
+/// the implementation, for single-core Cortex-M chips e.g. the `critical-section-single-core`
+/// feature of `cortex-m`.  As this is a link-time error, it can't happen at runtime, so the mutex
+/// does not check for it.
+///
+/// [critical-section]: https://docs.rs/critical-section
+/// [cs-mutex]: https://docs.rs/critical-section/1/critical_section/struct.Mutex.html
+///
+/// This type is only available with the `critical-section` feature.
+#[cfg(feature = "critical-section")]
+pub type AutoMutex<T> = critical_section::Mutex<cell::RefCell<T>>;
+
+#[cfg(feature = "critical-section")]
+impl<T> BusMutex for AutoMutex<T> {
+    type Bus = T;
+    const KIND: &'static str = "AutoMutex";
+
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
+        critical_section::with(|cs| {
+            let c = self.borrow(cs);
+            f(&mut c.borrow_mut())
+        })
+    }
+}
+
+#[cfg(feature = "critical-section")]
+impl<T> CreateBusMutex for AutoMutex<T> {
+    fn create(v: T) -> Self {
+        critical_section::Mutex::new(cell::RefCell::new(v))
+    }
+}
+
+#[cfg(feature = "critical-section")]
+impl<T> TryBusMutex for AutoMutex<T> {
+    fn try_lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> Result<R, LockError> {
+        critical_section::with(|cs| {
+            let mut v = self
+                .borrow(cs)
+                .try_borrow_mut()
+                .map_err(|_| LockError::Busy)?;
+            Ok(f(&mut v))
+        })
+    }
+}
+
+/// Mutex based on a user-supplied function for entering a critical section.
+///
+/// For platforms which are not covered by the other mutex types, the `ClosureMutex` only needs a
+/// function which runs its argument inside a critical section, e.g. using the enter/exit
+/// functions of an RTOS:
+///
+/// ```
+/// # mod rtos { pub fn enter_critical() {} pub fn exit_critical() {} }
+/// # struct SomeI2cBus;
+/// # let i2c = SomeI2cBus;
+/// // SAFETY: The RTOS critical section prevents all other tasks from running.
+/// let mutex = unsafe {
+///     shared_bus::ClosureMutex::new(i2c, |f: &mut dyn FnMut()| {
+///         rtos::enter_critical();
+///         f();
+///         rtos::exit_critical();
+///     })
+/// };
+/// let bus = shared_bus::BusManager::from_mutex(mutex);
+///
+/// let proxy = bus.acquire_i2c();
+/// ```
+///
+/// As the function can't be passed along with the bus, the `ClosureMutex` does not implement
+/// [`CreateBusMutex`].  It must be created using [`ClosureMutex::new()`] and passed to
+/// [`BusManager::from_mutex()`], constructors like `BusManager::new()` are not available for it.
+///
+/// [`CreateBusMutex`]: ./trait.CreateBusMutex.html
+/// [`ClosureMutex::new()`]: #method.new
+/// [`BusManager::from_mutex()`]: ./struct.BusManager.html#method.from_mutex
+pub struct ClosureMutex<T, F> {
+    bus: cell::RefCell<T>,
+    enter: F,
+}
+
+// Accesses to the bus are serialized by the critical section, as promised in `new()`.
+unsafe impl<T: Send, F: Sync> Sync for ClosureMutex<T, F> {}
+
+impl<T, F> core::fmt::Debug for ClosureMutex<T, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ClosureMutex").finish_non_exhaustive()
+    }
+}
+
+impl<T, F: Fn(&mut dyn FnMut())> ClosureMutex<T, F> {
+    /// Create a mutex for `bus` which uses `enter` to enter a critical section.
+    ///
+    /// `enter` must call its argument exactly once, inside the critical section.
+    ///
+    /// # Safety
+    /// No two calls of `enter` may run their argument at the same time, from any execution
+    /// context that can access this mutex.
+    pub unsafe fn new(bus: T, enter: F) -> Self {
+        ClosureMutex {
+            bus: cell::RefCell::new(bus),
+            enter,
+        }
+    }
+}
+
+impl<T, F: Fn(&mut dyn FnMut())> BusMutex for ClosureMutex<T, F> {
+    type Bus = T;
+    const KIND: &'static str = "ClosureMutex";
+
+    fn lock<R, G: FnOnce(&mut Self::Bus) -> R>(&self, f: G) -> R {
+        let mut f = Some(f);
+        let mut result = None;
+        (self.enter)(&mut || {
+            if let Some(f) = f.take() {
+                result = Some(f(&mut self.bus.borrow_mut()));
+            }
+        });
+        result.expect("ClosureMutex function did not call its argument")
+    }
+}
+
+/// Wrapper for an interrupt free spin mutex.
+///
+/// Based on [`spin::Mutex`][spin-mutex]. This mutex works by disabling
+/// interrupts while the mutex is locked.
+///
+/// When the other core holds the mutex, waiting uses an exponential backoff and only reads the
+/// lock state, so the waiting core does not keep stealing the cache line of the lock from the
+/// core which owns it.
+///
+/// [spin-mutex]: https://docs.rs/spin/0.9.2/spin/type.Mutex.html
+///
+/// This type is only available with the `xtensa` feature.
+#[cfg(feature = "xtensa")]
+pub struct XtensaMutex<T>(spin::Mutex<T>);
+
+/// Upper limit for the number of spin-loop hints between two checks of a contended lock.
+#[cfg(feature = "xtensa")]
+const XTENSA_MAX_BACKOFF: u32 = 64;
+
+#[cfg(feature = "xtensa")]
+impl<T> XtensaMutex<T> {
+    fn lock_with_backoff(&self) -> spin::MutexGuard<'_, T> {
+        let mut pause = 1;
+        loop {
+            if let Some(guard) = self.0.try_lock() {
+                return guard;
+            }
+            while self.0.is_locked() {
+                for _ in 0..pause {
+                    core::hint::spin_loop();
+                }
+                if pause < XTENSA_MAX_BACKOFF {
+                    pause *= 2;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "xtensa")]
+impl<T> BusMutex for XtensaMutex<T> {
+    type Bus = T;
+    const KIND: &'static str = "XtensaMutex";
+
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
+        xtensa_lx::interrupt::free(|_| f(&mut self.lock_with_backoff()))
+    }
+}
+
+#[cfg(feature = "xtensa")]
+impl<T> CreateBusMutex for XtensaMutex<T> {
+    fn create(v: T) -> Self {
+        XtensaMutex(spin::Mutex::new(v))
+    }
+}
+
+#[cfg(feature = "xtensa")]
+impl<T> TryBusMutex for XtensaMutex<T> {
+    /// Lock the mutex unless it is held already, e.g. by the code an interrupt handler preempted.
+    ///
+    /// Unlike [`lock()`](#method.lock), this never spins, so it is safe to call from an interrupt
+    /// handler.
+    fn try_lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> Result<R, LockError> {
+        xtensa_lx::interrupt::free(|_| {
+            let mut v = self.0.try_lock().ok_or(LockError::Busy)?;
+            Ok(f(&mut v))
+        })
+    }
+}
+
+#[cfg(feature = "xtensa")]
+impl<T> XtensaMutex<T> {
+    /// Access the inner `spin::Mutex`.
+    ///
+    /// **Note**: Locking the inner mutex directly does not disable interrupts.
+    pub fn inner(&self) -> &spin::Mutex<T> {
+        &self.0
+    }
+}
+
+/// Common interface for async mutex implementations.
+///
+/// This is the `async` counterpart to [`BusMutex`], used by the [`AsyncBusManager`].  As async bus
+/// operations need to hold the lock across `.await` points, locking yields a guard instead of
+/// calling a closure.  Here is an overview of the implementations in `shared-bus`:
+///
+/// | Mutex | Feature Name | Notes |
+/// | --- | --- | --- |
+/// | [`AsyncNullMutex`] | `async` | No locking, for sharing within a single task. |
+/// | [`AsyncAtomicCheckMutex`] | `async` | Panics on overlapping access from different tasks. |
+/// | [`FairAsyncMutex`] | `async` | Serves tasks in the order they started waiting. |
+/// | [`PriorityAsyncMutex`] | `priority` | Serves the waiting task with the highest priority first. |
+///
+/// All of them are cancel-safe:  Dropping a pending `lock()` future gives up its place among the
+/// waiting tasks, and dropping the guard releases the mutex.  Thus, a task which is cancelled
+/// while waiting for the bus or while using it cannot block the bus for others.  Custom
+/// implementations must uphold this as well.
+///
+/// [`BusMutex`]: ./trait.BusMutex.html
+/// [`AsyncBusManager`]: ./struct.AsyncBusManager.html
+/// [`AsyncNullMutex`]: ./struct.AsyncNullMutex.html
+/// [`AsyncAtomicCheckMutex`]: ./struct.AsyncAtomicCheckMutex.html
+/// [`FairAsyncMutex`]: ./struct.FairAsyncMutex.html
+/// [`PriorityAsyncMutex`]: ./struct.PriorityAsyncMutex.html
+///
+/// This trait is only available with the `async` feature.
+#[cfg(feature = "async")]
+pub trait AsyncBusMutex {
+    /// The actual bus that is wrapped inside this mutex.
+    type Bus;
+
+    /// The guard type which keeps the mutex locked while it is alive.
+    type Guard<'a>: core::ops::DerefMut<Target = Self::Bus>
+    where
+        Self: 'a;
+
+    /// Create a new mutex of this type.
+    fn create(v: Self::Bus) -> Self;
+
+    /// Wait until the mutex can be locked and return a guard giving access to the bus inside.
+    fn lock(&self) -> impl core::future::Future<Output = Self::Guard<'_>>;
+
+    /// Like [`lock()`](#tymethod.lock), but with a priority hint for mutexes which serve waiting
+    /// tasks by priority.  Higher values mean higher priority.
+    ///
+    /// Mutexes without priority support ignore the hint.
+    fn lock_with_priority(
+        &self,
+        priority: u8,
+    ) -> impl core::future::Future<Output = Self::Guard<'_>> {
+        let _ = priority;
+        self.lock()
+    }
+}
+
+/// "Dummy" async mutex for sharing within a single task.
+///
+/// This is the async counterpart to the [`NullMutex`]:  Locking never waits, the bus is simply
+/// borrowed from a `RefCell`.  As the mutex is not `Sync`, proxies using it can't leave the task
+/// they were created in.
+///
+/// **Note**: Drivers within a single task can still run concurrently, e.g. using `join()`.  If a
+/// driver tries to lock the bus while another one holds it across an `.await` point, this mutex
+/// panics instead of waiting.  Use the [`FairAsyncMutex`] in such cases.
+///
+/// [`NullMutex`]: ./struct.NullMutex.html
+/// [`FairAsyncMutex`]: ./struct.FairAsyncMutex.html
+///
+/// This type is only available with the `async` feature.
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub struct AsyncNullMutex<T> {
+    bus: cell::RefCell<T>,
+}
+
+#[cfg(feature = "async")]
+impl<T> AsyncBusMutex for AsyncNullMutex<T> {
+    type Bus = T;
+    type Guard<'a>
+        = cell::RefMut<'a, T>
+    where
+        T: 'a;
+
+    fn create(v: T) -> Self {
+        AsyncNullMutex {
+            bus: cell::RefCell::new(v),
+        }
+    }
+
+    async fn lock(&self) -> Self::Guard<'_> {
+        self.bus
+            .try_borrow_mut()
+            .expect("AsyncNullMutex locked concurrently, use a real async mutex instead")
+    }
+}
+
+/// A coherency checker for sharing between async tasks which never overlap.
+///
+/// This is the async counterpart to the [`AtomicCheckMutex`]:  When the executor guarantees that
+/// bus users never hold the bus at the same time, e.g. because each task finishes its bus
+/// transfers before handing control to another one, no real async mutex is needed.  To protect
+/// against accidental misuse, this mutex sets a busy flag from the moment it is locked until the
+/// guard is dropped, i.e. for as long as a future holds the bus across its `.await` points.  If
+/// another task locks the bus in the meantime, the mutex panics with a "Bus conflict" instead of
+/// waiting.
+///
+/// Unlike the [`AsyncNullMutex`], the mutex is `Sync` (for a `Send` bus), so proxies can be handed
+/// to different tasks.  The busy flag is taken inside a critical section, so a `critical-section`
+/// implementation must be available.
+///
+/// [`AtomicCheckMutex`]: ./struct.AtomicCheckMutex.html
+/// [`AsyncNullMutex`]: ./struct.AsyncNullMutex.html
+///
+/// This type is only available with the `async` feature.
+#[cfg(feature = "async")]
+pub struct AsyncAtomicCheckMutex<T> {
+    bus: cell::UnsafeCell<T>,
+    busy: critical_section::Mutex<cell::Cell<bool>>,
+}
+
+// The busy flag only ever lets one user access the bus, and the bus may be accessed from any
+// task holding a reference, so it must be `Send`.
+#[cfg(feature = "async")]
+unsafe impl<T: Send> Sync for AsyncAtomicCheckMutex<T> {}
+
+#[cfg(feature = "async")]
+impl<T> core::fmt::Debug for AsyncAtomicCheckMutex<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AsyncAtomicCheckMutex")
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> AsyncAtomicCheckMutex<T> {
+    /// Create a new mutex for `bus`.
+    ///
+    /// Unlike [`AsyncBusMutex::create()`], this can be used in const context, e.g. for a `static`
+    /// bus manager.
+    ///
+    /// [`AsyncBusMutex::create()`]: ./trait.AsyncBusMutex.html#tymethod.create
+    pub const fn new(bus: T) -> Self {
+        AsyncAtomicCheckMutex {
+            bus: cell::UnsafeCell::new(bus),
+            busy: critical_section::Mutex::new(cell::Cell::new(false)),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> AsyncBusMutex for AsyncAtomicCheckMutex<T> {
+    type Bus = T;
+    type Guard<'a>
+        = AsyncAtomicCheckMutexGuard<'a, T>
+    where
+        T: 'a;
+
+    fn create(v: T) -> Self {
+        Self::new(v)
+    }
+
+    async fn lock(&self) -> Self::Guard<'_> {
+        let was_busy = critical_section::with(|cs| self.busy.borrow(cs).replace(true));
+        if was_busy {
+            panic!("Bus conflict");
+        }
+        AsyncAtomicCheckMutexGuard {
+            mutex: self,
+            _bus: core::marker::PhantomData,
+        }
+    }
+}
+
+/// Guard keeping an [`AsyncAtomicCheckMutex`] busy.
+///
+/// [`AsyncAtomicCheckMutex`]: ./struct.AsyncAtomicCheckMutex.html
+///
+/// This type is only available with the `async` feature.
+#[cfg(feature = "async")]
+pub struct AsyncAtomicCheckMutexGuard<'a, T> {
+    mutex: &'a AsyncAtomicCheckMutex<T>,
+    _bus: core::marker::PhantomData<&'a mut T>,
+}
+
+#[cfg(feature = "async")]
+impl<'a, T> core::ops::Deref for AsyncAtomicCheckMutexGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // The guard is proof that the busy flag is ours.
+        unsafe { &*self.mutex.bus.get() }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, T> core::ops::DerefMut for AsyncAtomicCheckMutexGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // The guard is proof that the busy flag is ours.
+        unsafe { &mut *self.mutex.bus.get() }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, T> Drop for AsyncAtomicCheckMutexGuard<'a, T> {
+    fn drop(&mut self) {
+        critical_section::with(|cs| self.mutex.busy.borrow(cs).set(false));
+    }
+}
+
+/// FIFO list of tasks waiting for a place in the queue of an async mutex.
+///
+/// The queues of the async mutexes have a fixed size.  Tasks which find the queue full line up
+/// here instead, in arrival order.  To make do without allocation, the list is intrusive: Each
+/// node lives inside the pinned lock future of its task, which unlinks it again when it is
+/// dropped.  The list and all nodes are only accessed inside the critical section protecting the
+/// mutex state.
+#[cfg(feature = "async")]
+struct OverflowList {
+    head: *mut OverflowNode,
+    tail: *mut OverflowNode,
+}
+
+/// Entry of a task in an [`OverflowList`].
+#[cfg(feature = "async")]
+struct OverflowNode {
+    waker: Option<core::task::Waker>,
+    prev: *mut OverflowNode,
+    next: *mut OverflowNode,
+    queued: bool,
+    _pin: core::marker::PhantomPinned,
+}
+
+#[cfg(feature = "async")]
+impl OverflowNode {
+    const fn new() -> Self {
+        OverflowNode {
+            waker: None,
+            prev: core::ptr::null_mut(),
+            next: core::ptr::null_mut(),
+            queued: false,
+            _pin: core::marker::PhantomPinned,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl OverflowList {
+    const fn new() -> Self {
+        OverflowList {
+            head: core::ptr::null_mut(),
+            tail: core::ptr::null_mut(),
+        }
+    }
 
-    /// Lock the mutex and give a closure access to the bus inside.
-    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R;
+    /// Whether the task owning `node` may enter the queue, i.e. no task arrived before it.
+    fn is_next(&self, node: *const OverflowNode) -> bool {
+        self.head.is_null() || core::ptr::eq(self.head, node)
+    }
+
+    /// Append `node` to the list, or update its waker if it is in the list already.
+    ///
+    /// # Safety
+    /// `node` must be pinned and must be removed from the list before it is dropped.
+    unsafe fn push(&mut self, node: *mut OverflowNode, waker: &core::task::Waker) {
+        let n = &mut *node;
+        match &n.waker {
+            Some(w) if w.will_wake(waker) => (),
+            _ => n.waker = Some(waker.clone()),
+        }
+        if n.queued {
+            return;
+        }
+        n.queued = true;
+        n.prev = self.tail;
+        n.next = core::ptr::null_mut();
+        match self.tail.is_null() {
+            true => self.head = node,
+            false => (*self.tail).next = node,
+        }
+        self.tail = node;
+    }
+
+    /// Remove `node` from the list, if it is in the list.  Returns whether it was the first.
+    ///
+    /// # Safety
+    /// `node` must be valid and either not queued or queued in this list.
+    unsafe fn remove(&mut self, node: *mut OverflowNode) -> bool {
+        let n = &mut *node;
+        if !n.queued {
+            return false;
+        }
+        let first = n.prev.is_null();
+        match n.prev.is_null() {
+            true => self.head = n.next,
+            false => (*n.prev).next = n.next,
+        }
+        match n.next.is_null() {
+            true => self.tail = n.prev,
+            false => (*n.next).prev = n.prev,
+        }
+        n.queued = false;
+        n.prev = core::ptr::null_mut();
+        n.next = core::ptr::null_mut();
+        n.waker = None;
+        first
+    }
+
+    /// Wake the task which arrived first, after a place in the queue became available.
+    fn wake_next(&self) {
+        // SAFETY: Queued nodes stay valid until they are removed.
+        if let Some(head) = unsafe { self.head.as_ref() } {
+            if let Some(waker) = &head.waker {
+                waker.wake_by_ref();
+            }
+        }
+    }
 }
 
-/// "Dummy" mutex for sharing in a single task/thread.
+/// Async mutex which hands out the bus in first-come, first-served order.
 ///
-/// This mutex type can be used when all bus users are contained in a single execution context.  In
-/// such a situation, no actual mutex is needed, because a RefCell alone is sufficient to ensuring
-/// only a single peripheral can access the bus at the same time.
+/// When the mutex is released while other tasks are waiting, ownership is passed on to the task
+/// which has been waiting the longest.  A task which re-locks the bus in a tight loop thus cannot
+/// starve tasks with a lower access frequency.
 ///
-/// This mutex type is used with the [`BusManagerSimple`] type.
+/// Up to `N` tasks can hold or wait for the mutex in order.  Additional tasks wait in line until
+/// a place in the queue becomes available, and enter it in the order they arrived.  Waiting for
+/// the lock is cancel-safe: dropping a pending `lock()` future gives up its place in the queue.
 ///
-/// To uphold safety, this type is `!Send` and `!Sync`.
+/// The internal state is protected by a critical section, so a `critical-section`
+/// implementation must be available.
 ///
-/// [`BusManagerSimple`]: ./type.BusManagerSimple.html
-#[derive(Debug)]
-pub struct NullMutex<T> {
-    bus: cell::RefCell<T>,
+/// This type is only available with the `async` feature.
+#[cfg(feature = "async")]
+pub struct FairAsyncMutex<T, const N: usize = 8> {
+    state: critical_section::Mutex<cell::RefCell<FairState<N>>>,
+    bus: cell::UnsafeCell<T>,
 }
 
-impl<T> BusMutex for NullMutex<T> {
-    type Bus = T;
+// Access to the bus is serialized by the ticket state, which is protected by a critical section.
+#[cfg(feature = "async")]
+unsafe impl<T: Send, const N: usize> Send for FairAsyncMutex<T, N> {}
+#[cfg(feature = "async")]
+unsafe impl<T: Send, const N: usize> Sync for FairAsyncMutex<T, N> {}
 
-    fn create(v: Self::Bus) -> Self {
-        NullMutex {
-            bus: cell::RefCell::new(v),
+#[cfg(feature = "async")]
+impl<T, const N: usize> core::fmt::Debug for FairAsyncMutex<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FairAsyncMutex").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "async")]
+enum FairSlot {
+    Empty,
+    Waiting(core::task::Waker),
+    Cancelled,
+}
+
+/// Ticket lock state.  Tickets `serving..next` are queued, the slot of ticket `serving` is
+/// `slots[head]`.
+#[cfg(feature = "async")]
+struct FairState<const N: usize> {
+    next: usize,
+    serving: usize,
+    head: usize,
+    locked: bool,
+    slots: [FairSlot; N],
+    overflow: OverflowList,
+}
+
+#[cfg(feature = "async")]
+impl<const N: usize> FairState<N> {
+    fn slot(&mut self, ticket: usize) -> &mut FairSlot {
+        let idx = (self.head + ticket.wrapping_sub(self.serving)) % N;
+        &mut self.slots[idx]
+    }
+
+    /// Pass the mutex on to the next ticket which is still waiting.
+    fn advance(&mut self) {
+        loop {
+            self.serving = self.serving.wrapping_add(1);
+            self.head = (self.head + 1) % N;
+            if self.serving == self.next {
+                break;
+            }
+            match core::mem::replace(&mut self.slots[self.head], FairSlot::Empty) {
+                FairSlot::Cancelled => continue,
+                FairSlot::Waiting(waker) => waker.wake(),
+                FairSlot::Empty => (),
+            }
+            break;
         }
+        self.overflow.wake_next();
     }
 
-    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
-        let mut v = self.bus.borrow_mut();
-        f(&mut v)
+    fn has_room(&self) -> bool {
+        self.next.wrapping_sub(self.serving) < N
     }
 }
 
-#[cfg(feature = "std")]
-impl<T> BusMutex for ::std::sync::Mutex<T> {
+#[cfg(feature = "async")]
+impl<T, const N: usize> AsyncBusMutex for FairAsyncMutex<T, N> {
     type Bus = T;
+    type Guard<'a>
+        = FairAsyncMutexGuard<'a, T, N>
+    where
+        T: 'a;
 
-    fn create(v: Self::Bus) -> Self {
-        ::std::sync::Mutex::new(v)
+    fn create(v: T) -> Self {
+        assert!(N > 0, "FairAsyncMutex needs room for at least one task");
+        FairAsyncMutex {
+            state: critical_section::Mutex::new(cell::RefCell::new(FairState {
+                next: 0,
+                serving: 0,
+                head: 0,
+                locked: false,
+                slots: core::array::from_fn(|_| FairSlot::Empty),
+                overflow: OverflowList::new(),
+            })),
+            bus: cell::UnsafeCell::new(v),
+        }
     }
 
-    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
-        let mut v = self.lock().unwrap();
-        f(&mut v)
+    fn lock(&self) -> impl core::future::Future<Output = Self::Guard<'_>> {
+        FairLock {
+            mutex: self,
+            ticket: None,
+            node: OverflowNode::new(),
+        }
     }
 }
 
-/// Alias for a Cortex-M mutex.
+#[cfg(feature = "async")]
+struct FairLock<'a, T, const N: usize> {
+    mutex: &'a FairAsyncMutex<T, N>,
+    ticket: Option<usize>,
+    node: OverflowNode,
+}
+
+#[cfg(feature = "async")]
+impl<'a, T, const N: usize> core::future::Future for FairLock<'a, T, N> {
+    type Output = FairAsyncMutexGuard<'a, T, N>;
+
+    fn poll(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        // SAFETY: The overflow node is never moved out of the pinned future.
+        let this = unsafe { self.get_unchecked_mut() };
+        let node: *mut OverflowNode = &mut this.node;
+        critical_section::with(|cs| {
+            let mut state = this.mutex.state.borrow_ref_mut(cs);
+
+            let ticket = match this.ticket {
+                Some(ticket) => ticket,
+                None if !state.has_room() || !state.overflow.is_next(node) => {
+                    // No place in the queue, wait in line until one becomes available.
+                    // SAFETY: The node is pinned and `drop()` removes it from the list.
+                    unsafe { state.overflow.push(node, cx.waker()) };
+                    return core::task::Poll::Pending;
+                }
+                None => {
+                    // SAFETY: The node is either not queued or queued in this list.
+                    unsafe { state.overflow.remove(node) };
+                    let ticket = state.next;
+                    state.next = ticket.wrapping_add(1);
+                    this.ticket = Some(ticket);
+                    if state.has_room() {
+                        state.overflow.wake_next();
+                    }
+                    ticket
+                }
+            };
+
+            if ticket == state.serving && !state.locked {
+                state.locked = true;
+                *state.slot(ticket) = FairSlot::Empty;
+                this.ticket = None;
+                core::task::Poll::Ready(FairAsyncMutexGuard {
+                    mutex: this.mutex,
+                    _bus: core::marker::PhantomData,
+                })
+            } else {
+                *state.slot(ticket) = FairSlot::Waiting(cx.waker().clone());
+                core::task::Poll::Pending
+            }
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, T, const N: usize> Drop for FairLock<'a, T, N> {
+    fn drop(&mut self) {
+        let node: *mut OverflowNode = &mut self.node;
+        critical_section::with(|cs| {
+            let mut state = self.mutex.state.borrow_ref_mut(cs);
+            match self.ticket {
+                Some(ticket) if ticket == state.serving => {
+                    // We were already woken up, pass the mutex on.
+                    state.advance();
+                }
+                Some(ticket) => *state.slot(ticket) = FairSlot::Cancelled,
+                None => {
+                    // SAFETY: The node is either not queued or queued in this list.
+                    let first = unsafe { state.overflow.remove(node) };
+                    if first && state.has_room() {
+                        // We might have been woken up already, pass the wakeup on.
+                        state.overflow.wake_next();
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Guard keeping a [`FairAsyncMutex`] locked.
 ///
-/// Based on [`cortex_m::interrupt::Mutex`][cortexm-mutex].  This mutex works by disabling
-/// interrupts while the mutex is locked.
+/// [`FairAsyncMutex`]: ./struct.FairAsyncMutex.html
 ///
-/// [cortexm-mutex]: https://docs.rs/cortex-m/0.6.3/cortex_m/interrupt/struct.Mutex.html
+/// This type is only available with the `async` feature.
+#[cfg(feature = "async")]
+pub struct FairAsyncMutexGuard<'a, T, const N: usize> {
+    mutex: &'a FairAsyncMutex<T, N>,
+    _bus: core::marker::PhantomData<&'a mut T>,
+}
+
+#[cfg(feature = "async")]
+impl<'a, T, const N: usize> core::ops::Deref for FairAsyncMutexGuard<'a, T, N> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // The guard is proof that we hold the lock.
+        unsafe { &*self.mutex.bus.get() }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, T, const N: usize> core::ops::DerefMut for FairAsyncMutexGuard<'a, T, N> {
+    fn deref_mut(&mut self) -> &mut T {
+        // The guard is proof that we hold the lock.
+        unsafe { &mut *self.mutex.bus.get() }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, T, const N: usize> Drop for FairAsyncMutexGuard<'a, T, N> {
+    fn drop(&mut self) {
+        critical_section::with(|cs| {
+            let mut state = self.mutex.state.borrow_ref_mut(cs);
+            state.locked = false;
+            state.advance();
+        });
+    }
+}
+
+/// Async mutex which hands out the bus to the waiting task with the highest priority.
 ///
-/// This type is only available with the `cortex-m` feature.
-#[cfg(feature = "cortex-m")]
-pub type CortexMMutex<T> = cortex_m::interrupt::Mutex<cell::RefCell<T>>;
+/// Tasks lock the mutex with a priority using [`lock_with_priority()`][lock_with_priority] (e.g.
+/// via [`AsyncBusManager::acquire_i2c_with_priority()`][acquire]).  When the mutex is released,
+/// it goes to the waiting task with the highest priority.  Tasks with the same priority are served
+/// in the order they started waiting.  A plain [`lock()`][lock] uses priority `0`, the lowest.
+///
+/// Note that this is only a priority _queue_:  A high-priority task still has to wait until the
+/// current holder of the bus releases it, and tasks with a low priority can be starved by tasks
+/// with a higher priority which access the bus continuously.
+///
+/// Up to `N` tasks can wait for the mutex in priority order.  Additional tasks wait in line until
+/// a place in the queue becomes available, and enter it in the order they arrived, regardless of
+/// their priority.  Waiting for the lock is cancel-safe.
+///
+/// The internal state is protected by a critical section, so a `critical-section`
+/// implementation must be available.
+///
+/// [lock_with_priority]: ./trait.AsyncBusMutex.html#method.lock_with_priority
+/// [lock]: ./trait.AsyncBusMutex.html#tymethod.lock
+/// [acquire]: ./struct.AsyncBusManager.html#method.acquire_i2c_with_priority
+///
+/// This type is only available with the `priority` feature.
+#[cfg(feature = "priority")]
+pub struct PriorityAsyncMutex<T, const N: usize = 8> {
+    state: critical_section::Mutex<cell::RefCell<PriorityState<N>>>,
+    bus: cell::UnsafeCell<T>,
+}
 
-#[cfg(feature = "cortex-m")]
-impl<T> BusMutex for CortexMMutex<T> {
+// Access to the bus is serialized by the `locked` flag, which is protected by a critical section.
+#[cfg(feature = "priority")]
+unsafe impl<T: Send, const N: usize> Send for PriorityAsyncMutex<T, N> {}
+#[cfg(feature = "priority")]
+unsafe impl<T: Send, const N: usize> Sync for PriorityAsyncMutex<T, N> {}
+
+#[cfg(feature = "priority")]
+impl<T, const N: usize> core::fmt::Debug for PriorityAsyncMutex<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PriorityAsyncMutex").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "priority")]
+struct PriorityWaiter {
+    priority: u8,
+    seq: usize,
+    waker: core::task::Waker,
+}
+
+#[cfg(feature = "priority")]
+struct PriorityState<const N: usize> {
+    locked: bool,
+    seq: usize,
+    waiters: [Option<PriorityWaiter>; N],
+    overflow: OverflowList,
+}
+
+#[cfg(feature = "priority")]
+impl<const N: usize> PriorityState<N> {
+    /// Find the waiter which is next in line.
+    fn best(&self) -> Option<usize> {
+        self.waiters
+            .iter()
+            .enumerate()
+            .filter_map(|(i, w)| w.as_ref().map(|w| (i, w)))
+            .max_by(|(_, a), (_, b)| {
+                // Equal priorities are served in order, the sequence numbers may wrap around.
+                a.priority
+                    .cmp(&b.priority)
+                    .then(a.seq.wrapping_sub(b.seq).cmp(&b.seq.wrapping_sub(a.seq)))
+            })
+            .map(|(i, _)| i)
+    }
+
+    /// Wake the waiter which is next in line, after the mutex became available.
+    fn wake_next(&mut self) {
+        if let Some(i) = self.best() {
+            self.waiters[i].as_ref().unwrap().waker.wake_by_ref();
+        }
+        if self.has_room() {
+            self.overflow.wake_next();
+        }
+    }
+
+    fn has_room(&self) -> bool {
+        self.waiters.iter().any(Option::is_none)
+    }
+}
+
+#[cfg(feature = "priority")]
+impl<T, const N: usize> AsyncBusMutex for PriorityAsyncMutex<T, N> {
     type Bus = T;
+    type Guard<'a>
+        = PriorityAsyncMutexGuard<'a, T, N>
+    where
+        T: 'a;
 
     fn create(v: T) -> Self {
-        cortex_m::interrupt::Mutex::new(cell::RefCell::new(v))
+        assert!(N > 0, "PriorityAsyncMutex needs room for at least one task");
+        PriorityAsyncMutex {
+            state: critical_section::Mutex::new(cell::RefCell::new(PriorityState {
+                locked: false,
+                seq: 0,
+                waiters: core::array::from_fn(|_| None),
+                overflow: OverflowList::new(),
+            })),
+            bus: cell::UnsafeCell::new(v),
+        }
     }
 
-    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
-        cortex_m::interrupt::free(|cs| {
-            let c = self.borrow(cs);
-            f(&mut c.borrow_mut())
+    fn lock(&self) -> impl core::future::Future<Output = Self::Guard<'_>> {
+        self.lock_with_priority(0)
+    }
+
+    fn lock_with_priority(
+        &self,
+        priority: u8,
+    ) -> impl core::future::Future<Output = Self::Guard<'_>> {
+        PriorityLock {
+            mutex: self,
+            priority,
+            slot: None,
+            node: OverflowNode::new(),
+        }
+    }
+}
+
+#[cfg(feature = "priority")]
+struct PriorityLock<'a, T, const N: usize> {
+    mutex: &'a PriorityAsyncMutex<T, N>,
+    priority: u8,
+    slot: Option<usize>,
+    node: OverflowNode,
+}
+
+#[cfg(feature = "priority")]
+impl<'a, T, const N: usize> core::future::Future for PriorityLock<'a, T, N> {
+    type Output = PriorityAsyncMutexGuard<'a, T, N>;
+
+    fn poll(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        // SAFETY: The overflow node is never moved out of the pinned future.
+        let this = unsafe { self.get_unchecked_mut() };
+        let node: *mut OverflowNode = &mut this.node;
+        critical_section::with(|cs| {
+            let mut state = this.mutex.state.borrow_ref_mut(cs);
+
+            if this.slot.is_none() && !state.overflow.is_next(node) {
+                // Tasks which wait for a place in the queue enter it in arrival order.
+                // SAFETY: The node is pinned and `drop()` removes it from the list.
+                unsafe { state.overflow.push(node, cx.waker()) };
+                return core::task::Poll::Pending;
+            }
+
+            let next_in_line = match (this.slot, state.best()) {
+                (_, None) => true,
+                (Some(slot), Some(best)) => slot == best,
+                // Tasks which are not queued yet only overtake lower priorities.
+                (None, Some(best)) => {
+                    state.waiters[best].as_ref().unwrap().priority < this.priority
+                }
+            };
+
+            if next_in_line && !state.locked {
+                state.locked = true;
+                if let Some(slot) = this.slot.take() {
+                    state.waiters[slot] = None;
+                }
+                // SAFETY: The node is either not queued or queued in this list.
+                unsafe { state.overflow.remove(node) };
+                if state.has_room() {
+                    state.overflow.wake_next();
+                }
+                return core::task::Poll::Ready(PriorityAsyncMutexGuard {
+                    mutex: this.mutex,
+                    _bus: core::marker::PhantomData,
+                });
+            }
+
+            match this.slot {
+                Some(slot) => state.waiters[slot].as_mut().unwrap().waker = cx.waker().clone(),
+                None => match state.waiters.iter().position(Option::is_none) {
+                    Some(slot) => {
+                        let seq = state.seq;
+                        state.seq = seq.wrapping_add(1);
+                        state.waiters[slot] = Some(PriorityWaiter {
+                            priority: this.priority,
+                            seq,
+                            waker: cx.waker().clone(),
+                        });
+                        this.slot = Some(slot);
+                        // SAFETY: The node is either not queued or queued in this list.
+                        unsafe { state.overflow.remove(node) };
+                        if state.has_room() {
+                            state.overflow.wake_next();
+                        }
+                    }
+                    None => {
+                        // No place in the queue, wait in line until one becomes available.
+                        // SAFETY: The node is pinned and `drop()` removes it from the list.
+                        unsafe { state.overflow.push(node, cx.waker()) };
+                    }
+                },
+            }
+            core::task::Poll::Pending
         })
     }
 }
 
-/// Wrapper for an interrupt free spin mutex.
-///
-/// Based on [`spin::Mutex`][spin-mutex]. This mutex works by disabling
-/// interrupts while the mutex is locked.
+#[cfg(feature = "priority")]
+impl<'a, T, const N: usize> Drop for PriorityLock<'a, T, N> {
+    fn drop(&mut self) {
+        let node: *mut OverflowNode = &mut self.node;
+        critical_section::with(|cs| {
+            let mut state = self.mutex.state.borrow_ref_mut(cs);
+            if let Some(slot) = self.slot {
+                state.waiters[slot] = None;
+                if !state.locked {
+                    // We might have been woken up already, pass the wakeup on.
+                    state.wake_next();
+                } else {
+                    state.overflow.wake_next();
+                }
+            } else {
+                // SAFETY: The node is either not queued or queued in this list.
+                let first = unsafe { state.overflow.remove(node) };
+                if first && state.has_room() {
+                    // We might have been woken up already, pass the wakeup on.
+                    state.overflow.wake_next();
+                }
+            }
+        });
+    }
+}
+
+/// Guard keeping a [`PriorityAsyncMutex`] locked.
 ///
-/// [spin-mutex]: https://docs.rs/spin/0.9.2/spin/type.Mutex.html
+/// [`PriorityAsyncMutex`]: ./struct.PriorityAsyncMutex.html
 ///
-/// This type is only available with the `xtensa` feature.
-#[cfg(feature = "xtensa")]
-pub struct XtensaMutex<T>(spin::Mutex<T>);
+/// This type is only available with the `priority` feature.
+#[cfg(feature = "priority")]
+pub struct PriorityAsyncMutexGuard<'a, T, const N: usize> {
+    mutex: &'a PriorityAsyncMutex<T, N>,
+    _bus: core::marker::PhantomData<&'a mut T>,
+}
 
-#[cfg(feature = "xtensa")]
-impl<T> BusMutex for XtensaMutex<T> {
-    type Bus = T;
+#[cfg(feature = "priority")]
+impl<'a, T, const N: usize> core::ops::Deref for PriorityAsyncMutexGuard<'a, T, N> {
+    type Target = T;
 
-    fn create(v: T) -> Self {
-        XtensaMutex(spin::Mutex::new(v))
+    fn deref(&self) -> &T {
+        // The guard is proof that we hold the lock.
+        unsafe { &*self.mutex.bus.get() }
     }
+}
 
-    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
-        xtensa_lx::interrupt::free(|_| f(&mut (*self.0.lock())))
+#[cfg(feature = "priority")]
+impl<'a, T, const N: usize> core::ops::DerefMut for PriorityAsyncMutexGuard<'a, T, N> {
+    fn deref_mut(&mut self) -> &mut T {
+        // The guard is proof that we hold the lock.
+        unsafe { &mut *self.mutex.bus.get() }
+    }
+}
+
+#[cfg(feature = "priority")]
+impl<'a, T, const N: usize> Drop for PriorityAsyncMutexGuard<'a, T, N> {
+    fn drop(&mut self) {
+        critical_section::with(|cs| {
+            let mut state = self.mutex.state.borrow_ref_mut(cs);
+            state.locked = false;
+            state.wake_next();
+        });
     }
 }
 
@@ -154,7 +2125,7 @@ mod tests {
     #[test]
     fn std_mutex_api_test() {
         let t = "hello ".to_string();
-        let m: std::sync::Mutex<_> = BusMutex::create(t);
+        let m: std::sync::Mutex<_> = CreateBusMutex::create(t);
 
         BusMutex::lock(&m, |s| {
             s.push_str("world");
@@ -164,6 +2135,90 @@ mod tests {
             assert_eq!("hello world", s);
         });
     }
+
+    #[test]
+    fn std_mutex_guard_test() {
+        let m: std::sync::Mutex<_> = CreateBusMutex::create("hello ".to_string());
+
+        {
+            let mut guard = GuardedBusMutex::lock_guard(&m);
+            guard.push_str("world");
+            assert!(m.try_lock().is_err());
+        }
+
+        BusMutex::lock(&m, |s| {
+            assert_eq!("hello world", s);
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "cortex-m")]
+    fn atomic_check_mutex_test() {
+        let m: AtomicCheckMutex<_> = CreateBusMutex::create(0u8);
+
+        BusMutex::lock(&m, |v| *v += 1);
+        BusMutex::lock(&m, |v| *v += 1);
+
+        BusMutex::lock(&m, |v| assert_eq!(*v, 2));
+    }
+
+    #[test]
+    #[cfg(feature = "cortex-m")]
+    #[should_panic(expected = "Bus conflict")]
+    fn atomic_check_mutex_conflict_test() {
+        let m: AtomicCheckMutex<_> = CreateBusMutex::create(0u8);
+
+        BusMutex::lock(&m, |_| {
+            BusMutex::lock(&m, |_| ());
+        });
+    }
+
+    #[test]
+    #[cfg(all(feature = "cortex-m", feature = "cache-padding"))]
+    fn atomic_check_mutex_padding_test() {
+        assert_eq!(core::mem::align_of::<AtomicCheckMutex<u8>>(), 64);
+        assert_eq!(core::mem::size_of::<[AtomicCheckMutex<u8>; 2]>(), 128);
+    }
+
+    #[test]
+    #[cfg(feature = "cortex-m")]
+    fn atomic_check_mutex_try_lock_test() {
+        let m: AtomicCheckMutex<_> = CreateBusMutex::create(0u8);
+
+        let res = TryBusMutex::try_lock(&m, |_| TryBusMutex::try_lock(&m, |_| ()));
+        assert_eq!(res, Ok(Err(LockError::Busy)));
+
+        // The failed attempt did not leave the mutex locked
+        assert_eq!(TryBusMutex::try_lock(&m, |v| *v), Ok(0));
+    }
+
+    #[test]
+    fn std_mutex_poisoned_test() {
+        let m: std::sync::Mutex<_> = CreateBusMutex::create(0u8);
+
+        let _ = std::panic::catch_unwind(|| BusMutex::lock(&m, |_| panic!("driver bug")));
+
+        assert_eq!(TryBusMutex::try_lock(&m, |_| ()), Err(LockError::Poisoned));
+    }
+
+    #[test]
+    fn null_mutex_try_lock_test() {
+        let m: NullMutex<_> = CreateBusMutex::create(0u8);
+
+        let res = TryBusMutex::try_lock(&m, |_| TryBusMutex::try_lock(&m, |_| ()));
+        assert_eq!(res, Ok(Err(LockError::Busy)));
+    }
+
+    #[test]
+    fn null_mutex_guard_test() {
+        let m: NullMutex<_> = CreateBusMutex::create(0u8);
+
+        let mut guard = m.lock_guard();
+        *guard += 1;
+        drop(guard);
+
+        BusMutex::lock(&m, |v| assert_eq!(*v, 1));
+    }
 }
 
 /// A simple coherency checker for sharing across multiple tasks/threads.
@@ -174,48 +2229,202 @@ mod tests {
 /// this mutex uses an atomic bool to determine when the bus is in use. If a bus collision is
 /// detected, the code will panic.
 ///
-/// This mutex type is used with the [`BusManagerAtomicMutex`] type.
+/// It can just as well be used without RTIC when the application guarantees that bus accesses
+/// never overlap by other means, e.g. by masking an interrupt while the main loop is using the
+/// bus.  See [`BusManagerAtomicCheck`] for an example.
+///
+/// This mutex type is used with the [`BusManagerAtomicCheck`] type.
 ///
 /// This manager type is explicitly safe to share across threads because it checks to ensure that
 /// collisions due to bus sharing do not occur.
 ///
-/// [`BusManagerAtomicMutex`]: ./type.BusManagerAtomicMutex.html
+/// # Soundness on multi-core systems
+/// The check does not rely on a single-core system or on a framework like RTIC:  The `busy` flag
+/// is taken with an atomic compare-and-swap, so the mutex is a real (non-blocking) lock which
+/// never hands out the bus twice, no matter how many cores access it.  Where the target has no
+/// native compare-and-swap (e.g. Cortex-M0+), `portable-atomic` implements it with a critical
+/// section, so a `critical-section` implementation which covers all cores is needed there (the
+/// usual ones for multi-core chips like the RP2040 do).
+///
+/// What the assumption about cooperating bus users buys is only that the lock is never
+/// contended; if it is, the conflict is detected and the mutex panics instead of waiting.  As
+/// the bus is then accessed from whichever thread or core takes the lock, the mutex is only
+/// `Sync` when the bus is `Send`.  The only way around the check is the `unsafe`
+/// [`with_checks_disabled()`][with_checks_disabled].
+///
+/// [`BusManagerAtomicCheck`]: ./type.BusManagerAtomicCheck.html
+/// [with_checks_disabled]: #method.with_checks_disabled
+///
+/// With the `cache-padding` feature, the mutex is aligned to a 64 byte cache line.  This avoids
+/// false sharing between the `busy` flags of multiple mutexes on multi-core systems with data
+/// caches, at the cost of extra RAM.  It does not help on cores without a data cache (e.g.
+/// Cortex-M0+ as used in the RP2040).
 #[cfg(feature = "cortex-m")]
+#[cfg_attr(feature = "cache-padding", repr(align(64)))]
 #[derive(Debug)]
 pub struct AtomicCheckMutex<BUS> {
     bus: core::cell::UnsafeCell<BUS>,
-    busy: atomic_polyfill::AtomicBool,
+    busy: portable_atomic::AtomicBool,
+    unchecked: portable_atomic::AtomicUsize,
 }
 
 // It is explicitly safe to share this across threads because there is a coherency check using an
-// atomic bool comparison.
+// atomic compare-and-swap, which only ever lets one user access the bus.  The bus may be accessed
+// from any thread holding a reference, so it must be `Send`.
+#[cfg(feature = "cortex-m")]
+unsafe impl<BUS: Send> Sync for AtomicCheckMutex<BUS> {}
+
 #[cfg(feature = "cortex-m")]
-unsafe impl<BUS> Sync for AtomicCheckMutex<BUS> {}
+impl<BUS> AtomicCheckMutex<BUS> {
+    /// Create a new mutex for `bus`.
+    ///
+    /// Unlike [`CreateBusMutex::create()`], this can be used in const context, e.g. for a `static` bus
+    /// manager.
+    ///
+    /// [`CreateBusMutex::create()`]: ./trait.CreateBusMutex.html#tymethod.create
+    pub const fn new(bus: BUS) -> Self {
+        Self {
+            bus: core::cell::UnsafeCell::new(bus),
+            busy: portable_atomic::AtomicBool::new(false),
+            unchecked: portable_atomic::AtomicUsize::new(0),
+        }
+    }
+
+    /// Run `f` with the bus conflict check of this mutex disabled.
+    ///
+    /// While `f` runs, locking the mutex never fails, even when the bus is already in use.  This
+    /// is meant for exceptional situations where the usual access discipline is broken on
+    /// purpose, e.g. a maintenance mode in which a bootloader routine accesses the bus from an
+    /// unusual context.  The check is disabled for all users of the mutex, including interrupt
+    /// handlers and other cores, until `f` returns.
+    ///
+    /// **This is a foot-gun**:  It switches off exactly the fail-safe which protects against
+    /// corrupting the bus.  Only use it for the maintenance scenario described above.
+    ///
+    /// # Safety
+    /// Without the check, two users can get a mutable reference to the bus at the same time.
+    /// The caller must ensure that while the check is disabled, a user holding the bus does not
+    /// access it while another one does.
+    pub unsafe fn with_checks_disabled<R>(&self, f: impl FnOnce() -> R) -> R {
+        /// Enables the check again, even if `f` panics.
+        struct Enable<'a>(&'a portable_atomic::AtomicUsize);
+
+        impl Drop for Enable<'_> {
+            fn drop(&mut self) {
+                self.0.fetch_sub(1, core::sync::atomic::Ordering::Release);
+            }
+        }
+
+        // A counter, so nested and concurrent calls don't enable the check too early.
+        self.unchecked
+            .fetch_add(1, core::sync::atomic::Ordering::Acquire);
+        let _enable = Enable(&self.unchecked);
+        f()
+    }
+}
 
 #[cfg(feature = "cortex-m")]
 impl<BUS> BusMutex for AtomicCheckMutex<BUS> {
     type Bus = BUS;
+    const KIND: &'static str = "AtomicCheckMutex";
+
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
+        self.try_lock(f).expect("Bus conflict")
+    }
+}
 
+#[cfg(feature = "cortex-m")]
+impl<BUS> CreateBusMutex for AtomicCheckMutex<BUS> {
     fn create(v: BUS) -> Self {
-        Self {
-            bus: core::cell::UnsafeCell::new(v),
-            busy: atomic_polyfill::AtomicBool::from(false),
-        }
+        Self::new(v)
     }
+}
 
-    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
+#[cfg(feature = "cortex-m")]
+impl<BUS> TryBusMutex for AtomicCheckMutex<BUS> {
+    fn try_lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> Result<R, LockError> {
+        if self.unchecked.load(core::sync::atomic::Ordering::Acquire) != 0 {
+            // SAFETY: The caller of `with_checks_disabled()` is responsible for this access.
+            return Ok(f(unsafe { &mut *self.bus.get() }));
+        }
+
+        // Acquire/Release is sufficient to order the bus accesses between two lock holders.
         self.busy
             .compare_exchange(
                 false,
                 true,
-                core::sync::atomic::Ordering::SeqCst,
-                core::sync::atomic::Ordering::SeqCst,
+                core::sync::atomic::Ordering::Acquire,
+                core::sync::atomic::Ordering::Relaxed,
             )
-            .expect("Bus conflict");
+            .map_err(|_| LockError::Busy)?;
         let result = f(unsafe { &mut *self.bus.get() });
 
-        self.busy.store(false, core::sync::atomic::Ordering::SeqCst);
+        self.busy
+            .store(false, core::sync::atomic::Ordering::Release);
+
+        Ok(result)
+    }
+}
+
+/// Guard giving exclusive access to the bus of an [`AtomicCheckMutex`], e.g. for a firmware
+/// update.
+///
+/// The bus is marked busy while the guard is alive, so all proxies fail with a bus conflict
+/// instead of accessing it.  Dropping the guard makes the bus available again.
+///
+/// A `DfuGuard` is created by calling [`BusManager::take_for_dfu()`][take_for_dfu].
+///
+/// [`AtomicCheckMutex`]: ./struct.AtomicCheckMutex.html
+/// [take_for_dfu]: ./struct.BusManager.html#method.take_for_dfu
+#[cfg(feature = "cortex-m")]
+#[derive(Debug)]
+pub struct DfuGuard<'a, BUS> {
+    mutex: &'a AtomicCheckMutex<BUS>,
+    _bus: core::marker::PhantomData<&'a mut BUS>,
+}
+
+#[cfg(feature = "cortex-m")]
+impl<BUS> AtomicCheckMutex<BUS> {
+    /// Mark the bus busy and return a guard for it, if it is not in use.
+    pub(crate) fn try_take(&self) -> Option<DfuGuard<'_, BUS>> {
+        self.busy
+            .compare_exchange(
+                false,
+                true,
+                core::sync::atomic::Ordering::Acquire,
+                core::sync::atomic::Ordering::Relaxed,
+            )
+            .ok()
+            .map(|_| DfuGuard {
+                mutex: self,
+                _bus: core::marker::PhantomData,
+            })
+    }
+}
+
+#[cfg(feature = "cortex-m")]
+impl<BUS> core::ops::Deref for DfuGuard<'_, BUS> {
+    type Target = BUS;
+
+    fn deref(&self) -> &BUS {
+        // SAFETY: The busy flag is held by this guard, so no lock holder can access the bus.
+        unsafe { &*self.mutex.bus.get() }
+    }
+}
+
+#[cfg(feature = "cortex-m")]
+impl<BUS> core::ops::DerefMut for DfuGuard<'_, BUS> {
+    fn deref_mut(&mut self) -> &mut BUS {
+        // SAFETY: The busy flag is held by this guard, so no lock holder can access the bus.
+        unsafe { &mut *self.mutex.bus.get() }
+    }
+}
 
-        result
+#[cfg(feature = "cortex-m")]
+impl<BUS> Drop for DfuGuard<'_, BUS> {
+    fn drop(&mut self) {
+        self.mutex
+            .busy
+            .store(false, core::sync::atomic::Ordering::Release);
     }
 }
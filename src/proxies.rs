@@ -1,8 +1,22 @@
-#[cfg(feature = "eh-alpha")]
-use embedded_hal_alpha::i2c as i2c_alpha;
+#[cfg(feature = "eh1")]
+use embedded_hal_1::delay as delay_eh1;
+#[cfg(all(feature = "spi", feature = "eh1"))]
+use embedded_hal_1::digital as digital_eh1;
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+use embedded_hal_1::i2c as i2c_eh1;
+#[cfg(all(feature = "spi", feature = "eh1"))]
+use embedded_hal_1::spi as spi_eh1;
+#[cfg(all(feature = "i2c", feature = "async"))]
+use embedded_hal_async::i2c as i2c_async;
+#[cfg(all(feature = "spi", feature = "async"))]
+use embedded_hal_async::spi as spi_async;
 
+#[cfg(feature = "adc")]
 use embedded_hal::adc;
+use embedded_hal::blocking::delay;
+#[cfg(feature = "i2c")]
 use embedded_hal::blocking::i2c;
+#[cfg(feature = "spi")]
 use embedded_hal::blocking::spi;
 
 /// Proxy type for I2C bus sharing.
@@ -13,18 +27,220 @@ use embedded_hal::blocking::spi;
 ///
 /// An `I2cProxy` is created by calling [`BusManager::acquire_i2c()`][acquire_i2c].
 ///
+/// A `transaction()` with an empty list of operations (`embedded-hal` 1.0) is a no-op and returns
+/// `Ok(())` without accessing the bus.
+///
+/// Combined operations (`write_read()`, `write_iter_read()`, and `transaction()`) are passed to
+/// the bus in one piece, while holding the lock.  The proxy never splits them, so the repeated
+/// start between the write and read phases which the HAL generates is preserved, and no other
+/// proxy can access the bus in between.
+///
+/// The I2C traits are also implemented for `&I2cProxy`.  As all accesses go through the mutex
+/// anyway, a shared reference to a proxy can be passed to drivers which expect a mutable bus.
+/// This is useful when the proxy lives in a structure that is only available by shared reference.
+///
+/// # Using with other bus abstractions
+/// Some driver crates define their own bus trait instead of using the `embedded-hal` traits
+/// directly.  When the driver crate provides an interface type which is created `From` the I2C
+/// bus, [`BusManager::acquire_i2c_into()`][acquire_i2c_into] hands out such an interface
+/// directly.  Otherwise, as the bus trait is local to the driver crate, it can be implemented
+/// for `I2cProxy` there (or in your application for a trait of your own) by forwarding to the
+/// `embedded-hal` implementation of the proxy:
+///
+/// ```
+/// use embedded_hal::blocking::i2c;
+///
+/// // A bus trait as defined by some driver crate
+/// pub trait RegisterBus {
+///     type Error;
+///
+///     fn read_register(&mut self, addr: u8, reg: u8) -> Result<u8, Self::Error>;
+/// }
+///
+/// impl<'a, M: shared_bus::BusMutex> RegisterBus for shared_bus::I2cProxy<'a, M>
+/// where
+///     M::Bus: i2c::WriteRead,
+/// {
+///     type Error = <M::Bus as i2c::WriteRead>::Error;
+///
+///     fn read_register(&mut self, addr: u8, reg: u8) -> Result<u8, Self::Error> {
+///         let mut buf = [0u8];
+///         i2c::WriteRead::write_read(self, addr, &[reg], &mut buf)?;
+///         Ok(buf[0])
+///     }
+/// }
+/// #
+/// # struct Bus;
+/// # impl i2c::WriteRead for Bus {
+/// #     type Error = ();
+/// #     fn write_read(&mut self, _: u8, _: &[u8], b: &mut [u8]) -> Result<(), ()> {
+/// #         b[0] = 0x42;
+/// #         Ok(())
+/// #     }
+/// # }
+/// # let bus = shared_bus::BusManagerSimple::new(Bus);
+/// # let mut proxy = bus.acquire_i2c();
+/// # assert_eq!(proxy.read_register(0x20, 0x0f), Ok(0x42));
+/// ```
+///
 /// [acquire_i2c]: ./struct.BusManager.html#method.acquire_i2c
+/// [acquire_i2c_into]: ./struct.BusManager.html#method.acquire_i2c_into
+#[cfg(feature = "i2c")]
 #[derive(Debug)]
 pub struct I2cProxy<'a, M> {
     pub(crate) mutex: &'a M,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: &'a crate::metrics::ByteCounters,
 }
 
+#[cfg(feature = "i2c")]
 impl<'a, M: crate::BusMutex> Clone for I2cProxy<'a, M> {
     fn clone(&self) -> Self {
-        Self { mutex: &self.mutex }
+        Self {
+            mutex: self.mutex,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
+        }
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M> I2cProxy<'a, M> {
+    /// Account a successful transfer in the byte counters of the manager.
+    #[inline]
+    fn count<T, E>(&self, res: Result<T, E>, written: usize, read: usize) -> Result<T, E> {
+        #[cfg(feature = "metrics")]
+        if res.is_ok() {
+            self.metrics.add(written, read);
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = (written, read);
+        res
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::TryBusMutex> I2cProxy<'a, M> {
+    /// Like [`i2c::Write::write()`], but return an error if the bus cannot be locked.
+    ///
+    /// See [`TryBusMutex`](./trait.TryBusMutex.html) for details.
+    pub fn try_write(
+        &mut self,
+        addr: u8,
+        buffer: &[u8],
+    ) -> Result<(), crate::Error<<M::Bus as i2c::Write>::Error>>
+    where
+        M::Bus: i2c::Write,
+    {
+        let res = self
+            .mutex
+            .try_lock(|bus| i2c::Write::write(bus, addr, buffer))?
+            .map_err(crate::Error::Bus);
+        self.count(res, buffer.len(), 0)
+    }
+
+    /// Like [`i2c::Read::read()`], but return an error if the bus cannot be locked.
+    ///
+    /// See [`TryBusMutex`](./trait.TryBusMutex.html) for details.
+    pub fn try_read(
+        &mut self,
+        addr: u8,
+        buffer: &mut [u8],
+    ) -> Result<(), crate::Error<<M::Bus as i2c::Read>::Error>>
+    where
+        M::Bus: i2c::Read,
+    {
+        let len = buffer.len();
+        let res = self
+            .mutex
+            .try_lock(|bus| i2c::Read::read(bus, addr, buffer))?
+            .map_err(crate::Error::Bus);
+        self.count(res, 0, len)
+    }
+
+    /// Like [`i2c::WriteRead::write_read()`], but return an error if the bus cannot be locked.
+    ///
+    /// See [`TryBusMutex`](./trait.TryBusMutex.html) for details.
+    pub fn try_write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), crate::Error<<M::Bus as i2c::WriteRead>::Error>>
+    where
+        M::Bus: i2c::WriteRead,
+    {
+        let len = buffer_out.len();
+        let res = self
+            .mutex
+            .try_lock(|bus| i2c::WriteRead::write_read(bus, addr, buffer_in, buffer_out))?
+            .map_err(crate::Error::Bus);
+        self.count(res, buffer_in.len(), len)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex> I2cProxy<'a, M>
+where
+    M::Bus: i2c::WriteRead + i2c::Write<Error = <M::Bus as i2c::WriteRead>::Error>,
+{
+    /// Atomically modify an 8-bit register of a device with 8-bit register addresses.
+    ///
+    /// The register `reg` is read, passed through `f`, and the result is written back.  The bus
+    /// stays locked for the whole sequence so no other proxy can access the device in between.
+    ///
+    /// ```
+    /// # use embedded_hal::blocking::i2c;
+    /// # fn _example<B: i2c::WriteRead + i2c::Write<Error = <B as i2c::WriteRead>::Error>>(i2c: B) {
+    /// let bus = shared_bus::BusManagerSimple::new(i2c);
+    /// let mut proxy = bus.acquire_i2c();
+    ///
+    /// // Set the enable bit of the control register without touching the other bits
+    /// let _ = proxy.modify_register(0x39, 0x80, |ctrl| ctrl | 0x01);
+    /// # }
+    /// ```
+    pub fn modify_register<F: FnOnce(u8) -> u8>(
+        &mut self,
+        addr: u8,
+        reg: u8,
+        f: F,
+    ) -> Result<(), <M::Bus as i2c::WriteRead>::Error> {
+        self.modify(addr, &[reg], f)
+    }
+
+    /// Atomically modify an 8-bit register of a device with 16-bit register addresses.
+    ///
+    /// The register address is sent in big-endian byte order.  See
+    /// [`modify_register()`](#method.modify_register) for details.
+    pub fn modify_register16<F: FnOnce(u8) -> u8>(
+        &mut self,
+        addr: u8,
+        reg: u16,
+        f: F,
+    ) -> Result<(), <M::Bus as i2c::WriteRead>::Error> {
+        self.modify(addr, &reg.to_be_bytes(), f)
+    }
+
+    fn modify<F: FnOnce(u8) -> u8>(
+        &mut self,
+        addr: u8,
+        reg: &[u8],
+        f: F,
+    ) -> Result<(), <M::Bus as i2c::WriteRead>::Error> {
+        let res = self.mutex.lock(|bus| {
+            let mut value = [0u8];
+            i2c::WriteRead::write_read(bus, addr, reg, &mut value)?;
+
+            let mut buffer = [0u8; 3];
+            buffer[..reg.len()].copy_from_slice(reg);
+            buffer[reg.len()] = f(value[0]);
+            i2c::Write::write(bus, addr, &buffer[..reg.len() + 1])
+        });
+        self.count(res, 2 * reg.len() + 1, 1)
     }
 }
 
+#[cfg(feature = "i2c")]
 impl<'a, M: crate::BusMutex> i2c::Write for I2cProxy<'a, M>
 where
     M::Bus: i2c::Write,
@@ -32,10 +248,15 @@ where
     type Error = <M::Bus as i2c::Write>::Error;
 
     fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
-        self.mutex.lock(|bus| bus.write(addr, buffer))
+        self.count(
+            self.mutex.lock(|bus| bus.write(addr, buffer)),
+            buffer.len(),
+            0,
+        )
     }
 }
 
+#[cfg(feature = "i2c")]
 impl<'a, M: crate::BusMutex> i2c::Read for I2cProxy<'a, M>
 where
     M::Bus: i2c::Read,
@@ -43,10 +264,12 @@ where
     type Error = <M::Bus as i2c::Read>::Error;
 
     fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
-        self.mutex.lock(|bus| bus.read(addr, buffer))
+        let len = buffer.len();
+        self.count(self.mutex.lock(|bus| bus.read(addr, buffer)), 0, len)
     }
 }
 
+#[cfg(feature = "i2c")]
 impl<'a, M: crate::BusMutex> i2c::WriteRead for I2cProxy<'a, M>
 where
     M::Bus: i2c::WriteRead,
@@ -59,11 +282,15 @@ where
         buffer_in: &[u8],
         buffer_out: &mut [u8],
     ) -> Result<(), Self::Error> {
-        self.mutex
-            .lock(|bus| bus.write_read(addr, buffer_in, buffer_out))
+        let len = buffer_out.len();
+        let res = self
+            .mutex
+            .lock(|bus| bus.write_read(addr, buffer_in, buffer_out));
+        self.count(res, buffer_in.len(), len)
     }
 }
 
+#[cfg(feature = "i2c")]
 impl<'a, M: crate::BusMutex> i2c::WriteIterRead for I2cProxy<'a, M>
 where
     M::Bus: i2c::WriteIterRead,
@@ -79,11 +306,17 @@ where
     where
         B: IntoIterator<Item = u8>,
     {
-        self.mutex
-            .lock(|bus| bus.write_iter_read(address, bytes, buffer))
+        let mut written = 0;
+        let bytes = bytes.into_iter().inspect(|_| written += 1);
+        let len = buffer.len();
+        let res = self
+            .mutex
+            .lock(|bus| bus.write_iter_read(address, bytes, buffer));
+        self.count(res, written, len)
     }
 }
 
+#[cfg(feature = "i2c")]
 impl<'a, M: crate::BusMutex> i2c::WriteIter for I2cProxy<'a, M>
 where
     M::Bus: i2c::WriteIter,
@@ -94,49 +327,69 @@ where
     where
         B: IntoIterator<Item = u8>,
     {
-        self.mutex.lock(|bus| bus.write(address, bytes))
+        let mut written = 0;
+        let bytes = bytes.into_iter().inspect(|_| written += 1);
+        let res = self.mutex.lock(|bus| bus.write(address, bytes));
+        self.count(res, written, 0)
     }
 }
 
-// Implementations for the embedded_hal alpha
-
-#[cfg(feature = "eh-alpha")]
-impl<'a, M: crate::BusMutex> i2c_alpha::ErrorType for I2cProxy<'a, M>
+#[cfg(feature = "i2c")]
+impl<'a, 'b, M: crate::BusMutex> i2c::Write for &'b I2cProxy<'a, M>
 where
-    M::Bus: i2c_alpha::ErrorType,
+    M::Bus: i2c::Write,
 {
-    type Error = <M::Bus as i2c_alpha::ErrorType>::Error;
+    type Error = <M::Bus as i2c::Write>::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.count(
+            self.mutex.lock(|bus| bus.write(addr, buffer)),
+            buffer.len(),
+            0,
+        )
+    }
 }
 
-#[cfg(feature = "eh-alpha")]
-impl<'a, M: crate::BusMutex> i2c_alpha::I2c for I2cProxy<'a, M>
+#[cfg(feature = "i2c")]
+impl<'a, 'b, M: crate::BusMutex> i2c::Read for &'b I2cProxy<'a, M>
 where
-    M::Bus: i2c_alpha::I2c,
+    M::Bus: i2c::Read,
 {
-    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
-        self.mutex.lock(|bus| bus.read(address, buffer))
-    }
+    type Error = <M::Bus as i2c::Read>::Error;
 
-    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
-        self.mutex.lock(|bus| bus.write(address, bytes))
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let len = buffer.len();
+        self.count(self.mutex.lock(|bus| bus.read(addr, buffer)), 0, len)
     }
+}
 
-    fn write_iter<B>(&mut self, address: u8, bytes: B) -> Result<(), Self::Error>
-    where
-        B: IntoIterator<Item = u8>,
-    {
-        self.mutex.lock(|bus| bus.write_iter(address, bytes))
-    }
+#[cfg(feature = "i2c")]
+impl<'a, 'b, M: crate::BusMutex> i2c::WriteRead for &'b I2cProxy<'a, M>
+where
+    M::Bus: i2c::WriteRead,
+{
+    type Error = <M::Bus as i2c::WriteRead>::Error;
 
     fn write_read(
         &mut self,
-        address: u8,
-        bytes: &[u8],
-        buffer: &mut [u8],
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
     ) -> Result<(), Self::Error> {
-        self.mutex
-            .lock(|bus| bus.write_read(address, bytes, buffer))
+        let len = buffer_out.len();
+        let res = self
+            .mutex
+            .lock(|bus| bus.write_read(addr, buffer_in, buffer_out));
+        self.count(res, buffer_in.len(), len)
     }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, 'b, M: crate::BusMutex> i2c::WriteIterRead for &'b I2cProxy<'a, M>
+where
+    M::Bus: i2c::WriteIterRead,
+{
+    type Error = <M::Bus as i2c::WriteIterRead>::Error;
 
     fn write_iter_read<B>(
         &mut self,
@@ -147,113 +400,3439 @@ where
     where
         B: IntoIterator<Item = u8>,
     {
-        self.mutex
-            .lock(|bus| bus.write_iter_read(address, bytes, buffer))
+        let mut written = 0;
+        let bytes = bytes.into_iter().inspect(|_| written += 1);
+        let len = buffer.len();
+        let res = self
+            .mutex
+            .lock(|bus| bus.write_iter_read(address, bytes, buffer));
+        self.count(res, written, len)
     }
+}
 
-    fn transaction<'b>(
-        &mut self,
-        address: u8,
-        operations: &mut [i2c_alpha::Operation<'b>],
-    ) -> Result<(), Self::Error> {
-        self.mutex.lock(|bus| bus.transaction(address, operations))
+#[cfg(feature = "i2c")]
+impl<'a, 'b, M: crate::BusMutex> i2c::WriteIter for &'b I2cProxy<'a, M>
+where
+    M::Bus: i2c::WriteIter,
+{
+    type Error = <M::Bus as i2c::WriteIter>::Error;
+
+    fn write<B>(&mut self, address: u8, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        let mut written = 0;
+        let bytes = bytes.into_iter().inspect(|_| written += 1);
+        let res = self.mutex.lock(|bus| bus.write(address, bytes));
+        self.count(res, written, 0)
+    }
+}
+
+/// Proxy type for I2C bus sharing, bound to a single device address.
+///
+/// The `AddressedI2cProxy` works like the [`I2cProxy`], but the device address is part of its
+/// type.  Its methods thus don't take an address, and proxies for different devices have distinct
+/// types, so they cannot be mixed up:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # struct Sensor<'a, M>(shared_bus::AddressedI2cProxy<'a, 0x48, M>);
+/// # fn _example<I2C: i2c::Write>(i2c: I2C) {
+/// let bus = shared_bus::BusManagerSimple::new(i2c);
+///
+/// let mut sensor = bus.acquire_i2c_at::<0x48>();
+/// let mut eeprom = bus.acquire_i2c_at::<0x50>();
+///
+/// let _ = sensor.write(&[0x01, 0x60]);
+/// let _ = eeprom.write(&[0x00, 0x00, 0xaa]);
+///
+/// // Does not compile: the sensor expects a proxy for address 0x48
+/// // let sensor = Sensor(eeprom);
+/// # let _ = Sensor(sensor);
+/// # }
+/// ```
+///
+/// An `AddressedI2cProxy` is created by calling [`BusManager::acquire_i2c_at()`][acquire_i2c_at].
+///
+/// [`I2cProxy`]: ./struct.I2cProxy.html
+/// [acquire_i2c_at]: ./struct.BusManager.html#method.acquire_i2c_at
+#[cfg(feature = "i2c")]
+#[derive(Debug)]
+pub struct AddressedI2cProxy<'a, const ADDR: u8, M> {
+    pub(crate) mutex: &'a M,
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, const ADDR: u8, M: crate::BusMutex> Clone for AddressedI2cProxy<'a, ADDR, M> {
+    fn clone(&self) -> Self {
+        Self { mutex: self.mutex }
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, const ADDR: u8, M: crate::BusMutex> AddressedI2cProxy<'a, ADDR, M> {
+    /// The address of the device.
+    pub const ADDRESS: u8 = ADDR;
+
+    /// Write bytes to the device.
+    pub fn write(&mut self, buffer: &[u8]) -> Result<(), <M::Bus as i2c::Write>::Error>
+    where
+        M::Bus: i2c::Write,
+    {
+        self.mutex.lock(|bus| i2c::Write::write(bus, ADDR, buffer))
+    }
+
+    /// Read bytes from the device.
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<(), <M::Bus as i2c::Read>::Error>
+    where
+        M::Bus: i2c::Read,
+    {
+        self.mutex.lock(|bus| i2c::Read::read(bus, ADDR, buffer))
     }
 
-    fn transaction_iter<'b, O>(&mut self, address: u8, operations: O) -> Result<(), Self::Error>
+    /// Write bytes to the device and then read bytes back, without a stop condition in between.
+    pub fn write_read(
+        &mut self,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), <M::Bus as i2c::WriteRead>::Error>
     where
-        O: IntoIterator<Item = i2c_alpha::Operation<'b>>,
+        M::Bus: i2c::WriteRead,
     {
         self.mutex
-            .lock(|bus| bus.transaction_iter(address, operations))
+            .lock(|bus| i2c::WriteRead::write_read(bus, ADDR, buffer_in, buffer_out))
     }
 }
 
-/// Proxy type for SPI bus sharing.
+/// Direction of a bus transfer, as reported by the [`TracingI2cProxy`].
 ///
-/// The `SpiProxy` implements all (blocking) SPI traits so it can be passed to drivers instead of
-/// the bus instance.  An `SpiProxy` is created by calling [`BusManager::acquire_spi()`][acquire_spi].
+/// [`TracingI2cProxy`]: ./struct.TracingI2cProxy.html
+#[cfg(feature = "i2c")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Data was read from the device.
+    Read,
+    /// Data was written to the device.
+    Write,
+}
+
+/// Proxy type for I2C bus sharing which reports all transfers to a callback.
 ///
-/// **Note**: The `SpiProxy` can only be used for sharing **withing a single task/thread**.  This
-/// is due to drivers usually managing the chip-select pin manually which would be inherently racy
-/// in a concurrent environment (because the mutex is locked only after asserting CS).  To ensure
-/// safe usage, a `SpiProxy` can only be created when using [`BusManagerSimple`] and is `!Send`.
+/// The `TracingI2cProxy` works just like the [`I2cProxy`] but calls its callback with the
+/// address, [`Direction`], and data of each successful transfer.  A `write_read()` is reported
+/// as two transfers.  The callback is called after the bus lock was released.
 ///
-/// [acquire_spi]: ./struct.BusManager.html#method.acquire_spi
-/// [`BusManagerSimple`]: ./type.BusManagerSimple.html
+/// For the `embedded-hal` 1.0 `I2c` trait, the callback gets the address in the address mode
+/// of the transaction, so a callback taking a `u16` traces transactions with 10-bit addresses.
+///
+/// A `TracingI2cProxy` is created by calling
+/// [`BusManager::acquire_i2c_traced()`][acquire_i2c_traced], or
+/// [`BusManager::acquire_i2c_traced_ten_bit()`][acquire_i2c_traced_ten_bit] for 10-bit
+/// addresses.
+///
+/// [`I2cProxy`]: ./struct.I2cProxy.html
+/// [`Direction`]: ./enum.Direction.html
+/// [acquire_i2c_traced]: ./struct.BusManager.html#method.acquire_i2c_traced
+/// [acquire_i2c_traced_ten_bit]: ./struct.BusManager.html#method.acquire_i2c_traced_ten_bit
+#[cfg(feature = "i2c")]
 #[derive(Debug)]
-pub struct SpiProxy<'a, M> {
+pub struct TracingI2cProxy<'a, M, F> {
     pub(crate) mutex: &'a M,
-    pub(crate) _u: core::marker::PhantomData<*mut ()>,
+    pub(crate) callback: F,
 }
 
-impl<'a, M: crate::BusMutex> Clone for SpiProxy<'a, M> {
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F: Clone> Clone for TracingI2cProxy<'a, M, F> {
     fn clone(&self) -> Self {
         Self {
-            mutex: &self.mutex,
-            _u: core::marker::PhantomData,
+            mutex: self.mutex,
+            callback: self.callback.clone(),
         }
     }
 }
 
-impl<'a, M: crate::BusMutex> spi::Transfer<u8> for SpiProxy<'a, M>
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::Write for TracingI2cProxy<'a, M, F>
 where
-    M::Bus: spi::Transfer<u8>,
+    M::Bus: i2c::Write,
+    F: FnMut(u8, Direction, &[u8]),
 {
-    type Error = <M::Bus as spi::Transfer<u8>>::Error;
+    type Error = <M::Bus as i2c::Write>::Error;
 
-    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
-        self.mutex.lock(move |bus| bus.transfer(words))
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.mutex.lock(|bus| bus.write(addr, buffer))?;
+        (self.callback)(addr, Direction::Write, buffer);
+        Ok(())
     }
 }
 
-impl<'a, M: crate::BusMutex> spi::Write<u8> for SpiProxy<'a, M>
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::Read for TracingI2cProxy<'a, M, F>
 where
-    M::Bus: spi::Write<u8>,
+    M::Bus: i2c::Read,
+    F: FnMut(u8, Direction, &[u8]),
 {
-    type Error = <M::Bus as spi::Write<u8>>::Error;
+    type Error = <M::Bus as i2c::Read>::Error;
 
-    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
-        self.mutex.lock(|bus| bus.write(words))
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.mutex.lock(|bus| bus.read(addr, buffer))?;
+        (self.callback)(addr, Direction::Read, buffer);
+        Ok(())
     }
 }
 
-/// Proxy type for ADC sharing.
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::WriteRead for TracingI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::WriteRead,
+    F: FnMut(u8, Direction, &[u8]),
+{
+    type Error = <M::Bus as i2c::WriteRead>::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.mutex
+            .lock(|bus| bus.write_read(addr, buffer_in, buffer_out))?;
+        (self.callback)(addr, Direction::Write, buffer_in);
+        (self.callback)(addr, Direction::Read, buffer_out);
+        Ok(())
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, F> i2c_eh1::ErrorType for TracingI2cProxy<'a, M, F>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = <M::Bus as i2c_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, F, A: i2c_eh1::AddressMode + Copy> i2c_eh1::I2c<A>
+    for TracingI2cProxy<'a, M, F>
+where
+    M::Bus: i2c_eh1::I2c<A>,
+    F: FnMut(A, Direction, &[u8]),
+{
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        self.mutex
+            .lock(|bus| bus.transaction(address, operations))?;
+        for op in operations.iter() {
+            match op {
+                i2c_eh1::Operation::Read(buf) => (self.callback)(address, Direction::Read, buf),
+                i2c_eh1::Operation::Write(buf) => (self.callback)(address, Direction::Write, buf),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Proxy type for I2C bus sharing which retries failed transfers.
 ///
-/// The `AdcProxy` implements OneShot trait so it can be passed to drivers instead of
-/// ADC instance. Internally, it holds reference to the bus via a mutex, ensuring
-/// that all accesses are strictly synchronized.
+/// The `RetryingI2cProxy` works just like the [`I2cProxy`] but when an operation fails with an
+/// error for which the `is_retryable` predicate returns `true`, the operation is retried up to
+/// `retries` times.  The retries happen inside the bus lock, so no other bus user can interfere
+/// with them.  This is e.g. useful for Linux `I2cdev` busses whose operations can be interrupted
+/// by signals (`EINTR`).
 ///
-/// An `AdcProxy` is created by calling [`BusManager::acquire_adc()`][acquire_adc].
+/// Only the operations which can be repeated (write, read, write-read, and `embedded-hal` 1.0
+/// transactions) are supported.
 ///
-/// **Note**: The [`adc::OneShot`] trait proxied by this type describes a
-/// non-blocking contract for ADC read operation.  However access to a shared ADC
-/// unit can not be arbitrated in a completely non-blocking and concurrency safe way.
-/// Any reading from a channel shall be completed before `shared-bus` can allow the
-/// next read from the same or another channel. So the current implementation breaks
-/// the non-blocking contract of the trait and just busy-spins until a sample is
-/// returned.
+/// **Note**: The `is_retryable` predicate is called while the bus is locked, so it must not
+/// access the same bus manager (e.g. for logging through another proxy).  With `std`, this is
+/// detected in debug builds and leads to a panic instead of a deadlock.
 ///
-/// [acquire_adc]: ./struct.BusManager.html#method.acquire_adc
+/// A `RetryingI2cProxy` is created by calling
+/// [`BusManager::acquire_i2c_retrying()`][acquire_i2c_retrying].
+///
+/// [`I2cProxy`]: ./struct.I2cProxy.html
+/// [acquire_i2c_retrying]: ./struct.BusManager.html#method.acquire_i2c_retrying
+#[cfg(feature = "i2c")]
 #[derive(Debug)]
-pub struct AdcProxy<'a, M> {
+pub struct RetryingI2cProxy<'a, M, F> {
     pub(crate) mutex: &'a M,
+    pub(crate) retries: usize,
+    pub(crate) is_retryable: F,
 }
 
-impl<'a, M: crate::BusMutex> Clone for AdcProxy<'a, M> {
-    fn clone(&self) -> Self {
-        Self { mutex: &self.mutex }
+#[cfg(feature = "i2c")]
+impl<'a, M, F> RetryingI2cProxy<'a, M, F> {
+    fn retry<E>(&self, mut op: impl FnMut() -> Result<(), E>) -> Result<(), E>
+    where
+        F: Fn(&E) -> bool,
+    {
+        let is_retryable = |e: &E| crate::mutex::in_callback(self.mutex, || (self.is_retryable)(e));
+        let mut attempts = 0;
+        loop {
+            match op() {
+                Err(e) if attempts < self.retries && is_retryable(&e) => attempts += 1,
+                res => return res,
+            }
+        }
     }
 }
 
-impl<'a, M: crate::BusMutex, ADC, Word, Pin> adc::OneShot<ADC, Word, Pin> for AdcProxy<'a, M>
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::Write for RetryingI2cProxy<'a, M, F>
 where
-    Pin: adc::Channel<ADC>,
-    M::Bus: adc::OneShot<ADC, Word, Pin>,
+    M::Bus: i2c::Write,
+    F: Fn(&<M::Bus as i2c::Write>::Error) -> bool,
 {
-    type Error = <M::Bus as adc::OneShot<ADC, Word, Pin>>::Error;
+    type Error = <M::Bus as i2c::Write>::Error;
 
-    fn read(&mut self, pin: &mut Pin) -> nb::Result<Word, Self::Error> {
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
         self.mutex
-            .lock(|bus| nb::block!(bus.read(pin)).map_err(nb::Error::Other))
+            .lock(|bus| self.retry(|| bus.write(addr, buffer)))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::Read for RetryingI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::Read,
+    F: Fn(&<M::Bus as i2c::Read>::Error) -> bool,
+{
+    type Error = <M::Bus as i2c::Read>::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.mutex.lock(|bus| self.retry(|| bus.read(addr, buffer)))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::WriteRead for RetryingI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::WriteRead,
+    F: Fn(&<M::Bus as i2c::WriteRead>::Error) -> bool,
+{
+    type Error = <M::Bus as i2c::WriteRead>::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.mutex
+            .lock(|bus| self.retry(|| bus.write_read(addr, buffer_in, buffer_out)))
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, F> i2c_eh1::ErrorType for RetryingI2cProxy<'a, M, F>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = <M::Bus as i2c_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, F, A: i2c_eh1::AddressMode + Copy> i2c_eh1::I2c<A>
+    for RetryingI2cProxy<'a, M, F>
+where
+    M::Bus: i2c_eh1::I2c<A>,
+    F: Fn(&<M::Bus as i2c_eh1::ErrorType>::Error) -> bool,
+{
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        self.mutex
+            .lock(|bus| self.retry(|| bus.transaction(address, operations)))
+    }
+}
+
+/// Proxy type for I2C bus sharing which recovers a hung bus.
+///
+/// The `RecoveringI2cProxy` works just like the [`I2cProxy`] but when an operation fails, it
+/// calls the `recover` routine with the bus and then retries the operation once.  Both happen
+/// inside the bus lock, so no other device can interfere with the recovery.
+///
+/// A typical recovery routine clocks SCL manually until a device holding SDA low releases it.  As
+/// this needs access to the SCL pin, the routine usually owns (a handle to) it.  If the second
+/// attempt fails as well, its error is returned.
+///
+/// A `RecoveringI2cProxy` is created by calling
+/// [`BusManager::acquire_i2c_recovering()`][acquire_i2c_recovering].
+///
+/// [`I2cProxy`]: ./struct.I2cProxy.html
+/// [acquire_i2c_recovering]: ./struct.BusManager.html#method.acquire_i2c_recovering
+#[cfg(feature = "i2c")]
+#[derive(Debug)]
+pub struct RecoveringI2cProxy<'a, M, F> {
+    pub(crate) mutex: &'a M,
+    pub(crate) recover: F,
+}
+
+#[cfg(feature = "i2c")]
+fn with_recovery<B, E>(
+    bus: &mut B,
+    recover: &mut impl FnMut(&mut B),
+    mut op: impl FnMut(&mut B) -> Result<(), E>,
+) -> Result<(), E> {
+    match op(bus) {
+        Err(_) => {
+            recover(bus);
+            op(bus)
+        }
+        res => res,
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::Write for RecoveringI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::Write,
+    F: FnMut(&mut M::Bus),
+{
+    type Error = <M::Bus as i2c::Write>::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        let recover = &mut self.recover;
+        self.mutex
+            .lock(|bus| with_recovery(bus, recover, |bus| bus.write(addr, buffer)))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::Read for RecoveringI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::Read,
+    F: FnMut(&mut M::Bus),
+{
+    type Error = <M::Bus as i2c::Read>::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let recover = &mut self.recover;
+        self.mutex
+            .lock(|bus| with_recovery(bus, recover, |bus| bus.read(addr, buffer)))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::WriteRead for RecoveringI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::WriteRead,
+    F: FnMut(&mut M::Bus),
+{
+    type Error = <M::Bus as i2c::WriteRead>::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let recover = &mut self.recover;
+        self.mutex.lock(|bus| {
+            with_recovery(bus, recover, |bus| {
+                bus.write_read(addr, buffer_in, buffer_out)
+            })
+        })
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, F> i2c_eh1::ErrorType for RecoveringI2cProxy<'a, M, F>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = <M::Bus as i2c_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, F, A: i2c_eh1::AddressMode + Copy> i2c_eh1::I2c<A>
+    for RecoveringI2cProxy<'a, M, F>
+where
+    M::Bus: i2c_eh1::I2c<A>,
+    F: FnMut(&mut M::Bus),
+{
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        let recover = &mut self.recover;
+        self.mutex
+            .lock(|bus| with_recovery(bus, recover, |bus| bus.transaction(address, operations)))
+    }
+}
+
+/// Proxy type for I2C bus sharing with a per-device bus clock.
+///
+/// The `SpeedI2cProxy` works just like the [`I2cProxy`] but calls the `set_speed` closure with the
+/// bus and the clock rate of its device before each transaction, inside the same lock.  This
+/// allows slow and fast devices to share a bus, as long as the HAL allows changing the clock rate
+/// between transactions.
+///
+/// By default, the clock rate is left as is after the transaction.  Use
+/// [`restore_after()`][restore_after] to switch back to a default clock rate instead, e.g. when
+/// other users of the bus don't set the clock rate themselves.
+///
+/// A `SpeedI2cProxy` is created by calling
+/// [`BusManager::acquire_i2c_at_speed()`][acquire_i2c_at_speed].
+///
+/// [`I2cProxy`]: ./struct.I2cProxy.html
+/// [restore_after]: #method.restore_after
+/// [acquire_i2c_at_speed]: ./struct.BusManager.html#method.acquire_i2c_at_speed
+#[cfg(feature = "i2c")]
+#[derive(Debug)]
+pub struct SpeedI2cProxy<'a, M, F> {
+    pub(crate) mutex: &'a M,
+    pub(crate) hz: u32,
+    pub(crate) restore: Option<u32>,
+    pub(crate) set_speed: F,
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> SpeedI2cProxy<'a, M, F>
+where
+    F: FnMut(&mut M::Bus, u32),
+{
+    /// Set the clock rate back to `hz` after each transaction.
+    pub fn restore_after(mut self, hz: u32) -> Self {
+        self.restore = Some(hz);
+        self
+    }
+
+    fn with_speed<R>(&mut self, op: impl FnOnce(&mut M::Bus) -> R) -> R {
+        let hz = self.hz;
+        let restore = self.restore;
+        let set_speed = &mut self.set_speed;
+        self.mutex.lock(|bus| {
+            set_speed(bus, hz);
+            let res = op(bus);
+            if let Some(restore) = restore {
+                set_speed(bus, restore);
+            }
+            res
+        })
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::Write for SpeedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::Write,
+    F: FnMut(&mut M::Bus, u32),
+{
+    type Error = <M::Bus as i2c::Write>::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.with_speed(|bus| bus.write(addr, buffer))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::Read for SpeedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::Read,
+    F: FnMut(&mut M::Bus, u32),
+{
+    type Error = <M::Bus as i2c::Read>::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.with_speed(|bus| bus.read(addr, buffer))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::WriteRead for SpeedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::WriteRead,
+    F: FnMut(&mut M::Bus, u32),
+{
+    type Error = <M::Bus as i2c::WriteRead>::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.with_speed(|bus| bus.write_read(addr, buffer_in, buffer_out))
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, F> i2c_eh1::ErrorType for SpeedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = <M::Bus as i2c_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, F, A: i2c_eh1::AddressMode> i2c_eh1::I2c<A> for SpeedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c_eh1::I2c<A>,
+    F: FnMut(&mut M::Bus, u32),
+{
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        self.with_speed(|bus| bus.transaction(address, operations))
+    }
+}
+
+/// Proxy type for I2C bus sharing which coalesces consecutive writes to one register.
+///
+/// Some devices, like displays, take a stream of data through a single data register.  Writing
+/// each small update separately means a bus transaction (and locking the bus) every time.  The
+/// `BufferedI2cProxy` instead collects the data of consecutive writes to its register in a
+/// buffer of `N` bytes (including the register byte) and sends it as a single write:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # fn _example(i2c: impl i2c::Write) {
+/// let bus = shared_bus::BusManagerSimple::new(i2c);
+/// let mut display = bus.acquire_i2c_buffered::<32>(0x40);
+///
+/// for pixel in &[0x12, 0x34, 0x56] {
+///     // Only buffered
+///     let _ = display.write(0x3c, &[0x40, *pixel]);
+/// }
+/// // Sends [0x40, 0x12, 0x34, 0x56] in one write
+/// let _ = display.flush();
+/// # }
+/// ```
+///
+/// A write is buffered if it starts with the register byte and carries at least one data byte.
+/// The buffer is flushed
+/// - by calling [`flush()`][flush],
+/// - when it is full,
+/// - before a write to another address, and
+/// - before any other operation (a different write, a read, or an `embedded-hal` 1.0
+///   transaction), in the same bus lock as the operation.
+///
+/// So the device sees all operations of the proxy in their original order, only the buffered
+/// writes are merged.  Operations of other proxies can still happen in between a buffered write
+/// and its flush.  Errors of a flush are returned by the operation which caused it; the buffered
+/// data is discarded in that case.
+///
+/// **Note**: Buffered data is not sent when the proxy is dropped; call `flush()` first.
+///
+/// A `BufferedI2cProxy` is created by calling
+/// [`BusManager::acquire_i2c_buffered()`][acquire_i2c_buffered].
+///
+/// [flush]: #method.flush
+/// [acquire_i2c_buffered]: ./struct.BusManager.html#method.acquire_i2c_buffered
+#[cfg(feature = "i2c")]
+#[derive(Debug)]
+pub struct BufferedI2cProxy<'a, M, const N: usize> {
+    pub(crate) mutex: &'a M,
+    pub(crate) reg: u8,
+    pub(crate) addr: u8,
+    /// Number of bytes in `buf`, including the register byte, or 0 if empty.
+    pub(crate) len: usize,
+    pub(crate) buf: [u8; N],
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, const N: usize> BufferedI2cProxy<'a, M, N> {
+    /// Get the number of buffered data bytes which were not sent yet.
+    pub fn pending(&self) -> usize {
+        self.len.saturating_sub(1)
+    }
+
+    /// Send the buffered data, if any.
+    pub fn flush(&mut self) -> Result<(), <M::Bus as i2c::Write>::Error>
+    where
+        M::Bus: i2c::Write,
+    {
+        self.flushed(i2c::Write::write, |_| Ok(()))
+    }
+
+    /// Lock the bus, send the buffered data with `write`, and then run `op`.
+    fn flushed<E, R>(
+        &mut self,
+        write: impl FnOnce(&mut M::Bus, u8, &[u8]) -> Result<(), E>,
+        op: impl FnOnce(&mut M::Bus) -> Result<R, E>,
+    ) -> Result<R, E> {
+        let len = core::mem::replace(&mut self.len, 0);
+        let addr = self.addr;
+        let buf = &self.buf[..len];
+        self.mutex.lock(|bus| {
+            if len != 0 {
+                write(bus, addr, buf)?;
+            }
+            op(bus)
+        })
+    }
+
+    /// Buffer the data of a write to the register, or flush and write directly otherwise.
+    fn queue<E>(
+        &mut self,
+        addr: u8,
+        bytes: &[u8],
+        write: impl Fn(&mut M::Bus, u8, &[u8]) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let data = match bytes.split_first() {
+            Some((&reg, data)) if reg == self.reg && !data.is_empty() && data.len() < N => data,
+            _ => return self.flushed(&write, |bus| write(bus, addr, bytes)),
+        };
+
+        if self.len != 0 && (self.addr != addr || self.len + data.len() > N) {
+            self.flushed(&write, |_| Ok(()))?;
+        }
+        if self.len == 0 {
+            self.buf[0] = self.reg;
+            self.addr = addr;
+            self.len = 1;
+        }
+        self.buf[self.len..][..data.len()].copy_from_slice(data);
+        self.len += data.len();
+
+        if self.len == N {
+            self.flushed(&write, |_| Ok(()))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, const N: usize> i2c::Write for BufferedI2cProxy<'a, M, N>
+where
+    M::Bus: i2c::Write,
+{
+    type Error = <M::Bus as i2c::Write>::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.queue(addr, buffer, i2c::Write::write)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, const N: usize> i2c::Read for BufferedI2cProxy<'a, M, N>
+where
+    M::Bus: i2c::Read + i2c::Write<Error = <M::Bus as i2c::Read>::Error>,
+{
+    type Error = <M::Bus as i2c::Read>::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.flushed(i2c::Write::write, |bus| bus.read(addr, buffer))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, const N: usize> i2c::WriteRead for BufferedI2cProxy<'a, M, N>
+where
+    M::Bus: i2c::WriteRead + i2c::Write<Error = <M::Bus as i2c::WriteRead>::Error>,
+{
+    type Error = <M::Bus as i2c::WriteRead>::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.flushed(i2c::Write::write, |bus| {
+            bus.write_read(addr, buffer_in, buffer_out)
+        })
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, const N: usize> i2c_eh1::ErrorType for BufferedI2cProxy<'a, M, N>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = <M::Bus as i2c_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, const N: usize> i2c_eh1::I2c for BufferedI2cProxy<'a, M, N>
+where
+    M::Bus: i2c_eh1::I2c,
+{
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        self.flushed(i2c_eh1::I2c::write, |bus| {
+            bus.transaction(address, operations)
+        })
+    }
+}
+
+/// Longest write part of a `write_read()` which the [`ChunkedI2cProxy`] can adjust per chunk.
+#[cfg(feature = "i2c")]
+const MAX_CHUNK_PREFIX: usize = 4;
+
+/// Proxy type for I2C bus sharing which splits long transfers into several shorter ones.
+///
+/// Some HALs limit the length of a single transfer (e.g. to 255 bytes).  The `ChunkedI2cProxy`
+/// works just like the [`I2cProxy`] but splits reads and writes longer than `max_len` into
+/// chunks of at most `max_len` bytes.  Each chunk is a transfer of its own, locking the bus
+/// separately.
+///
+/// For every chunk after the first one, the `next_address` closure gets the offset of the chunk
+/// in the buffer, the device address, and the write part of the original `write_read()` (e.g.
+/// the memory address of an EEPROM), and adjusts the latter two to point at the chunk.  For an
+/// EEPROM with 16-bit memory addresses:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # fn _example(i2c: impl i2c::WriteRead) {
+/// let bus = shared_bus::BusManagerSimple::new(i2c);
+/// let mut eeprom = bus.acquire_i2c_chunked(255, |offset, _addr: &mut u8, mem: &mut [u8]| {
+///     let start = u16::from_be_bytes([mem[0], mem[1]]) as usize + offset;
+///     mem.copy_from_slice(&(start as u16).to_be_bytes());
+/// });
+///
+/// // Read the whole 4 KiB in chunks of 255 bytes
+/// let mut contents = [0u8; 4096];
+/// let _ = eeprom.write_read(0x50, &[0x00, 0x00], &mut contents);
+/// # }
+/// ```
+///
+/// Plain reads and writes are split the same way, with an empty write part, so `next_address`
+/// can only adjust the device address for them.  The write part of a chunked `write_read()` may
+/// be at most 4 bytes long, longer ones are passed on unchanged without splitting the transfer.
+///
+/// Of the `embedded-hal` 1.0 transactions, those consisting of a single read or write, or of a
+/// write followed by a read, are split like the respective methods.  Other transactions are passed
+/// on unchanged.
+///
+/// A `ChunkedI2cProxy` is created by calling
+/// [`BusManager::acquire_i2c_chunked()`][acquire_i2c_chunked].
+///
+/// [`I2cProxy`]: ./struct.I2cProxy.html
+/// [acquire_i2c_chunked]: ./struct.BusManager.html#method.acquire_i2c_chunked
+#[cfg(feature = "i2c")]
+#[derive(Debug)]
+pub struct ChunkedI2cProxy<'a, M, F> {
+    pub(crate) mutex: &'a M,
+    pub(crate) max_len: usize,
+    pub(crate) next_address: F,
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F: Clone> Clone for ChunkedI2cProxy<'a, M, F> {
+    fn clone(&self) -> Self {
+        Self {
+            mutex: self.mutex,
+            max_len: self.max_len,
+            next_address: self.next_address.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F: FnMut(usize, &mut u8, &mut [u8])> ChunkedI2cProxy<'a, M, F> {
+    /// Perform `op` for each chunk of `buffer`, with the address and write part adjusted.
+    fn chunked<E>(
+        &mut self,
+        addr: u8,
+        prefix: &[u8],
+        buffer: &mut [u8],
+        mut op: impl FnMut(&mut M::Bus, u8, &[u8], &mut [u8]) -> Result<(), E>,
+    ) -> Result<(), E> {
+        if buffer.len() <= self.max_len || prefix.len() > MAX_CHUNK_PREFIX {
+            return self.mutex.lock(|bus| op(bus, addr, prefix, buffer));
+        }
+
+        for (i, chunk) in buffer.chunks_mut(self.max_len).enumerate() {
+            let mut chunk_addr = addr;
+            let mut chunk_prefix = [0u8; MAX_CHUNK_PREFIX];
+            let chunk_prefix = &mut chunk_prefix[..prefix.len()];
+            chunk_prefix.copy_from_slice(prefix);
+            if i > 0 {
+                (self.next_address)(i * self.max_len, &mut chunk_addr, chunk_prefix);
+            }
+            self.mutex
+                .lock(|bus| op(bus, chunk_addr, chunk_prefix, chunk))?;
+        }
+        Ok(())
+    }
+
+    /// Perform `op` for each chunk of `buffer`, with the address adjusted.
+    fn chunked_write<E>(
+        &mut self,
+        addr: u8,
+        buffer: &[u8],
+        mut op: impl FnMut(&mut M::Bus, u8, &[u8]) -> Result<(), E>,
+    ) -> Result<(), E> {
+        if buffer.len() <= self.max_len {
+            return self.mutex.lock(|bus| op(bus, addr, buffer));
+        }
+
+        for (i, chunk) in buffer.chunks(self.max_len).enumerate() {
+            let mut chunk_addr = addr;
+            if i > 0 {
+                (self.next_address)(i * self.max_len, &mut chunk_addr, &mut []);
+            }
+            self.mutex.lock(|bus| op(bus, chunk_addr, chunk))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::Write for ChunkedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::Write,
+    F: FnMut(usize, &mut u8, &mut [u8]),
+{
+    type Error = <M::Bus as i2c::Write>::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.chunked_write(addr, buffer, |bus, addr, chunk| bus.write(addr, chunk))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::Read for ChunkedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::Read,
+    F: FnMut(usize, &mut u8, &mut [u8]),
+{
+    type Error = <M::Bus as i2c::Read>::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.chunked(addr, &[], buffer, |bus, addr, _, chunk| {
+            bus.read(addr, chunk)
+        })
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::WriteRead for ChunkedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::WriteRead,
+    F: FnMut(usize, &mut u8, &mut [u8]),
+{
+    type Error = <M::Bus as i2c::WriteRead>::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.chunked(addr, buffer_in, buffer_out, |bus, addr, prefix, chunk| {
+            bus.write_read(addr, prefix, chunk)
+        })
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, F> i2c_eh1::ErrorType for ChunkedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = <M::Bus as i2c_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, F> i2c_eh1::I2c for ChunkedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c_eh1::I2c,
+    F: FnMut(usize, &mut u8, &mut [u8]),
+{
+    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.chunked(address, &[], read, |bus, addr, _, chunk| {
+            bus.read(addr, chunk)
+        })
+    }
+
+    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        self.chunked_write(address, write, |bus, addr, chunk| bus.write(addr, chunk))
+    }
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.chunked(address, write, read, |bus, addr, prefix, chunk| {
+            bus.write_read(addr, prefix, chunk)
+        })
+    }
+
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        use i2c_eh1::Operation;
+
+        match operations {
+            [] => Ok(()),
+            [Operation::Read(read)] => i2c_eh1::I2c::read(self, address, read),
+            [Operation::Write(write)] => i2c_eh1::I2c::write(self, address, write),
+            [Operation::Write(write), Operation::Read(read)] => {
+                i2c_eh1::I2c::write_read(self, address, write, read)
+            }
+            _ => self.mutex.lock(|bus| bus.transaction(address, operations)),
+        }
+    }
+}
+
+/// Proxy type for a device which is reachable on two buses.
+///
+/// The `FailoverI2cProxy` performs each operation on the primary bus first.  If it fails, the
+/// operation is repeated on the secondary bus.  Each attempt locks only the bus it uses, so other
+/// devices on the primary bus can use it again while the secondary bus is tried.  If both
+/// attempts fail, the error of the secondary bus is returned.
+///
+/// A `FailoverI2cProxy` is created by calling
+/// [`DualBusManager::acquire_failover_i2c()`][acquire_failover_i2c].
+///
+/// [acquire_failover_i2c]: ./struct.DualBusManager.html#method.acquire_failover_i2c
+#[cfg(feature = "i2c")]
+#[derive(Debug)]
+pub struct FailoverI2cProxy<'a, M> {
+    pub(crate) primary: &'a M,
+    pub(crate) secondary: &'a M,
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex> Clone for FailoverI2cProxy<'a, M> {
+    fn clone(&self) -> Self {
+        Self {
+            primary: self.primary,
+            secondary: self.secondary,
+        }
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex> FailoverI2cProxy<'a, M> {
+    fn failover<E>(&self, mut op: impl FnMut(&mut M::Bus) -> Result<(), E>) -> Result<(), E> {
+        match self.primary.lock(&mut op) {
+            Err(_) => self.secondary.lock(op),
+            res => res,
+        }
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex> i2c::Write for FailoverI2cProxy<'a, M>
+where
+    M::Bus: i2c::Write,
+{
+    type Error = <M::Bus as i2c::Write>::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.failover(|bus| bus.write(addr, buffer))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex> i2c::Read for FailoverI2cProxy<'a, M>
+where
+    M::Bus: i2c::Read,
+{
+    type Error = <M::Bus as i2c::Read>::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.failover(|bus| bus.read(addr, buffer))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex> i2c::WriteRead for FailoverI2cProxy<'a, M>
+where
+    M::Bus: i2c::WriteRead,
+{
+    type Error = <M::Bus as i2c::WriteRead>::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.failover(|bus| bus.write_read(addr, buffer_in, buffer_out))
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex> i2c_eh1::ErrorType for FailoverI2cProxy<'a, M>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = <M::Bus as i2c_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, A: i2c_eh1::AddressMode + Copy> i2c_eh1::I2c<A>
+    for FailoverI2cProxy<'a, M>
+where
+    M::Bus: i2c_eh1::I2c<A>,
+{
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        self.failover(|bus| bus.transaction(address, operations))
+    }
+}
+
+/// Proxy type for an I2C bus which is split into separate read and write peripherals.
+///
+/// Writes only lock the write half and reads only lock the read half of the bus, so a read and a
+/// write of different devices can happen at the same time.  A `write_read()` locks both halves,
+/// always the write half first, and then performs the write followed by the read.  As all proxies
+/// lock in the same order, this cannot deadlock.
+///
+/// As the halves are separate peripherals, a `write_read()` cannot use a repeated start
+/// condition.  It is still atomic with respect to all other users of the bus.
+///
+/// A `SplitI2cProxy` is created by calling
+/// [`SplitBusManager::acquire_i2c()`][acquire_i2c].
+///
+/// [acquire_i2c]: ./struct.SplitBusManager.html#method.acquire_i2c
+#[cfg(feature = "i2c")]
+#[derive(Debug)]
+pub struct SplitI2cProxy<'a, R, W> {
+    pub(crate) read: &'a R,
+    pub(crate) write: &'a W,
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, R: crate::BusMutex, W: crate::BusMutex> Clone for SplitI2cProxy<'a, R, W> {
+    fn clone(&self) -> Self {
+        Self {
+            read: self.read,
+            write: self.write,
+        }
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, R: crate::BusMutex, W: crate::BusMutex> i2c::Write for SplitI2cProxy<'a, R, W>
+where
+    W::Bus: i2c::Write,
+{
+    type Error = <W::Bus as i2c::Write>::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.write.lock(|bus| bus.write(addr, buffer))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, R: crate::BusMutex, W: crate::BusMutex> i2c::Read for SplitI2cProxy<'a, R, W>
+where
+    R::Bus: i2c::Read,
+{
+    type Error = <R::Bus as i2c::Read>::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.read.lock(|bus| bus.read(addr, buffer))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, R: crate::BusMutex, W: crate::BusMutex> i2c::WriteRead for SplitI2cProxy<'a, R, W>
+where
+    W::Bus: i2c::Write,
+    R::Bus: i2c::Read<Error = <W::Bus as i2c::Write>::Error>,
+{
+    type Error = <W::Bus as i2c::Write>::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let read = self.read;
+        // Always lock the write half first, see the type documentation.
+        self.write.lock(|write_bus| {
+            read.lock(|read_bus| {
+                i2c::Write::write(write_bus, addr, buffer_in)?;
+                i2c::Read::read(read_bus, addr, buffer_out)
+            })
+        })
+    }
+}
+
+/// Proxy type for an I2C bus whose writes are mirrored to a shadow bus.
+///
+/// Each operation is performed on the real bus first.  Afterwards, the data written by it is
+/// replayed on the shadow bus, whether the operation succeeded or not.  Reads are only performed
+/// on the real bus, for a `write_read()` only the write is replayed.  Errors of the shadow bus are
+/// ignored, the result of an operation is always the one of the real bus.
+///
+/// Both happen while holding the lock, so the shadow bus sees the writes in the same order as the
+/// real bus.
+///
+/// A `TeeI2cProxy` is created by calling [`TeeBusManager::acquire_i2c()`][acquire_i2c].
+///
+/// [acquire_i2c]: ./struct.TeeBusManager.html#method.acquire_i2c
+#[cfg(feature = "i2c")]
+#[derive(Debug)]
+pub struct TeeI2cProxy<'a, M> {
+    pub(crate) mutex: &'a M,
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex> Clone for TeeI2cProxy<'a, M> {
+    fn clone(&self) -> Self {
+        Self { mutex: self.mutex }
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex<Bus = (P, S)>, P: i2c::Write, S: i2c::Write> i2c::Write
+    for TeeI2cProxy<'a, M>
+{
+    type Error = P::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.mutex.lock(|(primary, shadow)| {
+            let res = primary.write(addr, buffer);
+            let _ = shadow.write(addr, buffer);
+            res
+        })
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex<Bus = (P, S)>, P: i2c::Read, S> i2c::Read for TeeI2cProxy<'a, M> {
+    type Error = P::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.mutex.lock(|(primary, _)| primary.read(addr, buffer))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex<Bus = (P, S)>, P: i2c::WriteRead, S: i2c::Write> i2c::WriteRead
+    for TeeI2cProxy<'a, M>
+{
+    type Error = P::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.mutex.lock(|(primary, shadow)| {
+            let res = primary.write_read(addr, buffer_in, buffer_out);
+            let _ = shadow.write(addr, buffer_in);
+            res
+        })
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex<Bus = (P, S)>, P: i2c_eh1::ErrorType, S> i2c_eh1::ErrorType
+    for TeeI2cProxy<'a, M>
+{
+    type Error = P::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex<Bus = (P, S)>, P: i2c_eh1::I2c, S: i2c_eh1::I2c> i2c_eh1::I2c
+    for TeeI2cProxy<'a, M>
+{
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.mutex.lock(|(primary, shadow)| {
+            let res = primary.transaction(address, operations);
+            for op in operations.iter() {
+                if let i2c_eh1::Operation::Write(buf) = op {
+                    let _ = shadow.write(address, buf);
+                }
+            }
+            res
+        })
+    }
+}
+
+/// Proxy type for I2C bus sharing which converts bus errors using a function.
+///
+/// The `MappedI2cProxy` works just like the [`I2cProxy`] but passes all bus errors through the
+/// `map` function, so its `Error` type is the result of that function.  This is useful for
+/// converting the bus error into a unified application error once, instead of calling
+/// `.map_err()` everywhere.
+///
+/// For the `embedded-hal` 1.0 traits, the mapped error type must implement
+/// `embedded_hal::i2c::Error`.
+///
+/// A `MappedI2cProxy` is created by calling
+/// [`BusManager::acquire_i2c_map_err()`][acquire_i2c_map_err].
+///
+/// [`I2cProxy`]: ./struct.I2cProxy.html
+/// [acquire_i2c_map_err]: ./struct.BusManager.html#method.acquire_i2c_map_err
+#[cfg(feature = "i2c")]
+#[derive(Debug)]
+pub struct MappedI2cProxy<'a, M, F> {
+    pub(crate) mutex: &'a M,
+    pub(crate) map: F,
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F: Clone> Clone for MappedI2cProxy<'a, M, F> {
+    fn clone(&self) -> Self {
+        Self {
+            mutex: self.mutex,
+            map: self.map.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F, E> i2c::Write for MappedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::Write,
+    F: Fn(<M::Bus as i2c::Write>::Error) -> E,
+{
+    type Error = E;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.mutex
+            .lock(|bus| bus.write(addr, buffer))
+            .map_err(&self.map)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F, E> i2c::Read for MappedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::Read,
+    F: Fn(<M::Bus as i2c::Read>::Error) -> E,
+{
+    type Error = E;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.mutex
+            .lock(|bus| bus.read(addr, buffer))
+            .map_err(&self.map)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F, E> i2c::WriteRead for MappedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::WriteRead,
+    F: Fn(<M::Bus as i2c::WriteRead>::Error) -> E,
+{
+    type Error = E;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.mutex
+            .lock(|bus| bus.write_read(addr, buffer_in, buffer_out))
+            .map_err(&self.map)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F, E> i2c::WriteIterRead for MappedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::WriteIterRead,
+    F: Fn(<M::Bus as i2c::WriteIterRead>::Error) -> E,
+{
+    type Error = E;
+
+    fn write_iter_read<B>(
+        &mut self,
+        address: u8,
+        bytes: B,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        self.mutex
+            .lock(|bus| bus.write_iter_read(address, bytes, buffer))
+            .map_err(&self.map)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F, E> i2c::WriteIter for MappedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::WriteIter,
+    F: Fn(<M::Bus as i2c::WriteIter>::Error) -> E,
+{
+    type Error = E;
+
+    fn write<B>(&mut self, address: u8, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        self.mutex
+            .lock(|bus| bus.write(address, bytes))
+            .map_err(&self.map)
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, F, E> i2c_eh1::ErrorType for MappedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c_eh1::ErrorType,
+    F: Fn(<M::Bus as i2c_eh1::ErrorType>::Error) -> E,
+    E: i2c_eh1::Error,
+{
+    type Error = E;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, F, E, A: i2c_eh1::AddressMode> i2c_eh1::I2c<A>
+    for MappedI2cProxy<'a, M, F>
+where
+    M::Bus: i2c_eh1::I2c<A>,
+    F: Fn(<M::Bus as i2c_eh1::ErrorType>::Error) -> E,
+    E: i2c_eh1::Error,
+{
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        self.mutex
+            .lock(|bus| bus.transaction(address, operations))
+            .map_err(&self.map)
+    }
+}
+
+/// Proxy type for I2C bus sharing on a segment of the bus.
+///
+/// The `SegmentI2cProxy` works just like the [`I2cProxy`] but calls the segment's `select` closure
+/// before each transaction, inside the same lock.  This ensures e.g. the right multiplexer
+/// channel is always selected when a device is accessed.
+///
+/// A `SegmentI2cProxy` is created by calling
+/// [`SegmentedBusManager::acquire_i2c()`][acquire_i2c].
+///
+/// [`I2cProxy`]: ./struct.I2cProxy.html
+/// [acquire_i2c]: ./struct.SegmentedBusManager.html#method.acquire_i2c
+#[cfg(feature = "i2c")]
+#[derive(Debug)]
+pub struct SegmentI2cProxy<'a, M, F> {
+    pub(crate) mutex: &'a M,
+    pub(crate) select: &'a F,
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> Clone for SegmentI2cProxy<'a, M, F> {
+    fn clone(&self) -> Self {
+        Self {
+            mutex: self.mutex,
+            select: self.select,
+        }
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::Write for SegmentI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::Write,
+    F: Fn(&mut M::Bus) -> Result<(), <M::Bus as i2c::Write>::Error>,
+{
+    type Error = <M::Bus as i2c::Write>::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.mutex.lock(|bus| {
+            (self.select)(bus)?;
+            bus.write(addr, buffer)
+        })
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::Read for SegmentI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::Read,
+    F: Fn(&mut M::Bus) -> Result<(), <M::Bus as i2c::Read>::Error>,
+{
+    type Error = <M::Bus as i2c::Read>::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.mutex.lock(|bus| {
+            (self.select)(bus)?;
+            bus.read(addr, buffer)
+        })
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::WriteRead for SegmentI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::WriteRead,
+    F: Fn(&mut M::Bus) -> Result<(), <M::Bus as i2c::WriteRead>::Error>,
+{
+    type Error = <M::Bus as i2c::WriteRead>::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.mutex.lock(|bus| {
+            (self.select)(bus)?;
+            bus.write_read(addr, buffer_in, buffer_out)
+        })
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::WriteIterRead for SegmentI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::WriteIterRead,
+    F: Fn(&mut M::Bus) -> Result<(), <M::Bus as i2c::WriteIterRead>::Error>,
+{
+    type Error = <M::Bus as i2c::WriteIterRead>::Error;
+
+    fn write_iter_read<B>(
+        &mut self,
+        address: u8,
+        bytes: B,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        self.mutex.lock(|bus| {
+            (self.select)(bus)?;
+            bus.write_iter_read(address, bytes, buffer)
+        })
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::BusMutex, F> i2c::WriteIter for SegmentI2cProxy<'a, M, F>
+where
+    M::Bus: i2c::WriteIter,
+    F: Fn(&mut M::Bus) -> Result<(), <M::Bus as i2c::WriteIter>::Error>,
+{
+    type Error = <M::Bus as i2c::WriteIter>::Error;
+
+    fn write<B>(&mut self, address: u8, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        self.mutex.lock(|bus| {
+            (self.select)(bus)?;
+            bus.write(address, bytes)
+        })
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, F> i2c_eh1::ErrorType for SegmentI2cProxy<'a, M, F>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = <M::Bus as i2c_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, F, A: i2c_eh1::AddressMode> i2c_eh1::I2c<A>
+    for SegmentI2cProxy<'a, M, F>
+where
+    M::Bus: i2c_eh1::I2c<A>,
+    F: Fn(&mut M::Bus) -> Result<(), <M::Bus as i2c_eh1::ErrorType>::Error>,
+{
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        self.mutex.lock(|bus| {
+            (self.select)(bus)?;
+            bus.transaction(address, operations)
+        })
+    }
+}
+
+// Implementations for embedded-hal 1.0
+
+/// Number of bytes written and read by a list of I2C operations.
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+fn i2c_operation_bytes(operations: &[i2c_eh1::Operation<'_>]) -> (usize, usize) {
+    operations.iter().fold((0, 0), |(w, r), op| match op {
+        i2c_eh1::Operation::Write(buf) => (w + buf.len(), r),
+        i2c_eh1::Operation::Read(buf) => (w, r + buf.len()),
+    })
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex> i2c_eh1::ErrorType for I2cProxy<'a, M>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = <M::Bus as i2c_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, A: i2c_eh1::AddressMode> i2c_eh1::I2c<A> for I2cProxy<'a, M>
+where
+    M::Bus: i2c_eh1::I2c<A>,
+{
+    fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let len = buffer.len();
+        self.count(self.mutex.lock(|bus| bus.read(address, buffer)), 0, len)
+    }
+
+    fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.count(
+            self.mutex.lock(|bus| bus.write(address, bytes)),
+            bytes.len(),
+            0,
+        )
+    }
+
+    fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let len = buffer.len();
+        let res = self
+            .mutex
+            .lock(|bus| bus.write_read(address, bytes, buffer));
+        self.count(res, bytes.len(), len)
+    }
+
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        // Some HALs emit a spurious start/stop condition for an empty transaction which may
+        // confuse other devices on the bus.  Treat it as the no-op it is supposed to be.
+        if operations.is_empty() {
+            return Ok(());
+        }
+        let (written, read) = i2c_operation_bytes(operations);
+        let res = self.mutex.lock(|bus| bus.transaction(address, operations));
+        self.count(res, written, read)
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, 'b, M: crate::BusMutex> i2c_eh1::ErrorType for &'b I2cProxy<'a, M>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = <M::Bus as i2c_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, 'b, M: crate::BusMutex, A: i2c_eh1::AddressMode> i2c_eh1::I2c<A> for &'b I2cProxy<'a, M>
+where
+    M::Bus: i2c_eh1::I2c<A>,
+{
+    fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let len = buffer.len();
+        self.count(self.mutex.lock(|bus| bus.read(address, buffer)), 0, len)
+    }
+
+    fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.count(
+            self.mutex.lock(|bus| bus.write(address, bytes)),
+            bytes.len(),
+            0,
+        )
+    }
+
+    fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let len = buffer.len();
+        let res = self
+            .mutex
+            .lock(|bus| bus.write_read(address, bytes, buffer));
+        self.count(res, bytes.len(), len)
+    }
+
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        // Some HALs emit a spurious start/stop condition for an empty transaction which may
+        // confuse other devices on the bus.  Treat it as the no-op it is supposed to be.
+        if operations.is_empty() {
+            return Ok(());
+        }
+        let (written, read) = i2c_operation_bytes(operations);
+        let res = self.mutex.lock(|bus| bus.transaction(address, operations));
+        self.count(res, written, read)
+    }
+}
+
+/// Adapter which makes an `embedded-hal` 1.0 I2C proxy usable by `embedded-hal` 0.2 drivers.
+///
+/// The `Eh0Compat` implements the 0.2 `blocking::i2c` traits on top of any proxy implementing the
+/// 1.0 `I2c` trait.  This allows using a legacy driver on a bus which only implements
+/// `embedded-hal` 1.0, without converting the driver:
+///
+/// ```
+/// # use embedded_hal_1::i2c::I2c;
+/// # fn legacy_driver(_: impl embedded_hal::blocking::i2c::Write) {}
+/// # fn _example(i2c: impl I2c) {
+/// let bus = shared_bus::BusManagerSimple::new(i2c);
+/// let proxy = bus.acquire_i2c();
+///
+/// legacy_driver(proxy.as_eh0());
+/// # }
+/// ```
+///
+/// An `Eh0Compat` is created by calling [`I2cProxy::as_eh0()`][as_eh0] or
+/// [`Eh0Compat::new()`][new] for other proxies.
+///
+/// [as_eh0]: ./struct.I2cProxy.html#method.as_eh0
+/// [new]: #method.new
+///
+/// This type is only available with the `eh1` feature.
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+#[derive(Debug, Clone)]
+pub struct Eh0Compat<P> {
+    proxy: P,
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<P> Eh0Compat<P> {
+    /// Wrap an `embedded-hal` 1.0 I2C proxy.
+    pub fn new(proxy: P) -> Self {
+        Eh0Compat { proxy }
+    }
+
+    /// Get back the wrapped proxy.
+    pub fn into_inner(self) -> P {
+        self.proxy
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::BusMutex> I2cProxy<'a, M>
+where
+    M::Bus: i2c_eh1::I2c,
+{
+    /// Get a copy of this proxy which implements the `embedded-hal` 0.2 traits.
+    ///
+    /// See [`Eh0Compat`] for details.
+    ///
+    /// [`Eh0Compat`]: ./struct.Eh0Compat.html
+    pub fn as_eh0(&self) -> Eh0Compat<Self> {
+        Eh0Compat::new(self.clone())
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<P: i2c_eh1::I2c> i2c::Write for Eh0Compat<P> {
+    type Error = P::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.proxy.write(addr, buffer)
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<P: i2c_eh1::I2c> i2c::Read for Eh0Compat<P> {
+    type Error = P::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.proxy.read(addr, buffer)
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<P: i2c_eh1::I2c> i2c::WriteRead for Eh0Compat<P> {
+    type Error = P::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.proxy.write_read(addr, buffer_in, buffer_out)
+    }
+}
+
+/// Proxy type for I2C bus sharing in async code.
+///
+/// The `AsyncI2cProxy` implements the `embedded-hal-async` I2C trait so it can be passed to
+/// async drivers instead of the bus instance.  The bus is locked for the whole duration of each
+/// operation, including all `.await` points in between.
+///
+/// An `AsyncI2cProxy` is created by calling [`AsyncBusManager::acquire_i2c()`][acquire_i2c].
+///
+/// [acquire_i2c]: ./struct.AsyncBusManager.html#method.acquire_i2c
+///
+/// This type is only available with the `async` feature.
+#[cfg(all(feature = "i2c", feature = "async"))]
+#[derive(Debug)]
+pub struct AsyncI2cProxy<'a, M> {
+    pub(crate) mutex: &'a M,
+    pub(crate) priority: u8,
+}
+
+#[cfg(all(feature = "i2c", feature = "async"))]
+impl<'a, M: crate::AsyncBusMutex> Clone for AsyncI2cProxy<'a, M> {
+    fn clone(&self) -> Self {
+        Self {
+            mutex: self.mutex,
+            priority: self.priority,
+        }
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "async"))]
+impl<'a, M: crate::AsyncBusMutex> i2c_eh1::ErrorType for AsyncI2cProxy<'a, M>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = <M::Bus as i2c_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "async"))]
+impl<'a, M: crate::AsyncBusMutex, A: i2c_eh1::AddressMode> i2c_async::I2c<A>
+    for AsyncI2cProxy<'a, M>
+where
+    M::Bus: i2c_async::I2c<A>,
+{
+    async fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.mutex
+            .lock_with_priority(self.priority)
+            .await
+            .read(address, buffer)
+            .await
+    }
+
+    async fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.mutex
+            .lock_with_priority(self.priority)
+            .await
+            .write(address, bytes)
+            .await
+    }
+
+    async fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.mutex
+            .lock_with_priority(self.priority)
+            .await
+            .write_read(address, bytes, buffer)
+            .await
+    }
+
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        self.mutex
+            .lock_with_priority(self.priority)
+            .await
+            .transaction(address, operations)
+            .await
+    }
+}
+
+/// Proxy type for SPI bus sharing.
+///
+/// The `SpiProxy` implements all (blocking) SPI traits so it can be passed to drivers instead of
+/// the bus instance.  An `SpiProxy` is created by calling [`BusManager::acquire_spi()`][acquire_spi].
+///
+/// The traits are implemented for `u8`, `u16`, and `u32` words, so e.g. a bus implementing
+/// `spi::Write<u16>` can also be shared with drivers using 16-bit words.
+///
+/// **Note**: The `SpiProxy` can only be used for sharing **withing a single task/thread**.  This
+/// is due to drivers usually managing the chip-select pin manually which would be inherently racy
+/// in a concurrent environment (because the mutex is locked only after asserting CS).  To ensure
+/// safe usage, a `SpiProxy` can only be created when using [`BusManagerSimple`] and is `!Send`.
+///
+/// For the same reason, and unlike [`I2cProxy`], the SPI traits are not implemented for
+/// `&SpiProxy`: each driver should own its proxy so the chip-select handling stays with it.
+///
+/// # Missing chip-select warning
+/// A common mistake is never toggling chip-select around the transfers of a proxy, so the device
+/// never responds.  With the `log` feature, debug builds emit a warning (once per proxy) when a
+/// proxy is used before [`mark_cs_managed()`][mark_cs_managed] was called for it.  Without the
+/// feature, `mark_cs_managed()` does nothing.
+///
+/// [acquire_spi]: ./struct.BusManager.html#method.acquire_spi
+/// [`BusManagerSimple`]: ./type.BusManagerSimple.html
+/// [`I2cProxy`]: ./struct.I2cProxy.html
+/// [mark_cs_managed]: #method.mark_cs_managed
+#[cfg(feature = "spi")]
+#[derive(Debug)]
+pub struct SpiProxy<'a, M> {
+    pub(crate) mutex: &'a M,
+    pub(crate) _u: core::marker::PhantomData<*mut ()>,
+    #[cfg(feature = "log")]
+    pub(crate) check_cs: bool,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: &'a crate::metrics::ByteCounters,
+}
+
+#[cfg(feature = "spi")]
+impl<'a, M: crate::BusMutex> Clone for SpiProxy<'a, M> {
+    fn clone(&self) -> Self {
+        Self {
+            mutex: self.mutex,
+            _u: core::marker::PhantomData,
+            #[cfg(feature = "log")]
+            check_cs: self.check_cs,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
+        }
+    }
+}
+
+#[cfg(feature = "spi")]
+impl<'a, M> SpiProxy<'a, M> {
+    /// Declare that chip-select is managed for the device using this proxy.
+    ///
+    /// This silences the missing chip-select warning emitted with the `log` feature.
+    pub fn mark_cs_managed(&mut self) {
+        #[cfg(feature = "log")]
+        {
+            self.check_cs = false;
+        }
+    }
+
+    #[inline]
+    fn check_cs(&mut self) {
+        #[cfg(all(feature = "log", debug_assertions))]
+        if self.check_cs {
+            self.check_cs = false;
+            log::warn!(
+                "SpiProxy used without chip-select management; \
+                 call mark_cs_managed() once CS is toggled around its transfers"
+            );
+        }
+    }
+
+    /// Account a successful transfer in the byte counters of the manager.
+    #[inline]
+    fn count<T, E>(&self, res: Result<T, E>, written: usize, read: usize) -> Result<T, E> {
+        #[cfg(feature = "metrics")]
+        if res.is_ok() {
+            self.metrics.add(written, read);
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = (written, read);
+        res
+    }
+}
+
+#[cfg(feature = "spi")]
+impl<'a, M: crate::TryBusMutex> SpiProxy<'a, M> {
+    /// Like [`spi::Transfer::transfer()`], but return an error if the bus cannot be locked.
+    ///
+    /// See [`TryBusMutex`](./trait.TryBusMutex.html) for details.
+    pub fn try_transfer<'w, W>(
+        &mut self,
+        words: &'w mut [W],
+    ) -> Result<&'w [W], crate::Error<<M::Bus as spi::Transfer<W>>::Error>>
+    where
+        M::Bus: spi::Transfer<W>,
+    {
+        self.check_cs();
+        let len = core::mem::size_of_val(words);
+        let res = self
+            .mutex
+            .try_lock(move |bus| spi::Transfer::transfer(bus, words))?
+            .map_err(crate::Error::Bus);
+        self.count(res, len, len)
+    }
+
+    /// Like [`spi::Write::write()`], but return an error if the bus cannot be locked.
+    ///
+    /// See [`TryBusMutex`](./trait.TryBusMutex.html) for details.
+    pub fn try_write<W>(
+        &mut self,
+        words: &[W],
+    ) -> Result<(), crate::Error<<M::Bus as spi::Write<W>>::Error>>
+    where
+        M::Bus: spi::Write<W>,
+    {
+        self.check_cs();
+        let res = self
+            .mutex
+            .try_lock(|bus| spi::Write::write(bus, words))?
+            .map_err(crate::Error::Bus);
+        self.count(res, core::mem::size_of_val(words), 0)
+    }
+}
+
+// A generic implementation over the word type would conflict with the blanket implementations in
+// `embedded_hal::blocking::spi`, so implement the traits for each common word size instead.
+#[cfg(feature = "spi")]
+macro_rules! spi_proxy_word_impls {
+    ($($word:ty),*) => {
+        $(
+            impl<'a, M: crate::BusMutex> spi::Transfer<$word> for SpiProxy<'a, M>
+            where
+                M::Bus: spi::Transfer<$word>,
+            {
+                type Error = <M::Bus as spi::Transfer<$word>>::Error;
+
+                fn transfer<'w>(
+                    &mut self,
+                    words: &'w mut [$word],
+                ) -> Result<&'w [$word], Self::Error> {
+                    self.check_cs();
+                    let len = core::mem::size_of_val(words);
+                    let res = self.mutex.lock(move |bus| bus.transfer(words));
+                    self.count(res, len, len)
+                }
+            }
+
+            impl<'a, M: crate::BusMutex> spi::Write<$word> for SpiProxy<'a, M>
+            where
+                M::Bus: spi::Write<$word>,
+            {
+                type Error = <M::Bus as spi::Write<$word>>::Error;
+
+                fn write(&mut self, words: &[$word]) -> Result<(), Self::Error> {
+                    self.check_cs();
+                    let res = self.mutex.lock(|bus| bus.write(words));
+                    self.count(res, core::mem::size_of_val(words), 0)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "spi")]
+spi_proxy_word_impls!(u8, u16, u32);
+
+/// Proxy type for sharing an SPI bus between devices, including chip-select management.
+///
+/// Unlike the [`SpiProxy`], the `OwnedSpiDeviceProxy` owns the chip-select pin of its device and
+/// implements the `embedded-hal` 1.0 [`SpiDevice`][spi-device] trait.  The chip-select pin is
+/// asserted and deasserted _inside_ the bus lock, for each transaction.  This makes it safe to
+/// share an SPI bus across tasks/threads with any mutex type: the proxy is `Send` whenever the
+/// mutex is `Sync` and the chip-select pin is `Send`.
+///
+/// An `OwnedSpiDeviceProxy` is created by calling
+/// [`BusManager::acquire_spi_device()`][acquire_spi_device] on a manager with `'static` lifetime.
+///
+/// **Note**: Transactions containing `Operation::DelayNs` need a delay source.  It must be
+/// supplied by acquiring the proxy using
+/// [`BusManager::acquire_spi_device_with_delay()`][acquire_spi_device_with_delay], otherwise such
+/// transactions fail with [`SpiDeviceError::NoDelay`] before the bus is touched.  The delay runs
+/// while chip-select is asserted and the bus is locked.
+///
+/// Chip-select is active-low by default.  For devices with an active-high chip-select, configure
+/// the proxy with [`cs_polarity()`][cs_polarity].
+///
+/// This type is only available with the `eh1` feature.
+///
+/// [cs_polarity]: #method.cs_polarity
+/// [`SpiProxy`]: ./struct.SpiProxy.html
+/// [spi-device]: https://docs.rs/embedded-hal/1/embedded_hal/spi/trait.SpiDevice.html
+/// [acquire_spi_device]: ./struct.BusManager.html#method.acquire_spi_device
+/// [acquire_spi_device_with_delay]: ./struct.BusManager.html#method.acquire_spi_device_with_delay
+/// [`SpiDeviceError::NoDelay`]: ./enum.SpiDeviceError.html#variant.NoDelay
+#[cfg(all(feature = "spi", feature = "eh1"))]
+#[derive(Debug)]
+pub struct OwnedSpiDeviceProxy<M: 'static, CS, D = NoDelay> {
+    pub(crate) mutex: &'static M,
+    pub(crate) cs: CS,
+    pub(crate) polarity: CsPolarity,
+    pub(crate) delay: Option<D>,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: &'static crate::metrics::ByteCounters,
+}
+
+/// Delay source type of an [`OwnedSpiDeviceProxy`] which was acquired without one.
+///
+/// This type has no values, the proxy just has no delay source.  Transactions containing
+/// `Operation::DelayNs` fail with [`SpiDeviceError::NoDelay`].
+///
+/// [`OwnedSpiDeviceProxy`]: ./struct.OwnedSpiDeviceProxy.html
+/// [`SpiDeviceError::NoDelay`]: ./enum.SpiDeviceError.html#variant.NoDelay
+///
+/// This type is only available with the `eh1` feature.
+#[cfg(all(feature = "spi", feature = "eh1"))]
+#[derive(Debug, Clone, Copy)]
+pub enum NoDelay {}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl embedded_hal_1::delay::DelayNs for NoDelay {
+    fn delay_ns(&mut self, _ns: u32) {
+        match *self {}
+    }
+}
+
+#[cfg(all(feature = "spi", feature = "async"))]
+impl embedded_hal_async::delay::DelayNs for NoDelay {
+    async fn delay_ns(&mut self, _ns: u32) {
+        match *self {}
+    }
+}
+
+/// Level of the chip-select pin which selects a device.
+///
+/// This type is only available with the `eh1` feature.
+#[cfg(all(feature = "spi", feature = "eh1"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CsPolarity {
+    /// The device is selected while chip-select is low.  This is what almost all devices use.
+    #[default]
+    ActiveLow,
+    /// The device is selected while chip-select is high.
+    ActiveHigh,
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl CsPolarity {
+    fn assert<CS: digital_eh1::OutputPin>(self, cs: &mut CS) -> Result<(), CS::Error> {
+        match self {
+            CsPolarity::ActiveLow => cs.set_low(),
+            CsPolarity::ActiveHigh => cs.set_high(),
+        }
+    }
+
+    fn deassert<CS: digital_eh1::OutputPin>(self, cs: &mut CS) -> Result<(), CS::Error> {
+        match self {
+            CsPolarity::ActiveLow => cs.set_high(),
+            CsPolarity::ActiveHigh => cs.set_low(),
+        }
+    }
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<M: crate::BusMutex, CS, D> OwnedSpiDeviceProxy<M, CS, D> {
+    /// Set the level of the chip-select pin which selects the device.
+    ///
+    /// The default is [`CsPolarity::ActiveLow`].  The pin is not touched until the next
+    /// transaction, so it should already be initialized to the deasserted level.
+    ///
+    /// [`CsPolarity::ActiveLow`]: ./enum.CsPolarity.html#variant.ActiveLow
+    pub fn cs_polarity(mut self, polarity: CsPolarity) -> Self {
+        self.polarity = polarity;
+        self
+    }
+
+    /// Release the chip-select pin of this proxy.
+    pub fn release(self) -> CS {
+        self.cs
+    }
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<M: crate::BusMutex, CS, D> OwnedSpiDeviceProxy<M, CS, D>
+where
+    M::Bus: spi_eh1::SpiBus,
+    CS: digital_eh1::OutputPin,
+    D: embedded_hal_1::delay::DelayNs,
+{
+    /// Write `write`, then read into `read`, while chip-select stays asserted.
+    ///
+    /// This is a shorthand for a `transaction()` consisting of an `Operation::Write` followed by
+    /// an `Operation::Read`.  Unlike `transfer()`, both buffers may have different lengths which
+    /// is what half-duplex devices usually expect, e.g. a 2 byte command followed by reading 8
+    /// bytes of data.
+    pub fn write_then_read(
+        &mut self,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), SpiDeviceError<<M::Bus as spi_eh1::ErrorType>::Error, CS::Error>> {
+        spi_eh1::SpiDevice::transaction(
+            self,
+            &mut [
+                spi_eh1::Operation::Write(write),
+                spi_eh1::Operation::Read(read),
+            ],
+        )
+    }
+
+    /// Run a closure with direct access to the bus while chip-select is asserted.
+    ///
+    /// The bus is locked, chip-select is asserted (driven low, unless the proxy was configured
+    /// as [active-high][cs_polarity]), and the closure is called.  Afterwards, the bus
+    /// is flushed and chip-select is deasserted again.  All of this happens atomically, so this is
+    /// useful for devices which need chip-select to stay asserted across several operations which
+    /// cannot be expressed as a single `transaction()`:
+    ///
+    /// ```
+    /// # use embedded_hal_1::spi::{self, SpiBus};
+    /// # use embedded_hal_1::digital::OutputPin;
+    /// # struct SomeSpiBus;
+    /// # impl spi::ErrorType for SomeSpiBus { type Error = core::convert::Infallible; }
+    /// # impl SpiBus for SomeSpiBus {
+    /// #     fn read(&mut self, _: &mut [u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn write(&mut self, _: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn transfer(&mut self, _: &mut [u8], _: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn transfer_in_place(&mut self, _: &mut [u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn flush(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    /// # }
+    /// # fn _example(spi: SomeSpiBus, cs: impl OutputPin) {
+    /// let bus: &'static _ = shared_bus::new_std!(SomeSpiBus = spi).unwrap();
+    /// let mut device = bus.acquire_spi_device(cs);
+    ///
+    /// let mut data = [0u8; 64];
+    /// let res = device.with_cs_low(|bus| {
+    ///     bus.write(&[0x03, 0x00, 0x10])?;
+    ///     for chunk in data.chunks_mut(16) {
+    ///         bus.read(chunk)?;
+    ///     }
+    ///     Ok(())
+    /// });
+    /// # let _: Result<Result<(), core::convert::Infallible>, _> = res;
+    /// # }
+    /// ```
+    ///
+    /// [cs_polarity]: #method.cs_polarity
+    pub fn with_cs_low<R, F>(
+        &mut self,
+        f: F,
+    ) -> Result<R, SpiDeviceError<<M::Bus as spi_eh1::ErrorType>::Error, CS::Error>>
+    where
+        F: FnOnce(&mut M::Bus) -> R,
+    {
+        let cs = &mut self.cs;
+        let polarity = self.polarity;
+        self.mutex.lock(|bus| {
+            polarity.assert(cs).map_err(SpiDeviceError::Cs)?;
+
+            let res = f(bus);
+
+            // The bus must be flushed before deasserting CS.
+            let flush_res = spi_eh1::SpiBus::flush(bus);
+            let cs_res = polarity.deassert(cs);
+
+            flush_res.map_err(SpiDeviceError::Spi)?;
+            cs_res.map_err(SpiDeviceError::Cs)?;
+
+            Ok(res)
+        })
+    }
+}
+
+/// Error type for the [`OwnedSpiDeviceProxy`].
+///
+/// [`OwnedSpiDeviceProxy`]: ./struct.OwnedSpiDeviceProxy.html
+///
+/// This type is only available with the `eh1` feature.
+#[cfg(all(feature = "spi", feature = "eh1"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpiDeviceError<BUS, CS> {
+    /// An error occurred on the SPI bus.
+    Spi(BUS),
+    /// An error occurred when asserting or deasserting the chip-select pin.
+    Cs(CS),
+    /// The transaction contains an `Operation::DelayNs`, but the proxy has no delay source.
+    NoDelay,
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<BUS: spi_eh1::Error, CS: core::fmt::Debug> spi_eh1::Error for SpiDeviceError<BUS, CS> {
+    fn kind(&self) -> spi_eh1::ErrorKind {
+        match self {
+            SpiDeviceError::Spi(e) => e.kind(),
+            SpiDeviceError::Cs(_) => spi_eh1::ErrorKind::ChipSelectFault,
+            SpiDeviceError::NoDelay => spi_eh1::ErrorKind::Other,
+        }
+    }
+}
+
+/// Check that a delay source is available if `operations` need one.
+#[cfg(all(feature = "spi", feature = "eh1"))]
+fn check_delay<D, W, BUS, CS>(
+    delay: &Option<D>,
+    operations: &[spi_eh1::Operation<'_, W>],
+) -> Result<(), SpiDeviceError<BUS, CS>> {
+    let needs_delay = operations
+        .iter()
+        .any(|op| matches!(op, spi_eh1::Operation::DelayNs(_)));
+    if needs_delay && delay.is_none() {
+        return Err(SpiDeviceError::NoDelay);
+    }
+    Ok(())
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<M: crate::BusMutex, CS, D> spi_eh1::ErrorType for OwnedSpiDeviceProxy<M, CS, D>
+where
+    M::Bus: spi_eh1::ErrorType,
+    CS: digital_eh1::OutputPin,
+{
+    type Error = SpiDeviceError<<M::Bus as spi_eh1::ErrorType>::Error, CS::Error>;
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<M: crate::BusMutex, CS, D, W: Copy + 'static> spi_eh1::SpiDevice<W>
+    for OwnedSpiDeviceProxy<M, CS, D>
+where
+    M::Bus: spi_eh1::SpiBus<W>,
+    CS: digital_eh1::OutputPin,
+    D: embedded_hal_1::delay::DelayNs,
+{
+    fn transaction(
+        &mut self,
+        operations: &mut [spi_eh1::Operation<'_, W>],
+    ) -> Result<(), Self::Error> {
+        use spi_eh1::SpiBus;
+
+        #[cfg(feature = "metrics")]
+        let (written, read) = operations.iter().fold((0, 0), |(w, r), op| match op {
+            spi_eh1::Operation::Read(buf) => (w, r + core::mem::size_of_val(*buf)),
+            spi_eh1::Operation::Write(buf) => (w + core::mem::size_of_val(*buf), r),
+            spi_eh1::Operation::Transfer(read, write) => (
+                w + core::mem::size_of_val(*write),
+                r + core::mem::size_of_val(*read),
+            ),
+            spi_eh1::Operation::TransferInPlace(buf) => {
+                let len = core::mem::size_of_val(*buf);
+                (w + len, r + len)
+            }
+            spi_eh1::Operation::DelayNs(_) => (w, r),
+        });
+
+        check_delay(&self.delay, operations)?;
+
+        let cs = &mut self.cs;
+        let polarity = self.polarity;
+        let delay = &mut self.delay;
+        let res = self.mutex.lock(|bus| {
+            polarity.assert(cs).map_err(SpiDeviceError::Cs)?;
+
+            let op_res = operations.iter_mut().try_for_each(|op| match op {
+                spi_eh1::Operation::Read(buf) => bus.read(buf),
+                spi_eh1::Operation::Write(buf) => bus.write(buf),
+                spi_eh1::Operation::Transfer(read, write) => bus.transfer(read, write),
+                spi_eh1::Operation::TransferInPlace(buf) => bus.transfer_in_place(buf),
+                spi_eh1::Operation::DelayNs(ns) => {
+                    // Pending transfers must be finished before the delay starts.
+                    bus.flush()?;
+                    if let Some(delay) = delay {
+                        delay.delay_ns(*ns);
+                    }
+                    Ok(())
+                }
+            });
+
+            // The bus must be flushed before deasserting CS, even if an operation failed.
+            let flush_res = bus.flush();
+            let cs_res = polarity.deassert(cs);
+
+            op_res.map_err(SpiDeviceError::Spi)?;
+            flush_res.map_err(SpiDeviceError::Spi)?;
+            cs_res.map_err(SpiDeviceError::Cs)?;
+
+            Ok(())
+        });
+
+        #[cfg(feature = "metrics")]
+        if res.is_ok() {
+            self.metrics.add(written, read);
+        }
+        res
+    }
+}
+
+/// SPI bus wrapper carrying a scratch buffer for staging transfers.
+///
+/// The `ScratchBus` lives inside the bus mutex together with the bus, so the scratch buffer is
+/// protected by the same lock.  It is created by [`BusManager::with_scratch()`][with_scratch]
+/// and implements the `embedded-hal` 1.0 `SpiBus` trait by forwarding to the wrapped bus, so all
+/// SPI proxies work as usual.  Additionally, devices acquired using
+/// [`BusManager::acquire_spi_device()`][acquire_spi_device] can borrow the buffer by calling
+/// [`OwnedSpiDeviceProxy::with_scratch()`][proxy_with_scratch].
+///
+/// # Reentrancy
+/// The scratch buffer is shared by all devices on the bus.  Its contents are not preserved
+/// between two calls, so every user must initialize the part of the buffer it uses.  While a
+/// closure has access to the scratch buffer, the bus is locked; using another proxy of the same
+/// manager from within the closure will therefore deadlock or panic (depending on the mutex
+/// type), just like for any other nested bus access.
+///
+/// [with_scratch]: ./struct.BusManager.html#method.with_scratch
+/// [acquire_spi_device]: ./struct.BusManager.html#method.acquire_spi_device
+/// [proxy_with_scratch]: ./struct.OwnedSpiDeviceProxy.html#method.with_scratch
+///
+/// This type is only available with the `eh1` feature.
+#[cfg(all(feature = "spi", feature = "eh1"))]
+#[derive(Debug)]
+pub struct ScratchBus<B, const N: usize> {
+    bus: B,
+    scratch: [u8; N],
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<B, const N: usize> ScratchBus<B, N> {
+    /// Wrap `bus` and attach a zeroed scratch buffer of `N` bytes.
+    pub fn new(bus: B) -> Self {
+        ScratchBus {
+            bus,
+            scratch: [0; N],
+        }
+    }
+
+    /// Get back the wrapped bus.
+    pub fn into_inner(self) -> B {
+        self.bus
+    }
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<B: spi_eh1::ErrorType, const N: usize> spi_eh1::ErrorType for ScratchBus<B, N> {
+    type Error = B::Error;
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<B: spi_eh1::SpiBus<W>, W: Copy + 'static, const N: usize> spi_eh1::SpiBus<W>
+    for ScratchBus<B, N>
+{
+    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.bus.read(words)
+    }
+
+    fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        self.bus.write(words)
+    }
+
+    fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        self.bus.transfer(read, write)
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.bus.transfer_in_place(words)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.bus.flush()
+    }
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<M, CS, D, B, const N: usize> OwnedSpiDeviceProxy<M, CS, D>
+where
+    M: crate::BusMutex<Bus = ScratchBus<B, N>>,
+    B: spi_eh1::SpiBus,
+    CS: digital_eh1::OutputPin,
+    D: embedded_hal_1::delay::DelayNs,
+{
+    /// Run a closure with access to the bus and the scratch buffer while chip-select is asserted.
+    ///
+    /// This works like [`with_cs_low()`][with_cs_low], but the closure also receives the scratch
+    /// buffer of a bus created with [`BusManager::with_scratch()`][with_scratch].  The buffer
+    /// contents are left over from the last user, see the [`ScratchBus`] documentation for the
+    /// constraints.
+    ///
+    /// [with_cs_low]: #method.with_cs_low
+    /// [with_scratch]: ./struct.BusManager.html#method.with_scratch
+    /// [`ScratchBus`]: ./struct.ScratchBus.html
+    pub fn with_scratch<R, F>(&mut self, f: F) -> Result<R, SpiDeviceError<B::Error, CS::Error>>
+    where
+        F: FnOnce(&mut B, &mut [u8; N]) -> R,
+    {
+        self.with_cs_low(|bus| f(&mut bus.bus, &mut bus.scratch))
+    }
+}
+
+/// Proxy type for sharing an SPI bus between async devices, including chip-select management.
+///
+/// This is the async counterpart to the [`OwnedSpiDeviceProxy`].  It implements the
+/// `embedded-hal-async` [`SpiDevice`][spi-device] trait and owns the chip-select pin of its
+/// device.
+///
+/// The bus stays locked for the entire transaction, including all `.await` points.  This is
+/// essential for DMA-backed buses:  The lock is only released after the DMA transfer has
+/// completed, so another device's transfer can never corrupt a transfer which is still in flight.
+///
+/// Transactions are cancel-safe:  When a transaction future is dropped before completing, CS is
+/// deasserted and the bus is released again.  Note that a DMA transfer which was cancelled this
+/// way might still be running, it is up to the bus implementation to abort it.
+///
+/// Just like for the `OwnedSpiDeviceProxy`, transactions containing `Operation::DelayNs` need a
+/// delay source, which is supplied by acquiring the proxy using
+/// [`AsyncBusManager::acquire_spi_device_with_delay()`][acquire_spi_device_with_delay].
+/// Otherwise, such transactions fail with [`SpiDeviceError::NoDelay`] before the bus is locked.
+///
+/// An `AsyncSpiDeviceProxy` is created by calling
+/// [`AsyncBusManager::acquire_spi_device()`][acquire_spi_device].
+///
+/// [`OwnedSpiDeviceProxy`]: ./struct.OwnedSpiDeviceProxy.html
+/// [spi-device]: https://docs.rs/embedded-hal-async/1/embedded_hal_async/spi/trait.SpiDevice.html
+/// [acquire_spi_device]: ./struct.AsyncBusManager.html#method.acquire_spi_device
+/// [acquire_spi_device_with_delay]: ./struct.AsyncBusManager.html#method.acquire_spi_device_with_delay
+/// [`SpiDeviceError::NoDelay`]: ./enum.SpiDeviceError.html#variant.NoDelay
+///
+/// This type is only available with the `async` feature.
+#[cfg(all(feature = "spi", feature = "async"))]
+#[derive(Debug)]
+pub struct AsyncSpiDeviceProxy<'a, M, CS, D = NoDelay> {
+    pub(crate) mutex: &'a M,
+    pub(crate) cs: CS,
+    pub(crate) delay: Option<D>,
+}
+
+#[cfg(all(feature = "spi", feature = "async"))]
+impl<'a, M: crate::AsyncBusMutex, CS, D> AsyncSpiDeviceProxy<'a, M, CS, D> {
+    /// Release the chip-select pin of this proxy.
+    pub fn release(self) -> CS {
+        self.cs
+    }
+}
+
+#[cfg(all(feature = "spi", feature = "async"))]
+impl<'a, M: crate::AsyncBusMutex, CS, D> spi_eh1::ErrorType for AsyncSpiDeviceProxy<'a, M, CS, D>
+where
+    M::Bus: spi_eh1::ErrorType,
+    CS: digital_eh1::OutputPin,
+{
+    type Error = SpiDeviceError<<M::Bus as spi_eh1::ErrorType>::Error, CS::Error>;
+}
+
+#[cfg(all(feature = "spi", feature = "async"))]
+impl<'a, M: crate::AsyncBusMutex, CS, D, W: Copy + 'static> spi_async::SpiDevice<W>
+    for AsyncSpiDeviceProxy<'a, M, CS, D>
+where
+    M::Bus: spi_async::SpiBus<W>,
+    CS: digital_eh1::OutputPin,
+    D: embedded_hal_async::delay::DelayNs,
+{
+    async fn transaction(
+        &mut self,
+        operations: &mut [spi_eh1::Operation<'_, W>],
+    ) -> Result<(), Self::Error> {
+        async_spi_transaction(self.mutex, &mut self.cs, self.delay.as_mut(), operations).await
+    }
+}
+
+/// Run an async SPI transaction while holding the bus lock, managing chip-select.
+///
+/// `delay` is used for `Operation::DelayNs`.
+#[cfg(all(feature = "spi", feature = "async"))]
+async fn async_spi_transaction<M, CS, D, W>(
+    mutex: &M,
+    cs: &mut CS,
+    mut delay: Option<&mut D>,
+    operations: &mut [spi_eh1::Operation<'_, W>],
+) -> Result<(), SpiDeviceError<<M::Bus as spi_eh1::ErrorType>::Error, CS::Error>>
+where
+    M: crate::AsyncBusMutex,
+    M::Bus: spi_async::SpiBus<W>,
+    CS: digital_eh1::OutputPin,
+    D: embedded_hal_async::delay::DelayNs,
+    W: Copy + 'static,
+{
+    use spi_async::SpiBus;
+
+    check_delay(&delay, operations)?;
+
+    // The guard is held until the end of this function, across all awaited bus operations.
+    let mut bus = mutex.lock().await;
+    let bus = &mut *bus;
+
+    cs.set_low().map_err(SpiDeviceError::Cs)?;
+    // Deasserts CS if the transaction is cancelled.  Being declared after the bus guard, it is
+    // dropped before the bus is released.
+    let cs = CancelCs(Some(cs));
+
+    let mut op_res = Ok(());
+    for op in operations.iter_mut() {
+        op_res = match op {
+            spi_eh1::Operation::Read(buf) => bus.read(buf).await,
+            spi_eh1::Operation::Write(buf) => bus.write(buf).await,
+            spi_eh1::Operation::Transfer(read, write) => bus.transfer(read, write).await,
+            spi_eh1::Operation::TransferInPlace(buf) => bus.transfer_in_place(buf).await,
+            spi_eh1::Operation::DelayNs(ns) => {
+                // Pending transfers must be finished before the delay starts.
+                let res = bus.flush().await;
+                if let (Ok(()), Some(delay)) = (&res, delay.as_mut()) {
+                    delay.delay_ns(*ns).await;
+                }
+                res
+            }
+        };
+        if op_res.is_err() {
+            break;
+        }
+    }
+
+    // The bus must be flushed before deasserting CS, even if an operation failed.
+    let flush_res = bus.flush().await;
+    let cs_res = cs.finish();
+
+    op_res.map_err(SpiDeviceError::Spi)?;
+    flush_res.map_err(SpiDeviceError::Spi)?;
+    cs_res.map_err(SpiDeviceError::Cs)?;
+
+    Ok(())
+}
+
+/// Chip-select pin which is deasserted when an async transaction is dropped before completing.
+#[cfg(all(feature = "spi", feature = "async"))]
+struct CancelCs<'c, CS: digital_eh1::OutputPin>(Option<&'c mut CS>);
+
+#[cfg(all(feature = "spi", feature = "async"))]
+impl<'c, CS: digital_eh1::OutputPin> CancelCs<'c, CS> {
+    /// Deassert CS at the regular end of the transaction.
+    fn finish(mut self) -> Result<(), CS::Error> {
+        self.0.take().map_or(Ok(()), |cs| cs.set_high())
+    }
+}
+
+#[cfg(all(feature = "spi", feature = "async"))]
+impl<'c, CS: digital_eh1::OutputPin> Drop for CancelCs<'c, CS> {
+    fn drop(&mut self) {
+        if let Some(cs) = self.0.take() {
+            // Nobody is left to report an error to.
+            let _ = cs.set_high();
+        }
+    }
+}
+
+/// Retry policy of an [`AsyncRetryingSpiDeviceProxy`].
+///
+/// A failed transaction is attempted up to `max_attempts` times in total.  Between two attempts,
+/// the proxy waits for `backoff_ns` nanoseconds using the async `delay` source.
+///
+/// [`AsyncRetryingSpiDeviceProxy`]: ./struct.AsyncRetryingSpiDeviceProxy.html
+///
+/// This type is only available with the `async` feature.
+#[cfg(all(feature = "spi", feature = "async"))]
+#[derive(Debug, Clone)]
+pub struct RetryPolicy<D> {
+    pub(crate) max_attempts: usize,
+    pub(crate) backoff_ns: u32,
+    pub(crate) delay: D,
+}
+
+#[cfg(all(feature = "spi", feature = "async"))]
+impl<D: embedded_hal_async::delay::DelayNs> RetryPolicy<D> {
+    /// Create a new retry policy.
+    ///
+    /// A `max_attempts` of 0 is treated like 1, i.e. no retries.
+    pub fn new(max_attempts: usize, backoff_ns: u32, delay: D) -> Self {
+        RetryPolicy {
+            max_attempts,
+            backoff_ns,
+            delay,
+        }
+    }
+}
+
+/// Proxy type for sharing an SPI bus between async devices which retries failed transactions.
+///
+/// The `AsyncRetryingSpiDeviceProxy` works just like the [`AsyncSpiDeviceProxy`] but when a
+/// transaction fails with a bus error, it is repeated according to its [`RetryPolicy`].  This is
+/// useful for devices like radios which occasionally report transient errors.
+///
+/// Each attempt locks the bus anew.  During the backoff delay the bus is released, so other
+/// devices can use it in the meantime.  Errors of the chip-select pin are not retried.  The delay
+/// source of the policy is also used for `Operation::DelayNs` in transactions.
+///
+/// **Note**: The operations of a failed transaction are repeated as they are.  Transactions
+/// containing an `Operation::TransferInPlace` are never retried, because the failed attempt might
+/// already have overwritten the data to send.  For those, the first error is returned.
+///
+/// An `AsyncRetryingSpiDeviceProxy` is created by calling
+/// [`AsyncBusManager::acquire_spi_device_retrying()`][acquire_spi_device_retrying].
+///
+/// [`AsyncSpiDeviceProxy`]: ./struct.AsyncSpiDeviceProxy.html
+/// [`RetryPolicy`]: ./struct.RetryPolicy.html
+/// [acquire_spi_device_retrying]: ./struct.AsyncBusManager.html#method.acquire_spi_device_retrying
+///
+/// This type is only available with the `async` feature.
+#[cfg(all(feature = "spi", feature = "async"))]
+#[derive(Debug)]
+pub struct AsyncRetryingSpiDeviceProxy<'a, M, CS, D> {
+    pub(crate) device: AsyncSpiDeviceProxy<'a, M, CS>,
+    pub(crate) policy: RetryPolicy<D>,
+}
+
+#[cfg(all(feature = "spi", feature = "async"))]
+impl<'a, M: crate::AsyncBusMutex, CS, D> AsyncRetryingSpiDeviceProxy<'a, M, CS, D> {
+    /// Release the chip-select pin and the delay source of this proxy.
+    pub fn release(self) -> (CS, D) {
+        (self.device.release(), self.policy.delay)
+    }
+}
+
+#[cfg(all(feature = "spi", feature = "async"))]
+impl<'a, M: crate::AsyncBusMutex, CS, D> spi_eh1::ErrorType
+    for AsyncRetryingSpiDeviceProxy<'a, M, CS, D>
+where
+    M::Bus: spi_eh1::ErrorType,
+    CS: digital_eh1::OutputPin,
+{
+    type Error = SpiDeviceError<<M::Bus as spi_eh1::ErrorType>::Error, CS::Error>;
+}
+
+#[cfg(all(feature = "spi", feature = "async"))]
+impl<'a, M: crate::AsyncBusMutex, CS, D, W: Copy + 'static> spi_async::SpiDevice<W>
+    for AsyncRetryingSpiDeviceProxy<'a, M, CS, D>
+where
+    M::Bus: spi_async::SpiBus<W>,
+    CS: digital_eh1::OutputPin,
+    D: embedded_hal_async::delay::DelayNs,
+{
+    async fn transaction(
+        &mut self,
+        operations: &mut [spi_eh1::Operation<'_, W>],
+    ) -> Result<(), Self::Error> {
+        let retryable = !operations
+            .iter()
+            .any(|op| matches!(op, spi_eh1::Operation::TransferInPlace(_)));
+
+        let mut attempts = 1;
+        loop {
+            // The bus lock is only held by the inner transaction, not during the backoff.
+            let device = &mut self.device;
+            let delay = Some(&mut self.policy.delay);
+            match async_spi_transaction(device.mutex, &mut device.cs, delay, operations).await {
+                Err(SpiDeviceError::Spi(_)) if retryable && attempts < self.policy.max_attempts => {
+                    attempts += 1;
+                    self.policy.delay.delay_ns(self.policy.backoff_ns).await;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+/// Proxy type for ADC sharing.
+///
+/// The `AdcProxy` implements OneShot trait so it can be passed to drivers instead of
+/// ADC instance. Internally, it holds reference to the bus via a mutex, ensuring
+/// that all accesses are strictly synchronized.
+///
+/// An `AdcProxy` is created by calling [`BusManager::acquire_adc()`][acquire_adc].
+///
+/// **Note**: The [`adc::OneShot`] trait proxied by this type describes a
+/// non-blocking contract for ADC read operation.  However access to a shared ADC
+/// unit can not be arbitrated in a completely non-blocking and concurrency safe way.
+/// Any reading from a channel shall be completed before `shared-bus` can allow the
+/// next read from the same or another channel. So the current implementation breaks
+/// the non-blocking contract of the trait and just busy-spins until a sample is
+/// returned.
+///
+/// Just like for the [`I2cProxy`], the `OneShot` trait is also implemented for `&AdcProxy`.
+///
+/// [acquire_adc]: ./struct.BusManager.html#method.acquire_adc
+/// [`I2cProxy`]: ./struct.I2cProxy.html
+#[cfg(feature = "adc")]
+#[derive(Debug)]
+pub struct AdcProxy<'a, M> {
+    pub(crate) mutex: &'a M,
+}
+
+#[cfg(feature = "adc")]
+impl<'a, M: crate::BusMutex> Clone for AdcProxy<'a, M> {
+    fn clone(&self) -> Self {
+        Self { mutex: self.mutex }
+    }
+}
+
+#[cfg(feature = "adc")]
+impl<'a, M: crate::BusMutex, ADC, Word, Pin> adc::OneShot<ADC, Word, Pin> for AdcProxy<'a, M>
+where
+    Pin: adc::Channel<ADC>,
+    M::Bus: adc::OneShot<ADC, Word, Pin>,
+{
+    type Error = <M::Bus as adc::OneShot<ADC, Word, Pin>>::Error;
+
+    fn read(&mut self, pin: &mut Pin) -> nb::Result<Word, Self::Error> {
+        self.mutex
+            .lock(|bus| nb::block!(bus.read(pin)).map_err(nb::Error::Other))
+    }
+}
+
+#[cfg(feature = "adc")]
+impl<'a, 'b, M: crate::BusMutex, ADC, Word, Pin> adc::OneShot<ADC, Word, Pin>
+    for &'b AdcProxy<'a, M>
+where
+    Pin: adc::Channel<ADC>,
+    M::Bus: adc::OneShot<ADC, Word, Pin>,
+{
+    type Error = <M::Bus as adc::OneShot<ADC, Word, Pin>>::Error;
+
+    fn read(&mut self, pin: &mut Pin) -> nb::Result<Word, Self::Error> {
+        self.mutex
+            .lock(|bus| nb::block!(bus.read(pin)).map_err(nb::Error::Other))
+    }
+}
+
+/// Proxy type for sharing a timer used as a delay source.
+///
+/// The `DelayProxy` implements the delay traits so it can be passed to drivers which need a
+/// delay, while the timer itself is shared through a bus manager of its own.  Each delay locks
+/// the timer for its whole duration, so delays of different proxies are performed one after
+/// the other:  A driver may have to wait for another driver's delay to finish before its own
+/// starts.  Keep this in mind for time-critical drivers.
+///
+/// A `DelayProxy` is created by calling [`BusManager::acquire_delay()`][acquire_delay], or
+/// together with a bus proxy by calling
+/// [`BusManager::acquire_i2c_with_delay()`][acquire_i2c_with_delay].
+///
+/// [acquire_delay]: ./struct.BusManager.html#method.acquire_delay
+/// [acquire_i2c_with_delay]: ./struct.BusManager.html#method.acquire_i2c_with_delay
+#[derive(Debug)]
+pub struct DelayProxy<'a, M> {
+    pub(crate) mutex: &'a M,
+}
+
+impl<'a, M: crate::BusMutex> Clone for DelayProxy<'a, M> {
+    fn clone(&self) -> Self {
+        Self { mutex: self.mutex }
+    }
+}
+
+impl<'a, M: crate::BusMutex, UXX> delay::DelayUs<UXX> for DelayProxy<'a, M>
+where
+    M::Bus: delay::DelayUs<UXX>,
+{
+    fn delay_us(&mut self, us: UXX) {
+        self.mutex.lock(|timer| timer.delay_us(us))
+    }
+}
+
+impl<'a, M: crate::BusMutex, UXX> delay::DelayMs<UXX> for DelayProxy<'a, M>
+where
+    M::Bus: delay::DelayMs<UXX>,
+{
+    fn delay_ms(&mut self, ms: UXX) {
+        self.mutex.lock(|timer| timer.delay_ms(ms))
+    }
+}
+
+#[cfg(feature = "eh1")]
+impl<'a, M: crate::BusMutex> delay_eh1::DelayNs for DelayProxy<'a, M>
+where
+    M::Bus: delay_eh1::DelayNs,
+{
+    fn delay_ns(&mut self, ns: u32) {
+        self.mutex.lock(|timer| timer.delay_ns(ns))
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.mutex.lock(|timer| timer.delay_us(us))
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.mutex.lock(|timer| timer.delay_ms(ms))
+    }
+}
+
+/// Proxy type for sharing an ADC between async tasks, bound to one channel.
+///
+/// `embedded-hal-async` does not define an ADC trait (yet), so this proxy builds upon the
+/// non-blocking `embedded-hal` 0.2 [`adc::OneShot`] trait instead:  [`read()`][read] starts a
+/// conversion on the proxy's channel and, while the ADC reports that it is still busy, yields to
+/// the executor instead of busy-spinning.  The ADC stays locked until the conversion is complete,
+/// so conversions of different channels can never interfere.
+///
+/// An `AsyncAdcProxy` is created by calling [`AsyncBusManager::acquire_adc()`][acquire_adc].
+///
+/// [read]: #method.read
+/// [acquire_adc]: ./struct.AsyncBusManager.html#method.acquire_adc
+///
+/// This type is only available with the `async` feature.
+#[cfg(all(feature = "adc", feature = "async"))]
+#[derive(Debug)]
+pub struct AsyncAdcProxy<'a, M, Pin> {
+    pub(crate) mutex: &'a M,
+    pub(crate) pin: Pin,
+}
+
+#[cfg(all(feature = "adc", feature = "async"))]
+impl<'a, M: crate::AsyncBusMutex, Pin> AsyncAdcProxy<'a, M, Pin> {
+    /// Sample the channel of this proxy.
+    pub async fn read<ADC, Word>(
+        &mut self,
+    ) -> Result<Word, <M::Bus as adc::OneShot<ADC, Word, Pin>>::Error>
+    where
+        Pin: adc::Channel<ADC>,
+        M::Bus: adc::OneShot<ADC, Word, Pin>,
+    {
+        let mut bus = self.mutex.lock().await;
+        let pin = &mut self.pin;
+        core::future::poll_fn(|cx| match adc::OneShot::read(&mut *bus, pin) {
+            Ok(word) => core::task::Poll::Ready(Ok(word)),
+            Err(nb::Error::Other(e)) => core::task::Poll::Ready(Err(e)),
+            Err(nb::Error::WouldBlock) => {
+                cx.waker().wake_by_ref();
+                core::task::Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Release the channel of this proxy.
+    pub fn release(self) -> Pin {
+        self.pin
+    }
+}
+
+/// Exclusive access to the bus for the duration of [`BusManager::lock_exclusive()`][lock_exclusive].
+///
+/// The `ExclusiveBus` implements the same bus traits as the bus it wraps, so existing driver code
+/// can run on it unchanged.  As the bus lock is held for as long as the `ExclusiveBus` exists, no
+/// proxy can access the bus between two calls of the driver.
+///
+/// [lock_exclusive]: ./struct.BusManager.html#method.lock_exclusive
+#[derive(Debug)]
+pub struct ExclusiveBus<'a, B> {
+    pub(crate) bus: &'a mut B,
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, B: i2c::Write> i2c::Write for ExclusiveBus<'a, B> {
+    type Error = B::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.bus.write(addr, buffer)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, B: i2c::Read> i2c::Read for ExclusiveBus<'a, B> {
+    type Error = B::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.read(addr, buffer)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, B: i2c::WriteRead> i2c::WriteRead for ExclusiveBus<'a, B> {
+    type Error = B::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.bus.write_read(addr, buffer_in, buffer_out)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, B: i2c::WriteIterRead> i2c::WriteIterRead for ExclusiveBus<'a, B> {
+    type Error = B::Error;
+
+    fn write_iter_read<I>(
+        &mut self,
+        address: u8,
+        bytes: I,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        self.bus.write_iter_read(address, bytes, buffer)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, B: i2c::WriteIter> i2c::WriteIter for ExclusiveBus<'a, B> {
+    type Error = B::Error;
+
+    fn write<I>(&mut self, address: u8, bytes: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        self.bus.write(address, bytes)
+    }
+}
+
+#[cfg(feature = "spi")]
+macro_rules! exclusive_bus_spi_word_impls {
+    ($($word:ty),*) => {
+        $(
+            impl<'a, B: spi::Transfer<$word>> spi::Transfer<$word> for ExclusiveBus<'a, B> {
+                type Error = B::Error;
+
+                fn transfer<'w>(
+                    &mut self,
+                    words: &'w mut [$word],
+                ) -> Result<&'w [$word], Self::Error> {
+                    self.bus.transfer(words)
+                }
+            }
+
+            impl<'a, B: spi::Write<$word>> spi::Write<$word> for ExclusiveBus<'a, B> {
+                type Error = B::Error;
+
+                fn write(&mut self, words: &[$word]) -> Result<(), Self::Error> {
+                    self.bus.write(words)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "spi")]
+exclusive_bus_spi_word_impls!(u8, u16, u32);
+
+#[cfg(feature = "adc")]
+impl<'a, B, ADC, Word, Pin> adc::OneShot<ADC, Word, Pin> for ExclusiveBus<'a, B>
+where
+    Pin: adc::Channel<ADC>,
+    B: adc::OneShot<ADC, Word, Pin>,
+{
+    type Error = B::Error;
+
+    fn read(&mut self, pin: &mut Pin) -> nb::Result<Word, Self::Error> {
+        self.bus.read(pin)
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, B: i2c_eh1::ErrorType> i2c_eh1::ErrorType for ExclusiveBus<'a, B> {
+    type Error = B::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, B: i2c_eh1::I2c<A>, A: i2c_eh1::AddressMode> i2c_eh1::I2c<A> for ExclusiveBus<'a, B> {
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.bus.transaction(address, operations)
+    }
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<'a, B: spi_eh1::ErrorType> spi_eh1::ErrorType for ExclusiveBus<'a, B> {
+    type Error = B::Error;
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<'a, B: spi_eh1::SpiBus<W>, W: Copy + 'static> spi_eh1::SpiBus<W> for ExclusiveBus<'a, B> {
+    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.bus.read(words)
+    }
+
+    fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        self.bus.write(words)
+    }
+
+    fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        self.bus.transfer(read, write)
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        self.bus.transfer_in_place(words)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.bus.flush()
+    }
+}
+
+/// Proxy handed out by a [`BoundedBusManager`], giving its slot back when dropped.
+///
+/// The `BoundedProxy` wraps one of the regular proxies and implements the same bus traits.  It
+/// is not `Clone`, so the number of proxies which exist at the same time can never exceed the
+/// bound of the manager.  Once a `BoundedProxy` is dropped, its slot can be acquired again.
+///
+/// [`BoundedBusManager`]: ./struct.BoundedBusManager.html
+///
+/// This type is only available on targets with atomic compare-and-swap, or with the `cortex-m`
+/// feature.
+#[cfg(any(feature = "cortex-m", target_has_atomic = "ptr"))]
+#[derive(Debug)]
+pub struct BoundedProxy<'a, P> {
+    pub(crate) proxy: P,
+    pub(crate) acquired: &'a crate::manager::SlotCounter,
+}
+
+#[cfg(any(feature = "cortex-m", target_has_atomic = "ptr"))]
+impl<'a, P> Drop for BoundedProxy<'a, P> {
+    fn drop(&mut self) {
+        self.acquired
+            .fetch_sub(1, core::sync::atomic::Ordering::Release);
+    }
+}
+
+#[cfg(all(feature = "i2c", any(feature = "cortex-m", target_has_atomic = "ptr")))]
+impl<'a, P: i2c::Write> i2c::Write for BoundedProxy<'a, P> {
+    type Error = P::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.proxy.write(addr, buffer)
+    }
+}
+
+#[cfg(all(feature = "i2c", any(feature = "cortex-m", target_has_atomic = "ptr")))]
+impl<'a, P: i2c::Read> i2c::Read for BoundedProxy<'a, P> {
+    type Error = P::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.proxy.read(addr, buffer)
+    }
+}
+
+#[cfg(all(feature = "i2c", any(feature = "cortex-m", target_has_atomic = "ptr")))]
+impl<'a, P: i2c::WriteRead> i2c::WriteRead for BoundedProxy<'a, P> {
+    type Error = P::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.proxy.write_read(addr, buffer_in, buffer_out)
+    }
+}
+
+#[cfg(all(feature = "i2c", any(feature = "cortex-m", target_has_atomic = "ptr")))]
+impl<'a, P: i2c::WriteIterRead> i2c::WriteIterRead for BoundedProxy<'a, P> {
+    type Error = P::Error;
+
+    fn write_iter_read<I>(
+        &mut self,
+        address: u8,
+        bytes: I,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        self.proxy.write_iter_read(address, bytes, buffer)
+    }
+}
+
+#[cfg(all(feature = "i2c", any(feature = "cortex-m", target_has_atomic = "ptr")))]
+impl<'a, P: i2c::WriteIter> i2c::WriteIter for BoundedProxy<'a, P> {
+    type Error = P::Error;
+
+    fn write<I>(&mut self, address: u8, bytes: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        self.proxy.write(address, bytes)
+    }
+}
+
+#[cfg(all(feature = "spi", any(feature = "cortex-m", target_has_atomic = "ptr")))]
+macro_rules! bounded_proxy_spi_word_impls {
+    ($($word:ty),*) => {
+        $(
+            impl<'a, P: spi::Transfer<$word>> spi::Transfer<$word> for BoundedProxy<'a, P> {
+                type Error = P::Error;
+
+                fn transfer<'w>(
+                    &mut self,
+                    words: &'w mut [$word],
+                ) -> Result<&'w [$word], Self::Error> {
+                    self.proxy.transfer(words)
+                }
+            }
+
+            impl<'a, P: spi::Write<$word>> spi::Write<$word> for BoundedProxy<'a, P> {
+                type Error = P::Error;
+
+                fn write(&mut self, words: &[$word]) -> Result<(), Self::Error> {
+                    self.proxy.write(words)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(all(feature = "spi", any(feature = "cortex-m", target_has_atomic = "ptr")))]
+bounded_proxy_spi_word_impls!(u8, u16, u32);
+
+#[cfg(all(feature = "adc", any(feature = "cortex-m", target_has_atomic = "ptr")))]
+impl<'a, P, ADC, Word, Pin> adc::OneShot<ADC, Word, Pin> for BoundedProxy<'a, P>
+where
+    Pin: adc::Channel<ADC>,
+    P: adc::OneShot<ADC, Word, Pin>,
+{
+    type Error = P::Error;
+
+    fn read(&mut self, pin: &mut Pin) -> nb::Result<Word, Self::Error> {
+        self.proxy.read(pin)
+    }
+}
+
+#[cfg(all(
+    feature = "i2c",
+    feature = "eh1",
+    any(feature = "cortex-m", target_has_atomic = "ptr")
+))]
+impl<'a, P: i2c_eh1::ErrorType> i2c_eh1::ErrorType for BoundedProxy<'a, P> {
+    type Error = P::Error;
+}
+
+#[cfg(all(
+    feature = "i2c",
+    feature = "eh1",
+    any(feature = "cortex-m", target_has_atomic = "ptr")
+))]
+impl<'a, P: i2c_eh1::I2c<A>, A: i2c_eh1::AddressMode> i2c_eh1::I2c<A> for BoundedProxy<'a, P> {
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.proxy.transaction(address, operations)
+    }
+}
+
+/// Exclusive access to the bus for as long as the token is alive.
+///
+/// The `BusScope` is the value-based counterpart to [`BusManager::lock_exclusive()`][lock_exclusive]:
+/// It holds the bus lock from its creation using [`BusManager::begin_scope()`][begin_scope] until
+/// it is dropped.  Like the [`ExclusiveBus`], it implements the same bus traits as the bus it
+/// wraps, so it can be handed to a driver for a sequence of operations which must not be
+/// interleaved with other bus users.
+///
+/// [lock_exclusive]: ./struct.BusManager.html#method.lock_exclusive
+/// [begin_scope]: ./struct.BusManager.html#method.begin_scope
+/// [`ExclusiveBus`]: ./struct.ExclusiveBus.html
+pub struct BusScope<'a, M: crate::GuardedBusMutex + 'a> {
+    pub(crate) guard: M::Guard<'a>,
+}
+
+impl<'a, M: crate::GuardedBusMutex + 'a> core::fmt::Debug for BusScope<'a, M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BusScope").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::GuardedBusMutex + 'a> i2c::Write for BusScope<'a, M>
+where
+    M::Bus: i2c::Write,
+{
+    type Error = <M::Bus as i2c::Write>::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        i2c::Write::write(&mut *self.guard, addr, buffer)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::GuardedBusMutex + 'a> i2c::Read for BusScope<'a, M>
+where
+    M::Bus: i2c::Read,
+{
+    type Error = <M::Bus as i2c::Read>::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        i2c::Read::read(&mut *self.guard, addr, buffer)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::GuardedBusMutex + 'a> i2c::WriteRead for BusScope<'a, M>
+where
+    M::Bus: i2c::WriteRead,
+{
+    type Error = <M::Bus as i2c::WriteRead>::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        i2c::WriteRead::write_read(&mut *self.guard, addr, buffer_in, buffer_out)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::GuardedBusMutex + 'a> i2c::WriteIterRead for BusScope<'a, M>
+where
+    M::Bus: i2c::WriteIterRead,
+{
+    type Error = <M::Bus as i2c::WriteIterRead>::Error;
+
+    fn write_iter_read<I>(
+        &mut self,
+        address: u8,
+        bytes: I,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        i2c::WriteIterRead::write_iter_read(&mut *self.guard, address, bytes, buffer)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<'a, M: crate::GuardedBusMutex + 'a> i2c::WriteIter for BusScope<'a, M>
+where
+    M::Bus: i2c::WriteIter,
+{
+    type Error = <M::Bus as i2c::WriteIter>::Error;
+
+    fn write<I>(&mut self, address: u8, bytes: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        i2c::WriteIter::write(&mut *self.guard, address, bytes)
+    }
+}
+
+#[cfg(feature = "spi")]
+macro_rules! bus_scope_spi_word_impls {
+    ($($word:ty),*) => {
+        $(
+            impl<'a, M: crate::GuardedBusMutex + 'a> spi::Transfer<$word> for BusScope<'a, M>
+            where
+                M::Bus: spi::Transfer<$word>,
+            {
+                type Error = <M::Bus as spi::Transfer<$word>>::Error;
+
+                fn transfer<'w>(
+                    &mut self,
+                    words: &'w mut [$word],
+                ) -> Result<&'w [$word], Self::Error> {
+                    spi::Transfer::transfer(&mut *self.guard, words)
+                }
+            }
+
+            impl<'a, M: crate::GuardedBusMutex + 'a> spi::Write<$word> for BusScope<'a, M>
+            where
+                M::Bus: spi::Write<$word>,
+            {
+                type Error = <M::Bus as spi::Write<$word>>::Error;
+
+                fn write(&mut self, words: &[$word]) -> Result<(), Self::Error> {
+                    spi::Write::write(&mut *self.guard, words)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "spi")]
+bus_scope_spi_word_impls!(u8, u16, u32);
+
+#[cfg(feature = "adc")]
+impl<'a, M: crate::GuardedBusMutex + 'a, ADC, Word, Pin> adc::OneShot<ADC, Word, Pin>
+    for BusScope<'a, M>
+where
+    Pin: adc::Channel<ADC>,
+    M::Bus: adc::OneShot<ADC, Word, Pin>,
+{
+    type Error = <M::Bus as adc::OneShot<ADC, Word, Pin>>::Error;
+
+    fn read(&mut self, pin: &mut Pin) -> nb::Result<Word, Self::Error> {
+        adc::OneShot::read(&mut *self.guard, pin)
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::GuardedBusMutex + 'a> i2c_eh1::ErrorType for BusScope<'a, M>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = <M::Bus as i2c_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<'a, M: crate::GuardedBusMutex + 'a, A: i2c_eh1::AddressMode> i2c_eh1::I2c<A>
+    for BusScope<'a, M>
+where
+    M::Bus: i2c_eh1::I2c<A>,
+{
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        i2c_eh1::I2c::transaction(&mut *self.guard, address, operations)
+    }
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<'a, M: crate::GuardedBusMutex + 'a> spi_eh1::ErrorType for BusScope<'a, M>
+where
+    M::Bus: spi_eh1::ErrorType,
+{
+    type Error = <M::Bus as spi_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<'a, M: crate::GuardedBusMutex + 'a, W: Copy + 'static> spi_eh1::SpiBus<W> for BusScope<'a, M>
+where
+    M::Bus: spi_eh1::SpiBus<W>,
+{
+    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        spi_eh1::SpiBus::read(&mut *self.guard, words)
+    }
+
+    fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        spi_eh1::SpiBus::write(&mut *self.guard, words)
+    }
+
+    fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        spi_eh1::SpiBus::transfer(&mut *self.guard, read, write)
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        spi_eh1::SpiBus::transfer_in_place(&mut *self.guard, words)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        spi_eh1::SpiBus::flush(&mut *self.guard)
     }
 }
@@ -0,0 +1,381 @@
+#[cfg(feature = "eh1")]
+use embedded_hal_1::i2c as i2c_eh1;
+
+use core::convert::TryFrom;
+use core::time::Duration;
+use embedded_hal::blocking::i2c;
+#[cfg(feature = "rate-limit")]
+use portable_atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Time source for the [`RateLimitedProxy`] and the [`TimedI2cProxy`].
+///
+/// The clock counts microseconds from an arbitrary starting point and is allowed to wrap around.
+/// All rate-limited proxies of a bus manager compare their timestamps, so they must use the same
+/// clock (e.g. the same hardware timer).
+///
+/// [`RateLimitedProxy`]: ./struct.RateLimitedProxy.html
+/// [`TimedI2cProxy`]: ./struct.TimedI2cProxy.html
+pub trait Clock {
+    /// Get the current time in microseconds, wrapping around on overflow.
+    fn now_us(&mut self) -> u32;
+
+    /// Wait for `us` microseconds.
+    fn delay_us(&mut self, us: u32);
+}
+
+/// [`Clock`] based on [`std::time::Instant`], with one starting point for the whole process.
+///
+/// [`Clock`]: ./trait.Clock.html
+///
+/// This type is only available with the `std` feature.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdClock;
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    fn now_us(&mut self) -> u32 {
+        static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+        // Truncation is intended, the clock wraps around.
+        START
+            .get_or_init(std::time::Instant::now)
+            .elapsed()
+            .as_micros() as u32
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        std::thread::sleep(Duration::from_micros(us.into()));
+    }
+}
+
+/// Time of the last rate-limited transaction on a bus.
+///
+/// This is only accessed while holding the bus lock, so plain loads and stores are enough.
+#[cfg(feature = "rate-limit")]
+#[derive(Debug)]
+pub(crate) struct LastTransaction {
+    valid: AtomicBool,
+    at: AtomicU32,
+}
+
+#[cfg(feature = "rate-limit")]
+impl LastTransaction {
+    pub(crate) const fn new() -> Self {
+        LastTransaction {
+            valid: AtomicBool::new(false),
+            at: AtomicU32::new(0),
+        }
+    }
+}
+
+/// Proxy type for I2C bus sharing which enforces a minimum time between transactions.
+///
+/// The `RateLimitedProxy` works just like the [`I2cProxy`] but before each transaction, it
+/// waits until at least `min_interval` has passed since the end of the previous rate-limited
+/// transaction.  This protects fragile devices which need some time to recover between
+/// accesses.  The wait happens inside the bus lock, so no other device can use the bus in the
+/// meantime:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # use std::time::Duration;
+/// # fn _example(i2c: impl i2c::Write) {
+/// let bus = shared_bus::BusManagerSimple::new(i2c);
+/// let interval = Duration::from_millis(5);
+///
+/// let mut proxy = bus.acquire_i2c_rate_limited(interval, shared_bus::StdClock);
+/// let _ = proxy.write(0x39, &[0xc0]);
+/// // Waits for 5 ms before writing
+/// let _ = proxy.write(0x39, &[0xff]);
+/// # }
+/// ```
+///
+/// The time of the last transaction is stored in the bus manager, so the limit applies to all
+/// rate-limited proxies of the manager together, not to each of them separately.  Accesses
+/// through other proxies are not limited.
+///
+/// As the clock wraps around, intervals are limited to `u32::MAX` microseconds (about 71
+/// minutes).  After the bus was idle for longer than that, one transaction may be delayed
+/// needlessly.
+///
+/// A `RateLimitedProxy` is created by calling
+/// [`BusManager::acquire_i2c_rate_limited()`][acquire_i2c_rate_limited].
+///
+/// [`I2cProxy`]: ./struct.I2cProxy.html
+/// [acquire_i2c_rate_limited]: ./struct.BusManager.html#method.acquire_i2c_rate_limited
+///
+/// This type is only available with the `rate-limit` feature.
+#[cfg(feature = "rate-limit")]
+#[derive(Debug)]
+pub struct RateLimitedProxy<'a, M, C> {
+    pub(crate) mutex: &'a M,
+    pub(crate) last: &'a LastTransaction,
+    pub(crate) min_interval: u32,
+    pub(crate) clock: C,
+}
+
+#[cfg(feature = "rate-limit")]
+impl<'a, M, C: Clone> Clone for RateLimitedProxy<'a, M, C> {
+    fn clone(&self) -> Self {
+        Self {
+            mutex: self.mutex,
+            last: self.last,
+            min_interval: self.min_interval,
+            clock: self.clock.clone(),
+        }
+    }
+}
+
+/// Convert an interval to microseconds, saturating at the limit of the clock.
+pub(crate) fn interval_us(interval: Duration) -> u32 {
+    u32::try_from(interval.as_micros()).unwrap_or(u32::MAX)
+}
+
+#[cfg(feature = "rate-limit")]
+impl<'a, M: crate::BusMutex, C: Clock> RateLimitedProxy<'a, M, C> {
+    fn limited<R>(&mut self, op: impl FnOnce(&mut M::Bus) -> R) -> R {
+        let last = self.last;
+        let min_interval = self.min_interval;
+        let clock = &mut self.clock;
+        self.mutex.lock(|bus| {
+            if last.valid.load(Ordering::Relaxed) {
+                let elapsed = clock.now_us().wrapping_sub(last.at.load(Ordering::Relaxed));
+                if elapsed < min_interval {
+                    clock.delay_us(min_interval - elapsed);
+                }
+            }
+            let res = op(bus);
+            last.at.store(clock.now_us(), Ordering::Relaxed);
+            last.valid.store(true, Ordering::Relaxed);
+            res
+        })
+    }
+}
+
+#[cfg(feature = "rate-limit")]
+impl<'a, M: crate::BusMutex, C: Clock> i2c::Write for RateLimitedProxy<'a, M, C>
+where
+    M::Bus: i2c::Write,
+{
+    type Error = <M::Bus as i2c::Write>::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.limited(|bus| bus.write(addr, buffer))
+    }
+}
+
+#[cfg(feature = "rate-limit")]
+impl<'a, M: crate::BusMutex, C: Clock> i2c::Read for RateLimitedProxy<'a, M, C>
+where
+    M::Bus: i2c::Read,
+{
+    type Error = <M::Bus as i2c::Read>::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.limited(|bus| bus.read(addr, buffer))
+    }
+}
+
+#[cfg(feature = "rate-limit")]
+impl<'a, M: crate::BusMutex, C: Clock> i2c::WriteRead for RateLimitedProxy<'a, M, C>
+where
+    M::Bus: i2c::WriteRead,
+{
+    type Error = <M::Bus as i2c::WriteRead>::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.limited(|bus| bus.write_read(addr, buffer_in, buffer_out))
+    }
+}
+
+#[cfg(all(feature = "rate-limit", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, C> i2c_eh1::ErrorType for RateLimitedProxy<'a, M, C>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = <M::Bus as i2c_eh1::ErrorType>::Error;
+}
+
+#[cfg(all(feature = "rate-limit", feature = "eh1"))]
+impl<'a, M: crate::BusMutex, C: Clock, A: i2c_eh1::AddressMode> i2c_eh1::I2c<A>
+    for RateLimitedProxy<'a, M, C>
+where
+    M::Bus: i2c_eh1::I2c<A>,
+{
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        self.limited(|bus| bus.transaction(address, operations))
+    }
+}
+
+/// Error type for the [`TimedI2cProxy`].
+///
+/// [`TimedI2cProxy`]: ./struct.TimedI2cProxy.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimedError<E> {
+    /// An error occurred on the bus.
+    Bus(E),
+    /// The transaction took longer than the timeout.  The abort function was called.
+    Timeout,
+}
+
+#[cfg(feature = "eh1")]
+impl<E: i2c_eh1::Error> i2c_eh1::Error for TimedError<E> {
+    fn kind(&self) -> i2c_eh1::ErrorKind {
+        match self {
+            TimedError::Bus(e) => e.kind(),
+            TimedError::Timeout => i2c_eh1::ErrorKind::Other,
+        }
+    }
+}
+
+/// Proxy type for I2C bus sharing which bounds the duration of each transaction.
+///
+/// The `TimedI2cProxy` works just like the [`I2cProxy`] but measures each transaction with a
+/// shared timer.  If a transaction takes longer than `timeout`, e.g. because a device stretched
+/// the clock for too long, the proxy calls the abort function with the bus and returns
+/// [`TimedError::Timeout`], even if the transaction succeeded in the end.  As `embedded-hal` has
+/// no notion of aborting a transfer, the abort function has to do this in a HAL-specific way,
+/// usually by resetting the peripheral:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # use std::time::Duration;
+/// # trait Reset { fn reset(&mut self); }
+/// # fn _example(i2c: impl i2c::Write + Reset) {
+/// let bus = shared_bus::BusManagerSimple::new(i2c);
+/// let timeout = Duration::from_millis(10);
+///
+/// let mut proxy = bus.acquire_i2c_timed(shared_bus::StdClock, timeout, |i2c| i2c.reset());
+/// match proxy.write(0x39, &[0xc0, 0xff, 0xee]) {
+///     Err(shared_bus::TimedError::Timeout) => { /* The device is stuck */ }
+///     _ => (),
+/// }
+/// # }
+/// ```
+///
+/// **Note**: The blocking bus traits cannot be interrupted, so the timeout is only checked
+/// between transactions, once the HAL returns.  It cannot interrupt a HAL call which hangs.
+/// This is enough for HALs which give up on a stuck bus
+/// after some (possibly long) time of their own, as the proxy then resets the peripheral and
+/// reports a consistent error.  It does not help against a HAL which waits forever; use the
+/// timeout support of the HAL or a hardware watchdog in that case.
+///
+/// Transactions of other proxies are not measured.  As the clock wraps around, the timeout is
+/// limited to `u32::MAX` microseconds (about 71 minutes).
+///
+/// A `TimedI2cProxy` is created by calling
+/// [`BusManager::acquire_i2c_timed()`][acquire_i2c_timed].
+///
+/// [`I2cProxy`]: ./struct.I2cProxy.html
+/// [`TimedError::Timeout`]: ./enum.TimedError.html#variant.Timeout
+/// [acquire_i2c_timed]: ./struct.BusManager.html#method.acquire_i2c_timed
+#[derive(Debug)]
+pub struct TimedI2cProxy<'a, M, C, F> {
+    pub(crate) mutex: &'a M,
+    pub(crate) clock: C,
+    pub(crate) timeout: u32,
+    pub(crate) abort: F,
+}
+
+impl<'a, M: crate::BusMutex, C: Clock, F: FnMut(&mut M::Bus)> TimedI2cProxy<'a, M, C, F> {
+    fn timed<E>(
+        &mut self,
+        op: impl FnOnce(&mut M::Bus) -> Result<(), E>,
+    ) -> Result<(), TimedError<E>> {
+        let clock = &mut self.clock;
+        let timeout = self.timeout;
+        let abort = &mut self.abort;
+        self.mutex.lock(|bus| {
+            let start = clock.now_us();
+            let res = op(bus);
+            if clock.now_us().wrapping_sub(start) > timeout {
+                abort(bus);
+                return Err(TimedError::Timeout);
+            }
+            res.map_err(TimedError::Bus)
+        })
+    }
+}
+
+impl<'a, M: crate::BusMutex, C: Clock, F: FnMut(&mut M::Bus)> i2c::Write
+    for TimedI2cProxy<'a, M, C, F>
+where
+    M::Bus: i2c::Write,
+{
+    type Error = TimedError<<M::Bus as i2c::Write>::Error>;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.timed(|bus| bus.write(addr, buffer))
+    }
+}
+
+impl<'a, M: crate::BusMutex, C: Clock, F: FnMut(&mut M::Bus)> i2c::Read
+    for TimedI2cProxy<'a, M, C, F>
+where
+    M::Bus: i2c::Read,
+{
+    type Error = TimedError<<M::Bus as i2c::Read>::Error>;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.timed(|bus| bus.read(addr, buffer))
+    }
+}
+
+impl<'a, M: crate::BusMutex, C: Clock, F: FnMut(&mut M::Bus)> i2c::WriteRead
+    for TimedI2cProxy<'a, M, C, F>
+where
+    M::Bus: i2c::WriteRead,
+{
+    type Error = TimedError<<M::Bus as i2c::WriteRead>::Error>;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.timed(|bus| bus.write_read(addr, buffer_in, buffer_out))
+    }
+}
+
+#[cfg(feature = "eh1")]
+impl<'a, M: crate::BusMutex, C, F> i2c_eh1::ErrorType for TimedI2cProxy<'a, M, C, F>
+where
+    M::Bus: i2c_eh1::ErrorType,
+{
+    type Error = TimedError<<M::Bus as i2c_eh1::ErrorType>::Error>;
+}
+
+#[cfg(feature = "eh1")]
+impl<'a, M: crate::BusMutex, C, F, A> i2c_eh1::I2c<A> for TimedI2cProxy<'a, M, C, F>
+where
+    M::Bus: i2c_eh1::I2c<A>,
+    C: Clock,
+    F: FnMut(&mut M::Bus),
+    A: i2c_eh1::AddressMode,
+{
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        self.timed(|bus| bus.transaction(address, operations))
+    }
+}
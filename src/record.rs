@@ -0,0 +1,455 @@
+use embedded_hal::blocking::i2c;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// I2C operation recorded by a [`RecordingBus`].
+///
+/// With the `serde` feature, this type implements `Serialize` and `Deserialize`.
+///
+/// [`RecordingBus`]: ./struct.RecordingBus.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RecordedOp {
+    /// I2C write.
+    I2cWrite {
+        /// Address of the device.
+        addr: u8,
+        /// The data which was written.
+        bytes: Vec<u8>,
+    },
+    /// I2C read.
+    I2cRead {
+        /// Address of the device.
+        addr: u8,
+        /// The data which was read (empty if the read failed).
+        bytes: Vec<u8>,
+    },
+    /// I2C write followed by a read.
+    I2cWriteRead {
+        /// Address of the device.
+        addr: u8,
+        /// The data which was written.
+        write: Vec<u8>,
+        /// The data which was read (empty if the operation failed).
+        read: Vec<u8>,
+    },
+}
+
+/// A single operation recorded by a [`RecordingBus`], together with its outcome.
+///
+/// With the `serde` feature, this type implements `Serialize` and `Deserialize`, so recordings
+/// can also be stored in other formats than the textual one of [`Recording`].
+///
+/// [`RecordingBus`]: ./struct.RecordingBus.html
+/// [`Recording`]: ./struct.Recording.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Record {
+    /// The operation.
+    pub op: RecordedOp,
+    /// Whether the operation succeeded.  The error itself is not recorded.
+    pub ok: bool,
+}
+
+/// Error returned by a [`ReplayBus`] for operations which failed during the recording.
+///
+/// [`ReplayBus`]: ./struct.ReplayBus.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordedError;
+
+/// Error when parsing a recording which is not in the format written by [`Recording`].
+///
+/// [`Recording`]: ./struct.Recording.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRecordingError {
+    /// The (1-based) line which could not be parsed.
+    pub line: usize,
+}
+
+impl fmt::Display for ParseRecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid bus recording in line {}", self.line)
+    }
+}
+
+impl std::error::Error for ParseRecordingError {}
+
+/// Shared handle to the log of a [`RecordingBus`].
+///
+/// The recording is serialized with its `Display` implementation, as one line per operation:
+///
+/// ```text
+/// write 39 c0ffee ok
+/// read 39 beef ok
+/// write_read 39 0f - err
+/// ```
+///
+/// Addresses and data are hex-encoded, `-` stands for no data.  The text can be turned back into
+/// a [`ReplayBus`] with [`ReplayBus::parse()`][parse].
+///
+/// [`RecordingBus`]: ./struct.RecordingBus.html
+/// [`ReplayBus`]: ./struct.ReplayBus.html
+/// [parse]: ./struct.ReplayBus.html#method.parse
+#[derive(Debug, Clone, Default)]
+pub struct Recording(Arc<Mutex<Vec<Record>>>);
+
+impl Recording {
+    /// Get a copy of all operations recorded so far, in the order they happened.
+    pub fn records(&self) -> Vec<Record> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Remove all operations.
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear()
+    }
+}
+
+fn write_hex(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    if bytes.is_empty() {
+        return f.write_str(" -");
+    }
+    f.write_str(" ")?;
+    bytes.iter().try_for_each(|b| write!(f, "{:02x}", b))
+}
+
+fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    if s == "-" {
+        return Some(Vec::new());
+    }
+    s.as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [hi, lo] => u8::from_str_radix(std::str::from_utf8(&[*hi, *lo]).ok()?, 16).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.op {
+            RecordedOp::I2cWrite { addr, bytes } => {
+                write!(f, "write {:02x}", addr)?;
+                write_hex(f, bytes)?;
+            }
+            RecordedOp::I2cRead { addr, bytes } => {
+                write!(f, "read {:02x}", addr)?;
+                write_hex(f, bytes)?;
+            }
+            RecordedOp::I2cWriteRead { addr, write, read } => {
+                write!(f, "write_read {:02x}", addr)?;
+                write_hex(f, write)?;
+                write_hex(f, read)?;
+            }
+        }
+        f.write_str(if self.ok { " ok" } else { " err" })
+    }
+}
+
+impl Record {
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        let kind = fields.next()?;
+        let addr = u8::from_str_radix(fields.next()?, 16).ok()?;
+        let op = match kind {
+            "write" => RecordedOp::I2cWrite {
+                addr,
+                bytes: parse_hex(fields.next()?)?,
+            },
+            "read" => RecordedOp::I2cRead {
+                addr,
+                bytes: parse_hex(fields.next()?)?,
+            },
+            "write_read" => RecordedOp::I2cWriteRead {
+                addr,
+                write: parse_hex(fields.next()?)?,
+                read: parse_hex(fields.next()?)?,
+            },
+            _ => return None,
+        };
+        let ok = match fields.next()? {
+            "ok" => true,
+            "err" => false,
+            _ => return None,
+        };
+        if fields.next().is_some() {
+            return None;
+        }
+        Some(Record { op, ok })
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .try_for_each(|record| writeln!(f, "{}", record))
+    }
+}
+
+/// Bus wrapper which records all I2C operations and their results, for reproducing them later.
+///
+/// Like the [`TracingBus`], the `RecordingBus` wraps the bus peripheral _before_ it is handed to
+/// a bus manager, so it sees each locked operation in the order the bus performed it.  Unlike the
+/// `TracingBus`, it records the data that was transferred, so a run on real hardware can be
+/// replayed later with a [`ReplayBus`]:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # fn _example(i2c: impl i2c::WriteRead) {
+/// let recording = shared_bus::RecordingBus::new(i2c);
+/// let log = recording.recording();
+///
+/// let bus = shared_bus::BusManagerSimple::new(recording);
+/// let mut proxy = bus.acquire_i2c();
+/// let mut buf = [0u8; 2];
+/// let _ = proxy.write_read(0x39, &[0x0f], &mut buf);
+///
+/// std::fs::write("bus.log", log.to_string()).unwrap();
+/// # }
+/// ```
+///
+/// The errors of failed operations are not recorded, only the fact that an operation failed.
+///
+/// [`TracingBus`]: ./struct.TracingBus.html
+/// [`ReplayBus`]: ./struct.ReplayBus.html
+///
+/// This type is only available with the `std` feature.
+#[derive(Debug)]
+pub struct RecordingBus<B> {
+    bus: B,
+    recording: Recording,
+}
+
+impl<B> RecordingBus<B> {
+    /// Wrap `bus`, recording all operations into a new log.
+    pub fn new(bus: B) -> Self {
+        RecordingBus {
+            bus,
+            recording: Recording::default(),
+        }
+    }
+
+    /// Get a handle to the recording, which stays usable after the bus was moved into a manager.
+    pub fn recording(&self) -> Recording {
+        self.recording.clone()
+    }
+
+    /// Destroy the wrapper, returning the bus.
+    pub fn into_inner(self) -> B {
+        self.bus
+    }
+
+    fn record<E>(&mut self, op: RecordedOp, res: Result<(), E>) -> Result<(), E> {
+        self.recording.0.lock().unwrap().push(Record {
+            op,
+            ok: res.is_ok(),
+        });
+        res
+    }
+}
+
+impl<B: i2c::Write> i2c::Write for RecordingBus<B> {
+    type Error = B::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        let res = self.bus.write(addr, buffer);
+        let bytes = buffer.to_vec();
+        self.record(RecordedOp::I2cWrite { addr, bytes }, res)
+    }
+}
+
+impl<B: i2c::Read> i2c::Read for RecordingBus<B> {
+    type Error = B::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let res = self.bus.read(addr, buffer);
+        let bytes = if res.is_ok() {
+            buffer.to_vec()
+        } else {
+            Vec::new()
+        };
+        self.record(RecordedOp::I2cRead { addr, bytes }, res)
+    }
+}
+
+impl<B: i2c::WriteRead> i2c::WriteRead for RecordingBus<B> {
+    type Error = B::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let res = self.bus.write_read(addr, buffer_in, buffer_out);
+        let read = if res.is_ok() {
+            buffer_out.to_vec()
+        } else {
+            Vec::new()
+        };
+        let write = buffer_in.to_vec();
+        self.record(RecordedOp::I2cWriteRead { addr, write, read }, res)
+    }
+}
+
+/// Bus which replays a recording of a [`RecordingBus`].
+///
+/// The `ReplayBus` expects exactly the recorded operations in the recorded order, and answers
+/// reads with the recorded data.  Operations which failed during the recording fail with a
+/// [`RecordedError`].  This makes bus-related bugs reproducible without the hardware:
+///
+/// ```
+/// # use embedded_hal::prelude::*;
+/// let replay = shared_bus::ReplayBus::parse("write_read 39 0f beef ok\n").unwrap();
+///
+/// let bus = shared_bus::BusManagerSimple::new(replay.clone());
+/// let mut proxy = bus.acquire_i2c();
+/// let mut buf = [0u8; 2];
+/// proxy.write_read(0x39, &[0x0f], &mut buf).unwrap();
+/// assert_eq!(buf, [0xbe, 0xef]);
+///
+/// replay.done();
+/// ```
+///
+/// Like the `embedded-hal-mock` mocks, the `ReplayBus` panics when an operation does not match
+/// the recording.  All clones share the same recording, so one clone can be kept for calling
+/// [`done()`][done] after the other was moved into a bus manager.
+///
+/// [`RecordingBus`]: ./struct.RecordingBus.html
+/// [`RecordedError`]: ./struct.RecordedError.html
+/// [done]: #method.done
+///
+/// This type is only available with the `std` feature.
+#[derive(Debug, Clone)]
+pub struct ReplayBus {
+    records: Arc<Mutex<VecDeque<Record>>>,
+}
+
+impl ReplayBus {
+    /// Create a bus replaying `records`.
+    pub fn new(records: impl IntoIterator<Item = Record>) -> Self {
+        ReplayBus {
+            records: Arc::new(Mutex::new(records.into_iter().collect())),
+        }
+    }
+
+    /// Create a bus replaying a recording serialized by [`Recording`].
+    ///
+    /// Empty lines are ignored.
+    ///
+    /// [`Recording`]: ./struct.Recording.html
+    pub fn parse(text: &str) -> Result<Self, ParseRecordingError> {
+        let records = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| Record::parse(line).ok_or(ParseRecordingError { line: i + 1 }))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(records))
+    }
+
+    /// Panic if not all recorded operations were replayed.
+    pub fn done(&self) {
+        let records = self.records.lock().unwrap();
+        assert!(
+            records.is_empty(),
+            "{} recorded operations were not replayed, next: {}",
+            records.len(),
+            records[0]
+        );
+    }
+
+    fn next(&mut self, kind: &str) -> Record {
+        self.records
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| panic!("unexpected {} after the end of the recording", kind))
+    }
+}
+
+fn replay_result(record: &Record) -> Result<(), RecordedError> {
+    if record.ok {
+        Ok(())
+    } else {
+        Err(RecordedError)
+    }
+}
+
+impl i2c::Write for ReplayBus {
+    type Error = RecordedError;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        let record = self.next("write");
+        let expected = RecordedOp::I2cWrite {
+            addr,
+            bytes: buffer.to_vec(),
+        };
+        assert_eq!(
+            record.op, expected,
+            "operation does not match the recording"
+        );
+        replay_result(&record)
+    }
+}
+
+impl i2c::Read for ReplayBus {
+    type Error = RecordedError;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let record = self.next("read");
+        match &record.op {
+            // Failed reads were recorded without data, so their length is unknown.
+            RecordedOp::I2cRead { addr: a, bytes }
+                if *a == addr && (!record.ok || bytes.len() == buffer.len()) =>
+            {
+                if record.ok {
+                    buffer.copy_from_slice(bytes);
+                }
+            }
+            op => panic!(
+                "read from {:02x} does not match the recording: {:?}",
+                addr, op
+            ),
+        }
+        replay_result(&record)
+    }
+}
+
+impl i2c::WriteRead for ReplayBus {
+    type Error = RecordedError;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let record = self.next("write_read");
+        match &record.op {
+            RecordedOp::I2cWriteRead {
+                addr: a,
+                write,
+                read,
+            } if *a == addr
+                && write[..] == *buffer_in
+                && (!record.ok || read.len() == buffer_out.len()) =>
+            {
+                if record.ok {
+                    buffer_out.copy_from_slice(read);
+                }
+            }
+            op => panic!(
+                "write_read to {:02x} does not match the recording: {:?}",
+                addr, op
+            ),
+        }
+        replay_result(&record)
+    }
+}
@@ -0,0 +1,184 @@
+#[cfg(feature = "i2c")]
+use embedded_hal_1::i2c as i2c_eh1;
+#[cfg(feature = "spi")]
+use embedded_hal_1::spi as spi_eh1;
+
+/// Adapter which lets drivers generated by the [`device-driver`] crate access their registers
+/// through an I2C proxy.
+///
+/// The `I2cRegisterInterface` implements `device_driver::RegisterInterface` on top of any proxy
+/// implementing the `embedded-hal` 1.0 `I2c` trait, so a generated driver can sit on a shared
+/// bus directly:
+///
+/// ```
+/// # use embedded_hal_1::i2c::I2c;
+/// device_driver::create_device!(
+///     device_name: Sensor,
+///     dsl: {
+///         config {
+///             type RegisterAddressType = u8;
+///         }
+///         register Id {
+///             const ADDRESS = 0x0f;
+///             const SIZE_BITS = 8;
+///             value: uint = 0..8,
+///         }
+///     }
+/// );
+///
+/// # fn _example(i2c: impl I2c) {
+/// let bus = shared_bus::BusManagerSimple::new(i2c);
+///
+/// let interface = shared_bus::I2cRegisterInterface::new(bus.acquire_i2c(), 0x39);
+/// let mut sensor = Sensor::new(interface);
+/// let _ = sensor.id().read();
+/// # }
+/// ```
+///
+/// A register is written as the register address followed by its data, in a single write
+/// transfer.  It is read by writing the register address and reading the data after a repeated
+/// start condition.
+///
+/// The adapter is written against `device-driver` 1.0.
+///
+/// [`device-driver`]: https://docs.rs/device-driver/1
+///
+/// This type is only available with the `device-driver` feature.
+#[cfg(feature = "i2c")]
+#[derive(Debug, Clone)]
+pub struct I2cRegisterInterface<P> {
+    proxy: P,
+    address: u8,
+}
+
+#[cfg(feature = "i2c")]
+impl<P> I2cRegisterInterface<P> {
+    /// Access the registers of the device at `address` through `proxy`.
+    pub fn new(proxy: P, address: u8) -> Self {
+        I2cRegisterInterface { proxy, address }
+    }
+
+    /// Get back the wrapped proxy.
+    pub fn into_inner(self) -> P {
+        self.proxy
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<P: i2c_eh1::I2c> device_driver::RegisterInterface for I2cRegisterInterface<P> {
+    type Error = P::Error;
+    type AddressType = u8;
+
+    fn write_register(
+        &mut self,
+        address: u8,
+        _size_bits: u32,
+        data: &[u8],
+    ) -> Result<(), Self::Error> {
+        // Adjacent write operations are sent without a repeated start in between.
+        self.proxy.transaction(
+            self.address,
+            &mut [
+                i2c_eh1::Operation::Write(&[address]),
+                i2c_eh1::Operation::Write(data),
+            ],
+        )
+    }
+
+    fn read_register(
+        &mut self,
+        address: u8,
+        _size_bits: u32,
+        data: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.proxy.write_read(self.address, &[address], data)
+    }
+}
+
+/// Adapter which lets drivers generated by the [`device-driver`] crate access their registers
+/// through an SPI device proxy.
+///
+/// The `SpiRegisterInterface` implements `device_driver::RegisterInterface` on top of any proxy
+/// implementing the `embedded-hal` 1.0 `SpiDevice` trait, like the [`OwnedSpiDeviceProxy`].  The
+/// [`SpiProxy`] does not manage chip-select and can thus not frame a register access on its own.
+///
+/// Each register access is a single transaction: The register address is written, followed by
+/// writing or reading the register data.  Many devices distinguish reads from writes by a flag
+/// in the address byte, which can be set using [`read_flag()`][read_flag]:
+///
+/// ```
+/// # use embedded_hal_1::spi::SpiDevice;
+/// # fn _example<D: SpiDevice>(device: D) {
+/// // Reads are signalled by setting the MSB of the address
+/// let interface = shared_bus::SpiRegisterInterface::new(device).read_flag(0x80);
+/// # }
+/// ```
+///
+/// The adapter is written against `device-driver` 1.0.
+///
+/// [`device-driver`]: https://docs.rs/device-driver/1
+/// [`OwnedSpiDeviceProxy`]: ./struct.OwnedSpiDeviceProxy.html
+/// [`SpiProxy`]: ./struct.SpiProxy.html
+/// [read_flag]: #method.read_flag
+///
+/// This type is only available with the `device-driver` feature.
+#[cfg(feature = "spi")]
+#[derive(Debug, Clone)]
+pub struct SpiRegisterInterface<P> {
+    proxy: P,
+    read_flag: u8,
+}
+
+#[cfg(feature = "spi")]
+impl<P> SpiRegisterInterface<P> {
+    /// Access the registers of the device behind `proxy`.
+    pub fn new(proxy: P) -> Self {
+        SpiRegisterInterface {
+            proxy,
+            read_flag: 0,
+        }
+    }
+
+    /// Set bits which are ORed into the register address when reading a register.
+    ///
+    /// The default is `0`, i.e. the address is sent unchanged.
+    pub fn read_flag(mut self, flag: u8) -> Self {
+        self.read_flag = flag;
+        self
+    }
+
+    /// Get back the wrapped proxy.
+    pub fn into_inner(self) -> P {
+        self.proxy
+    }
+}
+
+#[cfg(feature = "spi")]
+impl<P: spi_eh1::SpiDevice> device_driver::RegisterInterface for SpiRegisterInterface<P> {
+    type Error = P::Error;
+    type AddressType = u8;
+
+    fn write_register(
+        &mut self,
+        address: u8,
+        _size_bits: u32,
+        data: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.proxy.transaction(&mut [
+            spi_eh1::Operation::Write(&[address]),
+            spi_eh1::Operation::Write(data),
+        ])
+    }
+
+    fn read_register(
+        &mut self,
+        address: u8,
+        _size_bits: u32,
+        data: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.proxy.transaction(&mut [
+            spi_eh1::Operation::Write(&[address | self.read_flag]),
+            spi_eh1::Operation::Read(data),
+        ])
+    }
+}
@@ -0,0 +1,82 @@
+use portable_atomic::{AtomicU32, Ordering};
+
+/// Bitmap of the I2C addresses for which an `AddressedI2cProxy` was acquired.
+#[derive(Debug, Default)]
+pub(crate) struct AddressRegistry {
+    acquired: [AtomicU32; 8],
+}
+
+impl AddressRegistry {
+    pub(crate) const fn new() -> Self {
+        AddressRegistry {
+            acquired: [
+                AtomicU32::new(0),
+                AtomicU32::new(0),
+                AtomicU32::new(0),
+                AtomicU32::new(0),
+                AtomicU32::new(0),
+                AtomicU32::new(0),
+                AtomicU32::new(0),
+                AtomicU32::new(0),
+            ],
+        }
+    }
+
+    /// Record that a proxy for `addr` was acquired, warning when one was acquired before.
+    pub(crate) fn register(&self, addr: u8) {
+        let bit = 1 << (addr % 32);
+        let previous = self.acquired[usize::from(addr / 32)].fetch_or(bit, Ordering::Relaxed);
+        if previous & bit != 0 {
+            log::warn!(
+                "Multiple proxies acquired for I2C address 0x{:02x}; \
+                 check the bus configuration",
+                addr
+            );
+        }
+    }
+
+    /// Get the set of all addresses recorded so far.
+    pub(crate) fn acquired(&self) -> AddressSet {
+        let mut set = AddressSet { bits: [0; 8] };
+        for (bits, acquired) in set.bits.iter_mut().zip(&self.acquired) {
+            *bits = acquired.load(Ordering::Relaxed);
+        }
+        set
+    }
+}
+
+/// Set of I2C addresses, as returned by [`BusManager::validate_devices()`][validate_devices].
+///
+/// [validate_devices]: ./struct.BusManager.html#method.validate_devices
+///
+/// This type is only available with the `address-registry` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AddressSet {
+    bits: [u32; 8],
+}
+
+impl AddressSet {
+    /// Check whether `addr` is part of the set.
+    pub fn contains(&self, addr: u8) -> bool {
+        self.bits[usize::from(addr / 32)] & (1 << (addr % 32)) != 0
+    }
+
+    /// Number of addresses in the set.
+    pub fn len(&self) -> usize {
+        self.bits.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    /// Check whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|&b| b == 0)
+    }
+
+    /// Iterate over the addresses in the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..=u8::MAX).filter(move |&addr| self.contains(addr))
+    }
+
+    pub(crate) fn insert(&mut self, addr: u8) {
+        self.bits[usize::from(addr / 32)] |= 1 << (addr % 32);
+    }
+}
@@ -0,0 +1,92 @@
+use embedded_hal_nb::serial;
+
+/// Proxy type for sharing a UART, based on the `embedded-hal-nb` serial traits.
+///
+/// With `embedded-hal` 1.0, the serial traits moved out of `embedded-hal` itself.  The
+/// `SerialProxy` implements the non-blocking [`serial::Read`] and [`serial::Write`] traits of
+/// `embedded-hal-nb`, as these are the direct successors of the `embedded-hal` 0.2 serial traits
+/// and are what most HALs implement for their UARTs.  `embedded-io` is not supported, yet.
+///
+/// Each `read()`, `write()`, and `flush()` call locks the bus on its own and passes
+/// `WouldBlock` on to the caller, so the lock is never held while waiting.  This also means that
+/// words from different proxies may be interleaved on the wire.  To send a whole frame without
+/// interruption, use [`write_all()`][write_all] which holds the lock until the frame was
+/// written:
+///
+/// ```
+/// # use embedded_hal_nb::serial;
+/// # fn _example(uart: impl serial::Write) {
+/// let bus = shared_bus::BusManagerSimple::new(uart);
+/// let mut proxy = bus.acquire_serial();
+///
+/// let _ = proxy.write_all(b"AT+RST\r\n");
+/// # }
+/// ```
+///
+/// A `SerialProxy` is created by calling [`BusManager::acquire_serial()`][acquire_serial].
+///
+/// [`serial::Read`]: https://docs.rs/embedded-hal-nb/1/embedded_hal_nb/serial/trait.Read.html
+/// [`serial::Write`]: https://docs.rs/embedded-hal-nb/1/embedded_hal_nb/serial/trait.Write.html
+/// [write_all]: #method.write_all
+/// [acquire_serial]: ./struct.BusManager.html#method.acquire_serial
+///
+/// This type is only available with the `serial` feature.
+#[derive(Debug)]
+pub struct SerialProxy<'a, M> {
+    pub(crate) mutex: &'a M,
+}
+
+impl<'a, M: crate::BusMutex> Clone for SerialProxy<'a, M> {
+    fn clone(&self) -> Self {
+        Self { mutex: self.mutex }
+    }
+}
+
+impl<'a, M: crate::BusMutex> SerialProxy<'a, M> {
+    /// Write all `words` and wait until they were sent, while holding the lock.
+    ///
+    /// No other proxy can write in between, so the words arrive as one frame.
+    pub fn write_all<Word: Copy>(
+        &mut self,
+        words: &[Word],
+    ) -> Result<(), <M::Bus as serial::ErrorType>::Error>
+    where
+        M::Bus: serial::Write<Word>,
+    {
+        self.mutex.lock(|bus| {
+            for &word in words {
+                nb::block!(serial::Write::write(bus, word))?;
+            }
+            nb::block!(serial::Write::flush(bus))
+        })
+    }
+}
+
+impl<'a, M: crate::BusMutex> serial::ErrorType for SerialProxy<'a, M>
+where
+    M::Bus: serial::ErrorType,
+{
+    type Error = <M::Bus as serial::ErrorType>::Error;
+}
+
+impl<'a, M: crate::BusMutex, Word: Copy> serial::Read<Word> for SerialProxy<'a, M>
+where
+    M::Bus: serial::Read<Word>,
+{
+    fn read(&mut self) -> nb::Result<Word, Self::Error> {
+        self.mutex.lock(|bus| bus.read())
+    }
+}
+
+impl<'a, M: crate::BusMutex, Word: Copy> serial::Write<Word> for SerialProxy<'a, M>
+where
+    M::Bus: serial::Write<Word>,
+{
+    fn write(&mut self, word: Word) -> nb::Result<(), Self::Error> {
+        self.mutex.lock(|bus| bus.write(word))
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        self.mutex.lock(|bus| bus.flush())
+    }
+}
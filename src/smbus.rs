@@ -0,0 +1,190 @@
+use embedded_hal::blocking::i2c;
+
+/// Largest block size allowed by the SMBus specification.
+const MAX_BLOCK: usize = 32;
+
+/// Error type for the [`SmbusProxy`].
+///
+/// [`SmbusProxy`]: ./struct.SmbusProxy.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmbusError<E> {
+    /// An error occurred on the bus.
+    Bus(E),
+    /// The packet error code received from the device did not match the data.
+    Pec,
+    /// The device announced a block which is larger than the buffer, or than allowed by SMBus.
+    BlockLength(u8),
+}
+
+/// Update the SMBus CRC-8 (polynomial `x^8 + x^2 + x + 1`) with `data`.
+fn crc8(mut crc: u8, data: &[u8]) -> u8 {
+    for byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Proxy type for talking to SMBus devices, with optional packet error checking (PEC).
+///
+/// The `SmbusProxy` offers the common SMBus commands on top of a shared I2C bus.  Each command is
+/// performed atomically while holding the bus lock.  With PEC enabled, a CRC-8 byte is appended
+/// to all writes, and the one sent by the device is validated for all reads:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # fn _example<I2C>(i2c: I2C)
+/// # where
+/// #     I2C: i2c::Write + i2c::WriteRead<Error = <I2C as i2c::Write>::Error>,
+/// # {
+/// let bus = shared_bus::BusManagerSimple::new(i2c);
+/// let mut smbus = bus.acquire_smbus(true);
+///
+/// // Object temperature of an MLX90614 infrared thermometer
+/// let raw = smbus.read_word(0x5a, 0x07);
+/// # }
+/// ```
+///
+/// Words are transferred in little-endian byte order, as defined by SMBus.
+///
+/// An `SmbusProxy` is created by calling [`BusManager::acquire_smbus()`][acquire_smbus].
+///
+/// [acquire_smbus]: ./struct.BusManager.html#method.acquire_smbus
+#[derive(Debug)]
+pub struct SmbusProxy<'a, M> {
+    pub(crate) mutex: &'a M,
+    pub(crate) pec: bool,
+}
+
+impl<'a, M: crate::BusMutex> Clone for SmbusProxy<'a, M> {
+    fn clone(&self) -> Self {
+        Self {
+            mutex: self.mutex,
+            pec: self.pec,
+        }
+    }
+}
+
+impl<'a, M: crate::BusMutex> SmbusProxy<'a, M>
+where
+    M::Bus: i2c::Write + i2c::WriteRead<Error = <M::Bus as i2c::Write>::Error>,
+{
+    /// Write `data` after the `command` byte, appending the PEC if enabled.
+    fn write(
+        &mut self,
+        addr: u8,
+        command: u8,
+        data: &[u8],
+    ) -> Result<(), SmbusError<<M::Bus as i2c::Write>::Error>> {
+        let mut buf = [0u8; 4];
+        buf[0] = command;
+        buf[1..1 + data.len()].copy_from_slice(data);
+        let mut len = 1 + data.len();
+        if self.pec {
+            buf[len] = crc8(crc8(0, &[addr << 1]), &buf[..len]);
+            len += 1;
+        }
+        self.mutex
+            .lock(|bus| i2c::Write::write(bus, addr, &buf[..len]))
+            .map_err(SmbusError::Bus)
+    }
+
+    /// Read `N` bytes after writing the `command` byte, validating the PEC if enabled.
+    fn read<const N: usize>(
+        &mut self,
+        addr: u8,
+        command: u8,
+    ) -> Result<[u8; N], SmbusError<<M::Bus as i2c::Write>::Error>> {
+        let mut buf = [0u8; 3];
+        let len = N + usize::from(self.pec);
+        self.mutex
+            .lock(|bus| i2c::WriteRead::write_read(bus, addr, &[command], &mut buf[..len]))
+            .map_err(SmbusError::Bus)?;
+        if self.pec {
+            let crc = crc8(0, &[addr << 1, command, (addr << 1) | 1]);
+            if crc8(crc, &buf[..N]) != buf[N] {
+                return Err(SmbusError::Pec);
+            }
+        }
+        let mut data = [0u8; N];
+        data.copy_from_slice(&buf[..N]);
+        Ok(data)
+    }
+
+    /// SMBus "Write Byte": Write one byte to the register `command`.
+    pub fn write_byte(
+        &mut self,
+        addr: u8,
+        command: u8,
+        value: u8,
+    ) -> Result<(), SmbusError<<M::Bus as i2c::Write>::Error>> {
+        self.write(addr, command, &[value])
+    }
+
+    /// SMBus "Write Word": Write a 16-bit word to the register `command`.
+    pub fn write_word(
+        &mut self,
+        addr: u8,
+        command: u8,
+        value: u16,
+    ) -> Result<(), SmbusError<<M::Bus as i2c::Write>::Error>> {
+        self.write(addr, command, &value.to_le_bytes())
+    }
+
+    /// SMBus "Read Byte": Read one byte from the register `command`.
+    pub fn read_byte(
+        &mut self,
+        addr: u8,
+        command: u8,
+    ) -> Result<u8, SmbusError<<M::Bus as i2c::Write>::Error>> {
+        self.read::<1>(addr, command).map(|[value]| value)
+    }
+
+    /// SMBus "Read Word": Read a 16-bit word from the register `command`.
+    pub fn read_word(
+        &mut self,
+        addr: u8,
+        command: u8,
+    ) -> Result<u16, SmbusError<<M::Bus as i2c::Write>::Error>> {
+        self.read::<2>(addr, command).map(u16::from_le_bytes)
+    }
+
+    /// SMBus "Block Read": Read a block of up to 32 bytes from the register `command`.
+    ///
+    /// The device sends the length of the block first.  As plain I2C cannot adjust the length of
+    /// a read on the fly, `buf.len()` bytes are read (plus the length and PEC bytes), so `buf`
+    /// should be sized for the longest block the device may send.  Surplus bytes are ignored.
+    ///
+    /// Returns the length of the block, which was stored at the start of `buf`.
+    pub fn block_read(
+        &mut self,
+        addr: u8,
+        command: u8,
+        buf: &mut [u8],
+    ) -> Result<usize, SmbusError<<M::Bus as i2c::Write>::Error>> {
+        let mut raw = [0u8; 1 + MAX_BLOCK + 1];
+        let len = 1 + buf.len().min(MAX_BLOCK) + usize::from(self.pec);
+        self.mutex
+            .lock(|bus| i2c::WriteRead::write_read(bus, addr, &[command], &mut raw[..len]))
+            .map_err(SmbusError::Bus)?;
+
+        let count = usize::from(raw[0]);
+        if count > buf.len() || count > MAX_BLOCK {
+            return Err(SmbusError::BlockLength(raw[0]));
+        }
+        if self.pec {
+            let crc = crc8(0, &[addr << 1, command, (addr << 1) | 1]);
+            if crc8(crc, &raw[..1 + count]) != raw[1 + count] {
+                return Err(SmbusError::Pec);
+            }
+        }
+        buf[..count].copy_from_slice(&raw[1..1 + count]);
+        Ok(count)
+    }
+}
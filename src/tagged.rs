@@ -0,0 +1,189 @@
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+use embedded_hal_1::i2c as i2c_eh1;
+#[cfg(all(feature = "spi", feature = "eh1"))]
+use embedded_hal_1::spi as spi_eh1;
+
+#[cfg(feature = "adc")]
+use embedded_hal::adc;
+#[cfg(feature = "i2c")]
+use embedded_hal::blocking::i2c;
+#[cfg(feature = "spi")]
+use embedded_hal::blocking::spi;
+
+use core::marker::PhantomData;
+
+/// Proxy wrapper which tags a proxy with the device it serves.
+///
+/// The `Tagged` newtype forwards all bus traits to the wrapped proxy, but carries a marker type
+/// `Tag` which makes proxies for different devices distinct types.  This prevents mixing up
+/// proxies at compile time, at no runtime cost:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # use shared_bus::{BusMutex, I2cProxy, Tagged};
+/// struct Imu;
+/// struct Baro;
+///
+/// fn read_imu<M: BusMutex>(imu: &mut Tagged<Imu, I2cProxy<'_, M>>)
+/// where
+///     M::Bus: i2c::Write,
+/// {
+///     let _ = imu.write(0x68, &[0x3b]);
+/// }
+///
+/// # fn _example(i2c: impl i2c::Write) {
+/// let bus = shared_bus::BusManagerSimple::new(i2c);
+/// let mut imu = Tagged::<Imu, _>::new(bus.acquire_i2c());
+/// let baro = Tagged::<Baro, _>::new(bus.acquire_i2c());
+///
+/// read_imu(&mut imu);
+/// // read_imu(&mut baro); // Does not compile
+/// # }
+/// ```
+///
+/// The tag is never instantiated, so any type can be used for it.  The wrapped proxy can be
+/// accessed through `Deref` as well.
+#[repr(transparent)]
+pub struct Tagged<Tag, P> {
+    proxy: P,
+    tag: PhantomData<fn() -> Tag>,
+}
+
+impl<Tag, P> Tagged<Tag, P> {
+    /// Tag a proxy.
+    pub const fn new(proxy: P) -> Self {
+        Tagged {
+            proxy,
+            tag: PhantomData,
+        }
+    }
+
+    /// Remove the tag again.
+    pub fn into_inner(self) -> P {
+        self.proxy
+    }
+}
+
+impl<Tag, P: core::fmt::Debug> core::fmt::Debug for Tagged<Tag, P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Tagged")
+            .field(&core::any::type_name::<Tag>())
+            .field(&self.proxy)
+            .finish()
+    }
+}
+
+impl<Tag, P: Clone> Clone for Tagged<Tag, P> {
+    fn clone(&self) -> Self {
+        Self::new(self.proxy.clone())
+    }
+}
+
+impl<Tag, P> core::ops::Deref for Tagged<Tag, P> {
+    type Target = P;
+
+    fn deref(&self) -> &P {
+        &self.proxy
+    }
+}
+
+impl<Tag, P> core::ops::DerefMut for Tagged<Tag, P> {
+    fn deref_mut(&mut self) -> &mut P {
+        &mut self.proxy
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<Tag, P: i2c::Write> i2c::Write for Tagged<Tag, P> {
+    type Error = P::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.proxy.write(addr, buffer)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<Tag, P: i2c::Read> i2c::Read for Tagged<Tag, P> {
+    type Error = P::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.proxy.read(addr, buffer)
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<Tag, P: i2c::WriteRead> i2c::WriteRead for Tagged<Tag, P> {
+    type Error = P::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.proxy.write_read(addr, buffer_in, buffer_out)
+    }
+}
+
+#[cfg(feature = "spi")]
+impl<Tag, P: spi::Write<u8>> spi::Write<u8> for Tagged<Tag, P> {
+    type Error = P::Error;
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.proxy.write(words)
+    }
+}
+
+#[cfg(feature = "spi")]
+impl<Tag, P: spi::Transfer<u8>> spi::Transfer<u8> for Tagged<Tag, P> {
+    type Error = P::Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        self.proxy.transfer(words)
+    }
+}
+
+#[cfg(feature = "adc")]
+impl<Tag, P, ADC, Word, Pin> adc::OneShot<ADC, Word, Pin> for Tagged<Tag, P>
+where
+    Pin: adc::Channel<ADC>,
+    P: adc::OneShot<ADC, Word, Pin>,
+{
+    type Error = P::Error;
+
+    fn read(&mut self, pin: &mut Pin) -> nb::Result<Word, Self::Error> {
+        self.proxy.read(pin)
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<Tag, P: i2c_eh1::ErrorType> i2c_eh1::ErrorType for Tagged<Tag, P> {
+    type Error = P::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<Tag, P: i2c_eh1::I2c<A>, A: i2c_eh1::AddressMode> i2c_eh1::I2c<A> for Tagged<Tag, P> {
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.proxy.transaction(address, operations)
+    }
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<Tag, P: spi_eh1::ErrorType> spi_eh1::ErrorType for Tagged<Tag, P> {
+    type Error = P::Error;
+}
+
+#[cfg(all(feature = "spi", feature = "eh1"))]
+impl<Tag, P: spi_eh1::SpiDevice<W>, W: Copy + 'static> spi_eh1::SpiDevice<W> for Tagged<Tag, P> {
+    fn transaction(
+        &mut self,
+        operations: &mut [spi_eh1::Operation<'_, W>],
+    ) -> Result<(), Self::Error> {
+        self.proxy.transaction(operations)
+    }
+}
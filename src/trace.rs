@@ -0,0 +1,227 @@
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+use embedded_hal_1::i2c as i2c_eh1;
+
+#[cfg(feature = "i2c")]
+use embedded_hal::blocking::i2c;
+#[cfg(feature = "spi")]
+use embedded_hal::blocking::spi;
+use std::sync::{Arc, Mutex};
+use std::thread::ThreadId;
+use std::time::Instant;
+
+/// Kind of bus operation recorded by a [`TracingBus`].
+///
+/// [`TracingBus`]: ./struct.TracingBus.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceOp {
+    /// I2C write to the given address.
+    I2cWrite(u8),
+    /// I2C read from the given address.
+    I2cRead(u8),
+    /// I2C write followed by a read from the given address.
+    I2cWriteRead(u8),
+    /// I2C transaction (`embedded-hal` 1.0) with the given address.
+    I2cTransaction(u8),
+    /// SPI write.
+    SpiWrite,
+    /// SPI transfer.
+    SpiTransfer,
+}
+
+/// A single operation recorded by a [`TracingBus`].
+///
+/// [`TracingBus`]: ./struct.TracingBus.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// The thread which performed the operation.
+    pub thread: ThreadId,
+    /// The operation.
+    pub op: TraceOp,
+    /// When the operation started.
+    pub start: Instant,
+    /// When the operation ended.
+    pub end: Instant,
+}
+
+/// Shared handle to the log of a [`TracingBus`].
+///
+/// [`TracingBus`]: ./struct.TracingBus.html
+#[derive(Debug, Clone, Default)]
+pub struct TraceLog(Arc<Mutex<Vec<TraceEntry>>>);
+
+impl TraceLog {
+    /// Get a copy of all entries recorded so far, in the order the operations started.
+    pub fn entries(&self) -> Vec<TraceEntry> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Check that no two recorded operations overlapped in time.
+    pub fn is_serialized(&self) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .windows(2)
+            .all(|pair| pair[0].end <= pair[1].start)
+    }
+
+    /// Remove all entries.
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear()
+    }
+}
+
+/// Bus wrapper which records the thread and time of every operation, for testing.
+///
+/// Like the [`FormatBus`], the `TracingBus` wraps the bus peripheral _before_ it is handed to a
+/// bus manager.  It records which thread performed which operation and when, so tests can check
+/// that the accesses of different threads were properly serialized:
+///
+/// ```
+/// # use embedded_hal::blocking::i2c;
+/// # use embedded_hal::prelude::*;
+/// # fn _example(i2c: impl i2c::Write + Send + 'static) {
+/// let tracing = shared_bus::TracingBus::new(i2c);
+/// let log = tracing.log();
+///
+/// let bus: &'static _ = Box::leak(Box::new(shared_bus::BusManagerStd::new(tracing)));
+///
+/// let threads: Vec<_> = (0..4)
+///     .map(|_| {
+///         let mut proxy = bus.acquire_i2c();
+///         std::thread::spawn(move || {
+///             let _ = proxy.write(0x39, &[0xc0, 0xff, 0xee]);
+///         })
+///     })
+///     .collect();
+/// for t in threads {
+///     t.join().unwrap();
+/// }
+///
+/// assert_eq!(log.entries().len(), 4);
+/// assert!(log.is_serialized());
+/// # }
+/// ```
+///
+/// This is mostly useful for testing custom [`BusMutex`] implementations or code which locks the
+/// bus manually.
+///
+/// [`FormatBus`]: ./struct.FormatBus.html
+/// [`BusMutex`]: ./trait.BusMutex.html
+///
+/// This type is only available with the `std` feature.
+#[derive(Debug)]
+pub struct TracingBus<B> {
+    bus: B,
+    log: TraceLog,
+}
+
+impl<B> TracingBus<B> {
+    /// Wrap `bus`, recording all operations into a new log.
+    pub fn new(bus: B) -> Self {
+        TracingBus {
+            bus,
+            log: TraceLog::default(),
+        }
+    }
+
+    /// Get a handle to the log, which stays usable after the bus was moved into a manager.
+    pub fn log(&self) -> TraceLog {
+        self.log.clone()
+    }
+
+    /// Destroy the wrapper, returning the bus.
+    pub fn into_inner(self) -> B {
+        self.bus
+    }
+
+    #[cfg(any(feature = "i2c", feature = "spi"))]
+    fn record<R>(&mut self, op: TraceOp, f: impl FnOnce(&mut B) -> R) -> R {
+        let start = Instant::now();
+        let res = f(&mut self.bus);
+        let end = Instant::now();
+
+        let mut entries = self.log.0.lock().unwrap();
+        // Keep the log ordered by start time, even if operations overlapped.
+        let pos = entries.partition_point(|e| e.start <= start);
+        entries.insert(
+            pos,
+            TraceEntry {
+                thread: std::thread::current().id(),
+                op,
+                start,
+                end,
+            },
+        );
+        res
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<B: i2c::Write> i2c::Write for TracingBus<B> {
+    type Error = B::Error;
+
+    fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.record(TraceOp::I2cWrite(addr), |bus| bus.write(addr, buffer))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<B: i2c::Read> i2c::Read for TracingBus<B> {
+    type Error = B::Error;
+
+    fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.record(TraceOp::I2cRead(addr), |bus| bus.read(addr, buffer))
+    }
+}
+
+#[cfg(feature = "i2c")]
+impl<B: i2c::WriteRead> i2c::WriteRead for TracingBus<B> {
+    type Error = B::Error;
+
+    fn write_read(
+        &mut self,
+        addr: u8,
+        buffer_in: &[u8],
+        buffer_out: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.record(TraceOp::I2cWriteRead(addr), |bus| {
+            bus.write_read(addr, buffer_in, buffer_out)
+        })
+    }
+}
+
+#[cfg(feature = "spi")]
+impl<B: spi::Write<u8>> spi::Write<u8> for TracingBus<B> {
+    type Error = B::Error;
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.record(TraceOp::SpiWrite, |bus| bus.write(words))
+    }
+}
+
+#[cfg(feature = "spi")]
+impl<B: spi::Transfer<u8>> spi::Transfer<u8> for TracingBus<B> {
+    type Error = B::Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+        self.record(TraceOp::SpiTransfer, move |bus| bus.transfer(words))
+    }
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<B: i2c_eh1::ErrorType> i2c_eh1::ErrorType for TracingBus<B> {
+    type Error = B::Error;
+}
+
+#[cfg(all(feature = "i2c", feature = "eh1"))]
+impl<B: i2c_eh1::I2c> i2c_eh1::I2c for TracingBus<B> {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [i2c_eh1::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.record(TraceOp::I2cTransaction(address), |bus| {
+            bus.transaction(address, operations)
+        })
+    }
+}
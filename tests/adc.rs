@@ -1,5 +1,5 @@
 use embedded_hal::prelude::*;
-use embedded_hal_mock::adc;
+use embedded_hal_mock::eh0::adc;
 use std::thread;
 
 #[test]
@@ -105,3 +105,52 @@ fn adc_proxy_concurrent() {
 
     device.done()
 }
+
+#[test]
+fn adc_proxy_shared_ref() {
+    let expectations = [
+        adc::Transaction::read(0, 0xabcd),
+        adc::Transaction::read(1, 0xabba),
+    ];
+
+    let mut device = adc::Mock::new(&expectations);
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let proxy = manager.acquire_adc();
+
+    assert_eq!(0xabcd, (&proxy).read(&mut adc::MockChan0).unwrap());
+    assert_eq!(0xabba, (&proxy).read(&mut adc::MockChan1).unwrap());
+    device.done()
+}
+
+#[test]
+fn adc_manager_as_proxy() {
+    let expectations = [adc::Transaction::read(0, 0xabcd)];
+
+    let mut device = adc::Mock::new(&expectations);
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+
+    assert_eq!(0xabcd, (&manager).read(&mut adc::MockChan0).unwrap());
+    device.done()
+}
+
+#[test]
+fn adc_proxy_error() {
+    use embedded_hal_mock::eh0::MockError;
+    use std::io::ErrorKind;
+
+    let expectations = [
+        adc::Transaction::read(0, 0xabcd).with_error(MockError::Io(ErrorKind::InvalidData)),
+        adc::Transaction::read(1, 0xabba),
+    ];
+
+    let mut device = adc::Mock::new(&expectations);
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut proxy = manager.acquire_adc();
+
+    assert_eq!(
+        proxy.read(&mut adc::MockChan0),
+        Err(nb::Error::Other(MockError::Io(ErrorKind::InvalidData)))
+    );
+    assert_eq!(0xabba, proxy.read(&mut adc::MockChan1).unwrap());
+    device.done()
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "address-registry")]
+
+use embedded_hal_mock::eh0::i2c;
+use std::sync::Mutex;
+
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct Logger;
+
+impl log::Log for Logger {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        if record.level() == log::Level::Warn {
+            WARNINGS.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn address_registry_duplicate() {
+    log::set_logger(&Logger).unwrap();
+    log::set_max_level(log::LevelFilter::Warn);
+
+    let mut device = i2c::Mock::new(&[]);
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+
+    let _sensor = manager.acquire_i2c_at::<0x48>();
+    let _eeprom = manager.acquire_i2c_at::<0x50>();
+    let _high = manager.acquire_i2c_at::<0xc8>();
+    assert!(WARNINGS.lock().unwrap().is_empty());
+
+    let _other_sensor = manager.acquire_i2c_at::<0x48>();
+    assert_eq!(
+        *WARNINGS.lock().unwrap(),
+        vec!["Multiple proxies acquired for I2C address 0x48; check the bus configuration"]
+    );
+
+    device.done();
+}
+
+#[test]
+fn address_registry_validate_devices() {
+    use embedded_hal_mock::eh0::MockError;
+    use std::io::ErrorKind;
+
+    let expect = vec![
+        i2c::Transaction::write(0x48, vec![]),
+        i2c::Transaction::write(0x50, vec![]).with_error(MockError::Io(ErrorKind::Other)),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+
+    let _sensor = manager.acquire_i2c_at::<0x48>();
+    let _eeprom = manager.acquire_i2c_at::<0x50>();
+
+    let missing = manager.validate_devices().unwrap_err();
+    assert_eq!(missing.len(), 1);
+    assert!(missing.contains(0x50));
+    assert_eq!(missing.iter().collect::<Vec<_>>(), vec![0x50]);
+
+    device.done();
+}
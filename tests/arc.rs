@@ -0,0 +1,105 @@
+#![cfg(all(feature = "alloc", feature = "critical-section"))]
+
+use embedded_hal::prelude::*;
+use embedded_hal_mock::eh0::i2c;
+use std::thread;
+
+#[test]
+fn arc_i2c_tasks() {
+    let expect = vec![
+        i2c::Transaction::write(0x31, vec![0xaa]),
+        i2c::Transaction::write(0x31, vec![0xaa]),
+        i2c::Transaction::write(0x31, vec![0xaa]),
+        i2c::Transaction::write(0x31, vec![0xaa]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    {
+        let manager: shared_bus::ArcBusManager<shared_bus::AutoMutex<_>> =
+            shared_bus::ArcBusManager::new(device.clone());
+
+        // Each simulated task owns its proxy, the manager itself is not kept alive.
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let mut proxy = manager.acquire_i2c();
+                thread::spawn(move || proxy.write(0x31, &[0xaa]).unwrap())
+            })
+            .collect();
+        drop(manager);
+
+        for task in tasks {
+            task.join().unwrap();
+        }
+    }
+
+    device.done();
+}
+
+#[test]
+fn arc_i2c_clone() {
+    let expect = vec![
+        i2c::Transaction::write(0x31, vec![0xc0, 0xff, 0xee]),
+        i2c::Transaction::read(0x31, vec![0xbe, 0xef]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::ArcBusManager::<shared_bus::AutoMutex<_>>::new(device.clone());
+    let mut proxy = manager.acquire_i2c();
+    let mut clone = proxy.clone();
+
+    proxy.write(0x31, &[0xc0, 0xff, 0xee]).unwrap();
+    let mut buf = [0u8; 2];
+    clone.read(0x31, &mut buf).unwrap();
+    assert_eq!(buf, [0xbe, 0xef]);
+
+    device.done();
+}
+
+#[test]
+fn arc_i2c_outlives_manager() {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct State {
+        writes: AtomicUsize,
+        dropped: AtomicBool,
+    }
+
+    struct Bus(Arc<State>);
+
+    impl embedded_hal::blocking::i2c::Write for Bus {
+        type Error = ();
+
+        fn write(&mut self, _addr: u8, _bytes: &[u8]) -> Result<(), ()> {
+            assert!(!self.0.dropped.load(Ordering::SeqCst));
+            self.0.writes.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            self.0.dropped.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let state = Arc::new(State::default());
+    let manager: shared_bus::ArcBusManager<shared_bus::AutoMutex<_>> =
+        shared_bus::ArcBusManager::new(Bus(state.clone()));
+    let mut proxy = manager.acquire_i2c();
+    let mut clone = proxy.clone();
+
+    drop(manager);
+    assert!(!state.dropped.load(Ordering::SeqCst));
+    proxy.write(0x31, &[0xaa]).unwrap();
+
+    drop(proxy);
+    assert!(!state.dropped.load(Ordering::SeqCst));
+    clone.write(0x31, &[0xaa]).unwrap();
+
+    // The bus is only freed together with the last proxy
+    drop(clone);
+    assert!(state.dropped.load(Ordering::SeqCst));
+    assert_eq!(state.writes.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "async")]
+
+use embedded_hal::adc;
+use futures::executor::block_on;
+use futures::future::join;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+struct SlowAdc {
+    busy: Option<(u8, u32)>,
+    log: Log,
+}
+
+struct Channel<const N: u8>;
+
+impl<const N: u8> adc::Channel<SlowAdc> for Channel<N> {
+    type ID = u8;
+
+    fn channel() -> u8 {
+        N
+    }
+}
+
+impl<const N: u8> adc::OneShot<SlowAdc, u16, Channel<N>> for SlowAdc {
+    type Error = ();
+
+    fn read(&mut self, _pin: &mut Channel<N>) -> nb::Result<u16, ()> {
+        match self.busy {
+            None => {
+                self.log.borrow_mut().push(format!("start {}", N));
+                self.busy = Some((N, 3));
+                Err(nb::Error::WouldBlock)
+            }
+            // Starting a conversion while another one is running would mix up the results
+            Some((channel, _)) if channel != N => Err(nb::Error::Other(())),
+            Some((_, 0)) => {
+                self.log.borrow_mut().push(format!("done {}", N));
+                self.busy = None;
+                Ok(u16::from(N) * 100)
+            }
+            Some((channel, ref mut remaining)) => {
+                *remaining -= 1;
+                self.busy = Some((channel, *remaining));
+                Err(nb::Error::WouldBlock)
+            }
+        }
+    }
+}
+
+#[test]
+fn async_adc_proxy() {
+    let log = Log::default();
+    let manager = shared_bus::BusManagerAsync::new(SlowAdc {
+        busy: None,
+        log: log.clone(),
+    });
+
+    let mut proxy1 = manager.acquire_adc(Channel::<1>);
+    let mut proxy2 = manager.acquire_adc(Channel::<2>);
+
+    let task1 = async {
+        assert_eq!(proxy1.read().await, Ok(100u16));
+        assert_eq!(proxy1.read().await, Ok(100u16));
+    };
+    let task2 = async {
+        assert_eq!(proxy2.read().await, Ok(200u16));
+    };
+
+    block_on(join(task1, task2));
+
+    assert_eq!(
+        *log.borrow(),
+        vec!["start 1", "done 1", "start 2", "done 2", "start 1", "done 1"]
+    );
+}
@@ -0,0 +1,275 @@
+#![cfg(feature = "async")]
+
+mod common;
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use embedded_hal_async::i2c::I2c;
+use embedded_hal_mock::eh1::i2c;
+use futures::executor::block_on;
+use futures::future::join;
+use shared_bus::AsyncBusMutex;
+
+/// Yield to the executor once.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+#[test]
+fn async_i2c_proxy() {
+    let expect = vec![
+        i2c::Transaction::write(0xde, vec![0xad, 0xbe, 0xef]),
+        i2c::Transaction::write_read(0x44, vec![0x01, 0x02], vec![0x03, 0x04]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerAsync::new(device.clone());
+    let mut proxy1 = manager.acquire_i2c();
+    let mut proxy2 = manager.acquire_i2c();
+
+    block_on(async {
+        proxy1.write(0xde, &[0xad, 0xbe, 0xef]).await.unwrap();
+
+        let mut buf = [0u8; 2];
+        proxy2
+            .write_read(0x44, &[0x01, 0x02], &mut buf)
+            .await
+            .unwrap();
+        assert_eq!(&buf, &[0x03, 0x04]);
+    });
+
+    device.done();
+}
+
+#[test]
+fn async_i2c_transaction_empty() {
+    let mut device = i2c::Mock::new(&[]);
+
+    let manager = shared_bus::BusManagerAsync::new(device.clone());
+    let mut proxy = manager.acquire_i2c();
+
+    block_on(proxy.transaction(0x44, &mut [])).unwrap();
+
+    device.done();
+}
+
+#[test]
+fn fair_mutex_no_starvation() {
+    let mutex: shared_bus::FairAsyncMutex<()> = shared_bus::FairAsyncMutex::create(());
+    let greedy_count = core::cell::Cell::new(0);
+    let mut starved_at = None;
+
+    let greedy = async {
+        for _ in 0..10 {
+            let guard = mutex.lock().await;
+            YieldNow(false).await;
+            drop(guard);
+            greedy_count.set(greedy_count.get() + 1);
+        }
+    };
+
+    let starved = async {
+        let _guard = mutex.lock().await;
+        starved_at = Some(greedy_count.get());
+    };
+
+    block_on(join(greedy, starved));
+
+    // The starved task was waiting when the greedy task released the bus for the first time
+    assert_eq!(starved_at, Some(1));
+}
+
+#[test]
+fn fair_mutex_cancel() {
+    let mutex: shared_bus::FairAsyncMutex<u32, 2> = shared_bus::FairAsyncMutex::create(0);
+
+    block_on(async {
+        let mut guard = mutex.lock().await;
+
+        // Start waiting, then give up the place in the queue
+        let mut waiting = Box::pin(mutex.lock());
+        assert!(futures::poll!(waiting.as_mut()).is_pending());
+        drop(waiting);
+
+        *guard += 1;
+        drop(guard);
+
+        // The mutex is not blocked by the cancelled waiter
+        assert_eq!(*mutex.lock().await, 1);
+    });
+}
+
+#[test]
+fn fair_mutex_overflow_order() {
+    let mutex: shared_bus::FairAsyncMutex<(), 2> = shared_bus::FairAsyncMutex::create(());
+    let wakes = std::sync::Arc::new(common::CountingWaker(Default::default()));
+    let waker = futures::task::waker(wakes.clone());
+    let mut cx = core::task::Context::from_waker(&waker);
+
+    let guard = block_on(mutex.lock());
+    let mut waiting: Vec<_> = (0..6).map(|_| Box::pin(mutex.lock())).collect();
+    for w in waiting.iter_mut() {
+        assert!(w.as_mut().poll(&mut cx).is_pending());
+    }
+    // Tasks waiting for a place in the queue don't keep waking each other
+    for w in waiting.iter_mut().rev() {
+        assert!(w.as_mut().poll(&mut cx).is_pending());
+    }
+    assert_eq!(wakes.0.load(std::sync::atomic::Ordering::Relaxed), 0);
+    drop(guard);
+
+    // Polling in reverse order, the tasks still get the bus in the order they arrived
+    let mut order = Vec::new();
+    for _ in 0..100 {
+        for (i, w) in waiting.iter_mut().enumerate().rev() {
+            if !order.contains(&i) && w.as_mut().poll(&mut cx).is_ready() {
+                order.push(i);
+            }
+        }
+    }
+    assert_eq!(order, [0, 1, 2, 3, 4, 5]);
+}
+
+/// Bus which needs two polls per transaction, counting the completed ones.
+struct SlowBus {
+    completed: std::rc::Rc<core::cell::Cell<usize>>,
+}
+
+impl embedded_hal_1::i2c::ErrorType for SlowBus {
+    type Error = core::convert::Infallible;
+}
+
+impl I2c for SlowBus {
+    async fn transaction(
+        &mut self,
+        _address: u8,
+        _operations: &mut [embedded_hal_1::i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        YieldNow(false).await;
+        self.completed.set(self.completed.get() + 1);
+        Ok(())
+    }
+}
+
+#[test]
+fn async_i2c_proxy_cancel() {
+    let completed = std::rc::Rc::new(core::cell::Cell::new(0));
+    let manager = shared_bus::BusManagerAsync::new(SlowBus {
+        completed: completed.clone(),
+    });
+    let mut proxy1 = manager.acquire_i2c();
+    let mut proxy2 = manager.acquire_i2c();
+
+    block_on(async {
+        // Cancelled while holding the bus
+        let mut using = Box::pin(proxy1.write(0x10, &[0x01]));
+        assert!(futures::poll!(using.as_mut()).is_pending());
+
+        // Cancelled while waiting for the bus
+        let mut waiting = Box::pin(proxy2.write(0x20, &[0x02]));
+        assert!(futures::poll!(waiting.as_mut()).is_pending());
+
+        drop(waiting);
+        drop(using);
+
+        // The bus is usable again
+        proxy2.write(0x20, &[0x03]).await.unwrap();
+        proxy1.write(0x10, &[0x04]).await.unwrap();
+    });
+
+    // The cancelled transfers never completed
+    assert_eq!(completed.get(), 2);
+}
+
+#[test]
+fn async_i2c_single_task() {
+    let expect = vec![
+        i2c::Transaction::write(0x39, vec![0xc0, 0xff, 0xee]),
+        i2c::Transaction::write_read(0x48, vec![0x00], vec![0x12, 0x34]),
+        i2c::Transaction::write(0x39, vec![0x01]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerAsyncSimple::new(device.clone());
+    let mut proxy1 = manager.acquire_i2c();
+    let mut proxy2 = manager.acquire_i2c();
+
+    block_on(async {
+        proxy1.write(0x39, &[0xc0, 0xff, 0xee]).await.unwrap();
+
+        let mut buf = [0u8; 2];
+        proxy2.write_read(0x48, &[0x00], &mut buf).await.unwrap();
+        assert_eq!(buf, [0x12, 0x34]);
+
+        YieldNow(false).await;
+        proxy1.write(0x39, &[0x01]).await.unwrap();
+    });
+
+    device.done();
+}
+
+#[test]
+#[should_panic(expected = "AsyncNullMutex locked concurrently")]
+#[allow(clippy::await_holding_refcell_ref)]
+fn async_null_mutex_concurrent() {
+    let mutex = shared_bus::AsyncNullMutex::create(0u8);
+
+    block_on(async {
+        let _guard = mutex.lock().await;
+        let _guard2 = mutex.lock().await;
+    });
+}
+
+#[test]
+fn async_atomic_check_sequential() {
+    let expect = vec![
+        i2c::Transaction::write(0x39, vec![0xc0, 0xff, 0xee]),
+        i2c::Transaction::write(0x48, vec![0x00]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerAsyncAtomicCheck::new(device.clone());
+    let mut proxy1 = manager.acquire_i2c();
+    let mut proxy2 = manager.acquire_i2c();
+
+    block_on(join(
+        async { proxy1.write(0x39, &[0xc0, 0xff, 0xee]).await.unwrap() },
+        async {
+            YieldNow(false).await;
+            proxy2.write(0x48, &[0x00]).await.unwrap();
+        },
+    ));
+
+    device.done();
+}
+
+#[test]
+#[should_panic(expected = "Bus conflict")]
+fn async_atomic_check_conflict() {
+    let mutex = shared_bus::AsyncAtomicCheckMutex::new(0u8);
+
+    block_on(join(
+        async {
+            let mut guard = mutex.lock().await;
+            YieldNow(false).await;
+            *guard += 1;
+        },
+        async {
+            let mut guard = mutex.lock().await;
+            *guard += 1;
+        },
+    ));
+}
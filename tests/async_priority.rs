@@ -0,0 +1,119 @@
+#![cfg(feature = "priority")]
+
+mod common;
+
+use core::future::Future;
+use futures::executor::block_on;
+use futures::future::join3;
+use shared_bus::AsyncBusMutex;
+use std::cell::RefCell;
+
+#[test]
+fn priority_mutex_order() {
+    let mutex: shared_bus::PriorityAsyncMutex<()> = shared_bus::PriorityAsyncMutex::create(());
+    let order = RefCell::new(Vec::new());
+
+    block_on(async {
+        let guard = mutex.lock().await;
+
+        // Both start waiting while the mutex is held, the low-priority task first
+        let mut low = Box::pin(async {
+            let _guard = mutex.lock_with_priority(1).await;
+            order.borrow_mut().push("low");
+        });
+        let mut mid = Box::pin(async {
+            let _guard = mutex.lock_with_priority(5).await;
+            order.borrow_mut().push("mid");
+        });
+        let mut high = Box::pin(async {
+            let _guard = mutex.lock_with_priority(10).await;
+            order.borrow_mut().push("high");
+        });
+        assert!(futures::poll!(low.as_mut()).is_pending());
+        assert!(futures::poll!(mid.as_mut()).is_pending());
+        assert!(futures::poll!(high.as_mut()).is_pending());
+
+        drop(guard);
+        join3(low, mid, high).await;
+    });
+
+    assert_eq!(*order.borrow(), vec!["high", "mid", "low"]);
+}
+
+#[test]
+fn priority_mutex_same_priority_fifo() {
+    let mutex: shared_bus::PriorityAsyncMutex<()> = shared_bus::PriorityAsyncMutex::create(());
+    let order = RefCell::new(Vec::new());
+
+    block_on(async {
+        let guard = mutex.lock().await;
+
+        let mut first = Box::pin(async {
+            let _guard = mutex.lock_with_priority(3).await;
+            order.borrow_mut().push("first");
+        });
+        let mut second = Box::pin(async {
+            let _guard = mutex.lock_with_priority(3).await;
+            order.borrow_mut().push("second");
+        });
+        assert!(futures::poll!(second.as_mut()).is_pending());
+        assert!(futures::poll!(first.as_mut()).is_pending());
+
+        drop(guard);
+        futures::future::join(first, second).await;
+    });
+
+    assert_eq!(*order.borrow(), vec!["second", "first"]);
+}
+
+#[test]
+fn priority_mutex_cancel() {
+    let mutex: shared_bus::PriorityAsyncMutex<u32, 1> = shared_bus::PriorityAsyncMutex::create(0);
+
+    block_on(async {
+        let mut guard = mutex.lock().await;
+
+        let mut waiting = Box::pin(mutex.lock_with_priority(10));
+        assert!(futures::poll!(waiting.as_mut()).is_pending());
+        drop(waiting);
+
+        *guard += 1;
+        drop(guard);
+
+        // The queue place of the cancelled waiter was given up
+        assert_eq!(*mutex.lock().await, 1);
+    });
+}
+
+#[test]
+fn priority_mutex_overflow_order() {
+    let mutex: shared_bus::PriorityAsyncMutex<(), 2> = shared_bus::PriorityAsyncMutex::create(());
+    let wakes = std::sync::Arc::new(common::CountingWaker(Default::default()));
+    let waker = futures::task::waker(wakes.clone());
+    let mut cx = core::task::Context::from_waker(&waker);
+
+    let guard = block_on(mutex.lock());
+    let mut waiting: Vec<_> = (0..6)
+        .map(|_| Box::pin(mutex.lock_with_priority(1)))
+        .collect();
+    for w in waiting.iter_mut() {
+        assert!(w.as_mut().poll(&mut cx).is_pending());
+    }
+    // Tasks waiting for a place in the queue don't keep waking each other
+    for w in waiting.iter_mut().rev() {
+        assert!(w.as_mut().poll(&mut cx).is_pending());
+    }
+    assert_eq!(wakes.0.load(std::sync::atomic::Ordering::Relaxed), 0);
+    drop(guard);
+
+    // Polling in reverse order, the tasks still get the bus in the order they arrived
+    let mut order = Vec::new();
+    for _ in 0..100 {
+        for (i, w) in waiting.iter_mut().enumerate().rev() {
+            if !order.contains(&i) && w.as_mut().poll(&mut cx).is_ready() {
+                order.push(i);
+            }
+        }
+    }
+    assert_eq!(order, [0, 1, 2, 3, 4, 5]);
+}
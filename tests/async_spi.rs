@@ -0,0 +1,435 @@
+#![cfg(feature = "async")]
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use embedded_hal_async::spi::SpiDevice;
+use futures::executor::block_on;
+use futures::future::join;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+/// Completes after being polled a number of times, like a DMA transfer.
+struct Dma(u32);
+
+impl Future for Dma {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 == 0 {
+            Poll::Ready(())
+        } else {
+            self.0 -= 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+struct DmaBus {
+    log: Log,
+}
+
+impl DmaBus {
+    async fn dma(&mut self, what: &str, words: &[u8]) {
+        self.log
+            .borrow_mut()
+            .push(format!("{} {:?} start", what, words));
+        Dma(3).await;
+        self.log
+            .borrow_mut()
+            .push(format!("{} {:?} done", what, words));
+    }
+}
+
+impl embedded_hal_1::spi::ErrorType for DmaBus {
+    type Error = core::convert::Infallible;
+}
+
+impl embedded_hal_async::spi::SpiBus for DmaBus {
+    async fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        words.fill(0x55);
+        self.dma("read", words).await;
+        Ok(())
+    }
+
+    async fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.dma("write", words).await;
+        Ok(())
+    }
+
+    async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        read.copy_from_slice(write);
+        self.dma("transfer", write).await;
+        Ok(())
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.dma("transfer_in_place", words).await;
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.log.borrow_mut().push("flush".to_string());
+        Ok(())
+    }
+}
+
+struct CsPin {
+    name: &'static str,
+    log: Log,
+}
+
+impl embedded_hal_1::digital::ErrorType for CsPin {
+    type Error = core::convert::Infallible;
+}
+
+impl embedded_hal_1::digital::OutputPin for CsPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.log.borrow_mut().push(format!("{} low", self.name));
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.log.borrow_mut().push(format!("{} high", self.name));
+        Ok(())
+    }
+}
+
+#[test]
+fn async_spi_device_dma_no_interleaving() {
+    let log = Log::default();
+    let manager = shared_bus::BusManagerAsync::new(DmaBus { log: log.clone() });
+
+    let mut dev_a = manager.acquire_spi_device(CsPin {
+        name: "a",
+        log: log.clone(),
+    });
+    let mut dev_b = manager.acquire_spi_device(CsPin {
+        name: "b",
+        log: log.clone(),
+    });
+
+    let task_a = async {
+        dev_a.write(&[0x01, 0x02]).await.unwrap();
+        let mut buf = [0u8; 2];
+        dev_a.read(&mut buf).await.unwrap();
+        assert_eq!(buf, [0x55, 0x55]);
+    };
+    let task_b = async {
+        let mut buf = [0u8; 2];
+        dev_b.transfer(&mut buf, &[0x03, 0x04]).await.unwrap();
+        assert_eq!(buf, [0x03, 0x04]);
+    };
+
+    block_on(join(task_a, task_b));
+
+    assert_eq!(
+        *log.borrow(),
+        vec![
+            "a low",
+            "write [1, 2] start",
+            "write [1, 2] done",
+            "flush",
+            "a high",
+            "b low",
+            "transfer [3, 4] start",
+            "transfer [3, 4] done",
+            "flush",
+            "b high",
+            "a low",
+            "read [85, 85] start",
+            "read [85, 85] done",
+            "flush",
+            "a high",
+        ]
+    );
+}
+
+#[test]
+fn async_spi_device_cancel() {
+    let log = Log::default();
+    let manager = shared_bus::BusManagerAsync::new(DmaBus { log: log.clone() });
+
+    let mut dev_a = manager.acquire_spi_device(CsPin {
+        name: "a",
+        log: log.clone(),
+    });
+    let mut dev_b = manager.acquire_spi_device(CsPin {
+        name: "b",
+        log: log.clone(),
+    });
+
+    block_on(async {
+        // Cancelled in the middle of a transfer
+        let mut using = Box::pin(dev_a.write(&[0x01]));
+        assert!(futures::poll!(using.as_mut()).is_pending());
+
+        // Cancelled while waiting for the bus
+        let mut waiting = Box::pin(dev_b.write(&[0x02]));
+        assert!(futures::poll!(waiting.as_mut()).is_pending());
+
+        drop(waiting);
+        drop(using);
+
+        // The bus is usable again
+        dev_b.write(&[0x03]).await.unwrap();
+    });
+
+    assert_eq!(
+        *log.borrow(),
+        vec![
+            "a low",
+            "write [1] start",
+            "a high",
+            "b low",
+            "write [3] start",
+            "write [3] done",
+            "flush",
+            "b high",
+        ]
+    );
+}
+
+/// Delay which logs its invocations.
+struct LogDelay {
+    log: Log,
+}
+
+impl embedded_hal_async::delay::DelayNs for LogDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.log.borrow_mut().push(format!("delay {}ns", ns));
+    }
+}
+
+#[test]
+fn async_spi_device_delay() {
+    use embedded_hal_async::spi::Operation;
+
+    let log = Log::default();
+    let manager = shared_bus::BusManagerAsync::new(DmaBus { log: log.clone() });
+    let mut device = manager.acquire_spi_device_with_delay(
+        CsPin {
+            name: "dev",
+            log: log.clone(),
+        },
+        LogDelay { log: log.clone() },
+    );
+
+    let mut data = [0u8; 1];
+    block_on(device.transaction(&mut [
+        Operation::Write(&[0x9f]),
+        Operation::DelayNs(10_000),
+        Operation::Read(&mut data),
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        *log.borrow(),
+        vec![
+            "dev low",
+            "write [159] start",
+            "write [159] done",
+            "flush",
+            "delay 10000ns",
+            "read [85] start",
+            "read [85] done",
+            "flush",
+            "dev high",
+        ]
+    );
+}
+
+#[test]
+fn async_spi_device_delay_missing() {
+    use embedded_hal_async::spi::Operation;
+
+    let log = Log::default();
+    let manager = shared_bus::BusManagerAsync::new(DmaBus { log: log.clone() });
+    let mut device = manager.acquire_spi_device(CsPin {
+        name: "dev",
+        log: log.clone(),
+    });
+
+    let res =
+        block_on(device.transaction(&mut [Operation::Write(&[0x9f]), Operation::DelayNs(10_000)]));
+    assert!(matches!(res, Err(shared_bus::SpiDeviceError::NoDelay)));
+
+    // The transaction is rejected before touching the bus
+    assert!(log.borrow().is_empty());
+}
+
+/// Bus which fails the first `failures` writes and in-place transfers.
+struct FlakyBus {
+    failures: u32,
+    log: Log,
+}
+
+impl embedded_hal_1::spi::ErrorType for FlakyBus {
+    type Error = embedded_hal_1::spi::ErrorKind;
+}
+
+impl embedded_hal_async::spi::SpiBus for FlakyBus {
+    async fn read(&mut self, _: &mut [u8]) -> Result<(), Self::Error> {
+        unimplemented!()
+    }
+
+    async fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        if self.failures > 0 {
+            self.failures -= 1;
+            self.log
+                .borrow_mut()
+                .push(format!("write {:?} failed", words));
+            return Err(embedded_hal_1::spi::ErrorKind::Other);
+        }
+        self.log.borrow_mut().push(format!("write {:?}", words));
+        Ok(())
+    }
+
+    async fn transfer(&mut self, _: &mut [u8], _: &[u8]) -> Result<(), Self::Error> {
+        unimplemented!()
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        if self.failures > 0 {
+            self.failures -= 1;
+            self.log
+                .borrow_mut()
+                .push(format!("transfer_in_place {:?} failed", words));
+            // The received data has already replaced the data to send
+            words.fill(0xff);
+            return Err(embedded_hal_1::spi::ErrorKind::Other);
+        }
+        self.log
+            .borrow_mut()
+            .push(format!("transfer_in_place {:?}", words));
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[test]
+fn async_spi_device_retrying() {
+    use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+
+    let log = Log::default();
+    let manager = shared_bus::BusManagerAsync::new(FlakyBus {
+        failures: 1,
+        log: log.clone(),
+    });
+
+    let mut delay = CheckedDelay::new(&[DelayTransaction::async_delay_ns(1000)]);
+    let policy = shared_bus::RetryPolicy::new(3, 1000, delay.clone());
+
+    let mut radio = manager.acquire_spi_device_retrying(
+        CsPin {
+            name: "radio",
+            log: log.clone(),
+        },
+        policy,
+    );
+
+    block_on(radio.write(&[0x01])).unwrap();
+
+    assert_eq!(
+        *log.borrow(),
+        vec![
+            "radio low",
+            "write [1] failed",
+            "radio high",
+            "radio low",
+            "write [1]",
+            "radio high",
+        ]
+    );
+
+    delay.done();
+}
+
+#[test]
+fn async_spi_device_retrying_releases_bus() {
+    let log = Log::default();
+    let manager = shared_bus::BusManagerAsync::new(FlakyBus {
+        failures: 2,
+        log: log.clone(),
+    });
+
+    /// Backoff delay which uses the bus through another device.
+    struct BusyDelay<D>(D);
+
+    impl<D: SpiDevice> embedded_hal_async::delay::DelayNs for BusyDelay<D> {
+        async fn delay_ns(&mut self, _ns: u32) {
+            // Would never complete if the retrying device still held the bus
+            let _ = self.0.write(&[0xff]).await;
+        }
+    }
+
+    let other = manager.acquire_spi_device(CsPin {
+        name: "other",
+        log: log.clone(),
+    });
+    let policy = shared_bus::RetryPolicy::new(2, 1000, BusyDelay(other));
+    let mut radio = manager.acquire_spi_device_retrying(
+        CsPin {
+            name: "radio",
+            log: log.clone(),
+        },
+        policy,
+    );
+
+    // The first write fails and so does the write of the other device during the backoff
+    block_on(radio.write(&[0x01])).unwrap();
+
+    assert_eq!(
+        *log.borrow(),
+        vec![
+            "radio low",
+            "write [1] failed",
+            "radio high",
+            "other low",
+            "write [255] failed",
+            "other high",
+            "radio low",
+            "write [1]",
+            "radio high",
+        ]
+    );
+}
+
+#[test]
+fn async_spi_device_retrying_transfer_in_place() {
+    use embedded_hal_mock::eh1::delay::CheckedDelay;
+
+    let log = Log::default();
+    let manager = shared_bus::BusManagerAsync::new(FlakyBus {
+        failures: 1,
+        log: log.clone(),
+    });
+
+    let mut delay = CheckedDelay::new(&[]);
+    let policy = shared_bus::RetryPolicy::new(3, 1000, delay.clone());
+    let mut radio = manager.acquire_spi_device_retrying(
+        CsPin {
+            name: "radio",
+            log: log.clone(),
+        },
+        policy,
+    );
+
+    // The failed attempt overwrote the buffer, so it must not be sent again
+    let mut buf = [0x01];
+    let res = block_on(radio.transfer_in_place(&mut buf));
+    assert!(matches!(res, Err(shared_bus::SpiDeviceError::Spi(_))));
+
+    assert_eq!(
+        *log.borrow(),
+        vec!["radio low", "transfer_in_place [1] failed", "radio high"]
+    );
+
+    delay.done();
+}
@@ -0,0 +1,142 @@
+//! Compile-time checks for the auto-traits and driver-facing bounds of managers and proxies.
+
+#[allow(dead_code)]
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[allow(dead_code)]
+struct SendBus;
+
+#[test]
+#[cfg(feature = "cortex-m")]
+fn cortexm_proxies_send_sync() {
+    assert_send_sync::<shared_bus::BusManagerCortexM<SendBus>>();
+    assert_send_sync::<shared_bus::I2cProxy<'static, shared_bus::CortexMMutex<SendBus>>>();
+    assert_send_sync::<shared_bus::AdcProxy<'static, shared_bus::CortexMMutex<SendBus>>>();
+}
+
+#[test]
+#[cfg(feature = "cortex-m")]
+fn atomic_check_proxies_send_sync() {
+    // Only for a `Send` bus, as the bus may be accessed from any thread holding the mutex.
+    assert_send_sync::<shared_bus::BusManagerAtomicCheck<SendBus>>();
+    assert_send_sync::<shared_bus::I2cProxy<'static, shared_bus::AtomicCheckMutex<SendBus>>>();
+}
+
+/// Bus which accepts all writes.
+#[allow(dead_code)]
+struct NopBus;
+
+impl embedded_hal::blocking::i2c::Write for NopBus {
+    type Error = ();
+
+    fn write(&mut self, _addr: u8, _bytes: &[u8]) -> Result<(), ()> {
+        Ok(())
+    }
+}
+
+impl embedded_hal::blocking::spi::Write<u8> for NopBus {
+    type Error = ();
+
+    fn write(&mut self, _words: &[u8]) -> Result<(), ()> {
+        Ok(())
+    }
+}
+
+/// Driver which takes the bus by value in a builder and hands it back on release.
+struct OwningDriver<I2C> {
+    i2c: I2C,
+}
+
+impl<I2C: embedded_hal::blocking::i2c::Write> OwningDriver<I2C> {
+    fn new(i2c: I2C) -> Self {
+        OwningDriver { i2c }
+    }
+
+    fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+/// Driver for a combo chip which clones the bus for each of its sub-devices.
+struct ComboDriver<I2C> {
+    accel: I2C,
+    mag: I2C,
+}
+
+impl<I2C> ComboDriver<I2C>
+where
+    I2C: embedded_hal::blocking::i2c::Write + Clone,
+{
+    fn new(i2c: I2C) -> Self {
+        ComboDriver {
+            accel: i2c.clone(),
+            mag: i2c,
+        }
+    }
+
+    fn poll(&mut self) -> Result<(), I2C::Error> {
+        self.accel.write(0x19, &[0x00])?;
+        self.mag.write(0x1e, &[0x00])
+    }
+}
+
+/// Driver which moves itself into a worker thread.
+#[cfg(feature = "std")]
+fn spawn_driver<I2C>(mut i2c: I2C) -> std::thread::JoinHandle<()>
+where
+    I2C: embedded_hal::blocking::i2c::Write + Clone + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let _ = i2c.write(0x39, &[0x00]);
+    })
+}
+
+/// SPI driver which keeps a second handle to the bus for a DMA helper.
+struct SpiDriver<SPI> {
+    spi: SPI,
+    _dma: SPI,
+}
+
+impl<SPI> SpiDriver<SPI>
+where
+    SPI: embedded_hal::blocking::spi::Write<u8> + Clone,
+{
+    fn new(spi: SPI) -> Self {
+        SpiDriver {
+            _dma: spi.clone(),
+            spi,
+        }
+    }
+
+    fn send(&mut self) -> Result<(), SPI::Error> {
+        self.spi.write(&[0x00])
+    }
+}
+
+#[test]
+fn i2c_proxy_driver_bounds() {
+    let bus = shared_bus::BusManagerSimple::new(NopBus);
+
+    let driver = OwningDriver::new(bus.acquire_i2c());
+    let proxy = driver.release();
+    let driver = OwningDriver::new(proxy);
+    let _ = driver.release();
+
+    let mut combo = ComboDriver::new(bus.acquire_i2c());
+    combo.poll().unwrap();
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn i2c_proxy_driver_bounds_send() {
+    let bus: &'static _ = Box::leak(Box::new(shared_bus::BusManagerStd::new(NopBus)));
+    spawn_driver(bus.acquire_i2c()).join().unwrap();
+}
+
+#[test]
+fn spi_proxy_driver_bounds() {
+    // The `SpiProxy` is deliberately not `Send`, so only `Clone` is checked here.
+    let bus = shared_bus::BusManagerSimple::new(NopBus);
+    let mut driver = SpiDriver::new(bus.acquire_spi());
+    driver.send().unwrap();
+}
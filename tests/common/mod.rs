@@ -0,0 +1,12 @@
+//! Fixtures shared by the async tests.
+
+/// Waker which counts how often it was woken.
+pub struct CountingWaker(pub std::sync::atomic::AtomicUsize);
+
+impl futures::task::ArcWake for CountingWaker {
+    fn wake_by_ref(arc_self: &std::sync::Arc<Self>) {
+        arc_self
+            .0
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+}
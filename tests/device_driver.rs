@@ -0,0 +1,93 @@
+#![cfg(all(feature = "device-driver", feature = "std"))]
+
+use embedded_hal_mock::eh1::digital::{Mock as PinMock, State, Transaction as PinTransaction};
+use embedded_hal_mock::eh1::i2c;
+use embedded_hal_mock::eh1::spi;
+
+device_driver::create_device!(
+    device_name: Sensor,
+    dsl: {
+        config {
+            type RegisterAddressType = u8;
+            type DefaultByteOrder = LE;
+        }
+        register Id {
+            const ADDRESS = 0x0f;
+            const SIZE_BITS = 8;
+            type Access = RO;
+            value: uint = 0..8,
+        },
+        register Config {
+            const ADDRESS = 0x20;
+            const SIZE_BITS = 16;
+            rate: uint = 0..4,
+            enable: bool = 15,
+        }
+    }
+);
+
+#[test]
+fn device_driver_i2c() {
+    let expect = vec![
+        i2c::Transaction::write_read(0x39, vec![0x0f], vec![0x5a]),
+        i2c::Transaction::transaction_start(0x39),
+        i2c::Transaction::write(0x39, vec![0x20]),
+        i2c::Transaction::write(0x39, vec![0x03, 0x80]),
+        i2c::Transaction::transaction_end(0x39),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut sensor = Sensor::new(shared_bus::I2cRegisterInterface::new(
+        manager.acquire_i2c(),
+        0x39,
+    ));
+
+    assert_eq!(sensor.id().read().unwrap().value(), 0x5a);
+    sensor
+        .config()
+        .write(|config| {
+            config.set_rate(3);
+            config.set_enable(true);
+        })
+        .unwrap();
+
+    device.done();
+}
+
+#[test]
+fn device_driver_spi() {
+    let expect = vec![
+        spi::Transaction::write(0x8f),
+        spi::Transaction::read(0x5a),
+        spi::Transaction::flush(),
+        spi::Transaction::write(0x20),
+        spi::Transaction::write_vec(vec![0x03, 0x80]),
+        spi::Transaction::flush(),
+    ];
+    let mut bus = spi::Mock::new(&expect);
+    let mut cs = PinMock::new(&[
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+    ]);
+
+    let manager: &'static _ = shared_bus::new_std!(spi::Mock<u8> = bus.clone()).unwrap();
+    let mut sensor = Sensor::new(
+        shared_bus::SpiRegisterInterface::new(manager.acquire_spi_device(cs.clone()))
+            .read_flag(0x80),
+    );
+
+    assert_eq!(sensor.id().read().unwrap().value(), 0x5a);
+    sensor
+        .config()
+        .write(|config| {
+            config.set_rate(3);
+            config.set_enable(true);
+        })
+        .unwrap();
+
+    bus.done();
+    cs.done();
+}
@@ -0,0 +1,41 @@
+#![cfg(all(feature = "diagnostics", feature = "std"))]
+
+use shared_bus::{BusManager, DiagnosticMutex, HolderId};
+use std::sync::mpsc;
+use std::thread;
+
+#[test]
+fn diagnostics_current_holder() {
+    let manager: &'static BusManager<DiagnosticMutex<std::sync::Mutex<()>>> =
+        Box::leak(Box::new(BusManager::new(())));
+    assert_eq!(manager.current_holder(), None);
+
+    let (locked_tx, locked_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    let holder = thread::spawn(move || {
+        manager.lock_exclusive(|_| {
+            locked_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+        })
+    });
+
+    locked_rx.recv().unwrap();
+    assert_eq!(
+        manager.current_holder(),
+        Some(HolderId::Thread(holder.thread().id()))
+    );
+
+    release_tx.send(()).unwrap();
+    holder.join().unwrap();
+    assert_eq!(manager.current_holder(), None);
+}
+
+#[test]
+fn diagnostics_task_id() {
+    let manager = BusManager::from_mutex(
+        DiagnosticMutex::<shared_bus::NullMutex<()>>::with_task_id((), || 42),
+    );
+
+    manager.lock_exclusive(|_| assert_eq!(manager.current_holder(), Some(HolderId::Task(42))));
+    assert_eq!(manager.current_holder(), None);
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "std")]
+
+use shared_bus::{BusMutex, CreateBusMutex};
+use std::thread;
+
+#[test]
+fn fair_std_fifo_order() {
+    let mutex: &'static _ = Box::leak(Box::new(shared_bus::FairStdMutex::create(Vec::new())));
+
+    let threads: Vec<_> = mutex.lock(|_| {
+        (0..8)
+            .map(|i| {
+                let t = thread::spawn(move || mutex.lock(|order: &mut Vec<_>| order.push(i)));
+                // Wait until the thread queued up, so the arrival order is known
+                while mutex.queue_len() < i + 2 {
+                    thread::yield_now();
+                }
+                t
+            })
+            .collect()
+    });
+    for t in threads {
+        t.join().unwrap();
+    }
+
+    assert_eq!(
+        mutex.lock(|order| order.clone()),
+        (0..8).collect::<Vec<_>>()
+    );
+    assert_eq!(mutex.queue_len(), 0);
+}
+
+#[test]
+fn fair_std_manager() {
+    let manager = shared_bus::new_fair_std!(u32 = 0).unwrap();
+    assert_eq!(manager.mutex_kind(), "FairStdMutex");
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| thread::spawn(move || manager.configure(|count| *count += 1)))
+        .collect();
+    for t in threads {
+        t.join().unwrap();
+    }
+    manager.configure(|count| assert_eq!(*count, 4));
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "ffi")]
+
+use embedded_hal_mock::eh0::i2c;
+use shared_bus::ffi::{SHARED_BUS_ERR_BUS, SHARED_BUS_ERR_NULL, SHARED_BUS_OK};
+use std::os::raw::c_int;
+
+/// The opaque handle type, as seen by C code.
+#[repr(C)]
+struct SharedBusI2c {
+    _private: [u8; 0],
+}
+
+// Declared like C code would see them, so the calls go through the C ABI.
+extern "C" {
+    fn shared_bus_i2c_acquire(handle: *const SharedBusI2c) -> *mut SharedBusI2c;
+    fn shared_bus_i2c_release(handle: *mut SharedBusI2c);
+    fn shared_bus_i2c_write(
+        handle: *mut SharedBusI2c,
+        addr: u8,
+        data: *const u8,
+        len: usize,
+    ) -> c_int;
+    fn shared_bus_i2c_read(handle: *mut SharedBusI2c, addr: u8, data: *mut u8, len: usize)
+        -> c_int;
+}
+
+#[test]
+fn ffi_i2c_round_trip() {
+    let expect = vec![
+        i2c::Transaction::write(0x39, vec![0xc0, 0xff, 0xee]),
+        i2c::Transaction::read(0x20, vec![0x12, 0x34]),
+        i2c::Transaction::write(0x20, vec![0x00]).with_error(
+            embedded_hal_mock::eh0::MockError::Io(std::io::ErrorKind::Other),
+        ),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let handle1 = shared_bus::ffi::i2c_handle_new(device.clone()) as *mut SharedBusI2c;
+    let handle2 = unsafe { shared_bus_i2c_acquire(handle1) };
+    assert!(!handle2.is_null());
+
+    let data = [0xc0, 0xff, 0xee];
+    let res = unsafe { shared_bus_i2c_write(handle1, 0x39, data.as_ptr(), data.len()) };
+    assert_eq!(res, SHARED_BUS_OK);
+
+    // The first handle is gone, but the bus must stay alive for the second one.
+    unsafe { shared_bus_i2c_release(handle1) };
+
+    let mut buf = [0u8; 2];
+    let res = unsafe { shared_bus_i2c_read(handle2, 0x20, buf.as_mut_ptr(), buf.len()) };
+    assert_eq!(res, SHARED_BUS_OK);
+    assert_eq!(buf, [0x12, 0x34]);
+
+    let res = unsafe { shared_bus_i2c_write(handle2, 0x20, [0x00].as_ptr(), 1) };
+    assert_eq!(res, SHARED_BUS_ERR_BUS);
+
+    let res = unsafe { shared_bus_i2c_write(handle2, 0x20, std::ptr::null(), 0) };
+    assert_eq!(res, SHARED_BUS_ERR_NULL);
+    let res = unsafe { shared_bus_i2c_write(std::ptr::null_mut(), 0x20, data.as_ptr(), 1) };
+    assert_eq!(res, SHARED_BUS_ERR_NULL);
+
+    unsafe { shared_bus_i2c_release(handle2) };
+
+    device.done();
+}
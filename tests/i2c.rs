@@ -1,5 +1,5 @@
 use embedded_hal::prelude::*;
-use embedded_hal_mock::i2c;
+use embedded_hal_mock::eh0::i2c;
 use std::thread;
 
 #[test]
@@ -35,6 +35,29 @@ fn i2c_manager_macro() {
     device.done();
 }
 
+#[test]
+fn i2c_manager_leaked() {
+    let expect = vec![
+        i2c::Transaction::write(0xde, vec![0xad, 0xbe, 0xef]),
+        i2c::Transaction::write(0xde, vec![0x01]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+    let manager: &'static shared_bus::BusManagerStd<_> =
+        Box::leak(Box::new(shared_bus::BusManagerStd::new(device.clone())));
+    let mut proxy1 = manager.acquire_i2c();
+    let mut proxy2 = manager.acquire_i2c();
+
+    thread::spawn(move || {
+        proxy1.write(0xde, &[0xad, 0xbe, 0xef]).unwrap();
+    })
+    .join()
+    .unwrap();
+
+    proxy2.write(0xde, &[0x01]).unwrap();
+
+    device.done();
+}
+
 #[test]
 fn i2c_proxy() {
     let expect = vec![
@@ -115,3 +138,2010 @@ fn i2c_concurrent() {
 
     device.done();
 }
+
+#[test]
+fn i2c_proxy_shared_ref() {
+    let expect = vec![
+        i2c::Transaction::write(0xde, vec![0xad, 0xbe, 0xef]),
+        i2c::Transaction::write_read(0x44, vec![0x01, 0x02], vec![0x03, 0x04]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    struct Holder<'a> {
+        proxy: shared_bus::I2cProxy<'a, shared_bus::NullMutex<i2c::Mock>>,
+    }
+
+    fn do_write<I: embedded_hal::blocking::i2c::Write>(mut bus: I) {
+        bus.write(0xde, &[0xad, 0xbe, 0xef]).ok().unwrap();
+    }
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let holder = Holder {
+        proxy: manager.acquire_i2c(),
+    };
+
+    do_write(&holder.proxy);
+
+    let mut buf = [0u8; 2];
+    (&holder.proxy)
+        .write_read(0x44, &[0x01, 0x02], &mut buf)
+        .unwrap();
+    assert_eq!(&buf, &[0x03, 0x04]);
+
+    device.done();
+}
+
+#[test]
+fn i2c_manager_as_proxy() {
+    let expect = vec![
+        i2c::Transaction::write(0xde, vec![0xad, 0xbe, 0xef]),
+        i2c::Transaction::write_read(0x44, vec![0x01, 0x02], vec![0x03, 0x04]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    struct Driver<I>(I);
+
+    impl<I, E> Driver<I>
+    where
+        I: embedded_hal::blocking::i2c::Write<Error = E>
+            + embedded_hal::blocking::i2c::WriteRead<Error = E>,
+    {
+        fn run(&mut self) -> Result<[u8; 2], E> {
+            self.0.write(0xde, &[0xad, 0xbe, 0xef])?;
+            let mut buf = [0u8; 2];
+            self.0.write_read(0x44, &[0x01, 0x02], &mut buf)?;
+            Ok(buf)
+        }
+    }
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut driver = Driver(&manager);
+
+    assert_eq!(driver.run().unwrap(), [0x03, 0x04]);
+
+    device.done();
+}
+
+#[test]
+fn i2c_segment() {
+    let expect = vec![
+        i2c::Transaction::write(0x70, vec![0x01]),
+        i2c::Transaction::write(0x48, vec![0xab]),
+        i2c::Transaction::write(0x70, vec![0x02]),
+        i2c::Transaction::write_read(0x48, vec![0x00], vec![0x12, 0x34]),
+        i2c::Transaction::write(0x70, vec![0x01]),
+        i2c::Transaction::read(0x48, vec![0x56]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let channel0 = manager.segment(|bus: &mut i2c::Mock| bus.write(0x70, &[0x01]));
+    let channel1 = manager.segment(|bus: &mut i2c::Mock| bus.write(0x70, &[0x02]));
+    let mut proxy0 = channel0.acquire_i2c();
+    let mut proxy1 = channel1.acquire_i2c();
+
+    proxy0.write(0x48, &[0xab]).unwrap();
+
+    let mut buf = [0u8; 2];
+    proxy1.write_read(0x48, &[0x00], &mut buf).unwrap();
+    assert_eq!(&buf, &[0x12, 0x34]);
+
+    let mut buf = [0u8; 1];
+    proxy0.read(0x48, &mut buf).unwrap();
+    assert_eq!(&buf, &[0x56]);
+
+    device.done();
+}
+
+#[test]
+#[cfg(feature = "critical-section")]
+fn i2c_manager_auto() {
+    let expect = vec![i2c::Transaction::write(0xde, vec![0xad, 0xbe, 0xef])];
+    let mut device = i2c::Mock::new(&expect);
+    let manager: &'static shared_bus::BusManagerAuto<_> =
+        shared_bus::new_auto!(i2c::Mock = device.clone()).unwrap();
+    let mut proxy = manager.acquire_i2c();
+
+    thread::spawn(move || {
+        proxy.write(0xde, &[0xad, 0xbe, 0xef]).unwrap();
+    })
+    .join()
+    .unwrap();
+
+    device.done();
+}
+
+#[test]
+fn i2c_manager_macro_once() {
+    fn create(
+        device: i2c::Mock,
+    ) -> Result<&'static shared_bus::BusManagerStd<i2c::Mock>, shared_bus::BusManagerError<i2c::Mock>>
+    {
+        shared_bus::new_std!(i2c::Mock = device)
+    }
+
+    let expect = vec![i2c::Transaction::write(0x39, vec![0xc0])];
+    let mut device = i2c::Mock::new(&expect);
+
+    assert!(create(device.clone()).is_ok());
+    let err = create(device.clone()).unwrap_err();
+    assert!(matches!(
+        err,
+        shared_bus::BusManagerError::AlreadyInitialized(_)
+    ));
+    assert_eq!(format!("{:?}", err), "AlreadyInitialized(..)");
+
+    // The bus is handed back
+    err.into_bus().write(0x39, &[0xc0]).unwrap();
+
+    device.done();
+}
+
+#[cfg(feature = "eh1")]
+mod eh1 {
+    use embedded_hal_1::i2c::{I2c, Operation};
+    use embedded_hal_mock::eh1::i2c;
+
+    #[test]
+    fn i2c_transaction() {
+        let expect = vec![
+            i2c::Transaction::transaction_start(0x44),
+            i2c::Transaction::write(0x44, vec![0x01]),
+            i2c::Transaction::read(0x44, vec![0x02, 0x03]),
+            i2c::Transaction::transaction_end(0x44),
+        ];
+        let mut device = i2c::Mock::new(&expect);
+
+        let manager = shared_bus::BusManagerSimple::new(device.clone());
+        let mut proxy = manager.acquire_i2c();
+
+        let mut buf = [0u8; 2];
+        proxy
+            .transaction(
+                0x44,
+                &mut [Operation::Write(&[0x01]), Operation::Read(&mut buf)],
+            )
+            .unwrap();
+        assert_eq!(&buf, &[0x02, 0x03]);
+
+        device.done();
+    }
+
+    #[test]
+    fn i2c_eh0_compat() {
+        use embedded_hal::blocking::i2c as i2c_eh0;
+
+        /// Legacy driver which only speaks `embedded-hal` 0.2.
+        struct Driver<I2C>(I2C);
+
+        impl<I2C, E> Driver<I2C>
+        where
+            I2C: i2c_eh0::Write<Error = E> + i2c_eh0::WriteRead<Error = E>,
+        {
+            fn read_register(&mut self, reg: u8) -> Result<u8, E> {
+                let mut buf = [0u8];
+                self.0.write_read(0x44, &[reg], &mut buf)?;
+                Ok(buf[0])
+            }
+
+            fn write_register(&mut self, reg: u8, value: u8) -> Result<(), E> {
+                self.0.write(0x44, &[reg, value])
+            }
+        }
+
+        let expect = vec![
+            i2c::Transaction::write_read(0x44, vec![0x0f], vec![0x42]),
+            i2c::Transaction::write(0x44, vec![0x10, 0x01]),
+        ];
+        let mut device = i2c::Mock::new(&expect);
+
+        let manager = shared_bus::BusManagerSimple::new(device.clone());
+        let proxy = manager.acquire_i2c();
+        let mut driver = Driver(proxy.as_eh0());
+
+        assert_eq!(driver.read_register(0x0f).unwrap(), 0x42);
+        driver.write_register(0x10, 0x01).unwrap();
+
+        device.done();
+    }
+
+    /// Bus condition, as recorded by the `ConditionBus`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Cond {
+        Start(u8),
+        Write,
+        Read,
+        Stop,
+    }
+
+    /// Bus which records the conditions it would generate on the wire.
+    struct ConditionBus(std::sync::Arc<std::sync::Mutex<Vec<Cond>>>);
+
+    impl embedded_hal_1::i2c::ErrorType for ConditionBus {
+        type Error = core::convert::Infallible;
+    }
+
+    impl I2c for ConditionBus {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            for op in operations.iter() {
+                // A start for the first operation, a repeated start between write and read.
+                let cond = match op {
+                    Operation::Write(_) => Cond::Write,
+                    Operation::Read(_) => Cond::Read,
+                };
+                let mut log = self.0.lock().unwrap();
+                if log.last() != Some(&cond) {
+                    log.push(Cond::Start(address));
+                }
+                log.push(cond);
+                drop(log);
+                std::thread::yield_now();
+            }
+            self.0.lock().unwrap().push(Cond::Stop);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn i2c_repeated_start() {
+        let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let manager: &'static _ = Box::leak(Box::new(shared_bus::BusManagerStd::new(
+            ConditionBus(log.clone()),
+        )));
+
+        let threads: Vec<_> = (0x40..0x44)
+            .map(|addr| {
+                let mut proxy = manager.acquire_i2c();
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        let mut buf = [0u8; 2];
+                        proxy.write_read(addr, &[0x01], &mut buf).unwrap();
+                        proxy
+                            .transaction(
+                                addr,
+                                &mut [Operation::Write(&[0x02]), Operation::Read(&mut buf)],
+                            )
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        // Every sequence is start, write, repeated start, read, stop: There is never a stop (or
+        // another device's start) between the write and read phases.
+        let log = log.lock().unwrap();
+        assert_eq!(log.len(), 4 * 50 * 2 * 5);
+        for seq in log.chunks(5) {
+            match seq {
+                [Cond::Start(a), Cond::Write, Cond::Start(b), Cond::Read, Cond::Stop] => {
+                    assert_eq!(a, b)
+                }
+                _ => panic!("broken sequence {:?}", seq),
+            }
+        }
+    }
+
+    #[test]
+    fn i2c_transaction_empty() {
+        let mut device = i2c::Mock::new(&[]);
+
+        let manager = shared_bus::BusManagerSimple::new(device.clone());
+        let mut proxy = manager.acquire_i2c();
+
+        proxy.transaction(0x44, &mut []).unwrap();
+        (&proxy).transaction(0x44, &mut []).unwrap();
+
+        device.done();
+    }
+
+    #[test]
+    fn i2c_borrow() {
+        let expect = vec![
+            i2c::Transaction::write(0x39, vec![0x80, 0x03]),
+            i2c::Transaction::write(0x48, vec![0x01, 0x60]),
+        ];
+        let mut device = i2c::Mock::new(&expect);
+
+        let manager = shared_bus::BusManagerSimple::new(device.clone());
+
+        let res = manager.borrow_i2c(|i2c| {
+            i2c.write(0x39, &[0x80, 0x03])?;
+            i2c.write(0x48, &[0x01, 0x60])
+        });
+        assert!(res.is_ok());
+
+        device.done();
+    }
+
+    #[test]
+    fn i2c_chunked_transaction() {
+        let expect = vec![
+            i2c::Transaction::write(0x50, vec![0xaa; 4]),
+            i2c::Transaction::write(0x51, vec![0xbb; 2]),
+            i2c::Transaction::read(0x50, vec![0xcc; 4]),
+            i2c::Transaction::read(0x51, vec![0xdd; 1]),
+            i2c::Transaction::write_read(0x50, vec![0x10], vec![0xee; 4]),
+            i2c::Transaction::write_read(0x50, vec![0x14], vec![0xff; 4]),
+            // Other transactions are passed on unchanged
+            i2c::Transaction::transaction_start(0x50),
+            i2c::Transaction::write(0x50, vec![0x01]),
+            i2c::Transaction::write(0x50, vec![0x02; 6]),
+            i2c::Transaction::transaction_end(0x50),
+        ];
+        let mut device = i2c::Mock::new(&expect);
+
+        let manager = shared_bus::BusManagerSimple::new(device.clone());
+        let mut proxy = manager.acquire_i2c_chunked(4, |offset, addr: &mut u8, mem: &mut [u8]| {
+            if mem.is_empty() {
+                *addr += 1;
+            } else {
+                mem[0] += offset as u8;
+            }
+        });
+
+        let mut data = [0xaa; 6];
+        data[4..].fill(0xbb);
+        proxy
+            .transaction(0x50, &mut [Operation::Write(&data)])
+            .unwrap();
+
+        let mut buf = [0u8; 5];
+        proxy
+            .transaction(0x50, &mut [Operation::Read(&mut buf)])
+            .unwrap();
+        assert_eq!(&buf, &[0xcc, 0xcc, 0xcc, 0xcc, 0xdd]);
+
+        let mut buf = [0u8; 8];
+        proxy
+            .transaction(
+                0x50,
+                &mut [Operation::Write(&[0x10]), Operation::Read(&mut buf)],
+            )
+            .unwrap();
+        assert_eq!(&buf, &[0xee, 0xee, 0xee, 0xee, 0xff, 0xff, 0xff, 0xff]);
+
+        proxy
+            .transaction(
+                0x50,
+                &mut [Operation::Write(&[0x01]), Operation::Write(&[0x02; 6])],
+            )
+            .unwrap();
+
+        device.done();
+    }
+
+    #[test]
+    fn i2c_segment_transaction() {
+        let expect = vec![
+            i2c::Transaction::write(0x70, vec![0x01]),
+            i2c::Transaction::transaction_start(0x48),
+            i2c::Transaction::write(0x48, vec![0x00]),
+            i2c::Transaction::read(0x48, vec![0x12, 0x34]),
+            i2c::Transaction::transaction_end(0x48),
+            i2c::Transaction::write(0x70, vec![0x02]),
+            i2c::Transaction::transaction_start(0x48),
+            i2c::Transaction::write(0x48, vec![0xab]),
+            i2c::Transaction::transaction_end(0x48),
+        ];
+        let mut device = i2c::Mock::new(&expect);
+
+        let manager = shared_bus::BusManagerSimple::new(device.clone());
+        let channel0 = manager.segment(|bus: &mut i2c::Mock| bus.write(0x70, &[0x01]));
+        let channel1 = manager.segment(|bus: &mut i2c::Mock| bus.write(0x70, &[0x02]));
+        let mut proxy0 = channel0.acquire_i2c();
+        let mut proxy1 = channel1.acquire_i2c();
+
+        let mut buf = [0u8; 2];
+        proxy0
+            .transaction(
+                0x48,
+                &mut [Operation::Write(&[0x00]), Operation::Read(&mut buf)],
+            )
+            .unwrap();
+        assert_eq!(&buf, &[0x12, 0x34]);
+
+        proxy1
+            .transaction(0x48, &mut [Operation::Write(&[0xab])])
+            .unwrap();
+
+        // Empty transactions do not touch the bus, so the segment is not selected either.
+        proxy0.transaction(0x48, &mut []).unwrap();
+
+        device.done();
+    }
+
+    /// Bus which only speaks 10-bit addresses and reads back the lower address byte.
+    struct TenBitBus;
+
+    impl embedded_hal_1::i2c::ErrorType for TenBitBus {
+        type Error = core::convert::Infallible;
+    }
+
+    impl I2c<embedded_hal_1::i2c::TenBitAddress> for TenBitBus {
+        fn transaction(
+            &mut self,
+            address: u16,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            for op in operations.iter_mut() {
+                if let Operation::Read(buf) = op {
+                    buf.fill(address as u8);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn i2c_traced_ten_bit() {
+        let mut trace = Vec::new();
+        let manager = shared_bus::BusManagerSimple::new(TenBitBus);
+        {
+            let mut proxy = manager.acquire_i2c_traced_ten_bit(|addr, dir, bytes: &[u8]| {
+                trace.push((addr, dir, bytes.to_vec()));
+            });
+
+            let mut buf = [0u8; 2];
+            proxy.write_read(0x2a5, &[0x01], &mut buf).unwrap();
+            assert_eq!(&buf, &[0xa5, 0xa5]);
+        }
+
+        use shared_bus::Direction::{Read, Write};
+        assert_eq!(
+            trace,
+            vec![(0x2a5, Write, vec![0x01]), (0x2a5, Read, vec![0xa5, 0xa5])]
+        );
+    }
+
+    #[test]
+    fn i2c_traced_clone() {
+        let expect = vec![
+            i2c::Transaction::transaction_start(0x44),
+            i2c::Transaction::write(0x44, vec![0x01]),
+            i2c::Transaction::transaction_end(0x44),
+            i2c::Transaction::transaction_start(0x45),
+            i2c::Transaction::write(0x45, vec![0x02]),
+            i2c::Transaction::transaction_end(0x45),
+        ];
+        let mut device = i2c::Mock::new(&expect);
+
+        let count = std::cell::Cell::new(0);
+        let manager = shared_bus::BusManagerSimple::new(device.clone());
+        let mut proxy = manager.acquire_i2c_traced(|_, _, _: &[u8]| count.set(count.get() + 1));
+        let mut clone = proxy.clone();
+
+        proxy.write(0x44, &[0x01]).unwrap();
+        clone.write(0x45, &[0x02]).unwrap();
+        assert_eq!(count.get(), 2);
+
+        device.done();
+    }
+}
+
+#[test]
+fn i2c_traced() {
+    let expect = vec![
+        i2c::Transaction::write(0xde, vec![0xad, 0xbe, 0xef]),
+        i2c::Transaction::read(0xef, vec![0xbe, 0xad, 0xde]),
+        i2c::Transaction::write_read(0x44, vec![0x01, 0x02], vec![0x03, 0x04]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let mut trace = Vec::new();
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    {
+        let mut proxy = manager.acquire_i2c_traced(|addr, dir, bytes: &[u8]| {
+            trace.push((addr, dir, bytes.to_vec()));
+        });
+
+        proxy.write(0xde, &[0xad, 0xbe, 0xef]).unwrap();
+
+        let mut buf = [0u8; 3];
+        proxy.read(0xef, &mut buf).unwrap();
+
+        let mut buf = [0u8; 2];
+        proxy.write_read(0x44, &[0x01, 0x02], &mut buf).unwrap();
+    }
+
+    use shared_bus::Direction::{Read, Write};
+    assert_eq!(
+        trace,
+        vec![
+            (0xde, Write, vec![0xad, 0xbe, 0xef]),
+            (0xef, Read, vec![0xbe, 0xad, 0xde]),
+            (0x44, Write, vec![0x01, 0x02]),
+            (0x44, Read, vec![0x03, 0x04]),
+        ]
+    );
+
+    device.done();
+}
+
+#[test]
+fn i2c_retrying() {
+    use embedded_hal_mock::eh0::MockError;
+    use std::io::ErrorKind;
+
+    let expect = vec![
+        i2c::Transaction::write(0xde, vec![0xad]).with_error(MockError::Io(ErrorKind::Interrupted)),
+        i2c::Transaction::write(0xde, vec![0xad]),
+        i2c::Transaction::read(0xef, vec![0xbe]).with_error(MockError::Io(ErrorKind::Interrupted)),
+        i2c::Transaction::read(0xef, vec![0xbe]).with_error(MockError::Io(ErrorKind::Interrupted)),
+        i2c::Transaction::read(0xef, vec![0xbe]).with_error(MockError::Io(ErrorKind::Other)),
+        i2c::Transaction::write_read(0x44, vec![0x01], vec![0x02])
+            .with_error(MockError::Io(ErrorKind::Interrupted)),
+        i2c::Transaction::write_read(0x44, vec![0x01], vec![0x02])
+            .with_error(MockError::Io(ErrorKind::Interrupted)),
+        i2c::Transaction::write_read(0x44, vec![0x01], vec![0x02])
+            .with_error(MockError::Io(ErrorKind::Interrupted)),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut proxy = manager.acquire_i2c_retrying(2, |e: &MockError| {
+        *e == MockError::Io(ErrorKind::Interrupted)
+    });
+
+    // Retried once, then succeeds
+    proxy.write(0xde, &[0xad]).unwrap();
+
+    // Non-retryable errors are passed through
+    let mut buf = [0u8; 1];
+    assert_eq!(
+        proxy.read(0xef, &mut buf),
+        Err(MockError::Io(ErrorKind::Other))
+    );
+
+    // Gives up after the given number of retries
+    assert_eq!(
+        proxy.write_read(0x44, &[0x01], &mut buf),
+        Err(MockError::Io(ErrorKind::Interrupted))
+    );
+
+    device.done();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "callback re-entered the bus")]
+fn i2c_retrying_reentrant_predicate() {
+    use embedded_hal_mock::eh0::MockError;
+    use std::io::ErrorKind;
+
+    let expect =
+        vec![i2c::Transaction::write(0xde, vec![0xad])
+            .with_error(MockError::Io(ErrorKind::Interrupted))];
+    let device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerStd::new(device);
+    let logger = manager.acquire_i2c();
+    let mut proxy = manager.acquire_i2c_retrying(1, |_: &MockError| {
+        // Logging through the same bus from within the predicate would deadlock
+        let _ = embedded_hal::blocking::i2c::Write::write(&mut &logger, 0x20, &[0x00]);
+        true
+    });
+
+    let _ = proxy.write(0xde, &[0xad]);
+}
+
+#[test]
+fn i2c_manager_bounded() {
+    let expect = vec![
+        i2c::Transaction::write(0xde, vec![0xad]),
+        i2c::Transaction::write(0xef, vec![0xbe]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager: shared_bus::BoundedBusManager<shared_bus::NullMutex<_>, 2> =
+        shared_bus::BoundedBusManager::new(device.clone());
+    assert_eq!(manager.remaining(), 2);
+
+    let mut proxy1 = manager.acquire_i2c_checked().unwrap();
+    let mut proxy2 = manager.acquire_i2c_checked().unwrap();
+    assert_eq!(manager.remaining(), 0);
+    assert!(manager.acquire_i2c_checked().is_none());
+
+    proxy1.write(0xde, &[0xad]).unwrap();
+    proxy2.write(0xef, &[0xbe]).unwrap();
+
+    // Dropping a proxy gives its slot back
+    drop(proxy1);
+    assert_eq!(manager.remaining(), 1);
+    assert!(manager.acquire_i2c_checked().is_some());
+    assert_eq!(manager.remaining(), 1);
+
+    device.done();
+}
+
+#[test]
+fn i2c_manager_bounded_threads() {
+    struct NopBus;
+
+    impl embedded_hal::blocking::i2c::Write for NopBus {
+        type Error = ();
+
+        fn write(&mut self, _: u8, _: &[u8]) -> Result<(), ()> {
+            Ok(())
+        }
+    }
+
+    static BUS: shared_bus::BoundedBusManager<std::sync::Mutex<NopBus>, 2> =
+        shared_bus::BoundedBusManager::from_mutex(std::sync::Mutex::new(NopBus));
+
+    let mut proxy1 = BUS.acquire_i2c_checked().unwrap();
+    thread::scope(|s| {
+        s.spawn(|| {
+            let mut proxy2 = BUS.acquire_i2c_checked().unwrap();
+            assert!(BUS.acquire_i2c_checked().is_none());
+            proxy2.write(0x39, &[0x00]).unwrap();
+        });
+    });
+    assert_eq!(BUS.remaining(), 1);
+
+    proxy1.write(0x39, &[0x00]).unwrap();
+}
+
+#[test]
+fn i2c_manager_upgrade_std() {
+    let expect = vec![
+        i2c::Transaction::write(0xde, vec![0xad, 0xbe, 0xef]),
+        i2c::Transaction::write(0xef, vec![0xbe, 0xad, 0xde]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    manager
+        .acquire_i2c()
+        .write(0xde, &[0xad, 0xbe, 0xef])
+        .unwrap();
+
+    let manager = manager.upgrade_std();
+    let mut proxy = manager.acquire_i2c();
+    thread::scope(|s| {
+        s.spawn(move || {
+            proxy.write(0xef, &[0xbe, 0xad, 0xde]).unwrap();
+        });
+    });
+
+    device.done();
+}
+
+#[test]
+fn i2c_manager_mutex_kind() {
+    let mut device = i2c::Mock::new(&[]);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    assert_eq!(manager.mutex_kind(), "NullMutex");
+
+    let manager = manager.upgrade_std();
+    assert_eq!(manager.mutex_kind(), "std::sync::Mutex");
+
+    let manager = shared_bus::BusManager::<CountingMutex<_>>::new(device.clone());
+    assert_eq!(manager.mutex_kind(), "custom");
+
+    device.done();
+}
+
+#[test]
+fn i2c_manager_configure() {
+    struct Hal {
+        i2c: i2c::Mock,
+        speed: u32,
+    }
+
+    impl embedded_hal::blocking::i2c::Write for Hal {
+        type Error = embedded_hal_mock::eh0::MockError;
+
+        fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+            assert_eq!(self.speed, 400_000);
+            self.i2c.write(addr, bytes)
+        }
+    }
+
+    let expect = vec![i2c::Transaction::write(0xde, vec![0xad, 0xbe, 0xef])];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(Hal {
+        i2c: device.clone(),
+        speed: 100_000,
+    });
+    let mut proxy = manager.acquire_i2c();
+
+    let old = manager.configure(|hal| core::mem::replace(&mut hal.speed, 400_000));
+    assert_eq!(old, 100_000);
+
+    proxy.write(0xde, &[0xad, 0xbe, 0xef]).unwrap();
+
+    device.done();
+}
+
+#[test]
+fn i2c_manager_execute() {
+    struct Unlock {
+        addr: u8,
+        config: u8,
+    }
+
+    impl<B: embedded_hal::blocking::i2c::Write> shared_bus::BusTransaction<B> for Unlock {
+        type Output = Result<(), B::Error>;
+
+        fn run(self, bus: &mut B) -> Self::Output {
+            bus.write(self.addr, &[0x55])?;
+            bus.write(self.addr, &[0xaa])?;
+            bus.write(self.addr, &[0x01, self.config])
+        }
+    }
+
+    let expect = vec![
+        i2c::Transaction::write(0x39, vec![0x55]),
+        i2c::Transaction::write(0x39, vec![0xaa]),
+        i2c::Transaction::write(0x39, vec![0x01, 0x80]),
+        i2c::Transaction::write(0x39, vec![0x02]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut proxy = manager.acquire_i2c();
+
+    manager
+        .execute(Unlock {
+            addr: 0x39,
+            config: 0x80,
+        })
+        .unwrap();
+    proxy.write(0x39, &[0x02]).unwrap();
+
+    device.done();
+}
+
+/// Bus abstraction of a hypothetical driver crate.
+trait RegisterBus {
+    type Error;
+
+    fn read_register(&mut self, reg: u8) -> Result<u8, Self::Error>;
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), Self::Error>;
+}
+
+struct I2cInterface<I2C>(I2C);
+
+impl<I2C> From<I2C> for I2cInterface<I2C> {
+    fn from(i2c: I2C) -> Self {
+        I2cInterface(i2c)
+    }
+}
+
+impl<E, I2C> RegisterBus for I2cInterface<I2C>
+where
+    I2C: embedded_hal::blocking::i2c::WriteRead<Error = E>
+        + embedded_hal::blocking::i2c::Write<Error = E>,
+{
+    type Error = E;
+
+    fn read_register(&mut self, reg: u8) -> Result<u8, E> {
+        let mut buf = [0u8];
+        self.0.write_read(0x20, &[reg], &mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), E> {
+        self.0.write(0x20, &[reg, value])
+    }
+}
+
+#[test]
+fn i2c_acquire_into() {
+    let expect = vec![
+        i2c::Transaction::write_read(0x20, vec![0x0f], vec![0x42]),
+        i2c::Transaction::write(0x20, vec![0x10, 0x01]),
+        i2c::Transaction::write(0x20, vec![0x11, 0x02]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut interface1: I2cInterface<_> = manager.acquire_i2c_into();
+    let mut interface2: I2cInterface<_> = manager.acquire_i2c_into();
+
+    assert_eq!(interface1.read_register(0x0f).unwrap(), 0x42);
+    interface1.write_register(0x10, 0x01).unwrap();
+    interface2.write_register(0x11, 0x02).unwrap();
+
+    device.done();
+}
+
+#[test]
+fn i2c_map_err() {
+    use embedded_hal_mock::eh0::MockError;
+    use std::io::ErrorKind;
+
+    #[derive(Debug, PartialEq)]
+    enum AppError {
+        Bus(ErrorKind),
+    }
+
+    let expect = vec![
+        i2c::Transaction::write(0xde, vec![0xad]),
+        i2c::Transaction::write_read(0x44, vec![0x01], vec![0x02])
+            .with_error(MockError::Io(ErrorKind::TimedOut)),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut proxy = manager.acquire_i2c_map_err(|MockError::Io(kind)| AppError::Bus(kind));
+
+    proxy.write(0xde, &[0xad]).unwrap();
+
+    let mut buf = [0u8; 1];
+    assert_eq!(
+        proxy.write_read(0x44, &[0x01], &mut buf),
+        Err(AppError::Bus(ErrorKind::TimedOut))
+    );
+
+    device.done();
+}
+
+#[test]
+fn i2c_format_bus() {
+    use embedded_hal_mock::eh0::MockError;
+    use std::io::ErrorKind;
+
+    let expect = vec![
+        i2c::Transaction::write(0x39, vec![0xc0, 0xff, 0xee]),
+        i2c::Transaction::write_read(0x44, vec![0x01], vec![0x12, 0x34]),
+        i2c::Transaction::read(0x44, vec![0x00]).with_error(MockError::Io(ErrorKind::Other)),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(shared_bus::FormatBus::new(
+        device.clone(),
+        String::new(),
+    ));
+    let mut proxy1 = manager.acquire_i2c();
+    let mut proxy2 = manager.acquire_i2c();
+
+    proxy1.write(0x39, &[0xc0, 0xff, 0xee]).unwrap();
+    let mut buf = [0u8; 2];
+    proxy2.write_read(0x44, &[0x01], &mut buf).unwrap();
+    let mut buf = [0u8; 1];
+    proxy2.read(0x44, &mut buf).unwrap_err();
+
+    assert_eq!(
+        manager.configure(|bus| bus.sink().clone()),
+        "I2C 39 W: C0 FF EE\nI2C 44 W: 01\nI2C 44 R: 12 34\nI2C 44 R: 00 (failed)\n"
+    );
+
+    device.done();
+}
+
+/// Mutex which counts how often it was locked.
+struct CountingMutex<T> {
+    bus: std::cell::RefCell<T>,
+    locks: std::rc::Rc<std::cell::Cell<usize>>,
+}
+
+impl<T> shared_bus::BusMutex for CountingMutex<T> {
+    type Bus = T;
+
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R {
+        self.locks.set(self.locks.get() + 1);
+        f(&mut self.bus.borrow_mut())
+    }
+}
+
+impl<T> shared_bus::CreateBusMutex for CountingMutex<T> {
+    fn create(v: T) -> Self {
+        CountingMutex {
+            bus: std::cell::RefCell::new(v),
+            locks: Default::default(),
+        }
+    }
+}
+
+#[test]
+fn i2c_modify_register() {
+    let expect = vec![
+        i2c::Transaction::write_read(0x39, vec![0x80], vec![0xf0]),
+        i2c::Transaction::write(0x39, vec![0x80, 0xf1]),
+        i2c::Transaction::write_read(0x50, vec![0x12, 0x34], vec![0xff]),
+        i2c::Transaction::write(0x50, vec![0x12, 0x34, 0x7f]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let locks = std::rc::Rc::new(std::cell::Cell::new(0));
+    let manager = shared_bus::BusManager::from_mutex(CountingMutex {
+        bus: std::cell::RefCell::new(device.clone()),
+        locks: locks.clone(),
+    });
+    let mut proxy = manager.acquire_i2c();
+
+    proxy.modify_register(0x39, 0x80, |v| v | 0x01).unwrap();
+    assert_eq!(locks.get(), 1);
+
+    proxy.modify_register16(0x50, 0x1234, |v| v & 0x7f).unwrap();
+    assert_eq!(locks.get(), 2);
+
+    device.done();
+}
+
+#[test]
+fn i2c_lock_exclusive() {
+    let expect = vec![
+        i2c::Transaction::write(0x50, vec![0x00, 0x01]),
+        i2c::Transaction::write(0x50, vec![0x02, 0x03]),
+        i2c::Transaction::write(0x39, vec![0xff]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut proxy = manager.acquire_i2c();
+
+    let res = manager.lock_exclusive(|mut bus| {
+        bus.write(0x50, &[0x00, 0x01])?;
+        bus.write(0x50, &[0x02, 0x03])?;
+        Ok::<_, embedded_hal_mock::eh0::MockError>(42)
+    });
+    assert_eq!(res.unwrap(), 42);
+
+    proxy.write(0x39, &[0xff]).unwrap();
+
+    device.done();
+}
+
+#[test]
+fn i2c_lock_exclusive_blocks_proxies() {
+    let expect = vec![
+        i2c::Transaction::write(0x50, vec![0x00]),
+        i2c::Transaction::write(0x50, vec![0x01]),
+        i2c::Transaction::write(0x39, vec![0xff]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+    let manager = shared_bus::BusManagerStd::new(device.clone());
+    let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+
+    thread::scope(|s| {
+        let mut proxy = manager.acquire_i2c();
+
+        manager.lock_exclusive(|mut bus| {
+            s.spawn(move || {
+                locked_rx.recv().unwrap();
+                // Only runs once the exclusive section is over
+                proxy.write(0x39, &[0xff]).unwrap();
+            });
+
+            bus.write(0x50, &[0x00]).unwrap();
+            locked_tx.send(()).unwrap();
+            thread::sleep(std::time::Duration::from_millis(10));
+            bus.write(0x50, &[0x01]).unwrap();
+        });
+    });
+
+    device.done();
+}
+
+#[test]
+#[cfg(feature = "cortex-m")]
+fn i2c_try_write_conflict() {
+    let expect = vec![i2c::Transaction::write(0x39, vec![0xff])];
+    let mut device = i2c::Mock::new(&expect);
+    let manager = shared_bus::BusManagerAtomicCheck::new(device.clone());
+    let mut proxy = manager.acquire_i2c();
+
+    manager.configure(|_| {
+        assert_eq!(
+            proxy.try_write(0x39, &[0xff]),
+            Err(shared_bus::Error::Lock(shared_bus::LockError::Busy))
+        );
+    });
+
+    proxy.try_write(0x39, &[0xff]).unwrap();
+
+    device.done();
+}
+
+#[test]
+#[cfg(feature = "cortex-m")]
+#[should_panic(expected = "Bus conflict")]
+fn i2c_write_conflict() {
+    let device = i2c::Mock::new(&[]);
+    let manager = shared_bus::BusManagerAtomicCheck::new(device);
+    let mut proxy = manager.acquire_i2c();
+
+    manager.configure(|_| {
+        let _ = proxy.write(0x39, &[0xff]);
+    });
+}
+
+#[test]
+#[cfg(feature = "cortex-m")]
+fn i2c_write_conflict_checks_disabled() {
+    let expect = vec![
+        i2c::Transaction::write(0x39, vec![0xff]),
+        i2c::Transaction::write(0x39, vec![0x00]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+    let manager = shared_bus::BusManagerAtomicCheck::new(device.clone());
+    let mut proxy = manager.acquire_i2c();
+
+    // The DFU guard marks the bus busy without touching it, so the proxy is the only user.
+    let guard = manager.take_for_dfu().unwrap();
+    // SAFETY: The guard is not dereferenced while the proxy uses the bus.
+    unsafe {
+        manager.with_checks_disabled(|| {
+            proxy.write(0x39, &[0xff]).unwrap();
+        });
+    }
+
+    // The check is enabled again
+    assert_eq!(
+        proxy.try_write(0x39, &[0x00]),
+        Err(shared_bus::Error::Lock(shared_bus::LockError::Busy))
+    );
+    drop(guard);
+    proxy.write(0x39, &[0x00]).unwrap();
+
+    device.done();
+}
+
+#[test]
+#[cfg(feature = "cortex-m")]
+fn i2c_take_for_dfu() {
+    let expect = vec![
+        i2c::Transaction::write(0x50, vec![0xa5]),
+        i2c::Transaction::write(0x39, vec![0x00]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+    let manager = shared_bus::BusManagerAtomicCheck::new(device.clone());
+    let mut proxy = manager.acquire_i2c();
+
+    let mut guard = manager.take_for_dfu().unwrap();
+    guard.write(0x50, &[0xa5]).unwrap();
+    assert_eq!(
+        proxy.try_write(0x39, &[0x00]),
+        Err(shared_bus::Error::Lock(shared_bus::LockError::Busy))
+    );
+    drop(guard);
+
+    // Taking the bus fails while a proxy is in the middle of a transaction
+    manager.configure(|_| assert!(manager.take_for_dfu().is_none()));
+    proxy.write(0x39, &[0x00]).unwrap();
+
+    device.done();
+}
+
+#[test]
+fn i2c_downcast_bus() {
+    let expect = vec![
+        i2c::Transaction::write(0x39, vec![0xc0]),
+        i2c::Transaction::write(0x39, vec![0xff]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+
+    assert!(manager.downcast_bus_ref::<u32>().is_none());
+    {
+        let mut bus = manager.downcast_bus_ref::<i2c::Mock>().unwrap();
+        bus.write(0x39, &[0xc0]).unwrap();
+    }
+
+    // The bus is released again
+    manager.acquire_i2c().write(0x39, &[0xff]).unwrap();
+
+    device.done();
+}
+
+#[test]
+fn i2c_buffered() {
+    let expect = vec![
+        // Flushed by `flush()`
+        i2c::Transaction::write(0x3c, vec![0x40, 0x01, 0x02, 0x03]),
+        // Flushed when full
+        i2c::Transaction::write(0x3c, vec![0x40, 0x04, 0x05, 0x06, 0x07]),
+        // Flushed before a different write, in order
+        i2c::Transaction::write(0x3c, vec![0x40, 0x08]),
+        i2c::Transaction::write(0x3c, vec![0x00, 0xaf]),
+        // Flushed before a read
+        i2c::Transaction::write(0x3c, vec![0x40, 0x09]),
+        i2c::Transaction::write_read(0x3c, vec![0x00], vec![0x43]),
+        // Flushed before a write to another address
+        i2c::Transaction::write(0x3c, vec![0x40, 0x0a]),
+        i2c::Transaction::write(0x3d, vec![0x40, 0x0b]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut proxy = manager.acquire_i2c_buffered::<5>(0x40);
+
+    proxy.write(0x3c, &[0x40, 0x01]).unwrap();
+    proxy.write(0x3c, &[0x40, 0x02, 0x03]).unwrap();
+    assert_eq!(proxy.pending(), 3);
+    proxy.flush().unwrap();
+    assert_eq!(proxy.pending(), 0);
+    proxy.flush().unwrap();
+
+    proxy.write(0x3c, &[0x40, 0x04, 0x05]).unwrap();
+    proxy.write(0x3c, &[0x40, 0x06, 0x07]).unwrap();
+    assert_eq!(proxy.pending(), 0);
+
+    proxy.write(0x3c, &[0x40, 0x08]).unwrap();
+    proxy.write(0x3c, &[0x00, 0xaf]).unwrap();
+
+    proxy.write(0x3c, &[0x40, 0x09]).unwrap();
+    let mut buf = [0u8; 1];
+    proxy.write_read(0x3c, &[0x00], &mut buf).unwrap();
+    assert_eq!(buf, [0x43]);
+
+    proxy.write(0x3c, &[0x40, 0x0a]).unwrap();
+    proxy.write(0x3d, &[0x40, 0x0b]).unwrap();
+    proxy.flush().unwrap();
+
+    device.done();
+}
+
+#[test]
+fn i2c_tagged() {
+    use shared_bus::Tagged;
+    use std::any::TypeId;
+
+    struct Imu;
+    struct Baro;
+
+    fn write_to<I2C: embedded_hal::blocking::i2c::Write>(proxy: &mut I2C, addr: u8) {
+        assert!(proxy.write(addr, &[0xaa]).is_ok());
+    }
+
+    let expect = vec![
+        i2c::Transaction::write(0x68, vec![0xaa]),
+        i2c::Transaction::write(0x77, vec![0xaa]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut imu = Tagged::<Imu, _>::new(manager.acquire_i2c());
+    let mut baro = Tagged::<Baro, _>::new(manager.acquire_i2c());
+
+    // Distinct types, but no overhead over the plain proxy
+    assert_ne!(
+        TypeId::of::<Tagged<Imu, ()>>(),
+        TypeId::of::<Tagged<Baro, ()>>()
+    );
+    assert_eq!(
+        std::mem::size_of_val(&imu),
+        std::mem::size_of::<shared_bus::I2cProxy<'_, shared_bus::NullMutex<i2c::Mock>>>()
+    );
+
+    write_to(&mut imu, 0x68);
+    write_to(&mut baro, 0x77);
+
+    device.done();
+}
+
+#[test]
+fn i2c_with_delay() {
+    use embedded_hal::blocking::delay::DelayMs;
+
+    /// Timer which records the requested delays.
+    struct Timer(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+    impl DelayMs<u8> for Timer {
+        fn delay_ms(&mut self, ms: u8) {
+            self.0.borrow_mut().push(ms);
+        }
+    }
+
+    let expect = vec![i2c::Transaction::write(0x40, vec![0xfe])];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let delays = std::rc::Rc::default();
+    let timer = shared_bus::BusManagerSimple::new(Timer(std::rc::Rc::clone(&delays)));
+    {
+        let (mut proxy, mut delay) = manager.acquire_i2c_with_delay(&timer);
+        proxy.write(0x40, &[0xfe]).unwrap();
+        delay.delay_ms(15);
+
+        // Other proxies share the same timer
+        timer.acquire_delay().delay_ms(1);
+    }
+
+    assert_eq!(*delays.borrow(), vec![15, 1]);
+    device.done();
+}
+
+#[test]
+fn i2c_chunked() {
+    let data: Vec<u8> = (0..600).map(|i| i as u8).collect();
+    let expect = vec![
+        i2c::Transaction::write_read(0x50, vec![0x00, 0x00], data[..255].to_vec()),
+        i2c::Transaction::write_read(0x50, vec![0x00, 0xff], data[255..510].to_vec()),
+        i2c::Transaction::write_read(0x50, vec![0x01, 0xfe], data[510..].to_vec()),
+        i2c::Transaction::read(0x50, vec![0xaa; 10]),
+        i2c::Transaction::read(0x51, vec![0xbb; 2]),
+        i2c::Transaction::write(0x50, vec![0xcc; 10]),
+        i2c::Transaction::write(0x51, vec![0xdd; 5]),
+        i2c::Transaction::write_read(0x50, vec![0; 5], vec![0xee; 12]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut proxy = manager.acquire_i2c_chunked(255, |offset, _addr: &mut u8, mem: &mut [u8]| {
+        let start = u16::from_be_bytes([mem[0], mem[1]]) as usize + offset;
+        mem.copy_from_slice(&(start as u16).to_be_bytes());
+    });
+
+    let mut buf = [0u8; 600];
+    proxy.write_read(0x50, &[0x00, 0x00], &mut buf).unwrap();
+    assert_eq!(&buf[..], &data[..]);
+
+    // Plain reads are split as well, here switching the device address for the second chunk
+    let mut proxy = manager.acquire_i2c_chunked(10, |_, addr: &mut u8, _: &mut [u8]| *addr += 1);
+    let mut buf = [0u8; 12];
+    proxy.read(0x50, &mut buf).unwrap();
+    assert_eq!(&buf[..10], &[0xaa; 10]);
+    assert_eq!(&buf[10..], &[0xbb; 2]);
+
+    // So are writes
+    let mut data = [0xcc; 15];
+    data[10..].fill(0xdd);
+    proxy.write(0x50, &data).unwrap();
+
+    // A write part which is too long to adjust is passed on without splitting the transfer
+    proxy.write_read(0x50, &[0; 5], &mut buf).unwrap();
+    assert_eq!(&buf[..], &[0xee; 12]);
+
+    device.done();
+}
+
+#[test]
+fn i2c_try_new() {
+    let mut device = i2c::Mock::new(&[]);
+    let mut peripheral = Some(device.clone());
+
+    let manager: Option<shared_bus::BusManagerSimple<_>> =
+        shared_bus::BusManager::try_new(|| peripheral.take());
+    assert!(manager.is_some());
+
+    // The singleton was already taken
+    let manager: Option<shared_bus::BusManagerSimple<i2c::Mock>> =
+        shared_bus::BusManager::try_new(|| peripheral.take());
+    assert!(manager.is_none());
+
+    device.done();
+}
+
+#[test]
+fn i2c_try_write_poisoned() {
+    let mut device = i2c::Mock::new(&[]);
+    let manager = shared_bus::BusManagerStd::new(device.clone());
+    let mut proxy = manager.acquire_i2c();
+
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        manager.configure(|_| panic!("driver bug"));
+    }));
+
+    let mut buf = [0u8; 1];
+    assert_eq!(
+        proxy.try_write_read(0x39, &[0x00], &mut buf),
+        Err(shared_bus::Error::Lock(shared_bus::LockError::Poisoned))
+    );
+
+    device.done();
+}
+
+#[test]
+fn i2c_quiet_error() {
+    use shared_bus::{BusManager, NullMutex, QuietMutex, QuietPolicy};
+
+    let expect = vec![i2c::Transaction::write(0x39, vec![0x02])];
+    let mut device = i2c::Mock::new(&expect);
+    let manager = BusManager::from_mutex(QuietMutex::<NullMutex<_>>::with_policy(
+        device.clone(),
+        QuietPolicy::Error,
+    ));
+    let mut proxy = manager.acquire_i2c();
+
+    manager.set_quiet(true);
+    assert!(manager.is_quiet());
+    assert_eq!(
+        proxy.try_write(0x39, &[0x01]),
+        Err(shared_bus::Error::Lock(shared_bus::LockError::Quiet))
+    );
+
+    manager.set_quiet(false);
+    proxy.try_write(0x39, &[0x02]).unwrap();
+
+    device.done();
+}
+
+#[test]
+fn i2c_quiet_block() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let expect = vec![i2c::Transaction::write(0x39, vec![0x01])];
+    let mut device = i2c::Mock::new(&expect);
+    let manager: &'static shared_bus::BusManager<shared_bus::QuietMutex<std::sync::Mutex<_>>> =
+        Box::leak(Box::new(shared_bus::BusManager::new(device.clone())));
+    static WINDOW_OVER: AtomicBool = AtomicBool::new(false);
+
+    manager.set_quiet(true);
+    let mut proxy = manager.acquire_i2c();
+    let writer = thread::spawn(move || {
+        proxy.write(0x39, &[0x01]).unwrap();
+        // The write only happened after the quiet window
+        assert!(WINDOW_OVER.load(Ordering::SeqCst));
+    });
+
+    thread::sleep(std::time::Duration::from_millis(20));
+    assert!(!writer.is_finished());
+    WINDOW_OVER.store(true, Ordering::SeqCst);
+    manager.set_quiet(false);
+    writer.join().unwrap();
+
+    device.done();
+}
+
+#[test]
+fn i2c_channel_bus() {
+    let expect = vec![i2c::Transaction::write_read(0x39, vec![0x02], vec![0x03]); 4];
+    let mut device = i2c::Mock::new(&expect);
+
+    let bus = shared_bus::ChannelBus::new(device.clone());
+    let clients: Vec<_> = (0..2)
+        .map(|_| {
+            let mut proxy = bus.acquire_i2c();
+            thread::spawn(move || {
+                for _ in 0..2 {
+                    let mut buf = [0u8; 1];
+                    proxy.write_read(0x39, &[0x02], &mut buf).unwrap();
+                    assert_eq!(buf, [0x03]);
+                }
+            })
+        })
+        .collect();
+    let owner = thread::spawn(move || bus.run());
+
+    for client in clients {
+        client.join().unwrap();
+    }
+    owner.join().unwrap();
+
+    device.done();
+}
+
+#[test]
+fn i2c_addressed_proxies() {
+    let expect = vec![
+        i2c::Transaction::write(0x48, vec![0x01, 0x60]),
+        i2c::Transaction::write_read(0x50, vec![0x00, 0x10], vec![0xaa, 0xbb]),
+        i2c::Transaction::read(0x48, vec![0x12]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut sensor = manager.acquire_i2c_at::<0x48>();
+    let mut eeprom = manager.acquire_i2c_at::<0x50>();
+
+    sensor.write(&[0x01, 0x60]).unwrap();
+
+    let mut buf = [0u8; 2];
+    eeprom.write_read(&[0x00, 0x10], &mut buf).unwrap();
+    assert_eq!(buf, [0xaa, 0xbb]);
+
+    let mut buf = [0u8; 1];
+    sensor.read(&mut buf).unwrap();
+    assert_eq!(buf, [0x12]);
+
+    device.done();
+}
+
+#[test]
+fn i2c_manager_macro_single_evaluation() {
+    let mut device = i2c::Mock::new(&[]);
+    let mut evaluated = 0;
+
+    for run in 0..2 {
+        let manager = shared_bus::new_std!(
+            i2c::Mock = {
+                evaluated += 1;
+                device.clone()
+            }
+        );
+        assert_eq!(manager.is_ok(), run == 0);
+        assert_eq!(evaluated, run + 1);
+    }
+
+    device.done();
+}
+
+#[test]
+#[cfg(feature = "critical-section")]
+fn i2c_manager_auto_single_evaluation() {
+    let mut device = i2c::Mock::new(&[]);
+    let mut evaluated = 0;
+
+    for run in 0..2 {
+        let manager = shared_bus::new_auto!(
+            i2c::Mock = {
+                evaluated += 1;
+                device.clone()
+            }
+        );
+        assert_eq!(manager.is_ok(), run == 0);
+        assert_eq!(evaluated, run + 1);
+    }
+
+    device.done();
+}
+
+#[test]
+fn i2c_recovering() {
+    use embedded_hal_mock::eh0::MockError;
+    use std::io::ErrorKind;
+
+    let expect = vec![
+        i2c::Transaction::write(0x39, vec![0x01]).with_error(MockError::Io(ErrorKind::Other)),
+        // The recovery routine runs inside the lock
+        i2c::Transaction::write(0x00, vec![0x06]),
+        i2c::Transaction::write(0x39, vec![0x01]),
+        i2c::Transaction::read(0x39, vec![0x02]),
+        i2c::Transaction::read(0x39, vec![0x02]).with_error(MockError::Io(ErrorKind::Other)),
+        i2c::Transaction::write(0x00, vec![0x06]),
+        i2c::Transaction::read(0x39, vec![0x02]).with_error(MockError::Io(ErrorKind::Other)),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+    let recoveries = std::cell::Cell::new(0);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut proxy = manager.acquire_i2c_recovering(|bus: &mut i2c::Mock| {
+        recoveries.set(recoveries.get() + 1);
+        bus.write(0x00, &[0x06]).unwrap();
+    });
+
+    proxy.write(0x39, &[0x01]).unwrap();
+
+    // No recovery without an error
+    let mut buf = [0u8; 1];
+    proxy.read(0x39, &mut buf).unwrap();
+
+    // Only a single retry
+    assert_eq!(
+        proxy.read(0x39, &mut buf),
+        Err(MockError::Io(ErrorKind::Other))
+    );
+
+    assert_eq!(recoveries.get(), 2);
+    device.done();
+}
+
+#[test]
+fn i2c_at_speed() {
+    /// Bus which records the clock rate of each transfer.
+    struct ClockedBus {
+        i2c: i2c::Mock,
+        speed: u32,
+        log: Vec<(u32, u8)>,
+    }
+
+    impl ClockedBus {
+        fn set_speed(&mut self, hz: u32) {
+            self.speed = hz;
+        }
+    }
+
+    impl embedded_hal::blocking::i2c::Write for ClockedBus {
+        type Error = embedded_hal_mock::eh0::MockError;
+
+        fn write(&mut self, addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+            self.log.push((self.speed, addr));
+            self.i2c.write(addr, buffer)
+        }
+    }
+
+    impl embedded_hal::blocking::i2c::Read for ClockedBus {
+        type Error = embedded_hal_mock::eh0::MockError;
+
+        fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+            self.log.push((self.speed, addr));
+            self.i2c.read(addr, buffer)
+        }
+    }
+
+    let expect = vec![
+        i2c::Transaction::write(0x50, vec![0x00, 0x10]),
+        i2c::Transaction::write(0x68, vec![0x3b]),
+        i2c::Transaction::read(0x50, vec![0xaa]),
+        i2c::Transaction::write(0x68, vec![0x6b]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+    let bus = ClockedBus {
+        i2c: device.clone(),
+        speed: 400_000,
+        log: Vec::new(),
+    };
+
+    let manager = shared_bus::BusManagerSimple::new(bus);
+    let mut eeprom = manager
+        .acquire_i2c_at_speed(100_000, ClockedBus::set_speed)
+        .restore_after(400_000);
+    let mut imu = manager.acquire_i2c_at_speed(1_000_000, ClockedBus::set_speed);
+
+    eeprom.write(0x50, &[0x00, 0x10]).unwrap();
+    imu.write(0x68, &[0x3b]).unwrap();
+    let mut buf = [0u8; 1];
+    eeprom.read(0x50, &mut buf).unwrap();
+    manager.configure(|bus| assert_eq!(bus.speed, 400_000));
+    imu.write(0x68, &[0x6b]).unwrap();
+
+    manager.configure(|bus| {
+        assert_eq!(
+            bus.log,
+            vec![
+                (100_000, 0x50),
+                (1_000_000, 0x68),
+                (100_000, 0x50),
+                (1_000_000, 0x68),
+            ]
+        );
+        // The IMU doesn't restore the clock rate
+        assert_eq!(bus.speed, 1_000_000);
+    });
+
+    device.done();
+}
+
+#[test]
+fn i2c_failover() {
+    use embedded_hal_mock::eh0::MockError;
+    use std::io::ErrorKind;
+
+    let expect_a = vec![
+        i2c::Transaction::write(0x39, vec![0x01]),
+        i2c::Transaction::write(0x39, vec![0x02]).with_error(MockError::Io(ErrorKind::Other)),
+        i2c::Transaction::read(0x39, vec![0x00]).with_error(MockError::Io(ErrorKind::Other)),
+    ];
+    let expect_b = vec![
+        i2c::Transaction::write(0x39, vec![0x02]),
+        i2c::Transaction::read(0x39, vec![0x00]).with_error(MockError::Io(ErrorKind::Interrupted)),
+    ];
+    let mut bus_a = i2c::Mock::new(&expect_a);
+    let mut bus_b = i2c::Mock::new(&expect_b);
+
+    let manager: shared_bus::DualBusManager<shared_bus::NullMutex<_>> =
+        shared_bus::DualBusManager::new(bus_a.clone(), bus_b.clone());
+    let mut proxy = manager.acquire_failover_i2c();
+
+    // Bus B is not touched while bus A works
+    proxy.write(0x39, &[0x01]).unwrap();
+    // Bus A fails, bus B succeeds
+    proxy.write(0x39, &[0x02]).unwrap();
+
+    // Both fail, the error of bus B is reported
+    let mut buf = [0u8; 1];
+    assert_eq!(
+        proxy.read(0x39, &mut buf),
+        Err(MockError::Io(ErrorKind::Interrupted))
+    );
+
+    bus_a.done();
+    bus_b.done();
+}
+
+#[test]
+fn i2c_replace_bus() {
+    let expect_old = vec![i2c::Transaction::write(0x39, vec![0x01])];
+    let expect_new = vec![
+        i2c::Transaction::write(0x39, vec![0x02]),
+        i2c::Transaction::read(0x39, vec![0x03]),
+    ];
+    let mut old_device = i2c::Mock::new(&expect_old);
+    let mut new_device = i2c::Mock::new(&expect_new);
+
+    let manager = shared_bus::new_std!(i2c::Mock = old_device.clone()).unwrap();
+    let mut proxy = manager.acquire_i2c();
+
+    proxy.write(0x39, &[0x01]).unwrap();
+
+    let old = manager.replace_bus(new_device.clone());
+    drop(old);
+
+    // The existing proxy now talks to the new bus
+    let t = thread::spawn(move || {
+        proxy.write(0x39, &[0x02]).unwrap();
+        let mut buf = [0u8; 1];
+        proxy.read(0x39, &mut buf).unwrap();
+        assert_eq!(buf, [0x03]);
+    });
+    t.join().unwrap();
+
+    old_device.done();
+    new_device.done();
+}
+
+#[test]
+fn i2c_bus_scope() {
+    let expect = vec![
+        i2c::Transaction::write(0x50, vec![0x00]),
+        i2c::Transaction::write_read(0x50, vec![0x01], vec![0xaa]),
+        i2c::Transaction::write(0x50, vec![0x02]),
+        i2c::Transaction::write(0x39, vec![0xff]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+    let manager = shared_bus::BusManagerStd::new(device.clone());
+    let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+
+    thread::scope(|s| {
+        let mut proxy = manager.acquire_i2c();
+        let mut scope = manager.begin_scope();
+
+        s.spawn(move || {
+            locked_rx.recv().unwrap();
+            // Only runs once the scope was dropped
+            proxy.write(0x39, &[0xff]).unwrap();
+        });
+
+        scope.write(0x50, &[0x00]).unwrap();
+        locked_tx.send(()).unwrap();
+        thread::sleep(std::time::Duration::from_millis(10));
+        let mut buf = [0u8; 1];
+        scope.write_read(0x50, &[0x01], &mut buf).unwrap();
+        assert_eq!(buf, [0xaa]);
+        scope.write(0x50, &[0x02]).unwrap();
+        drop(scope);
+    });
+
+    device.done();
+}
+
+#[test]
+#[should_panic(expected = "bus was already acquired as I2c, cannot acquire it as Spi")]
+fn i2c_manager_single_kind() {
+    let expect = vec![i2c::Transaction::write(0xde, vec![0xad])];
+    let device = i2c::Mock::new(&expect);
+
+    let manager: shared_bus::SingleKindBusManager<shared_bus::NullMutex<_>> =
+        shared_bus::SingleKindBusManager::new(device);
+
+    let mut proxy1 = manager.acquire_i2c();
+    let _proxy2 = manager.acquire_i2c();
+    proxy1.write(0xde, &[0xad]).unwrap();
+
+    let _spi = manager.acquire_spi();
+}
+
+#[test]
+fn i2c_split_bus() {
+    let read_expect = vec![
+        i2c::Transaction::read(0x39, vec![0x12, 0x34]),
+        i2c::Transaction::read(0x48, vec![0x56]),
+    ];
+    let write_expect = vec![
+        i2c::Transaction::write(0x39, vec![0xc0]),
+        i2c::Transaction::write(0x48, vec![0x0f]),
+    ];
+    let mut read_half = i2c::Mock::new(&read_expect);
+    let mut write_half = i2c::Mock::new(&write_expect);
+
+    let manager: shared_bus::SplitBusManager<std::sync::Mutex<_>, std::sync::Mutex<_>> =
+        shared_bus::SplitBusManager::new(read_half.clone(), write_half.clone());
+    let mut proxy = manager.acquire_i2c();
+
+    proxy.write(0x39, &[0xc0]).unwrap();
+    let mut buf = [0u8; 2];
+    proxy.read(0x39, &mut buf).unwrap();
+    assert_eq!(buf, [0x12, 0x34]);
+
+    let mut buf = [0u8; 1];
+    proxy.write_read(0x48, &[0x0f], &mut buf).unwrap();
+    assert_eq!(buf, [0x56]);
+
+    read_half.done();
+    write_half.done();
+}
+
+#[test]
+fn i2c_split_bus_concurrent_write_read() {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// Half of a split bus which checks that it is never used concurrently.
+    struct Half {
+        busy: AtomicBool,
+        count: &'static AtomicUsize,
+    }
+
+    impl Half {
+        fn access(&mut self) {
+            assert!(!self.busy.swap(true, Ordering::SeqCst));
+            thread::yield_now();
+            self.count.fetch_add(1, Ordering::SeqCst);
+            self.busy.store(false, Ordering::SeqCst);
+        }
+    }
+
+    impl embedded_hal::blocking::i2c::Read for Half {
+        type Error = ();
+
+        fn read(&mut self, _: u8, _: &mut [u8]) -> Result<(), ()> {
+            self.access();
+            Ok(())
+        }
+    }
+
+    impl embedded_hal::blocking::i2c::Write for Half {
+        type Error = ();
+
+        fn write(&mut self, _: u8, _: &[u8]) -> Result<(), ()> {
+            self.access();
+            Ok(())
+        }
+    }
+
+    static READS: AtomicUsize = AtomicUsize::new(0);
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+
+    let manager: shared_bus::SplitBusManager<std::sync::Mutex<_>, std::sync::Mutex<_>> =
+        shared_bus::SplitBusManager::new(
+            Half {
+                busy: AtomicBool::new(false),
+                count: &READS,
+            },
+            Half {
+                busy: AtomicBool::new(false),
+                count: &WRITES,
+            },
+        );
+
+    // Threads doing write_read() compete with threads using only one of the halves
+    thread::scope(|s| {
+        for i in 0..6 {
+            let mut proxy = manager.acquire_i2c();
+            s.spawn(move || {
+                let mut buf = [0u8; 1];
+                for _ in 0..100 {
+                    match i % 3 {
+                        0 => proxy.write_read(0x39, &[0x00], &mut buf).unwrap(),
+                        1 => proxy.read(0x39, &mut buf).unwrap(),
+                        _ => proxy.write(0x39, &[0x00]).unwrap(),
+                    }
+                }
+            });
+        }
+    });
+
+    assert_eq!(READS.load(Ordering::SeqCst), 400);
+    assert_eq!(WRITES.load(Ordering::SeqCst), 400);
+}
+
+/// Bus which can be created in const context, counting the bytes written to it.
+struct ConstBus(usize);
+
+impl embedded_hal::blocking::i2c::Write for ConstBus {
+    type Error = ();
+
+    fn write(&mut self, _addr: u8, buffer: &[u8]) -> Result<(), Self::Error> {
+        self.0 += buffer.len();
+        Ok(())
+    }
+}
+
+static CONST_BUS: shared_bus::BusManager<std::sync::Mutex<ConstBus>> =
+    shared_bus::BusManager::from_mutex(std::sync::Mutex::new(ConstBus(0)));
+
+static CONST_DEVICES: [(
+    u8,
+    shared_bus::I2cProxy<'static, std::sync::Mutex<ConstBus>>,
+); 2] = [
+    (0x48, CONST_BUS.acquire_i2c()),
+    (0x50, CONST_BUS.acquire_i2c()),
+];
+
+#[test]
+fn i2c_const_device_table() {
+    // A `NullMutex` can be created in const context as well.
+    const fn null_bus() -> shared_bus::BusManager<shared_bus::NullMutex<ConstBus>> {
+        shared_bus::BusManager::from_mutex(shared_bus::NullMutex::new(ConstBus(0)))
+    }
+    let null_bus = null_bus();
+    embedded_hal::blocking::i2c::Write::write(&mut null_bus.acquire_i2c(), 0x20, &[0x00]).unwrap();
+
+    for (addr, proxy) in CONST_DEVICES.iter() {
+        embedded_hal::blocking::i2c::Write::write(&mut &*proxy, *addr, &[0x00, 0x01]).unwrap();
+    }
+
+    CONST_BUS.configure(|bus| assert_eq!(bus.0, 4));
+}
+
+#[test]
+fn i2c_tee_bus() {
+    let primary_expect = vec![
+        i2c::Transaction::write(0x39, vec![0xc0, 0xff, 0xee]),
+        i2c::Transaction::read(0x39, vec![0x12]),
+        i2c::Transaction::write_read(0x48, vec![0x0f], vec![0x34]),
+    ];
+    let shadow_expect = vec![
+        i2c::Transaction::write(0x39, vec![0xc0, 0xff, 0xee]),
+        i2c::Transaction::write(0x48, vec![0x0f]),
+    ];
+    let mut primary = i2c::Mock::new(&primary_expect);
+    let mut shadow = i2c::Mock::new(&shadow_expect);
+
+    let manager: shared_bus::TeeBusManager<shared_bus::NullMutex<_>> =
+        shared_bus::TeeBusManager::new(primary.clone(), shadow.clone());
+    let mut proxy = manager.acquire_i2c();
+
+    proxy.write(0x39, &[0xc0, 0xff, 0xee]).unwrap();
+    let mut buf = [0u8; 1];
+    proxy.read(0x39, &mut buf).unwrap();
+    assert_eq!(buf, [0x12]);
+    proxy.write_read(0x48, &[0x0f], &mut buf).unwrap();
+    assert_eq!(buf, [0x34]);
+
+    primary.done();
+    shadow.done();
+}
+
+#[test]
+fn i2c_smbus_pec() {
+    let expect = vec![
+        i2c::Transaction::write(0x5a, vec![0x01, 0x23, 0xbd]),
+        i2c::Transaction::write(0x5a, vec![0x02, 0x34, 0x12, 0xc5]),
+        i2c::Transaction::write_read(0x5a, vec![0x03], vec![0x42, 0xba]),
+        i2c::Transaction::write_read(0x5a, vec![0x07], vec![0xd2, 0x3a, 0x30]),
+        i2c::Transaction::write_read(0x5a, vec![0x04], vec![0x02, 0xaa, 0xbb, 0xd0, 0xff]),
+        // Corrupted PEC
+        i2c::Transaction::write_read(0x5a, vec![0x07], vec![0xd2, 0x3a, 0x31]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut smbus = manager.acquire_smbus(true);
+
+    smbus.write_byte(0x5a, 0x01, 0x23).unwrap();
+    smbus.write_word(0x5a, 0x02, 0x1234).unwrap();
+    assert_eq!(smbus.read_byte(0x5a, 0x03), Ok(0x42));
+    assert_eq!(smbus.read_word(0x5a, 0x07), Ok(0x3ad2));
+
+    let mut block = [0u8; 3];
+    assert_eq!(smbus.block_read(0x5a, 0x04, &mut block), Ok(2));
+    assert_eq!(block[..2], [0xaa, 0xbb]);
+
+    assert_eq!(
+        smbus.read_word(0x5a, 0x07),
+        Err(shared_bus::SmbusError::Pec)
+    );
+
+    device.done();
+}
+
+#[test]
+fn i2c_smbus_no_pec() {
+    let expect = vec![
+        i2c::Transaction::write(0x5a, vec![0x01, 0x23]),
+        i2c::Transaction::write_read(0x5a, vec![0x07], vec![0xd2, 0x3a]),
+        i2c::Transaction::write_read(0x5a, vec![0x04], vec![0x03, 0x01, 0x02]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut smbus = manager.acquire_smbus(false);
+
+    smbus.write_byte(0x5a, 0x01, 0x23).unwrap();
+    assert_eq!(smbus.read_word(0x5a, 0x07), Ok(0x3ad2));
+
+    // The device announces a longer block than fits into the buffer
+    let mut block = [0u8; 2];
+    assert_eq!(
+        smbus.block_read(0x5a, 0x04, &mut block),
+        Err(shared_bus::SmbusError::BlockLength(3))
+    );
+
+    device.done();
+}
+
+#[cfg(feature = "rate-limit")]
+#[test]
+fn i2c_rate_limited() {
+    let expect = vec![
+        i2c::Transaction::write(0x39, vec![0xc0]),
+        i2c::Transaction::write(0x39, vec![0xff]),
+        i2c::Transaction::write(0x39, vec![0xee]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let interval = std::time::Duration::from_millis(20);
+    let mut proxy1 = manager.acquire_i2c_rate_limited(interval, shared_bus::StdClock);
+    let mut proxy2 = manager.acquire_i2c_rate_limited(interval, shared_bus::StdClock);
+
+    proxy1.write(0x39, &[0xc0]).unwrap();
+    let start = std::time::Instant::now();
+    // The limit is shared between the proxies
+    proxy2.write(0x39, &[0xff]).unwrap();
+    let first = start.elapsed();
+    proxy1.write(0x39, &[0xee]).unwrap();
+    let second = start.elapsed();
+
+    assert!(first >= interval);
+    assert!(second >= first + interval);
+
+    device.done();
+}
+
+#[test]
+fn i2c_timed() {
+    /// Clock which advances by `step` microseconds on each reading.
+    struct StepClock<'a> {
+        now: u32,
+        step: &'a std::cell::Cell<u32>,
+    }
+
+    impl shared_bus::Clock for StepClock<'_> {
+        fn now_us(&mut self) -> u32 {
+            self.now = self.now.wrapping_add(self.step.get());
+            self.now
+        }
+
+        fn delay_us(&mut self, us: u32) {
+            self.now = self.now.wrapping_add(us);
+        }
+    }
+
+    let expect = vec![
+        i2c::Transaction::write(0x39, vec![0xc0]),
+        i2c::Transaction::write(0x39, vec![0xff]),
+        i2c::Transaction::write(0x39, vec![0xee]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let step = std::cell::Cell::new(1_000);
+    let aborts = std::cell::Cell::new(0);
+    let clock = StepClock {
+        now: u32::MAX - 500,
+        step: &step,
+    };
+    let timeout = std::time::Duration::from_millis(10);
+    let mut proxy = manager.acquire_i2c_timed(clock, timeout, |_| aborts.set(aborts.get() + 1));
+
+    proxy.write(0x39, &[0xc0]).unwrap();
+    assert_eq!(aborts.get(), 0);
+
+    // A transaction which stretches beyond the timeout
+    step.set(20_000);
+    assert_eq!(
+        proxy.write(0x39, &[0xff]),
+        Err(shared_bus::TimedError::Timeout)
+    );
+    assert_eq!(aborts.get(), 1);
+
+    step.set(1_000);
+    proxy.write(0x39, &[0xee]).unwrap();
+    assert_eq!(aborts.get(), 1);
+
+    device.done();
+}
+
+#[test]
+fn i2c_lazy_bus() {
+    let expect = vec![
+        i2c::Transaction::write(0x39, vec![0xc0]),
+        i2c::Transaction::write(0x39, vec![0xff]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+    let inits = std::cell::Cell::new(0);
+
+    let manager: shared_bus::BusManagerSimple<_> = shared_bus::BusManager::new_lazy(|| {
+        inits.set(inits.get() + 1);
+        device.clone()
+    });
+    let mut proxy1 = manager.acquire_i2c();
+    let mut proxy2 = manager.acquire_i2c();
+    assert_eq!(inits.get(), 0);
+    manager.configure(|bus| assert!(!bus.is_initialized()));
+
+    proxy1.write(0x39, &[0xc0]).unwrap();
+    proxy2.write(0x39, &[0xff]).unwrap();
+    assert_eq!(inits.get(), 1);
+
+    device.done();
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "async")]
+
+use embedded_hal_mock::eh1::i2c;
+use futures::executor::block_on;
+
+/// The same driver, compiled for the blocking and the async variant.
+macro_rules! sensor_driver {
+    ($name:ident, $bus:path, $mode:path) => {
+        mod $name {
+            use bus::I2c;
+            use $bus as bus;
+
+            pub struct Sensor<I2C> {
+                i2c: I2C,
+            }
+
+            #[$mode]
+            impl<I2C: I2c> Sensor<I2C> {
+                pub fn new(i2c: I2C) -> Self {
+                    Sensor { i2c }
+                }
+
+                pub async fn temperature(&mut self) -> Result<u16, I2C::Error> {
+                    let mut buf = [0u8; 2];
+                    self.i2c.write_read(0x48, &[0x00], &mut buf).await?;
+                    Ok(u16::from_be_bytes(buf))
+                }
+            }
+        }
+    };
+}
+
+sensor_driver!(sync_sensor, shared_bus::blocking, maybe_async::must_be_sync);
+sensor_driver!(async_sensor, shared_bus::asynch, maybe_async::must_be_async);
+
+fn expectations() -> Vec<i2c::Transaction> {
+    vec![i2c::Transaction::write_read(
+        0x48,
+        vec![0x00],
+        vec![0x01, 0x90],
+    )]
+}
+
+#[test]
+fn maybe_async_blocking() {
+    let mut device = i2c::Mock::new(&expectations());
+
+    let bus = shared_bus::blocking::BusManagerSimple::new(device.clone());
+    let proxy: shared_bus::blocking::I2cProxy<_> = bus.acquire_i2c();
+    let mut sensor = sync_sensor::Sensor::new(proxy);
+
+    assert_eq!(sensor.temperature().unwrap(), 400);
+
+    device.done();
+}
+
+#[test]
+fn maybe_async_async() {
+    let mut device = i2c::Mock::new(&expectations());
+
+    let bus = shared_bus::asynch::BusManagerSimple::new(device.clone());
+    let proxy: shared_bus::asynch::I2cProxy<_> = bus.acquire_i2c();
+    let mut sensor = async_sensor::Sensor::new(proxy);
+
+    assert_eq!(block_on(sensor.temperature()).unwrap(), 400);
+
+    device.done();
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "metrics")]
+
+use embedded_hal::prelude::*;
+use embedded_hal_mock::eh0::{i2c, spi, MockError};
+
+#[test]
+fn metrics_i2c() {
+    let expect = vec![
+        i2c::Transaction::write(0x39, vec![0xc0, 0xff, 0xee]),
+        i2c::Transaction::read(0x39, vec![0x12, 0x34]),
+        i2c::Transaction::write_read(0x39, vec![0x01], vec![0x56]),
+        i2c::Transaction::write(0x39, vec![0x00])
+            .with_error(MockError::Io(std::io::ErrorKind::Other)),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut proxy1 = manager.acquire_i2c();
+    let mut proxy2 = manager.acquire_i2c();
+
+    proxy1.write(0x39, &[0xc0, 0xff, 0xee]).unwrap();
+    let mut buf = [0u8; 2];
+    proxy2.read(0x39, &mut buf).unwrap();
+    let mut buf = [0u8; 1];
+    proxy1.write_read(0x39, &[0x01], &mut buf).unwrap();
+
+    // Failed transfers are not counted
+    proxy2.write(0x39, &[0x00]).unwrap_err();
+
+    assert_eq!(manager.bytes_written(), 4);
+    assert_eq!(manager.bytes_read(), 3);
+
+    device.done();
+}
+
+#[test]
+fn metrics_spi() {
+    let expect = vec![
+        spi::Transaction::write(vec![0xab, 0xcd]),
+        spi::Transaction::transfer(vec![0x01, 0x02, 0x03], vec![0x04, 0x05, 0x06]),
+    ];
+    let mut device = spi::Mock::new(&expect);
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut proxy = manager.acquire_spi();
+
+    proxy.write(&[0xab, 0xcd]).unwrap();
+    proxy.transfer(&mut [0x01, 0x02, 0x03]).unwrap();
+
+    assert_eq!(manager.bytes_written(), 5);
+    assert_eq!(manager.bytes_read(), 3);
+
+    device.done();
+}
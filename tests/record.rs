@@ -0,0 +1,106 @@
+#![cfg(feature = "std")]
+
+use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+use embedded_hal_mock::eh0::i2c;
+use embedded_hal_mock::eh0::MockError;
+use std::io::ErrorKind;
+
+/// Driver code under test, returning everything it observed.
+fn run<B>(bus: B) -> Vec<Result<Vec<u8>, ()>>
+where
+    B: Write + Read<Error = <B as Write>::Error> + WriteRead<Error = <B as Write>::Error>,
+{
+    let manager = shared_bus::BusManagerSimple::new(bus);
+    let mut proxy = manager.acquire_i2c();
+
+    let mut reg = [0u8; 2];
+    let mut status = [0u8; 1];
+    vec![
+        proxy
+            .write(0x39, &[0xc0, 0xff, 0xee])
+            .map(|_| vec![])
+            .map_err(|_| ()),
+        proxy
+            .write_read(0x39, &[0x0f], &mut reg)
+            .map(|_| reg.to_vec())
+            .map_err(|_| ()),
+        proxy
+            .read(0x48, &mut status)
+            .map(|_| status.to_vec())
+            .map_err(|_| ()),
+        proxy.write(0x48, &[]).map(|_| vec![]).map_err(|_| ()),
+    ]
+}
+
+#[test]
+fn record_replay_roundtrip() {
+    let expect = vec![
+        i2c::Transaction::write(0x39, vec![0xc0, 0xff, 0xee]),
+        i2c::Transaction::write_read(0x39, vec![0x0f], vec![0xbe, 0xef]),
+        i2c::Transaction::read(0x48, vec![0x00]).with_error(MockError::Io(ErrorKind::Other)),
+        i2c::Transaction::write(0x48, vec![]),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let recording = shared_bus::RecordingBus::new(device.clone());
+    let log = recording.recording();
+    let recorded = run(recording);
+    device.done();
+
+    let text = log.to_string();
+    assert_eq!(
+        text,
+        "write 39 c0ffee ok\nwrite_read 39 0f beef ok\nread 48 - err\nwrite 48 - ok\n"
+    );
+
+    let replay = shared_bus::ReplayBus::parse(&text).unwrap();
+    let replayed = run(replay.clone());
+    replay.done();
+
+    assert_eq!(replayed, recorded);
+    assert_eq!(recorded[1], Ok(vec![0xbe, 0xef]));
+    assert_eq!(recorded[2], Err(()));
+}
+
+#[test]
+fn replay_parse_error() {
+    let err = shared_bus::ReplayBus::parse("write 39 c0ffee ok\n\nread 39 bee ok\n").unwrap_err();
+    assert_eq!(err.line, 3);
+}
+
+#[test]
+#[should_panic(expected = "does not match the recording")]
+fn replay_mismatch() {
+    let mut replay = shared_bus::ReplayBus::parse("write 39 c0ffee ok\n").unwrap();
+    let _ = replay.write(0x39, &[0xc0]);
+}
+
+#[test]
+#[should_panic(expected = "does not match the recording")]
+fn replay_length_mismatch() {
+    let mut replay = shared_bus::ReplayBus::parse("write_read 39 0f beef ok\n").unwrap();
+    let mut buf = [0u8; 3];
+    let _ = replay.write_read(0x39, &[0x0f], &mut buf);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn record_serde() {
+    let expect = vec![
+        i2c::Transaction::write_read(0x39, vec![0x0f], vec![0xbe, 0xef]),
+        i2c::Transaction::read(0x48, vec![0x00]).with_error(MockError::Io(ErrorKind::Other)),
+    ];
+    let mut device = i2c::Mock::new(&expect);
+
+    let mut recording = shared_bus::RecordingBus::new(device.clone());
+    let log = recording.recording();
+    let mut reg = [0u8; 2];
+    recording.write_read(0x39, &[0x0f], &mut reg).unwrap();
+    assert!(recording.read(0x48, &mut [0u8; 1]).is_err());
+    device.done();
+
+    let records = log.records();
+    let json = serde_json::to_string(&records).unwrap();
+    let parsed: Vec<shared_bus::Record> = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, records);
+}
@@ -0,0 +1,15 @@
+#![cfg(all(feature = "rt", target_os = "linux"))]
+
+// Locking needs the privileges for real-time scheduling, which are usually not available in CI,
+// so only the creation of the mutex is tested.
+
+#[test]
+fn priority_ceiling_attribute() {
+    let mutex = shared_bus::PriorityCeilingMutex::new((), 42).unwrap();
+    assert_eq!(mutex.ceiling(), 42);
+}
+
+#[test]
+fn priority_ceiling_invalid() {
+    assert!(shared_bus::PriorityCeilingMutex::new((), -1).is_err());
+}
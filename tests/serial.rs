@@ -0,0 +1,46 @@
+#![cfg(feature = "serial")]
+
+use embedded_hal_mock::eh1::serial;
+use embedded_hal_nb::serial::{Read, Write};
+
+#[test]
+fn serial_frame() {
+    let expect = vec![
+        serial::Transaction::write_many(b"AT\r\n"),
+        serial::Transaction::flush(),
+        serial::Transaction::read_many(b"OK"),
+    ];
+    let mut device = serial::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut writer = manager.acquire_serial();
+    let mut reader = manager.acquire_serial();
+
+    writer.write_all(b"AT\r\n").unwrap();
+    assert_eq!(reader.read().unwrap(), b'O');
+    assert_eq!(reader.read().unwrap(), b'K');
+
+    device.done();
+}
+
+#[test]
+fn serial_would_block() {
+    let expect = vec![
+        serial::Transaction::read_error(nb::Error::WouldBlock),
+        serial::Transaction::read(0x42),
+        serial::Transaction::write(0x43),
+        serial::Transaction::flush(),
+    ];
+    let mut device = serial::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut proxy = manager.acquire_serial();
+
+    // The proxy does not wait itself, but passes `WouldBlock` on.
+    assert!(matches!(proxy.read(), Err(nb::Error::WouldBlock)));
+    assert_eq!(proxy.read().unwrap(), 0x42);
+    nb::block!(proxy.write(0x43)).unwrap();
+    nb::block!(proxy.flush()).unwrap();
+
+    device.done();
+}
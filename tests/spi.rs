@@ -1,5 +1,5 @@
 use embedded_hal::prelude::*;
-use embedded_hal_mock::spi;
+use embedded_hal_mock::eh0::spi;
 
 #[test]
 fn fake_spi_device() {
@@ -59,3 +59,126 @@ fn spi_multi() {
 
     device.done();
 }
+
+#[derive(Debug, Default, Clone)]
+struct WideSpi {
+    written: std::rc::Rc<std::cell::RefCell<Vec<u16>>>,
+}
+
+impl embedded_hal::blocking::spi::Write<u16> for WideSpi {
+    type Error = ();
+
+    fn write(&mut self, words: &[u16]) -> Result<(), Self::Error> {
+        self.written.borrow_mut().extend_from_slice(words);
+        Ok(())
+    }
+}
+
+impl embedded_hal::blocking::spi::Transfer<u16> for WideSpi {
+    type Error = ();
+
+    fn transfer<'w>(&mut self, words: &'w mut [u16]) -> Result<&'w [u16], Self::Error> {
+        for w in words.iter_mut() {
+            self.written.borrow_mut().push(*w);
+            *w = !*w;
+        }
+        Ok(words)
+    }
+}
+
+#[test]
+fn spi_proxy_u16() {
+    let device = WideSpi::default();
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut proxy1 = manager.acquire_spi();
+    let mut proxy2 = manager.acquire_spi();
+
+    proxy1.write(&[0xabcd_u16, 0x1234]).unwrap();
+
+    let mut buf = [0x00ff_u16];
+    proxy2.transfer(&mut buf).unwrap();
+    assert_eq!(&buf, &[0xff00]);
+
+    assert_eq!(&*device.written.borrow(), &[0xabcd, 0x1234, 0x00ff]);
+}
+
+#[derive(Debug, Default, Clone)]
+struct DualBus {
+    log: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+}
+
+impl embedded_hal::blocking::spi::Write<u8> for DualBus {
+    type Error = ();
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.log.borrow_mut().push(format!("spi: {:02x?}", words));
+        Ok(())
+    }
+}
+
+impl embedded_hal::blocking::i2c::Write for DualBus {
+    type Error = ();
+
+    fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.log
+            .borrow_mut()
+            .push(format!("i2c {:02x}: {:02x?}", addr, bytes));
+        Ok(())
+    }
+}
+
+#[test]
+fn spi_and_i2c_same_bus() {
+    let device = DualBus::default();
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+    let mut spi_proxy = manager.acquire_spi();
+    let mut i2c_proxy = manager.acquire_i2c();
+
+    embedded_hal::blocking::spi::Write::write(&mut spi_proxy, &[0xab, 0xcd]).unwrap();
+    embedded_hal::blocking::i2c::Write::write(&mut i2c_proxy, 0x39, &[0xef]).unwrap();
+    embedded_hal::blocking::spi::Write::write(&mut spi_proxy, &[0x01]).unwrap();
+
+    assert_eq!(
+        &*device.log.borrow(),
+        &["spi: [ab, cd]", "i2c 39: [ef]", "spi: [01]"]
+    );
+}
+
+#[test]
+fn spi_format_bus() {
+    let expect = vec![
+        spi::Transaction::write(vec![0x01, 0x02]),
+        spi::Transaction::transfer(vec![0xaa, 0xbb], vec![0x55, 0x66]),
+    ];
+    let mut device = spi::Mock::new(&expect);
+
+    let manager = shared_bus::BusManagerSimple::new(shared_bus::FormatBus::new(
+        device.clone(),
+        String::new(),
+    ));
+    let mut proxy = manager.acquire_spi();
+
+    proxy.write(&[0x01, 0x02]).unwrap();
+    let mut buf = [0xaa, 0xbb];
+    proxy.transfer(&mut buf).unwrap();
+
+    assert_eq!(
+        manager.configure(|bus| bus.sink().clone()),
+        "SPI W: 01 02\nSPI T: AA BB -> 55 66\n"
+    );
+
+    device.done();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "For SPI, this means a transfer was started")]
+fn spi_overlapping_access() {
+    let device = spi::Mock::new(&[]);
+    let manager = shared_bus::BusManagerSimple::new(device);
+    let mut proxy = manager.acquire_spi();
+
+    // The scope keeps the bus locked, so the proxy overlaps with it.
+    let _scope = manager.begin_scope();
+    let _ = proxy.write(&[0xab]);
+}
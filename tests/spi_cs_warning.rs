@@ -0,0 +1,51 @@
+#![cfg(all(feature = "log", debug_assertions))]
+
+use embedded_hal::prelude::*;
+use embedded_hal_mock::eh0::spi;
+use std::sync::Mutex;
+
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct Logger;
+
+impl log::Log for Logger {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        if record.level() == log::Level::Warn {
+            WARNINGS.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn spi_cs_warning() {
+    log::set_logger(&Logger).unwrap();
+    log::set_max_level(log::LevelFilter::Warn);
+
+    let expect = vec![
+        spi::Transaction::write(vec![0x01]),
+        spi::Transaction::write(vec![0x02]),
+        spi::Transaction::write(vec![0x03]),
+    ];
+    let mut device = spi::Mock::new(&expect);
+    let manager = shared_bus::BusManagerSimple::new(device.clone());
+
+    // Warns only once
+    let mut proxy1 = manager.acquire_spi();
+    proxy1.write(&[0x01]).unwrap();
+    proxy1.write(&[0x02]).unwrap();
+    assert_eq!(WARNINGS.lock().unwrap().len(), 1);
+
+    // No warning when CS is managed
+    let mut proxy2 = manager.acquire_spi();
+    proxy2.mark_cs_managed();
+    proxy2.write(&[0x03]).unwrap();
+    assert_eq!(WARNINGS.lock().unwrap().len(), 1);
+
+    device.done();
+}
@@ -0,0 +1,365 @@
+#![cfg(all(feature = "eh1", feature = "std"))]
+
+use embedded_hal_1::spi::SpiDevice;
+use embedded_hal_mock::eh1::digital::{Mock as PinMock, State, Transaction as PinTransaction};
+use embedded_hal_mock::eh1::spi;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+fn cs_expectations(n: usize) -> Vec<PinTransaction> {
+    (0..n)
+        .flat_map(|_| {
+            [
+                PinTransaction::set(State::Low),
+                PinTransaction::set(State::High),
+            ]
+        })
+        .collect()
+}
+
+type Log = Arc<Mutex<Vec<&'static str>>>;
+
+/// Bus which records the order of operations.
+struct LoggingBus(Log);
+
+impl embedded_hal_1::spi::ErrorType for LoggingBus {
+    type Error = core::convert::Infallible;
+}
+
+impl embedded_hal_1::spi::SpiBus for LoggingBus {
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        words.fill(0x55);
+        self.0.lock().unwrap().push("read");
+        Ok(())
+    }
+    fn write(&mut self, _: &[u8]) -> Result<(), Self::Error> {
+        self.0.lock().unwrap().push("write");
+        Ok(())
+    }
+    fn transfer(&mut self, _: &mut [u8], _: &[u8]) -> Result<(), Self::Error> {
+        unimplemented!()
+    }
+    fn transfer_in_place(&mut self, _: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.lock().unwrap().push("transfer_in_place");
+        Ok(())
+    }
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.0.lock().unwrap().push("flush");
+        Ok(())
+    }
+}
+
+/// Chip-select pin which records its changes into the same log as the bus.
+struct LoggingCs(Log);
+
+impl embedded_hal_1::digital::ErrorType for LoggingCs {
+    type Error = core::convert::Infallible;
+}
+
+impl embedded_hal_1::digital::OutputPin for LoggingCs {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.lock().unwrap().push("cs low");
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.lock().unwrap().push("cs high");
+        Ok(())
+    }
+}
+
+#[test]
+fn spi_device_proxy() {
+    let expect = vec![
+        spi::Transaction::write_vec(vec![0xab, 0xcd]),
+        spi::Transaction::flush(),
+        spi::Transaction::write_vec(vec![0x01]),
+        spi::Transaction::read_vec(vec![0x02, 0x03]),
+        spi::Transaction::flush(),
+    ];
+    let mut device = spi::Mock::new(&expect);
+    let mut cs = PinMock::new(&cs_expectations(2));
+
+    let manager: &'static shared_bus::BusManagerStd<_> =
+        shared_bus::new_std!(spi::Mock<u8> = device.clone()).unwrap();
+    let mut proxy = manager.acquire_spi_device(cs.clone());
+
+    proxy.write(&[0xab, 0xcd]).unwrap();
+
+    let mut buf = [0u8; 2];
+    proxy
+        .transaction(&mut [
+            embedded_hal_1::spi::Operation::Write(&[0x01]),
+            embedded_hal_1::spi::Operation::Read(&mut buf),
+        ])
+        .unwrap();
+    assert_eq!(&buf, &[0x02, 0x03]);
+
+    device.done();
+    cs.done();
+}
+
+#[test]
+fn spi_device_cs_active_high() {
+    let expect = vec![
+        spi::Transaction::write_vec(vec![0xab, 0xcd]),
+        spi::Transaction::flush(),
+        spi::Transaction::write_vec(vec![0x01]),
+        spi::Transaction::flush(),
+    ];
+    let mut device = spi::Mock::new(&expect);
+    let mut cs = PinMock::new(&[
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::Low),
+    ]);
+
+    let manager: &'static shared_bus::BusManagerStd<_> =
+        shared_bus::new_std!(spi::Mock<u8> = device.clone()).unwrap();
+    let mut proxy = manager
+        .acquire_spi_device(cs.clone())
+        .cs_polarity(shared_bus::CsPolarity::ActiveHigh);
+
+    proxy.write(&[0xab, 0xcd]).unwrap();
+    proxy
+        .with_cs_low(|bus| embedded_hal_1::spi::SpiBus::write(bus, &[0x01]))
+        .unwrap()
+        .unwrap();
+
+    device.done();
+    cs.done();
+}
+
+#[test]
+fn spi_device_concurrent() {
+    let expect = vec![
+        spi::Transaction::write_vec(vec![0xab, 0xcd, 0xef]),
+        spi::Transaction::flush(),
+        spi::Transaction::transfer_in_place(vec![0x01, 0x02], vec![0x03, 0x04]),
+        spi::Transaction::flush(),
+    ];
+    let mut device = spi::Mock::new(&expect);
+    let mut cs1 = PinMock::new(&cs_expectations(1));
+    let mut cs2 = PinMock::new(&cs_expectations(1));
+
+    let manager: &'static shared_bus::BusManagerStd<_> =
+        shared_bus::new_std!(spi::Mock<u8> = device.clone()).unwrap();
+    let mut proxy1 = manager.acquire_spi_device(cs1.clone());
+    let mut proxy2 = manager.acquire_spi_device(cs2.clone());
+
+    thread::spawn(move || {
+        proxy1.write(&[0xab, 0xcd, 0xef]).unwrap();
+    })
+    .join()
+    .unwrap();
+
+    thread::spawn(move || {
+        let mut buf = [0x01, 0x02];
+        proxy2.transfer_in_place(&mut buf).unwrap();
+        assert_eq!(&buf, &[0x03, 0x04]);
+    })
+    .join()
+    .unwrap();
+
+    device.done();
+    cs1.done();
+    cs2.done();
+}
+
+#[test]
+fn spi_device_with_cs_low() {
+    use embedded_hal_1::spi::SpiBus;
+
+    let expect = vec![
+        spi::Transaction::write_vec(vec![0x03, 0x10]),
+        spi::Transaction::read_vec(vec![0x01, 0x02]),
+        spi::Transaction::read_vec(vec![0x03, 0x04]),
+        spi::Transaction::flush(),
+    ];
+    let mut device = spi::Mock::new(&expect);
+    let mut cs = PinMock::new(&cs_expectations(1));
+
+    let manager: &'static shared_bus::BusManagerStd<_> =
+        shared_bus::new_std!(spi::Mock<u8> = device.clone()).unwrap();
+    let mut proxy = manager.acquire_spi_device(cs.clone());
+
+    let mut data = [0u8; 4];
+    proxy
+        .with_cs_low(|bus| {
+            SpiBus::write(bus, &[0x03, 0x10])?;
+            for chunk in data.chunks_mut(2) {
+                SpiBus::read(bus, chunk)?;
+            }
+            Ok::<_, embedded_hal_1::spi::ErrorKind>(())
+        })
+        .unwrap()
+        .unwrap();
+    assert_eq!(&data, &[0x01, 0x02, 0x03, 0x04]);
+
+    device.done();
+    cs.done();
+}
+
+#[test]
+fn spi_device_write_then_read() {
+    let expect = vec![
+        spi::Transaction::write_vec(vec![0x80, 0x0f]),
+        spi::Transaction::read_vec(vec![1, 2, 3, 4, 5, 6, 7, 8]),
+        spi::Transaction::flush(),
+    ];
+    let mut device = spi::Mock::new(&expect);
+    let mut cs = PinMock::new(&cs_expectations(1));
+
+    let manager: &'static shared_bus::BusManagerStd<_> =
+        shared_bus::new_std!(spi::Mock<u8> = device.clone()).unwrap();
+    let mut proxy = manager.acquire_spi_device(cs.clone());
+
+    let mut buf = [0u8; 8];
+    proxy.write_then_read(&[0x80, 0x0f], &mut buf).unwrap();
+    assert_eq!(&buf, &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+    device.done();
+    cs.done();
+}
+
+#[test]
+fn spi_device_delay() {
+    use embedded_hal_1::spi::Operation;
+    use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+
+    struct LoggingDelay(Log, CheckedDelay);
+
+    impl embedded_hal_1::delay::DelayNs for LoggingDelay {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0.lock().unwrap().push("delay");
+            self.1.delay_ns(ns);
+        }
+    }
+
+    let log = Log::default();
+    let mut delay = CheckedDelay::new(&[DelayTransaction::delay_ns(1500)]);
+    let mut cs = PinMock::new(&cs_expectations(1));
+
+    let manager: &'static shared_bus::BusManagerStd<_> =
+        shared_bus::new_std!(LoggingBus = LoggingBus(log.clone())).unwrap();
+    let mut proxy =
+        manager.acquire_spi_device_with_delay(cs.clone(), LoggingDelay(log.clone(), delay.clone()));
+
+    let mut buf = [0u8; 2];
+    proxy
+        .transaction(&mut [
+            Operation::Write(&[0x9f]),
+            Operation::DelayNs(1500),
+            Operation::Read(&mut buf),
+        ])
+        .unwrap();
+    assert_eq!(&buf, &[0x55, 0x55]);
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["write", "flush", "delay", "read", "flush"]
+    );
+
+    delay.done();
+    cs.done();
+}
+
+#[test]
+fn spi_device_delay_missing() {
+    use embedded_hal_1::spi::Operation;
+
+    let log = Log::default();
+    let mut cs = PinMock::new(&[]);
+
+    let manager: &'static shared_bus::BusManagerStd<_> =
+        shared_bus::new_std!(LoggingBus = LoggingBus(log.clone())).unwrap();
+    let mut proxy = manager.acquire_spi_device(cs.clone());
+
+    let mut buf = [0u8; 2];
+    let res = proxy.transaction(&mut [
+        Operation::Write(&[0x9f]),
+        Operation::DelayNs(1500),
+        Operation::Read(&mut buf),
+    ]);
+    assert_eq!(res, Err(shared_bus::SpiDeviceError::NoDelay));
+
+    // The bus was not touched
+    assert!(log.lock().unwrap().is_empty());
+    cs.done();
+}
+
+#[test]
+fn spi_device_flush_before_cs() {
+    use embedded_hal_1::spi::Operation;
+
+    let log = Log::default();
+    let manager: &'static shared_bus::BusManagerStd<_> =
+        shared_bus::new_std!(LoggingBus = LoggingBus(log.clone())).unwrap();
+    let mut proxy = manager.acquire_spi_device(LoggingCs(log.clone()));
+
+    let mut buf = [0u8; 2];
+    proxy
+        .transaction(&mut [
+            Operation::Write(&[0x01]),
+            Operation::TransferInPlace(&mut buf),
+        ])
+        .unwrap();
+    proxy
+        .with_cs_low(|bus| embedded_hal_1::spi::SpiBus::write(bus, &[0x02]))
+        .unwrap()
+        .unwrap();
+
+    // The last data operation must have left the FIFO before chip-select is deasserted
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![
+            "cs low",
+            "write",
+            "transfer_in_place",
+            "flush",
+            "cs high",
+            "cs low",
+            "write",
+            "flush",
+            "cs high",
+        ]
+    );
+}
+
+#[test]
+fn spi_device_scratch() {
+    use embedded_hal_1::spi::SpiBus;
+
+    let expect = vec![
+        spi::Transaction::transfer_in_place(vec![0x0b, 0x00, 0x00], vec![0xff, 0x12, 0x34]),
+        spi::Transaction::flush(),
+        spi::Transaction::write_vec(vec![0x12, 0x34]),
+        spi::Transaction::flush(),
+    ];
+    let mut spi = spi::Mock::new(&expect);
+    let mut cs = PinMock::new(&cs_expectations(2));
+
+    let manager: shared_bus::BusManagerStd<_> =
+        shared_bus::BusManager::with_scratch::<_, 16>(spi.clone());
+    let manager: &'static _ = Box::leak(Box::new(manager));
+    let mut device = manager.acquire_spi_device(cs.clone());
+
+    device
+        .with_scratch(|bus, scratch| {
+            scratch[..3].copy_from_slice(&[0x0b, 0x00, 0x00]);
+            SpiBus::transfer_in_place(bus, &mut scratch[..3])
+        })
+        .unwrap()
+        .unwrap();
+
+    // The scratch buffer is shared, another device sees the data left over by the last one
+    let mut other = manager.acquire_spi_device(cs.clone());
+    other
+        .with_scratch(|bus, scratch| SpiBus::write(bus, &scratch[1..3]))
+        .unwrap()
+        .unwrap();
+
+    spi.done();
+    cs.done();
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "std")]
+
+use embedded_hal::prelude::*;
+use std::collections::HashSet;
+use std::thread;
+use std::time::Duration;
+
+/// Bus which takes a while for each transfer, so overlaps would be visible.
+struct SlowBus;
+
+impl embedded_hal::blocking::i2c::Write for SlowBus {
+    type Error = ();
+
+    fn write(&mut self, _addr: u8, _buffer: &[u8]) -> Result<(), Self::Error> {
+        thread::sleep(Duration::from_micros(200));
+        Ok(())
+    }
+}
+
+#[test]
+fn tracing_bus_threads_serialized() {
+    let tracing = shared_bus::TracingBus::new(SlowBus);
+    let log = tracing.log();
+
+    let manager: &'static shared_bus::BusManagerStd<_> =
+        shared_bus::new_std!(shared_bus::TracingBus<SlowBus> = tracing).unwrap();
+
+    let threads: Vec<_> = (0..4u8)
+        .map(|i| {
+            let mut proxy = manager.acquire_i2c();
+            thread::spawn(move || {
+                for _ in 0..10 {
+                    proxy.write(0x10 + i, &[0x00]).unwrap();
+                }
+            })
+        })
+        .collect();
+    for t in threads {
+        t.join().unwrap();
+    }
+
+    let entries = log.entries();
+    assert_eq!(entries.len(), 40);
+    assert!(log.is_serialized());
+
+    // All threads show up in the log, each with its own address
+    let threads: HashSet<_> = entries.iter().map(|e| e.thread).collect();
+    assert_eq!(threads.len(), 4);
+    for e in &entries {
+        assert!(entries
+            .iter()
+            .filter(|other| other.thread == e.thread)
+            .all(|other| other.op == e.op));
+    }
+
+    log.clear();
+    assert!(log.entries().is_empty());
+}
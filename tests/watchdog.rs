@@ -0,0 +1,43 @@
+#![cfg(feature = "std")]
+
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn watchdog_long_lock() {
+    let manager: &'static _ = Box::leak(Box::new(shared_bus::BusManagerWatchdog::new(())));
+    let (tx, rx) = mpsc::channel();
+
+    manager.spawn_watchdog(Duration::from_millis(50), move |held| {
+        tx.send(held).unwrap();
+    });
+
+    // Short locks are fine
+    for _ in 0..10 {
+        manager.configure(|_| thread::sleep(Duration::from_millis(5)));
+    }
+    assert!(rx.try_recv().is_err());
+    assert_eq!(manager.held_for(), None);
+
+    manager.configure(|_| thread::sleep(Duration::from_millis(300)));
+
+    // Reported once, while the lock was still held
+    let held = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert!(held > Duration::from_millis(50));
+    assert!(held < Duration::from_millis(300));
+    assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+}
+
+#[test]
+fn watchdog_panic_releases() {
+    let manager = shared_bus::BusManagerWatchdog::new(());
+
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        manager.configure(|_| panic!("driver bug"));
+    }));
+    assert!(res.is_err());
+
+    // The bus is not reported as held forever
+    assert_eq!(manager.held_for(), None);
+}